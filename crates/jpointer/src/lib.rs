@@ -31,6 +31,70 @@ impl serde::Serialize for JsonPointer {
     }
 }
 
+impl JsonPointer {
+    /// Create an empty JSON Pointer that points to the whole document.
+    #[inline]
+    pub const fn new() -> Self {
+        JsonPointer(Vec::new())
+    }
+    /// Append a segment to the end of this pointer.
+    #[inline]
+    pub fn push(&mut self, segment: impl Into<Segment>) {
+        self.0.push(segment.into());
+    }
+    /// Remove the last segment of this pointer and return it.
+    #[inline]
+    pub fn pop(&mut self) -> Option<Segment> {
+        self.0.pop()
+    }
+    /// Return a new pointer with all segments of `self` followed by `segment`.
+    #[inline]
+    pub fn join(&self, segment: impl Into<Segment>) -> Self {
+        let mut segments = Vec::with_capacity(self.0.len() + 1);
+        segments.extend_from_slice(&self.0);
+        segments.push(segment.into());
+        JsonPointer(segments)
+    }
+    /// Extend this pointer with all segments of `other`.
+    #[inline]
+    pub fn extend(&mut self, other: &JsonPointer) {
+        self.0.extend_from_slice(&other.0);
+    }
+    /// Iterate over the segments of this pointer.
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, Segment> {
+        self.0.iter()
+    }
+    /// Return the number of segments in this pointer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    /// Return `true` if this pointer refers to the whole document.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Return a key that orders pointers segment by segment, with indices in numeric order
+    /// before keys in lexicographic order.
+    pub fn sort_key(&self) -> Vec<(usize, String)> {
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                Segment::Index(idx) => (*idx, String::new()),
+                Segment::Key(key) => (usize::MAX, key.to_string()),
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<Segment>> for JsonPointer {
+    #[inline]
+    fn from(segments: Vec<Segment>) -> Self {
+        JsonPointer(segments)
+    }
+}
+
 impl From<JsonPointerNode<'_>> for JsonPointer {
     #[inline]
     fn from(node: JsonPointerNode<'_>) -> Self {
@@ -38,6 +102,13 @@ impl From<JsonPointerNode<'_>> for JsonPointer {
     }
 }
 
+impl From<&JsonPointerNode<'_>> for JsonPointer {
+    #[inline]
+    fn from(node: &JsonPointerNode<'_>) -> Self {
+        JsonPointer(node.to_vec())
+    }
+}
+
 /// A segment within a JSON pointer.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Segment {
@@ -75,6 +146,13 @@ impl From<String> for Segment {
     }
 }
 
+impl From<&str> for Segment {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Segment::Key(value.into())
+    }
+}
+
 impl From<usize> for Segment {
     #[inline]
     fn from(value: usize) -> Self {
@@ -96,6 +174,12 @@ pub struct JsonPointerNode<'a> {
     pub(crate) parent: Option<&'a JsonPointerNode<'a>>,
 }
 
+impl Default for JsonPointerNode<'_> {
+    fn default() -> Self {
+        JsonPointerNode::new()
+    }
+}
+
 impl<'a> JsonPointerNode<'a> {
     #[inline]
     pub const fn new() -> Self {
@@ -160,6 +244,37 @@ mod tests {
         assert_eq!(segment, Segment::Key("foo".into()));
     }
 
+    #[test]
+    fn test_segment_from_str() {
+        let segment = Segment::from("foo");
+        assert_eq!(segment, Segment::Key("foo".into()));
+    }
+
+    #[test]
+    fn test_json_pointer_push_and_join() {
+        let mut pointer = JsonPointer::new();
+        pointer.push("foo");
+        let joined = pointer.join(42);
+        assert_eq!(pointer.to_string(), "/foo");
+        assert_eq!(joined.to_string(), "/foo/42");
+        assert_eq!(joined.len(), 2);
+        assert!(JsonPointer::new().is_empty());
+    }
+
+    #[test]
+    fn test_json_pointer_sort_key() {
+        let mut pointers = [
+            JsonPointer::new().join("b"),
+            JsonPointer::new().join(10),
+            JsonPointer::new().join("a").join(1),
+            JsonPointer::new().join(2),
+            JsonPointer::new(),
+        ];
+        pointers.sort_by_cached_key(JsonPointer::sort_key);
+        let pointers: Vec<_> = pointers.iter().map(ToString::to_string).collect();
+        assert_eq!(pointers, ["", "/2", "/10", "/a/1", "/b"]);
+    }
+
     #[test]
     fn test_segment_from_usize() {
         let segment = Segment::from(42_usize);
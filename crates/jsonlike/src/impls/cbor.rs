@@ -0,0 +1,427 @@
+use std::borrow::Cow;
+
+use ciborium::Value;
+
+use crate::{prelude::*, MAX_SAFE_INTEGER};
+
+fn no_equivalent(value: &Value) -> JsonError {
+    let description = match value {
+        Value::Bytes(_) => "Byte string".to_string(),
+        Value::Tag(tag, _) => format!("Tagged value (tag {tag})"),
+        Value::Float(float) => format!("Float `{float}`"),
+        _ => format!("{value:?}"),
+    };
+    JsonError::new(format!("{description} has no JSON equivalent").into())
+}
+
+pub struct ObjectIter<'a>(std::slice::Iter<'a, (Value, Value)>);
+
+impl<'a> Iterator for ObjectIter<'a> {
+    type Item = (Result<&'a str, JsonError>, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| match key {
+            Value::Text(key) => (Ok(key.as_str()), value),
+            _ => (
+                Err(JsonError::new(
+                    format!("CBOR map key {key:?} is not a string").into(),
+                )),
+                value,
+            ),
+        })
+    }
+}
+
+impl JsonObject for Vec<(Value, Value)> {
+    type Key = str;
+    type Value = Value;
+    type Iter<'a> = ObjectIter<'a>;
+
+    fn get(&self, key: &str) -> Option<&Self::Value> {
+        <[(Value, Value)]>::iter(self).find_map(|(k, v)| match k {
+            Value::Text(k) if k == key => Some(v),
+            _ => None,
+        })
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        ObjectIter(<[(Value, Value)]>::iter(self))
+    }
+
+    fn len(&self) -> usize {
+        <[(Value, Value)]>::len(self)
+    }
+}
+
+pub struct ArrayIter<'a>(std::slice::Iter<'a, Value>);
+
+impl<'a> Iterator for ArrayIter<'a> {
+    type Item = Result<&'a Value, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Ok)
+    }
+}
+
+impl JsonArray for Vec<Value> {
+    type Element = Value;
+    type Iter<'a> = ArrayIter<'a>;
+
+    fn try_iter(&self) -> Result<Self::Iter<'_>, JsonError> {
+        Ok(ArrayIter(<[Value]>::iter(self)))
+    }
+
+    fn try_get(&self, idx: usize) -> Result<Option<&Self::Element>, JsonError> {
+        Ok(<[Value]>::get(self, idx))
+    }
+
+    fn len(&self) -> usize {
+        <[Value]>::len(self)
+    }
+}
+
+/// Only `Value::Integer` and finite `Value::Float` are numbers.
+impl<'a> JsonNumber<'a> for Value {
+    type Integer = i128;
+
+    fn as_integer<I: TryFrom<<Self as JsonNumber<'a>>::Integer>>(
+        &'a self,
+    ) -> Option<<Self as JsonNumber<'a>>::Integer> {
+        match self {
+            Value::Integer(int) => Some(i128::from(*int)),
+            _ => None,
+        }
+    }
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Integer(int) => Some(i128::from(*int) as f64),
+            Value::Float(float) => Some(*float),
+            _ => None,
+        }
+    }
+    fn as_decimal(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::Integer(int)
+                if i128::from(*int).unsigned_abs() > u128::from(MAX_SAFE_INTEGER) =>
+            {
+                Some(Cow::Owned(i128::from(*int).to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Byte strings, tags and non-finite floats have no JSON equivalent and are reported as
+/// `JsonError`, as are non-string map keys while iterating.
+impl Json for Value {
+    type Object = Vec<(Value, Value)>;
+    type Array = Vec<Value>;
+    type String = str;
+    type Number = Value;
+
+    fn try_as_object(&self) -> Result<Option<&Vec<(Value, Value)>>, JsonError> {
+        match self {
+            Value::Map(map) => Ok(Some(map)),
+            Value::Bytes(_) | Value::Tag(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_array(&self) -> Result<Option<&Vec<Value>>, JsonError> {
+        match self {
+            Value::Array(array) => Ok(Some(array)),
+            Value::Bytes(_) | Value::Tag(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_string(&self) -> Result<Option<&str>, JsonError> {
+        match self {
+            Value::Text(text) => Ok(Some(text)),
+            Value::Bytes(_) | Value::Tag(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_number(&self) -> Result<Option<&Value>, JsonError> {
+        match self {
+            Value::Integer(_) => Ok(Some(self)),
+            Value::Float(float) if float.is_finite() => Ok(Some(self)),
+            Value::Float(_) | Value::Bytes(_) | Value::Tag(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_boolean(&self) -> Result<Option<bool>, JsonError> {
+        match self {
+            Value::Bool(boolean) => Ok(Some(*boolean)),
+            Value::Bytes(_) | Value::Tag(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_null(&self) -> Result<Option<()>, JsonError> {
+        match self {
+            Value::Null => Ok(Some(())),
+            Value::Bytes(_) | Value::Tag(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Maps are equal if they have the same entries, regardless of their order.
+    fn try_equal(&self, other: &Self) -> Result<bool, JsonError> {
+        match (self, other) {
+            (Value::Map(left), Value::Map(right)) => {
+                if left.len() != right.len() {
+                    return Ok(false);
+                }
+                for (key, value) in left {
+                    match <[(Value, Value)]>::iter(right).find(|(other, _)| key == other) {
+                        Some((_, other)) if value.try_equal(other)? => {}
+                        _ => return Ok(false),
+                    }
+                }
+                Ok(true)
+            }
+            (Value::Array(left), Value::Array(right)) => {
+                if left.len() != right.len() {
+                    return Ok(false);
+                }
+                for (left, right) in <[Value]>::iter(left).zip(right) {
+                    if !left.try_equal(right)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            _ => {
+                self.try_as_number()?;
+                other.try_as_number()?;
+                Ok(self == other)
+            }
+        }
+    }
+    /// Parse JSON text, as CBOR has no text syntax.
+    fn from_str(s: &str) -> Result<Self, JsonError>
+    where
+        Self: Sized,
+    {
+        let value = serde_json::from_str(s).map_err(|err| JsonError::new(Box::new(err)))?;
+        from_json(value)
+    }
+}
+
+/// Convert a `serde_json::Value` without relying on how `serde_json` stores numbers.
+fn from_json(value: serde_json::Value) -> Result<Value, JsonError> {
+    Ok(match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(boolean) => Value::Bool(boolean),
+        serde_json::Value::Number(number) => {
+            if let Some(int) = number.as_i64() {
+                Value::Integer(int.into())
+            } else if let Some(int) = number.as_u64() {
+                Value::Integer(int.into())
+            } else if let Some(float) = number.as_f64() {
+                Value::Float(float)
+            } else {
+                return Err(JsonError::new(
+                    format!("Number `{number}` is out of range").into(),
+                ));
+            }
+        }
+        serde_json::Value::String(string) => Value::Text(string),
+        serde_json::Value::Array(array) => {
+            Value::Array(array.into_iter().map(from_json).collect::<Result<_, _>>()?)
+        }
+        serde_json::Value::Object(object) => Value::Map(
+            object
+                .into_iter()
+                .map(|(key, value)| Ok((Value::Text(key), from_json(value)?)))
+                .collect::<Result<_, JsonError>>()?,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ciborium::{cbor, Value};
+    use test_case::test_case;
+
+    use crate::{
+        tests::{
+            assert_array_get, assert_array_number_iter, assert_as_array, assert_as_boolean,
+            assert_as_null, assert_as_number_float, assert_as_number_integer, assert_as_object,
+            assert_as_string, assert_object_get, assert_object_str_number_iter, CustomInteger,
+        },
+        Json, JsonNumber, JsonObject,
+    };
+
+    impl TryFrom<i128> for CustomInteger {
+        type Error = std::num::TryFromIntError;
+
+        fn try_from(value: i128) -> Result<Self, Self::Error> {
+            Ok(CustomInteger::new(i64::try_from(value)?))
+        }
+    }
+
+    fn build_object() -> Value {
+        cbor!({"a" => 1, "c" => "d"}).expect("Valid CBOR")
+    }
+
+    fn build_array() -> Value {
+        cbor!([1]).expect("Valid CBOR")
+    }
+
+    #[test]
+    fn test_object_get() {
+        assert_object_get(&build_object());
+    }
+
+    #[test]
+    fn test_object_iter() {
+        assert_object_str_number_iter(&build_object());
+    }
+
+    #[test]
+    fn test_array_iter() {
+        assert_array_number_iter(&build_array());
+    }
+
+    #[test]
+    fn test_array_get() {
+        assert_array_get(&build_array());
+    }
+
+    #[test_case(Value::Null, None)]
+    #[test_case(Value::Bool(true), None)]
+    #[test_case(Value::Integer(42.into()), Some(CustomInteger::new(42)))]
+    #[test_case(Value::Float(5.15), None)]
+    #[test_case(Value::Text("abc".into()), None)]
+    #[test_case(build_array(), None)]
+    #[test_case(build_object(), None)]
+    fn test_as_number_integer(value: Value, expected: Option<CustomInteger>) {
+        assert_as_number_integer(&value, expected);
+    }
+
+    #[test_case(Value::Null, None)]
+    #[test_case(Value::Bool(true), None)]
+    #[test_case(Value::Integer(42.into()), Some(42.0))]
+    #[test_case(Value::Float(5.15), Some(5.15))]
+    #[test_case(Value::Text("abc".into()), None)]
+    #[test_case(build_array(), None)]
+    #[test_case(build_object(), None)]
+    fn test_as_number_float(value: Value, expected: Option<f64>) {
+        assert_as_number_float(&value, expected);
+    }
+
+    #[test_case(Value::Integer(42.into()), None)]
+    #[test_case(Value::Float(5.15), None)]
+    #[test_case(Value::Integer(u64::MAX.into()), Some("18446744073709551615"))]
+    #[test_case(Value::Integer(ciborium::value::Integer::try_from(-(1_i128 << 64)).expect("In range")), Some("-18446744073709551616"))]
+    fn test_as_number_decimal(value: Value, expected: Option<&str>) {
+        let number = value.as_number().expect("Not a number");
+        assert_eq!(number.as_decimal().as_deref(), expected);
+    }
+
+    #[test_case(Value::Null, false)]
+    #[test_case(Value::Integer(42.into()), false)]
+    #[test_case(Value::Text("abc".into()), false)]
+    #[test_case(build_array(), false)]
+    #[test_case(build_object(), true)]
+    fn test_as_object(value: Value, expected: bool) {
+        assert_as_object(&value, expected);
+    }
+
+    #[test_case(Value::Null, false)]
+    #[test_case(Value::Text("abc".into()), false)]
+    #[test_case(build_array(), true)]
+    #[test_case(build_object(), false)]
+    fn test_as_array(value: Value, expected: bool) {
+        assert_as_array(&value, expected);
+    }
+
+    #[test_case(Value::Null, None)]
+    #[test_case(Value::Integer(42.into()), None)]
+    #[test_case(Value::Text("abc".into()), Some("abc"))]
+    #[test_case(build_array(), None)]
+    fn test_as_string(value: Value, expected: Option<&str>) {
+        assert_as_string(&value, expected);
+    }
+
+    #[test_case(Value::Null, None)]
+    #[test_case(Value::Bool(true), Some(true))]
+    #[test_case(Value::Text("abc".into()), None)]
+    fn test_as_boolean(value: Value, expected: Option<bool>) {
+        assert_as_boolean(&value, expected);
+    }
+
+    #[test_case(Value::Null, Some(()))]
+    #[test_case(Value::Bool(true), None)]
+    #[test_case(Value::Text("abc".into()), None)]
+    fn test_as_null(value: Value, expected: Option<()>) {
+        assert_as_null(&value, expected);
+    }
+
+    #[test_case(Value::Null)]
+    #[test_case(Value::Integer(42.into()))]
+    #[test_case(build_array())]
+    #[test_case(build_object())]
+    fn test_equal(value: Value) {
+        assert!(value.equal(&value));
+        assert!(!value.equal(&Value::Text("something else".into())));
+    }
+
+    #[test]
+    fn test_equal_key_order() {
+        let reordered = cbor!({"c" => "d", "a" => 1}).expect("Valid CBOR");
+        assert!(build_object().equal(&reordered));
+        let other = cbor!({"c" => "d", "a" => 2}).expect("Valid CBOR");
+        assert!(!build_object().equal(&other));
+    }
+
+    #[test_case(Value::Bytes(vec![1, 2]))]
+    #[test_case(Value::Tag(1, Box::new(Value::Integer(0.into()))))]
+    #[test_case(Value::Float(f64::NAN))]
+    #[test_case(Value::Float(f64::INFINITY))]
+    fn test_no_json_equivalent(value: Value) {
+        assert!(value.try_as_number().is_err());
+        assert!(!matches!(value.try_is_string(), Ok(true)));
+        assert!(value.try_equal(&value).is_err());
+        assert!(Value::Array(vec![value.clone()])
+            .try_equal(&Value::Array(vec![value]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_non_string_key() {
+        let value = Value::Map(vec![
+            (Value::Integer(1.into()), Value::Null),
+            (Value::Text("b".into()), Value::Null),
+        ]);
+        let object = value.as_object().expect("Should be an object");
+        let keys: Vec<_> = JsonObject::iter(object).map(|(key, _)| key.ok()).collect();
+        assert_eq!(keys, [None, Some("b")]);
+        assert!(JsonObject::get(object, "b").is_some());
+        assert!(JsonObject::get(object, "1").is_none());
+    }
+
+    #[test]
+    fn test_decode() {
+        // {"a": [1, -2.5, null]}
+        let bytes = [0xa1, 0x61, 0x61, 0x83, 0x01, 0xf9, 0xc1, 0x00, 0xf6];
+        let value: Value = ciborium::from_reader(&bytes[..]).expect("Valid CBOR");
+        assert!(value.equal(&Value::from_str(r#"{"a": [1, -2.5, null]}"#).expect("Valid JSON")));
+    }
+
+    #[test_case("18446744073709551615", Value::Integer(u64::MAX.into()))]
+    #[test_case("-9223372036854775808", Value::Integer(i64::MIN.into()))]
+    #[test_case("1.5", Value::Float(1.5))]
+    fn test_from_str_number(text: &str, expected: Value) {
+        assert_eq!(Value::from_str(text).expect("Valid JSON"), expected);
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        assert!(Value::from_str("{").is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use std::borrow::Cow;
+
+use crate::{
+    json5::{self, Kind, Number, Value},
+    prelude::*,
+    MAX_SAFE_INTEGER,
+};
+
+pub struct ObjectIter<'a>(std::slice::Iter<'a, (String, Value)>);
+
+impl<'a> Iterator for ObjectIter<'a> {
+    type Item = (Result<&'a String, JsonError>, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (Ok(k), v))
+    }
+}
+
+impl JsonObject for Vec<(String, Value)> {
+    type Key = String;
+    type Value = Value;
+    type Iter<'a> = ObjectIter<'a>;
+
+    fn get(&self, key: &str) -> Option<&Self::Value> {
+        <[(String, Value)]>::iter(self).find_map(|(k, v)| (k == key).then_some(v))
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        ObjectIter(<[(String, Value)]>::iter(self))
+    }
+
+    fn len(&self) -> usize {
+        <[(String, Value)]>::len(self)
+    }
+}
+
+pub struct ArrayIter<'a>(std::slice::Iter<'a, Value>);
+
+impl<'a> Iterator for ArrayIter<'a> {
+    type Item = Result<&'a Value, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Ok)
+    }
+}
+
+impl JsonArray for Vec<Value> {
+    type Element = Value;
+    type Iter<'a> = ArrayIter<'a>;
+
+    fn try_iter(&self) -> Result<Self::Iter<'_>, JsonError> {
+        Ok(ArrayIter(<[Value]>::iter(self)))
+    }
+
+    fn try_get(&self, idx: usize) -> Result<Option<&Self::Element>, JsonError> {
+        Ok(<[Value]>::get(self, idx))
+    }
+
+    fn len(&self) -> usize {
+        <[Value]>::len(self)
+    }
+}
+
+impl<'a> JsonNumber<'a> for Number {
+    type Integer = i64;
+
+    fn as_integer<I: TryFrom<Self::Integer>>(&'a self) -> Option<Self::Integer> {
+        self.as_str().parse().ok()
+    }
+    fn as_float(&self) -> Option<f64> {
+        self.as_str().parse().ok()
+    }
+    fn as_decimal(&self) -> Option<Cow<'_, str>> {
+        let is_safe = self
+            .as_str()
+            .parse::<i64>()
+            .is_ok_and(|int| int.unsigned_abs() <= MAX_SAFE_INTEGER);
+        (!is_safe).then(|| Cow::Borrowed(self.as_str()))
+    }
+}
+
+impl Json for Value {
+    type Object = Vec<(String, Value)>;
+    type Array = Vec<Value>;
+    type String = str;
+    type Number = Number;
+
+    fn try_as_object(&self) -> Result<Option<&Self::Object>, JsonError> {
+        match self.kind() {
+            Kind::Object(members) => Ok(Some(members)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_array(&self) -> Result<Option<&Self::Array>, JsonError> {
+        match self.kind() {
+            Kind::Array(items) => Ok(Some(items)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_string(&self) -> Result<Option<&Self::String>, JsonError> {
+        match self.kind() {
+            Kind::String(string) => Ok(Some(string)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_number(&self) -> Result<Option<&Self::Number>, JsonError> {
+        match self.kind() {
+            Kind::Number(number) if number.is_finite() => Ok(Some(number)),
+            Kind::Number(number) => {
+                let span = self.span();
+                Err(JsonError::new(
+                    format!(
+                        "`{number}` at line {} column {} has no JSON equivalent",
+                        span.start.line, span.start.column
+                    )
+                    .into(),
+                ))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_boolean(&self) -> Result<Option<bool>, JsonError> {
+        match self.kind() {
+            Kind::Bool(boolean) => Ok(Some(*boolean)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_null(&self) -> Result<Option<()>, JsonError> {
+        match self.kind() {
+            Kind::Null => Ok(Some(())),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_equal(&self, other: &Self) -> Result<bool, JsonError> {
+        Ok(self.eq(other))
+    }
+    fn from_str(s: &str) -> Result<Self, JsonError>
+    where
+        Self: Sized,
+    {
+        json5::from_str(s).map_err(|err| JsonError::new(Box::new(err)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use crate::{
+        json5::Value,
+        tests::{
+            assert_array_get, assert_array_number_iter, assert_as_array, assert_as_boolean,
+            assert_as_null, assert_as_number_float, assert_as_number_integer, assert_as_object,
+            assert_as_string, assert_object_get, assert_object_str_number_iter, CustomInteger,
+        },
+        Json, JsonNumber,
+    };
+
+    fn json5(s: &str) -> Value {
+        Value::from_str(s).expect("Valid JSON5")
+    }
+
+    #[test]
+    fn test_object_get() {
+        assert_object_get(&json5("{a: 1, c: 'd'}"));
+    }
+
+    #[test]
+    fn test_object_iter() {
+        assert_object_str_number_iter(&json5("{a: 1, c: 'd',}"));
+    }
+
+    #[test]
+    fn test_array_iter() {
+        assert_array_number_iter(&json5("[1,]"));
+    }
+
+    #[test]
+    fn test_array_get() {
+        assert_array_get(&json5("[1]"));
+    }
+
+    #[test_case("null", None)]
+    #[test_case("true", None)]
+    #[test_case("42", Some(CustomInteger::new(42)))]
+    #[test_case("0x2A", Some(CustomInteger::new(42)))]
+    #[test_case("5.15", None)]
+    #[test_case("'abc'", None)]
+    #[test_case("[1]", None)]
+    #[test_case("{a: 1}", None)]
+    fn test_as_number_integer(value: &str, expected: Option<CustomInteger>) {
+        assert_as_number_integer(&json5(value), expected);
+    }
+
+    #[test_case("null", None)]
+    #[test_case("42", Some(42.0))]
+    #[test_case("5.15", Some(5.15))]
+    #[test_case(".5", Some(0.5))]
+    #[test_case("'abc'", None)]
+    #[test_case("{a: 1}", None)]
+    fn test_as_number_float(value: &str, expected: Option<f64>) {
+        assert_as_number_float(&json5(value), expected);
+    }
+
+    #[test_case("42", None)]
+    #[test_case("0.1", Some("0.1"))]
+    #[test_case("9007199254740993", Some("9007199254740993"))]
+    #[test_case("0xFFFFFFFFFFFFFFFFFF", Some("4722366482869645213695"))]
+    fn test_as_number_decimal(value: &str, expected: Option<&str>) {
+        let value = json5(value);
+        let number = value.as_number().expect("Not a number");
+        assert_eq!(number.as_decimal().as_deref(), expected);
+    }
+
+    #[test_case("null", false)]
+    #[test_case("42", false)]
+    #[test_case("'abc'", false)]
+    #[test_case("[1]", false)]
+    #[test_case("{a: 1}", true)]
+    fn test_as_object(value: &str, expected: bool) {
+        assert_as_object(&json5(value), expected);
+    }
+
+    #[test_case("null", false)]
+    #[test_case("'abc'", false)]
+    #[test_case("[1]", true)]
+    #[test_case("{a: 1}", false)]
+    fn test_as_array(value: &str, expected: bool) {
+        assert_as_array(&json5(value), expected);
+    }
+
+    #[test_case("null", None)]
+    #[test_case("42", None)]
+    #[test_case("'abc'", Some("abc"))]
+    #[test_case("\"abc\"", Some("abc"))]
+    #[test_case("[1]", None)]
+    fn test_as_string(value: &str, expected: Option<&str>) {
+        assert_as_string(&json5(value), expected);
+    }
+
+    #[test_case("null", None)]
+    #[test_case("true", Some(true))]
+    #[test_case("'abc'", None)]
+    fn test_as_boolean(value: &str, expected: Option<bool>) {
+        assert_as_boolean(&json5(value), expected);
+    }
+
+    #[test_case("null", Some(()))]
+    #[test_case("true", None)]
+    #[test_case("'abc'", None)]
+    fn test_as_null(value: &str, expected: Option<()>) {
+        assert_as_null(&json5(value), expected);
+    }
+
+    #[test_case("null")]
+    #[test_case("42")]
+    #[test_case("[1]")]
+    #[test_case("{a: 1}")]
+    fn test_equal(value: &str) {
+        let value = json5(value);
+        assert!(value.equal(&value));
+        assert!(!value.equal(&json5("'something else'")));
+    }
+
+    #[test_case("Infinity")]
+    #[test_case("-Infinity")]
+    #[test_case("NaN")]
+    fn test_non_finite(value: &str) {
+        let error = json5(value)
+            .try_as_number()
+            .expect_err("No JSON equivalent");
+        assert!(error
+            .to_string()
+            .ends_with("at line 1 column 1 has no JSON equivalent"));
+    }
+}
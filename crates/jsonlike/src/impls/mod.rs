@@ -1,4 +1,14 @@
+#[cfg(any(feature = "cbor", test))]
+mod cbor;
+#[cfg(any(feature = "json5", test))]
+mod json5;
+#[cfg(any(feature = "msgpack", test))]
+mod msgpack;
 #[cfg(any(feature = "pyo3", test))]
 mod pyo3;
 #[cfg(any(feature = "serde_json", test))]
 mod serde_json;
+#[cfg(any(feature = "serde_yaml", test))]
+mod serde_yaml;
+#[cfg(any(feature = "toml", test))]
+mod toml;
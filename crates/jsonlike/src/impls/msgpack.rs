@@ -0,0 +1,452 @@
+use std::borrow::Cow;
+
+use rmpv::Value;
+
+use crate::{prelude::*, MAX_SAFE_INTEGER};
+
+fn no_equivalent(value: &Value) -> JsonError {
+    let description = match value {
+        Value::Binary(_) => "Binary data".to_string(),
+        Value::Ext(ty, _) => format!("Extension type {ty}"),
+        Value::String(_) => "String with invalid UTF-8".to_string(),
+        _ => format!("Float `{value}`"),
+    };
+    JsonError::new(format!("{description} has no JSON equivalent").into())
+}
+
+pub struct ObjectIter<'a>(std::slice::Iter<'a, (Value, Value)>);
+
+impl<'a> Iterator for ObjectIter<'a> {
+    type Item = (Result<&'a str, JsonError>, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| match key {
+            Value::String(string) if string.is_str() => {
+                (Ok(string.as_str().unwrap_or_default()), value)
+            }
+            _ => (
+                Err(JsonError::new(
+                    format!("MessagePack map key {key} is not a string").into(),
+                )),
+                value,
+            ),
+        })
+    }
+}
+
+impl JsonObject for Vec<(Value, Value)> {
+    type Key = str;
+    type Value = Value;
+    type Iter<'a> = ObjectIter<'a>;
+
+    fn get(&self, key: &str) -> Option<&Self::Value> {
+        <[(Value, Value)]>::iter(self).find_map(|(k, v)| match k {
+            Value::String(k) if k.as_str() == Some(key) => Some(v),
+            _ => None,
+        })
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        ObjectIter(<[(Value, Value)]>::iter(self))
+    }
+
+    fn len(&self) -> usize {
+        <[(Value, Value)]>::len(self)
+    }
+}
+
+pub struct ArrayIter<'a>(std::slice::Iter<'a, Value>);
+
+impl<'a> Iterator for ArrayIter<'a> {
+    type Item = Result<&'a Value, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Ok)
+    }
+}
+
+impl JsonArray for Vec<Value> {
+    type Element = Value;
+    type Iter<'a> = ArrayIter<'a>;
+
+    fn try_iter(&self) -> Result<Self::Iter<'_>, JsonError> {
+        Ok(ArrayIter(<[Value]>::iter(self)))
+    }
+
+    fn try_get(&self, idx: usize) -> Result<Option<&Self::Element>, JsonError> {
+        Ok(<[Value]>::get(self, idx))
+    }
+
+    fn len(&self) -> usize {
+        <[Value]>::len(self)
+    }
+}
+
+/// Only `Value::Integer` and finite `Value::F32` / `Value::F64` are numbers.
+impl<'a> JsonNumber<'a> for Value {
+    type Integer = i128;
+
+    fn as_integer<I: TryFrom<<Self as JsonNumber<'a>>::Integer>>(
+        &'a self,
+    ) -> Option<<Self as JsonNumber<'a>>::Integer> {
+        match self {
+            Value::Integer(int) => int
+                .as_u64()
+                .map(i128::from)
+                .or_else(|| int.as_i64().map(i128::from)),
+            _ => None,
+        }
+    }
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Integer(int) => int.as_f64(),
+            Value::F32(float) => Some(f64::from(*float)),
+            Value::F64(float) => Some(*float),
+            _ => None,
+        }
+    }
+    fn as_decimal(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::Integer(int) => {
+                let is_safe = int
+                    .as_u64()
+                    .or_else(|| int.as_i64().map(i64::unsigned_abs))
+                    .is_none_or(|int| int <= MAX_SAFE_INTEGER);
+                (!is_safe).then(|| Cow::Owned(int.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Binary data, extension types, strings with invalid UTF-8 and non-finite floats have no
+/// JSON equivalent and are reported as `JsonError`, as are non-string map keys while iterating.
+impl Json for Value {
+    type Object = Vec<(Value, Value)>;
+    type Array = Vec<Value>;
+    type String = str;
+    type Number = Value;
+
+    fn try_as_object(&self) -> Result<Option<&Vec<(Value, Value)>>, JsonError> {
+        match self {
+            Value::Map(map) => Ok(Some(map)),
+            Value::Binary(_) | Value::Ext(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_array(&self) -> Result<Option<&Vec<Value>>, JsonError> {
+        match self {
+            Value::Array(array) => Ok(Some(array)),
+            Value::Binary(_) | Value::Ext(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_string(&self) -> Result<Option<&str>, JsonError> {
+        match self {
+            Value::String(string) => string.as_str().map(Some).ok_or_else(|| no_equivalent(self)),
+            Value::Binary(_) | Value::Ext(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_number(&self) -> Result<Option<&Value>, JsonError> {
+        match self {
+            Value::Integer(_) => Ok(Some(self)),
+            Value::F32(float) if float.is_finite() => Ok(Some(self)),
+            Value::F64(float) if float.is_finite() => Ok(Some(self)),
+            Value::F32(_) | Value::F64(_) | Value::Binary(_) | Value::Ext(..) => {
+                Err(no_equivalent(self))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_boolean(&self) -> Result<Option<bool>, JsonError> {
+        match self {
+            Value::Boolean(boolean) => Ok(Some(*boolean)),
+            Value::Binary(_) | Value::Ext(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_null(&self) -> Result<Option<()>, JsonError> {
+        match self {
+            Value::Nil => Ok(Some(())),
+            Value::Binary(_) | Value::Ext(..) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Maps are equal if they have the same entries, regardless of their order.
+    fn try_equal(&self, other: &Self) -> Result<bool, JsonError> {
+        match (self, other) {
+            (Value::Map(left), Value::Map(right)) => {
+                if left.len() != right.len() {
+                    return Ok(false);
+                }
+                for (key, value) in left {
+                    match <[(Value, Value)]>::iter(right).find(|(other, _)| key == other) {
+                        Some((_, other)) if value.try_equal(other)? => {}
+                        _ => return Ok(false),
+                    }
+                }
+                Ok(true)
+            }
+            (Value::Array(left), Value::Array(right)) => {
+                if left.len() != right.len() {
+                    return Ok(false);
+                }
+                for (left, right) in <[Value]>::iter(left).zip(right) {
+                    if !left.try_equal(right)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            _ => {
+                self.try_as_number()?;
+                self.try_as_string()?;
+                other.try_as_number()?;
+                other.try_as_string()?;
+                Ok(self == other)
+            }
+        }
+    }
+    /// Parse JSON text, as MessagePack has no text syntax.
+    fn from_str(s: &str) -> Result<Self, JsonError>
+    where
+        Self: Sized,
+    {
+        let value = serde_json::from_str(s).map_err(|err| JsonError::new(Box::new(err)))?;
+        from_json(value)
+    }
+}
+
+/// Convert a `serde_json::Value` without relying on how `serde_json` stores numbers.
+fn from_json(value: serde_json::Value) -> Result<Value, JsonError> {
+    Ok(match value {
+        serde_json::Value::Null => Value::Nil,
+        serde_json::Value::Bool(boolean) => Value::Boolean(boolean),
+        serde_json::Value::Number(number) => {
+            if let Some(int) = number.as_i64() {
+                Value::from(int)
+            } else if let Some(int) = number.as_u64() {
+                Value::from(int)
+            } else if let Some(float) = number.as_f64() {
+                Value::F64(float)
+            } else {
+                return Err(JsonError::new(
+                    format!("Number `{number}` is out of range").into(),
+                ));
+            }
+        }
+        serde_json::Value::String(string) => Value::from(string),
+        serde_json::Value::Array(array) => {
+            Value::Array(array.into_iter().map(from_json).collect::<Result<_, _>>()?)
+        }
+        serde_json::Value::Object(object) => Value::Map(
+            object
+                .into_iter()
+                .map(|(key, value)| Ok((Value::from(key), from_json(value)?)))
+                .collect::<Result<_, JsonError>>()?,
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rmpv::Value;
+    use test_case::test_case;
+
+    use crate::{
+        tests::{
+            assert_array_get, assert_array_number_iter, assert_as_array, assert_as_boolean,
+            assert_as_null, assert_as_number_float, assert_as_number_integer, assert_as_object,
+            assert_as_string, assert_object_get, assert_object_str_number_iter, CustomInteger,
+        },
+        Json, JsonNumber, JsonObject,
+    };
+
+    fn build_object() -> Value {
+        Value::Map(vec![
+            (Value::from("a"), Value::from(1)),
+            (Value::from("c"), Value::from("d")),
+        ])
+    }
+
+    fn build_array() -> Value {
+        Value::Array(vec![Value::from(1)])
+    }
+
+    #[test]
+    fn test_object_get() {
+        assert_object_get(&build_object());
+    }
+
+    #[test]
+    fn test_object_iter() {
+        assert_object_str_number_iter(&build_object());
+    }
+
+    #[test]
+    fn test_array_iter() {
+        assert_array_number_iter(&build_array());
+    }
+
+    #[test]
+    fn test_array_get() {
+        assert_array_get(&build_array());
+    }
+
+    #[test_case(Value::Nil, None)]
+    #[test_case(Value::from(true), None)]
+    #[test_case(Value::from(42), Some(CustomInteger::new(42)))]
+    #[test_case(Value::from(5.15), None)]
+    #[test_case(Value::from("abc"), None)]
+    #[test_case(build_array(), None)]
+    #[test_case(build_object(), None)]
+    fn test_as_number_integer(value: Value, expected: Option<CustomInteger>) {
+        assert_as_number_integer(&value, expected);
+    }
+
+    #[test_case(Value::Nil, None)]
+    #[test_case(Value::from(true), None)]
+    #[test_case(Value::from(42), Some(42.0))]
+    #[test_case(Value::from(5.15), Some(5.15))]
+    #[test_case(Value::F32(0.5), Some(0.5))]
+    #[test_case(Value::from("abc"), None)]
+    #[test_case(build_array(), None)]
+    #[test_case(build_object(), None)]
+    fn test_as_number_float(value: Value, expected: Option<f64>) {
+        assert_as_number_float(&value, expected);
+    }
+
+    #[test_case(Value::from(42), None)]
+    #[test_case(Value::from(5.15), None)]
+    #[test_case(Value::from(u64::MAX), Some("18446744073709551615"))]
+    #[test_case(Value::from(i64::MIN), Some("-9223372036854775808"))]
+    fn test_as_number_decimal(value: Value, expected: Option<&str>) {
+        let number = value.as_number().expect("Not a number");
+        assert_eq!(number.as_decimal().as_deref(), expected);
+    }
+
+    #[test_case(Value::Nil, false)]
+    #[test_case(Value::from(42), false)]
+    #[test_case(Value::from("abc"), false)]
+    #[test_case(build_array(), false)]
+    #[test_case(build_object(), true)]
+    fn test_as_object(value: Value, expected: bool) {
+        assert_as_object(&value, expected);
+    }
+
+    #[test_case(Value::Nil, false)]
+    #[test_case(Value::from("abc"), false)]
+    #[test_case(build_array(), true)]
+    #[test_case(build_object(), false)]
+    fn test_as_array(value: Value, expected: bool) {
+        assert_as_array(&value, expected);
+    }
+
+    #[test_case(Value::Nil, None)]
+    #[test_case(Value::from(42), None)]
+    #[test_case(Value::from("abc"), Some("abc"))]
+    #[test_case(build_array(), None)]
+    fn test_as_string(value: Value, expected: Option<&str>) {
+        assert_as_string(&value, expected);
+    }
+
+    #[test_case(Value::Nil, None)]
+    #[test_case(Value::from(true), Some(true))]
+    #[test_case(Value::from("abc"), None)]
+    fn test_as_boolean(value: Value, expected: Option<bool>) {
+        assert_as_boolean(&value, expected);
+    }
+
+    #[test_case(Value::Nil, Some(()))]
+    #[test_case(Value::from(true), None)]
+    #[test_case(Value::from("abc"), None)]
+    fn test_as_null(value: Value, expected: Option<()>) {
+        assert_as_null(&value, expected);
+    }
+
+    #[test_case(Value::Nil)]
+    #[test_case(Value::from(42))]
+    #[test_case(build_array())]
+    #[test_case(build_object())]
+    fn test_equal(value: Value) {
+        assert!(value.equal(&value));
+        assert!(!value.equal(&Value::from("something else")));
+    }
+
+    #[test]
+    fn test_equal_key_order() {
+        let reordered = Value::Map(vec![
+            (Value::from("c"), Value::from("d")),
+            (Value::from("a"), Value::from(1)),
+        ]);
+        assert!(build_object().equal(&reordered));
+        let other = Value::Map(vec![
+            (Value::from("c"), Value::from("d")),
+            (Value::from("a"), Value::from(2)),
+        ]);
+        assert!(!build_object().equal(&other));
+    }
+
+    #[test_case(Value::Binary(vec![1, 2]))]
+    #[test_case(Value::Ext(1, vec![0]))]
+    #[test_case(Value::F64(f64::NAN))]
+    #[test_case(Value::F32(f32::NEG_INFINITY))]
+    fn test_no_json_equivalent(value: Value) {
+        assert!(value.try_as_number().is_err());
+        assert!(!matches!(value.try_is_string(), Ok(true)));
+        assert!(value.try_equal(&value).is_err());
+        assert!(Value::Array(vec![value.clone()])
+            .try_equal(&Value::Array(vec![value]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let value: Value =
+            rmpv::decode::read_value(&mut &[0xa1, 0xff][..]).expect("Valid MessagePack");
+        assert!(value.try_as_string().is_err());
+    }
+
+    #[test]
+    fn test_non_string_key() {
+        let value = Value::Map(vec![
+            (Value::from(1), Value::Nil),
+            (Value::from("b"), Value::Nil),
+        ]);
+        let object = value.as_object().expect("Should be an object");
+        let keys: Vec<_> = JsonObject::iter(object).map(|(key, _)| key.ok()).collect();
+        assert_eq!(keys, [None, Some("b")]);
+        assert!(JsonObject::get(object, "b").is_some());
+        assert!(JsonObject::get(object, "1").is_none());
+    }
+
+    #[test]
+    fn test_decode() {
+        // {"a": [1, -2.5, null]}
+        let bytes = [
+            0x81, 0xa1, 0x61, 0x93, 0x01, 0xcb, 0xc0, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0xc0,
+        ];
+        let value = rmpv::decode::read_value(&mut &bytes[..]).expect("Valid MessagePack");
+        assert!(value.equal(&Value::from_str(r#"{"a": [1, -2.5, null]}"#).expect("Valid JSON")));
+    }
+
+    #[test_case("18446744073709551615", Value::from(u64::MAX))]
+    #[test_case("-9223372036854775808", Value::from(i64::MIN))]
+    #[test_case("1.5", Value::F64(1.5))]
+    fn test_from_str_number(text: &str, expected: Value) {
+        assert_eq!(Value::from_str(text).expect("Valid JSON"), expected);
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        assert!(Value::from_str("{").is_err());
+    }
+}
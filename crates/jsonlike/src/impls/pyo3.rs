@@ -1,4 +1,6 @@
-use crate::prelude::*;
+use std::borrow::Cow;
+
+use crate::{prelude::*, MAX_SAFE_INTEGER};
 use pyo3::{
     exceptions::PyIndexError,
     prelude::*,
@@ -7,6 +9,27 @@ use pyo3::{
     },
 };
 
+/// Fail for objects that are neither of the JSON types nor a finite float.
+fn check(value: &PyAny) -> Result<(), JsonError> {
+    if value.downcast::<PyDict>().is_ok()
+        || value.downcast::<PyList>().is_ok()
+        || value.downcast::<PyString>().is_ok()
+        || value.downcast_exact::<PyLong>().is_ok()
+        || value.downcast_exact::<PyBool>().is_ok()
+        || value.downcast::<PyNone>().is_ok()
+    {
+        return Ok(());
+    }
+    let description = match value.downcast::<PyFloat>() {
+        Ok(float) if float.value().is_finite() => return Ok(()),
+        Ok(float) => format!("Float `{float}`"),
+        Err(_) => format!("Python object of type `{}`", value.get_type().name()?),
+    };
+    Err(JsonError::new(
+        format!("{description} has no JSON equivalent").into(),
+    ))
+}
+
 pub struct PyDictIteratorAdapter<'a>(PyDictIterator<'a>);
 
 impl From<PyErr> for JsonError {
@@ -46,6 +69,10 @@ impl JsonObject for PyDict {
     fn iter(&self) -> Self::Iter<'_> {
         PyDictIteratorAdapter(PyDict::iter(self))
     }
+
+    fn len(&self) -> usize {
+        PyDict::len(self)
+    }
 }
 
 pub struct PyIteratorAdapter<'a>(&'a PyIterator);
@@ -73,6 +100,10 @@ impl JsonArray for PyList {
             Err(err) => Err(err.into()),
         }
     }
+
+    fn len(&self) -> usize {
+        PyList::len(self)
+    }
 }
 
 impl<'a> JsonNumber<'a> for PyAny {
@@ -83,17 +114,14 @@ impl<'a> JsonNumber<'a> for PyAny {
     fn as_integer<I: TryFrom<<Self as JsonNumber<'a>>::Integer>>(
         &'a self,
     ) -> Option<<PyAny as JsonNumber<'a>>::Integer> {
-        if let Ok(integer) = self.downcast_exact::<PyLong>() {
-            #[cfg(not(feature = "arbitrary_precision"))]
-            {
-                integer.extract().ok()
-            }
-            #[cfg(feature = "arbitrary_precision")]
-            {
-                Some(integer)
-            }
-        } else {
-            None
+        let integer = self.downcast_exact::<PyLong>().ok()?;
+        #[cfg(not(feature = "arbitrary_precision"))]
+        {
+            integer.extract().ok()
+        }
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            Some(integer)
         }
     }
     fn as_float(&self) -> Option<f64> {
@@ -105,8 +133,19 @@ impl<'a> JsonNumber<'a> for PyAny {
             None
         }
     }
+    fn as_decimal(&self) -> Option<Cow<'_, str>> {
+        let integer = self.downcast_exact::<PyLong>().ok()?;
+        if let Ok(int) = integer.extract::<i64>() {
+            if int.unsigned_abs() <= MAX_SAFE_INTEGER {
+                return None;
+            }
+        }
+        Some(Cow::Owned(integer.str().ok()?.to_str().ok()?.to_owned()))
+    }
 }
 
+/// Finite floats and objects of the JSON types are readable, i.e. dicts, lists, strings, exact
+/// ints, bools and `None`. Other objects are reported as `JsonError`.
 impl Json for PyAny {
     type Object = PyDict;
     type Array = PyList;
@@ -114,14 +153,17 @@ impl Json for PyAny {
     type Number = PyAny;
 
     fn try_as_object(&self) -> Result<Option<&Self::Object>, JsonError> {
+        check(self)?;
         Ok(self.downcast::<PyDict>().ok())
     }
 
     fn try_as_array(&self) -> Result<Option<&Self::Array>, JsonError> {
+        check(self)?;
         Ok(self.downcast::<PyList>().ok())
     }
 
     fn try_as_string(&self) -> Result<Option<&Self::String>, JsonError> {
+        check(self)?;
         if let Ok(pystring) = self.downcast::<PyString>() {
             Ok(pystring.to_str().map(Some)?)
         } else {
@@ -130,6 +172,7 @@ impl Json for PyAny {
     }
 
     fn try_as_number(&self) -> Result<Option<&Self::Number>, JsonError> {
+        check(self)?;
         Ok(
             if self.downcast_exact::<PyLong>().is_ok() || self.downcast::<PyFloat>().is_ok() {
                 Some(self)
@@ -140,21 +183,29 @@ impl Json for PyAny {
     }
 
     fn try_as_boolean(&self) -> Result<Option<bool>, JsonError> {
+        check(self)?;
         Ok(self.downcast_exact::<PyBool>().ok().map(PyBool::is_true))
     }
 
     fn try_as_null(&self) -> Result<Option<()>, JsonError> {
+        check(self)?;
         Ok(self.downcast::<PyNone>().ok().map(|_| ()))
     }
 
     fn try_equal(&self, other: &Self) -> Result<bool, JsonError> {
         Ok(self.eq(other)?)
     }
-    fn from_str(s: &str) -> Result<Self, JsonError>
+    /// Python objects are only available by reference while the GIL is held, so there is no
+    /// owned `PyAny` to return. Load schemas with another backend, e.g. `serde_json`; instances
+    /// may still be Python objects.
+    fn from_str(_s: &str) -> Result<Self, JsonError>
     where
         Self: Sized,
     {
-        todo!("Parse JSON with ... python's json module?")
+        Err(JsonError::new(
+            "Python objects can not be created from JSON text, use another backend for the schema"
+                .into(),
+        ))
     }
 }
 
@@ -194,31 +245,31 @@ mod tests {
         py.eval(code, None, None).expect("Invalid Python code")
     }
 
-    fn build_object(py: Python) -> &PyAny {
+    fn build_object(py: Python<'_>) -> &PyAny {
         pyrun(py, "{\"a\": 1, \"c\": \"d\"}")
     }
 
-    fn build_array(py: Python) -> &PyAny {
+    fn build_array(py: Python<'_>) -> &PyAny {
         pyrun(py, "[1]")
     }
 
-    fn build_string(py: Python) -> &PyAny {
+    fn build_string(py: Python<'_>) -> &PyAny {
         pyrun(py, "\"abc\"")
     }
 
-    fn build_integer(py: Python) -> &PyAny {
+    fn build_integer(py: Python<'_>) -> &PyAny {
         pyrun(py, "42")
     }
 
-    fn build_float(py: Python) -> &PyAny {
+    fn build_float(py: Python<'_>) -> &PyAny {
         pyrun(py, "5.15")
     }
 
-    fn build_bool(py: Python) -> &PyAny {
+    fn build_bool(py: Python<'_>) -> &PyAny {
         pyrun(py, "True")
     }
 
-    fn build_none(py: Python) -> &PyAny {
+    fn build_none(py: Python<'_>) -> &PyAny {
         pyrun(py, "None")
     }
 
@@ -316,6 +367,17 @@ mod tests {
         pytest(|py| assert_as_number_float(factory(py), expected));
     }
 
+    #[test_case("42", None)]
+    #[test_case("5.15", None)]
+    #[test_case("2 ** 53 + 1", Some("9007199254740993"))]
+    #[test_case("-(2 ** 70)", Some("-1180591620717411303424"))]
+    fn test_as_number_decimal(code: &str, expected: Option<&str>) {
+        pytest(|py| {
+            let number = pyrun(py, code).as_number().expect("Not a number");
+            assert_eq!(number.as_decimal().as_deref(), expected);
+        });
+    }
+
     #[test_case(build_none, None)]
     #[test_case(build_bool, Some(true))]
     #[test_case(build_float, None)]
@@ -353,4 +415,27 @@ mod tests {
             assert!(!value.equal(other));
         });
     }
+
+    #[test_case("float('nan')")]
+    #[test_case("float('-inf')")]
+    #[test_case("(1, 2)")]
+    #[test_case("b'ab'")]
+    #[test_case("{1, 2}")]
+    fn test_no_json_equivalent(code: &str) {
+        pytest(|py| {
+            let value = pyrun(py, code);
+            assert!(value.try_as_number().is_err());
+            assert!(value.try_as_object().is_err());
+            assert!(value.try_is_null().is_err());
+        });
+    }
+
+    #[test]
+    fn test_from_str() {
+        let error = <PyAny as Json>::from_str("{}").expect_err("No owned Python objects");
+        assert_eq!(
+            error.to_string(),
+            "Python objects can not be created from JSON text, use another backend for the schema"
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use crate::prelude::*;
 
 pub struct ObjectIter<'a>(serde_json::map::Iter<'a>);
@@ -22,6 +24,10 @@ impl JsonObject for serde_json::Map<String, serde_json::Value> {
     fn iter(&self) -> Self::Iter<'_> {
         ObjectIter(<serde_json::Map<String, serde_json::Value>>::iter(self))
     }
+
+    fn len(&self) -> usize {
+        <serde_json::Map<String, serde_json::Value>>::len(self)
+    }
 }
 
 pub struct ArrayIter<'a>(std::slice::Iter<'a, serde_json::Value>);
@@ -45,6 +51,10 @@ impl JsonArray for Vec<serde_json::Value> {
     fn try_get(&self, idx: usize) -> Result<Option<&Self::Element>, JsonError> {
         Ok(<[serde_json::Value]>::get(self, idx))
     }
+
+    fn len(&self) -> usize {
+        <[serde_json::Value]>::len(self)
+    }
 }
 
 #[cfg(not(feature = "arbitrary_precision"))]
@@ -77,6 +87,20 @@ impl<'a> JsonNumber<'a> for serde_json::Number {
     fn as_float(&self) -> Option<f64> {
         self.as_f64()
     }
+    fn as_decimal(&self) -> Option<Cow<'_, str>> {
+        #[cfg(feature = "arbitrary_precision")]
+        {
+            Some(Cow::Borrowed(self.as_str()))
+        }
+        #[cfg(not(feature = "arbitrary_precision"))]
+        {
+            let is_safe = self
+                .as_u64()
+                .or_else(|| self.as_i64().map(i64::unsigned_abs))
+                .is_none_or(|int| int <= crate::MAX_SAFE_INTEGER);
+            (!is_safe).then(|| Cow::Owned(self.to_string()))
+        }
+    }
 }
 
 impl Json for serde_json::Value {
@@ -140,6 +164,8 @@ mod tests {
 
     #[cfg(not(feature = "arbitrary_precision"))]
     use super::SerdeInteger;
+    #[cfg(not(feature = "arbitrary_precision"))]
+    use crate::JsonNumber;
 
     #[cfg(feature = "arbitrary_precision")]
     impl TryFrom<&str> for CustomInteger {
@@ -212,6 +238,18 @@ mod tests {
         assert_as_number_float(&value, expected);
     }
 
+    #[cfg(not(feature = "arbitrary_precision"))]
+    #[test_case(json!(42), None)]
+    #[test_case(json!(5.15), None)]
+    #[test_case(json!(9007199254740991_i64), None)]
+    #[test_case(json!(9007199254740993_i64), Some("9007199254740993"))]
+    #[test_case(json!(-9007199254740993_i64), Some("-9007199254740993"))]
+    #[test_case(json!(u64::MAX), Some("18446744073709551615"))]
+    fn test_as_number_decimal(value: Value, expected: Option<&str>) {
+        let number = value.as_number().expect("Not a number");
+        assert_eq!(number.as_decimal().as_deref(), expected);
+    }
+
     #[test_case(json!(null), false)]
     #[test_case(json!(true), false)]
     #[test_case(json!(5.15), false)]
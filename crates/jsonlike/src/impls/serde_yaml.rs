@@ -0,0 +1,348 @@
+use std::borrow::Cow;
+
+use serde_yaml::{value::TaggedValue, Mapping, Number, Value};
+
+use crate::{prelude::*, MAX_SAFE_INTEGER};
+
+fn tagged(value: &TaggedValue) -> JsonError {
+    JsonError::new(format!("YAML tag `{}` has no JSON equivalent", value.tag).into())
+}
+
+pub struct ObjectIter<'a>(serde_yaml::mapping::Iter<'a>);
+
+impl<'a> Iterator for ObjectIter<'a> {
+    type Item = (Result<&'a str, JsonError>, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| match key {
+            Value::String(key) => (Ok(key.as_str()), value),
+            _ => (
+                Err(JsonError::new(
+                    format!("YAML mapping key {key:?} is not a string").into(),
+                )),
+                value,
+            ),
+        })
+    }
+}
+
+impl JsonObject for Mapping {
+    type Key = str;
+    type Value = Value;
+    type Iter<'a> = ObjectIter<'a>;
+
+    fn get(&self, key: &str) -> Option<&Self::Value> {
+        Mapping::get(self, key)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        ObjectIter(Mapping::iter(self))
+    }
+
+    fn len(&self) -> usize {
+        Mapping::len(self)
+    }
+}
+
+pub struct ArrayIter<'a>(std::slice::Iter<'a, Value>);
+
+impl<'a> Iterator for ArrayIter<'a> {
+    type Item = Result<&'a Value, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Ok)
+    }
+}
+
+impl JsonArray for Vec<Value> {
+    type Element = Value;
+    type Iter<'a> = ArrayIter<'a>;
+
+    fn try_iter(&self) -> Result<Self::Iter<'_>, JsonError> {
+        Ok(ArrayIter(<[Value]>::iter(self)))
+    }
+
+    fn try_get(&self, idx: usize) -> Result<Option<&Self::Element>, JsonError> {
+        Ok(<[Value]>::get(self, idx))
+    }
+
+    fn len(&self) -> usize {
+        <[Value]>::len(self)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum YamlInteger {
+    PosInt(u64),
+    NegInt(i64),
+}
+
+impl<'a> JsonNumber<'a> for Number {
+    type Integer = YamlInteger;
+
+    fn as_integer<I: TryFrom<Self::Integer>>(&'a self) -> Option<Self::Integer> {
+        if let Some(int) = self.as_u64() {
+            Some(YamlInteger::PosInt(int))
+        } else {
+            self.as_i64().map(YamlInteger::NegInt)
+        }
+    }
+    fn as_float(&self) -> Option<f64> {
+        self.as_f64()
+    }
+    fn as_decimal(&self) -> Option<Cow<'_, str>> {
+        let is_safe = self
+            .as_u64()
+            .or_else(|| self.as_i64().map(i64::unsigned_abs))
+            .is_none_or(|int| int <= MAX_SAFE_INTEGER);
+        (!is_safe).then(|| Cow::Owned(self.to_string()))
+    }
+}
+
+impl Json for Value {
+    type Object = Mapping;
+    type Array = Vec<Value>;
+    type String = str;
+    type Number = Number;
+
+    fn try_as_object(&self) -> Result<Option<&Mapping>, JsonError> {
+        match self {
+            Value::Tagged(value) => Err(tagged(value)),
+            _ => Ok(self.as_mapping()),
+        }
+    }
+
+    fn try_as_array(&self) -> Result<Option<&Vec<Value>>, JsonError> {
+        match self {
+            Value::Tagged(value) => Err(tagged(value)),
+            _ => Ok(self.as_sequence()),
+        }
+    }
+
+    fn try_as_string(&self) -> Result<Option<&str>, JsonError> {
+        match self {
+            Value::Tagged(value) => Err(tagged(value)),
+            _ => Ok(self.as_str()),
+        }
+    }
+
+    fn try_as_number(&self) -> Result<Option<&Number>, JsonError> {
+        match self {
+            Value::Tagged(value) => Err(tagged(value)),
+            Value::Number(number) if number.is_nan() || number.is_infinite() => Err(
+                JsonError::new(format!("YAML number `{number}` has no JSON equivalent").into()),
+            ),
+            Value::Number(number) => Ok(Some(number)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_boolean(&self) -> Result<Option<bool>, JsonError> {
+        match self {
+            Value::Tagged(value) => Err(tagged(value)),
+            _ => Ok(self.as_bool()),
+        }
+    }
+
+    fn try_as_null(&self) -> Result<Option<()>, JsonError> {
+        match self {
+            Value::Tagged(value) => Err(tagged(value)),
+            Value::Null => Ok(Some(())),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_equal(&self, other: &Self) -> Result<bool, JsonError> {
+        Ok(self.eq(other))
+    }
+    fn from_str(s: &str) -> Result<Self, JsonError>
+    where
+        Self: Sized,
+    {
+        let mut value: Value =
+            serde_yaml::from_str(s).map_err(|err| JsonError::new(Box::new(err)))?;
+        value
+            .apply_merge()
+            .map_err(|err| JsonError::new(Box::new(err)))?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::TryFromIntError;
+
+    use serde_yaml::Value;
+    use test_case::test_case;
+
+    use super::YamlInteger;
+    use crate::{
+        tests::{
+            assert_array_get, assert_array_number_iter, assert_as_array, assert_as_boolean,
+            assert_as_null, assert_as_number_float, assert_as_number_integer, assert_as_object,
+            assert_as_string, assert_object_get, assert_object_str_number_iter, CustomInteger,
+        },
+        Json, JsonNumber, JsonObject,
+    };
+
+    impl TryFrom<YamlInteger> for CustomInteger {
+        type Error = TryFromIntError;
+
+        fn try_from(value: YamlInteger) -> Result<Self, Self::Error> {
+            match value {
+                YamlInteger::PosInt(i) => Ok(CustomInteger::new(i64::try_from(i)?)),
+                YamlInteger::NegInt(i) => Ok(CustomInteger::new(i)),
+            }
+        }
+    }
+
+    fn yaml(s: &str) -> Value {
+        Value::from_str(s).expect("Valid YAML")
+    }
+
+    #[test]
+    fn test_object_get() {
+        assert_object_get(&yaml("a: 1\nc: d"));
+    }
+
+    #[test]
+    fn test_object_iter() {
+        assert_object_str_number_iter(&yaml("a: 1\nc: d"));
+    }
+
+    #[test]
+    fn test_array_iter() {
+        assert_array_number_iter(&yaml("- 1"));
+    }
+
+    #[test]
+    fn test_array_get() {
+        assert_array_get(&yaml("[1]"));
+    }
+
+    #[test_case("null", None)]
+    #[test_case("true", None)]
+    #[test_case("42", Some(CustomInteger::new(42)))]
+    #[test_case("5.15", None)]
+    #[test_case("abc", None)]
+    #[test_case("[1]", None)]
+    #[test_case("a: 1", None)]
+    fn test_as_number_integer(value: &str, expected: Option<CustomInteger>) {
+        assert_as_number_integer(&yaml(value), expected);
+    }
+
+    #[test_case("~", None)]
+    #[test_case("true", None)]
+    #[test_case("42", Some(42.0))]
+    #[test_case("5.15", Some(5.15))]
+    #[test_case("abc", None)]
+    #[test_case("[1]", None)]
+    #[test_case("a: 1", None)]
+    fn test_as_number_float(value: &str, expected: Option<f64>) {
+        assert_as_number_float(&yaml(value), expected);
+    }
+
+    #[test_case("42", None)]
+    #[test_case("5.15", None)]
+    #[test_case("9007199254740993", Some("9007199254740993"))]
+    #[test_case("-9007199254740993", Some("-9007199254740993"))]
+    fn test_as_number_decimal(value: &str, expected: Option<&str>) {
+        let value = yaml(value);
+        let number = value.as_number().expect("Not a number");
+        assert_eq!(number.as_decimal().as_deref(), expected);
+    }
+
+    #[test_case("null", false)]
+    #[test_case("5.15", false)]
+    #[test_case("abc", false)]
+    #[test_case("[1]", false)]
+    #[test_case("a: 1", true)]
+    #[test_case("{a: 1}", true)]
+    fn test_as_object(value: &str, expected: bool) {
+        assert_as_object(&yaml(value), expected);
+    }
+
+    #[test_case("null", false)]
+    #[test_case("abc", false)]
+    #[test_case("[1]", true)]
+    #[test_case("- 1", true)]
+    #[test_case("a: 1", false)]
+    fn test_as_array(value: &str, expected: bool) {
+        assert_as_array(&yaml(value), expected);
+    }
+
+    #[test_case("null", None)]
+    #[test_case("42", None)]
+    #[test_case("abc", Some("abc"))]
+    #[test_case("'42'", Some("42"))]
+    #[test_case("2024-01-01", Some("2024-01-01"))]
+    #[test_case("[1]", None)]
+    fn test_as_string(value: &str, expected: Option<&str>) {
+        assert_as_string(&yaml(value), expected);
+    }
+
+    #[test_case("null", None)]
+    #[test_case("true", Some(true))]
+    #[test_case("false", Some(false))]
+    #[test_case("abc", None)]
+    fn test_as_boolean(value: &str, expected: Option<bool>) {
+        assert_as_boolean(&yaml(value), expected);
+    }
+
+    #[test_case("null", Some(()))]
+    #[test_case("~", Some(()))]
+    #[test_case("", Some(()))]
+    #[test_case("true", None)]
+    #[test_case("abc", None)]
+    fn test_as_null(value: &str, expected: Option<()>) {
+        assert_as_null(&yaml(value), expected);
+    }
+
+    #[test_case("null")]
+    #[test_case("42")]
+    #[test_case("abc")]
+    #[test_case("[1]")]
+    #[test_case("a: 1")]
+    fn test_equal(value: &str) {
+        let value = yaml(value);
+        assert!(value.equal(&value));
+        assert!(!value.equal(&yaml("something else")));
+    }
+
+    #[test]
+    fn test_anchors_and_merge_keys() {
+        let value = yaml("base: &base {a: 1}\nderived:\n  <<: *base\n  b: 2\nalias: *base");
+        assert!(value.equal(&yaml("base: {a: 1}\nderived: {a: 1, b: 2}\nalias: {a: 1}")));
+    }
+
+    #[test_case("!custom 1")]
+    #[test_case("!custom {a: 1}")]
+    #[test_case(".nan")]
+    #[test_case("-.inf")]
+    fn test_no_json_equivalent(value: &str) {
+        let value = yaml(value);
+        assert!(value.try_as_number().is_err());
+    }
+
+    #[test]
+    fn test_tagged() {
+        let value = yaml("!custom a");
+        assert!(value.try_as_string().is_err());
+        assert!(value.try_as_object().is_err());
+        assert!(value.try_as_null().is_err());
+    }
+
+    #[test]
+    fn test_non_string_key() {
+        let value = yaml("1: a\nb: c");
+        let object = value.as_object().expect("Should be an object");
+        let keys: Vec<_> = JsonObject::iter(object).map(|(key, _)| key.ok()).collect();
+        assert_eq!(keys, [None, Some("b")]);
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert!(Value::from_str("a: [").is_err());
+        assert!(Value::from_str("a: 1\n---\nb: 2").is_err());
+    }
+}
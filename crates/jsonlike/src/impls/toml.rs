@@ -0,0 +1,320 @@
+use std::borrow::Cow;
+
+use toml::{Table, Value};
+
+use crate::{prelude::*, MAX_SAFE_INTEGER};
+
+fn no_equivalent(value: &Value) -> JsonError {
+    JsonError::new(format!("TOML value `{value}` has no JSON equivalent").into())
+}
+
+/// Datetimes have no text to borrow, so they are replaced with their RFC 3339 form.
+fn datetimes_to_strings(value: &mut Value) {
+    match value {
+        Value::Datetime(datetime) => *value = Value::String(datetime.to_string()),
+        Value::Array(array) => array.iter_mut().for_each(datetimes_to_strings),
+        Value::Table(table) => table
+            .iter_mut()
+            .for_each(|(_, value)| datetimes_to_strings(value)),
+        _ => {}
+    }
+}
+
+pub struct ObjectIter<'a>(toml::map::Iter<'a>);
+
+impl<'a> Iterator for ObjectIter<'a> {
+    type Item = (Result<&'a String, JsonError>, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (Ok(k), v))
+    }
+}
+
+impl JsonObject for Table {
+    type Key = String;
+    type Value = Value;
+    type Iter<'a> = ObjectIter<'a>;
+
+    fn get(&self, key: &str) -> Option<&Self::Value> {
+        Table::get(self, key)
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        ObjectIter(Table::iter(self))
+    }
+
+    fn len(&self) -> usize {
+        Table::len(self)
+    }
+}
+
+pub struct ArrayIter<'a>(std::slice::Iter<'a, Value>);
+
+impl<'a> Iterator for ArrayIter<'a> {
+    type Item = Result<&'a Value, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Ok)
+    }
+}
+
+impl JsonArray for Vec<Value> {
+    type Element = Value;
+    type Iter<'a> = ArrayIter<'a>;
+
+    fn try_iter(&self) -> Result<Self::Iter<'_>, JsonError> {
+        Ok(ArrayIter(<[Value]>::iter(self)))
+    }
+
+    fn try_get(&self, idx: usize) -> Result<Option<&Self::Element>, JsonError> {
+        Ok(<[Value]>::get(self, idx))
+    }
+
+    fn len(&self) -> usize {
+        <[Value]>::len(self)
+    }
+}
+
+/// Only `Value::Integer` and finite `Value::Float` are numbers.
+impl<'a> JsonNumber<'a> for Value {
+    type Integer = i64;
+
+    fn as_integer<I: TryFrom<<Self as JsonNumber<'a>>::Integer>>(
+        &'a self,
+    ) -> Option<<Self as JsonNumber<'a>>::Integer> {
+        Value::as_integer(self)
+    }
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Integer(int) => Some(*int as f64),
+            Value::Float(float) => Some(*float),
+            _ => None,
+        }
+    }
+    fn as_decimal(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::Integer(int) if int.unsigned_abs() > MAX_SAFE_INTEGER => {
+                Some(Cow::Owned(int.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// TOML has no null, so `try_as_null` never matches.
+///
+/// Datetimes are exposed as strings by `Json::from_str`, so `format: date-time` applies to
+/// them; values built elsewhere that still contain a `Value::Datetime` report it as
+/// `JsonError`, as do `nan` and `inf`.
+impl Json for Value {
+    type Object = Table;
+    type Array = Vec<Value>;
+    type String = str;
+    type Number = Value;
+
+    fn try_as_object(&self) -> Result<Option<&Table>, JsonError> {
+        match self {
+            Value::Datetime(_) => Err(no_equivalent(self)),
+            _ => Ok(self.as_table()),
+        }
+    }
+
+    fn try_as_array(&self) -> Result<Option<&Vec<Value>>, JsonError> {
+        match self {
+            Value::Datetime(_) => Err(no_equivalent(self)),
+            _ => Ok(self.as_array()),
+        }
+    }
+
+    fn try_as_string(&self) -> Result<Option<&str>, JsonError> {
+        match self {
+            Value::Datetime(_) => Err(no_equivalent(self)),
+            _ => Ok(self.as_str()),
+        }
+    }
+
+    fn try_as_number(&self) -> Result<Option<&Value>, JsonError> {
+        match self {
+            Value::Integer(_) => Ok(Some(self)),
+            Value::Float(float) if float.is_finite() => Ok(Some(self)),
+            Value::Float(_) | Value::Datetime(_) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_as_boolean(&self) -> Result<Option<bool>, JsonError> {
+        match self {
+            Value::Datetime(_) => Err(no_equivalent(self)),
+            _ => Ok(self.as_bool()),
+        }
+    }
+
+    fn try_as_null(&self) -> Result<Option<()>, JsonError> {
+        match self {
+            Value::Datetime(_) => Err(no_equivalent(self)),
+            _ => Ok(None),
+        }
+    }
+
+    fn try_equal(&self, other: &Self) -> Result<bool, JsonError> {
+        Ok(self.eq(other))
+    }
+    fn from_str(s: &str) -> Result<Self, JsonError>
+    where
+        Self: Sized,
+    {
+        let table: Table = toml::from_str(s).map_err(|err| JsonError::new(Box::new(err)))?;
+        let mut value = Value::Table(table);
+        datetimes_to_strings(&mut value);
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+    use toml::Value;
+
+    use crate::{
+        tests::{
+            assert_array_get, assert_array_number_iter, assert_as_array, assert_as_boolean,
+            assert_as_null, assert_as_number_float, assert_as_number_integer, assert_as_object,
+            assert_as_string, assert_object_get, assert_object_str_number_iter, CustomInteger,
+        },
+        Json, JsonNumber, JsonObject,
+    };
+
+    /// The value of `v` in a TOML document.
+    fn toml(v: &str) -> Value {
+        let document = Value::from_str(&format!("v = {v}")).expect("Valid TOML");
+        document
+            .as_object()
+            .and_then(|table| JsonObject::get(table, "v"))
+            .cloned()
+            .expect("Key exists")
+    }
+
+    #[test]
+    fn test_object_get() {
+        assert_object_get(&Value::from_str("a = 1\nc = \"d\"").expect("Valid TOML"));
+    }
+
+    #[test]
+    fn test_object_iter() {
+        assert_object_str_number_iter(&Value::from_str("a = 1\nc = \"d\"").expect("Valid TOML"));
+    }
+
+    #[test]
+    fn test_array_iter() {
+        assert_array_number_iter(&toml("[1]"));
+    }
+
+    #[test]
+    fn test_array_get() {
+        assert_array_get(&toml("[1]"));
+    }
+
+    #[test_case("true", None)]
+    #[test_case("42", Some(CustomInteger::new(42)))]
+    #[test_case("5.15", None)]
+    #[test_case("\"abc\"", None)]
+    #[test_case("[1]", None)]
+    #[test_case("{a = 1}", None)]
+    fn test_as_number_integer(value: &str, expected: Option<CustomInteger>) {
+        assert_as_number_integer(&toml(value), expected);
+    }
+
+    #[test_case("true", None)]
+    #[test_case("42", Some(42.0))]
+    #[test_case("5.15", Some(5.15))]
+    #[test_case("\"abc\"", None)]
+    #[test_case("[1]", None)]
+    #[test_case("{a = 1}", None)]
+    fn test_as_number_float(value: &str, expected: Option<f64>) {
+        assert_as_number_float(&toml(value), expected);
+    }
+
+    #[test_case("42", None)]
+    #[test_case("5.15", None)]
+    #[test_case("9007199254740993", Some("9007199254740993"))]
+    #[test_case("-9223372036854775808", Some("-9223372036854775808"))]
+    fn test_as_number_decimal(value: &str, expected: Option<&str>) {
+        let value = toml(value);
+        let number = value.as_number().expect("Not a number");
+        assert_eq!(number.as_decimal().as_deref(), expected);
+    }
+
+    #[test_case("true", false)]
+    #[test_case("5.15", false)]
+    #[test_case("\"abc\"", false)]
+    #[test_case("[1]", false)]
+    #[test_case("{a = 1}", true)]
+    fn test_as_object(value: &str, expected: bool) {
+        assert_as_object(&toml(value), expected);
+    }
+
+    #[test_case("true", false)]
+    #[test_case("\"abc\"", false)]
+    #[test_case("[1]", true)]
+    #[test_case("{a = 1}", false)]
+    fn test_as_array(value: &str, expected: bool) {
+        assert_as_array(&toml(value), expected);
+    }
+
+    #[test_case("true", None)]
+    #[test_case("42", None)]
+    #[test_case("\"abc\"", Some("abc"))]
+    #[test_case("'''abc'''", Some("abc"))]
+    #[test_case("1979-05-27T07:32:00Z", Some("1979-05-27T07:32:00Z"))]
+    #[test_case("1979-05-27 07:32:00-07:00", Some("1979-05-27T07:32:00-07:00"))]
+    #[test_case("1979-05-27", Some("1979-05-27"))]
+    #[test_case("07:32:00", Some("07:32:00"))]
+    fn test_as_string(value: &str, expected: Option<&str>) {
+        assert_as_string(&toml(value), expected);
+    }
+
+    #[test_case("true", Some(true))]
+    #[test_case("false", Some(false))]
+    #[test_case("\"abc\"", None)]
+    fn test_as_boolean(value: &str, expected: Option<bool>) {
+        assert_as_boolean(&toml(value), expected);
+    }
+
+    #[test_case("true")]
+    #[test_case("\"abc\"")]
+    #[test_case("[1]")]
+    fn test_as_null(value: &str) {
+        assert_as_null(&toml(value), None);
+    }
+
+    #[test_case("42")]
+    #[test_case("\"abc\"")]
+    #[test_case("[1]")]
+    #[test_case("{a = 1}")]
+    fn test_equal(value: &str) {
+        let value = toml(value);
+        assert!(value.equal(&value));
+        assert!(!value.equal(&toml("\"something else\"")));
+    }
+
+    #[test_case("nan")]
+    #[test_case("-inf")]
+    fn test_non_finite(value: &str) {
+        assert!(toml(value).try_as_number().is_err());
+    }
+
+    #[test]
+    fn test_datetime() {
+        let value = Value::Datetime("1979-05-27T07:32:00Z".parse().expect("Valid datetime"));
+        assert!(value.try_as_string().is_err());
+        assert!(value.try_as_number().is_err());
+        let nested = Value::from_str("a = [{b = 1979-05-27}]").expect("Valid TOML");
+        assert!(nested.equal(&Value::from_str("a = [{b = \"1979-05-27\"}]").expect("Valid TOML")));
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert!(Value::from_str("a = ").is_err());
+        assert!(Value::from_str("").expect("Valid TOML").is_object());
+    }
+}
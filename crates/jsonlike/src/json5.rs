@@ -0,0 +1,653 @@
+//! JSON5 and JSONC documents with source positions.
+//!
+//! Accepts comments, trailing commas, single-quoted strings, unquoted keys and the other JSON5
+//! extensions, so editor-authored files such as `tsconfig.json` are read as they are. Every
+//! value remembers where it was written:
+//!
+//! ```rust
+//! let document = jsonlike::json5::from_str("{\n  // Compiler settings\n  strict: true,\n}")
+//!     .expect("Valid JSON5");
+//! let strict = document.pointer("/strict").expect("Key exists");
+//! assert_eq!(strict.span().start.line, 3);
+//! assert_eq!(strict.span().start.column, 11);
+//! ```
+//!
+//! Numbers keep their exact decimal text; hexadecimal literals are converted to decimal.
+//! `Infinity` and `NaN` are parsed, but have no JSON equivalent and are reported as
+//! `JsonError` when used as numbers.
+use core::fmt;
+use std::collections::HashMap;
+
+/// Nesting depth at which parsing stops, so deeply nested input can not overflow the stack.
+const RECURSION_LIMIT: usize = 128;
+
+/// A location in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column in characters, starting at 1.
+    pub column: usize,
+    /// Byte offset from the start of the source.
+    pub offset: usize,
+}
+
+/// The source range of a value, from its first character up to, but not including, `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A parsed value with its location in the source.
+///
+/// Equality compares values only and ignores spans.
+#[derive(Debug, Clone)]
+pub struct Value {
+    kind: Kind,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<Value>),
+    /// Members in source order. A duplicate key keeps the position of its first occurrence and
+    /// the value of its last one.
+    Object(Vec<(String, Value)>),
+}
+
+/// A number as its decimal text in JSON syntax, e.g. `0.5` for `.5` or `255` for `0xFF`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number(String);
+
+impl Number {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    /// Whether this is `Infinity`, `-Infinity` or `NaN`.
+    pub fn is_finite(&self) -> bool {
+        !self.0.ends_with("Infinity") && self.0 != "NaN"
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Value {
+    pub fn kind(&self) -> &Kind {
+        &self.kind
+    }
+    pub fn span(&self) -> Span {
+        self.span
+    }
+    /// Look up a value by a JSON Pointer, e.g. `/compilerOptions/paths/0`.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        let mut value = self;
+        for token in pointer.strip_prefix('/')?.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            value = match &value.kind {
+                Kind::Object(members) => members
+                    .iter()
+                    .find_map(|(key, value)| (*key == token).then_some(value))?,
+                Kind::Array(items) => items.get(token.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+/// An error at a specific location of the source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub position: Position,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {} column {}",
+            self.message, self.position.line, self.position.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse a JSON5 or JSONC document.
+pub fn from_str(source: &str) -> Result<Value, ParseError> {
+    let mut parser = Parser {
+        source,
+        position: Position {
+            line: 1,
+            column: 1,
+            offset: 0,
+        },
+        depth: 0,
+    };
+    parser.skip_trivia()?;
+    let value = parser.value()?;
+    parser.skip_trivia()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(c) => Err(parser.error(format!("Unexpected {c:?} after the document"))),
+    }
+}
+
+fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '$' || c == '_'
+}
+
+fn is_identifier_part(c: char) -> bool {
+    is_identifier_start(c) || c.is_alphanumeric() || matches!(c, '\u{200c}' | '\u{200d}')
+}
+
+struct Parser<'s> {
+    source: &'s str,
+    position: Position,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.position.offset..]
+    }
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position.offset += c.len_utf8();
+        // `\r\n` is a single line break, counted at the `\n`
+        if is_line_terminator(c) && !(c == '\r' && self.peek() == Some('\n')) {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(c)
+    }
+    fn eat(&mut self, expected: &str) -> bool {
+        if self.rest().starts_with(expected) {
+            expected.chars().for_each(|_| {
+                self.bump();
+            });
+            true
+        } else {
+            false
+        }
+    }
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            message: message.into(),
+            position: self.position,
+        }
+    }
+    fn unexpected(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(c) => self.error(format!("Expected {expected}, found {c:?}")),
+            None => self.error(format!("Expected {expected}, found the end of input")),
+        }
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), ParseError> {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() || c == '\u{feff}' => {
+                    self.bump();
+                }
+                Some('/') if self.eat("//") => {
+                    while self.peek().is_some_and(|c| !is_line_terminator(c)) {
+                        self.bump();
+                    }
+                }
+                Some('/') if self.rest().starts_with("/*") => {
+                    let start = self.error("Unterminated comment");
+                    self.eat("/*");
+                    while !self.eat("*/") {
+                        if self.bump().is_none() {
+                            return Err(start);
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        let start = self.position;
+        let kind = match self.peek() {
+            Some('{') => self.nested(Self::object)?,
+            Some('[') => self.nested(Self::array)?,
+            Some(quote @ ('"' | '\'')) => Kind::String(self.string(quote)?),
+            Some(c) if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => {
+                Kind::Number(self.number()?)
+            }
+            Some(c) if is_identifier_start(c) => match self.identifier()?.as_str() {
+                "null" => Kind::Null,
+                "true" => Kind::Bool(true),
+                "false" => Kind::Bool(false),
+                "Infinity" => Kind::Number(Number("Infinity".to_string())),
+                "NaN" => Kind::Number(Number("NaN".to_string())),
+                other => {
+                    return Err(ParseError {
+                        message: format!("Unexpected identifier `{other}`"),
+                        position: start,
+                    })
+                }
+            },
+            _ => return Err(self.unexpected("a value")),
+        };
+        Ok(Value {
+            kind,
+            span: Span {
+                start,
+                end: self.position,
+            },
+        })
+    }
+
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Kind, ParseError>,
+    ) -> Result<Kind, ParseError> {
+        if self.depth == RECURSION_LIMIT {
+            return Err(self.error("Recursion limit exceeded"));
+        }
+        self.depth += 1;
+        let kind = parse(self);
+        self.depth -= 1;
+        kind
+    }
+
+    fn object(&mut self) -> Result<Kind, ParseError> {
+        self.bump();
+        let mut members: Vec<(String, Value)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        loop {
+            self.skip_trivia()?;
+            let key = match self.peek() {
+                Some('}') => break,
+                Some(quote @ ('"' | '\'')) => self.string(quote)?,
+                Some(c) if is_identifier_start(c) => self.identifier()?,
+                _ => return Err(self.unexpected("a key or '}'")),
+            };
+            self.skip_trivia()?;
+            if !self.eat(":") {
+                return Err(self.unexpected("':'"));
+            }
+            self.skip_trivia()?;
+            let value = self.value()?;
+            match positions.get(&key) {
+                Some(&position) => members[position].1 = value,
+                None => {
+                    positions.insert(key.clone(), members.len());
+                    members.push((key, value));
+                }
+            }
+            self.skip_trivia()?;
+            if !self.eat(",") {
+                if self.peek() == Some('}') {
+                    break;
+                }
+                return Err(self.unexpected("',' or '}'"));
+            }
+        }
+        self.bump();
+        Ok(Kind::Object(members))
+    }
+
+    fn array(&mut self) -> Result<Kind, ParseError> {
+        self.bump();
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some(']') {
+                break;
+            }
+            items.push(self.value()?);
+            self.skip_trivia()?;
+            if !self.eat(",") {
+                if self.peek() == Some(']') {
+                    break;
+                }
+                return Err(self.unexpected("',' or ']'"));
+            }
+        }
+        self.bump();
+        Ok(Kind::Array(items))
+    }
+
+    fn identifier(&mut self) -> Result<String, ParseError> {
+        let mut identifier = String::new();
+        while let Some(c) = self.peek().filter(|c| is_identifier_part(*c)) {
+            identifier.push(c);
+            self.bump();
+        }
+        if self.peek() == Some('\\') {
+            return Err(self.error("Escapes in identifiers are not supported"));
+        }
+        Ok(identifier)
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, ParseError> {
+        let start = self.error("Unterminated string");
+        self.bump();
+        let mut string = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(start),
+                Some(c) if c == quote => return Ok(string),
+                Some('\\') => {
+                    if let Some(c) = self.escape()? {
+                        string.push(c);
+                    }
+                }
+                Some('\n' | '\r') => return Err(self.error("Unescaped line break in string")),
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    /// The character of an escape sequence after `\`, `None` for a line continuation.
+    fn escape(&mut self) -> Result<Option<char>, ParseError> {
+        let c = match self.bump() {
+            None => return Err(self.error("Unterminated string")),
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('v') => '\u{b}',
+            Some('0') if !self.peek().is_some_and(|c| c.is_ascii_digit()) => '\0',
+            Some(c @ '0'..='9') => return Err(self.error(format!("Invalid escape '\\{c}'"))),
+            Some('x') => {
+                let code = self.hex(2)?;
+                char::from_u32(code).expect("Two hex digits are a valid char")
+            }
+            Some('u') => {
+                let high = self.hex(4)?;
+                if (0xd800..0xdc00).contains(&high) && self.eat("\\u") {
+                    let low = self.hex(4)?;
+                    char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00)))
+                        .filter(|_| (0xdc00..0xe000).contains(&low))
+                        .ok_or_else(|| self.error("Invalid surrogate pair"))?
+                } else {
+                    char::from_u32(high).ok_or_else(|| self.error("Unpaired surrogate"))?
+                }
+            }
+            Some('\r') => {
+                self.eat("\n");
+                return Ok(None);
+            }
+            Some(c) if is_line_terminator(c) => return Ok(None),
+            Some(c) => c,
+        };
+        Ok(Some(c))
+    }
+
+    fn hex(&mut self, digits: usize) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..digits {
+            let digit = self
+                .peek()
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.unexpected("a hexadecimal digit"))?;
+            self.bump();
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn digits(&mut self, radix: u32) -> String {
+        let mut digits = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_digit(radix)) {
+            digits.push(c);
+            self.bump();
+        }
+        digits
+    }
+
+    fn number(&mut self) -> Result<Number, ParseError> {
+        let start = self.position;
+        let sign = if self.eat("-") {
+            "-"
+        } else {
+            self.eat("+");
+            ""
+        };
+        if self.eat("Infinity") {
+            return Ok(Number(format!("{sign}Infinity")));
+        }
+        if self.eat("NaN") {
+            return Ok(Number("NaN".to_string()));
+        }
+        if self.eat("0x") || self.eat("0X") {
+            let digits = self.digits(16);
+            if digits.is_empty() {
+                return Err(self.unexpected("a hexadecimal digit"));
+            }
+            return Ok(Number(format!("{sign}{}", hex_to_decimal(&digits))));
+        }
+        let integer = self.digits(10);
+        if integer.len() > 1 && integer.starts_with('0') {
+            return Err(ParseError {
+                message: "Leading zeros are not allowed".to_string(),
+                position: start,
+            });
+        }
+        let fraction = if self.eat(".") {
+            self.digits(10)
+        } else {
+            String::new()
+        };
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(self.unexpected("a digit"));
+        }
+        let mut number = format!("{sign}{}", if integer.is_empty() { "0" } else { &integer });
+        if !fraction.is_empty() {
+            number.push('.');
+            number.push_str(&fraction);
+        }
+        if self.eat("e") || self.eat("E") {
+            number.push('e');
+            if let Some(c @ ('+' | '-')) = self.peek() {
+                number.push(c);
+                self.bump();
+            }
+            let exponent = self.digits(10);
+            if exponent.is_empty() {
+                return Err(self.unexpected("a digit"));
+            }
+            number.push_str(&exponent);
+        }
+        Ok(Number(number))
+    }
+}
+
+/// Convert hexadecimal digits of any length to decimal digits.
+fn hex_to_decimal(hex: &str) -> String {
+    // Little-endian decimal digits
+    let mut decimal = vec![0_u32];
+    for digit in hex.chars().filter_map(|c| c.to_digit(16)) {
+        let mut carry = digit;
+        for d in &mut decimal {
+            let value = *d * 16 + carry;
+            *d = value % 10;
+            carry = value / 10;
+        }
+        while carry > 0 {
+            decimal.push(carry % 10);
+            carry /= 10;
+        }
+    }
+    while decimal.len() > 1 && decimal.last() == Some(&0) {
+        decimal.pop();
+    }
+    decimal
+        .iter()
+        .rev()
+        .map(|d| char::from_digit(*d, 10).expect("Decimal digit"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{from_str, Kind, Number};
+
+    fn number(source: &str) -> String {
+        match from_str(source).expect("Valid JSON5").kind() {
+            Kind::Number(number) => number.as_str().to_string(),
+            other => panic!("Not a number: {other:?}"),
+        }
+    }
+
+    #[test_case("1", "1")]
+    #[test_case("-1.5e3", "-1.5e3")]
+    #[test_case("+1", "1")]
+    #[test_case(".5", "0.5")]
+    #[test_case("5.", "5")]
+    #[test_case("0xFF", "255")]
+    #[test_case("-0x10", "-16")]
+    #[test_case("0x1FFFFFFFFFFFFFFFF", "36893488147419103231")]
+    #[test_case("1E+2", "1e+2")]
+    #[test_case("-Infinity", "-Infinity")]
+    #[test_case("NaN", "NaN")]
+    fn test_numbers(source: &str, expected: &str) {
+        assert_eq!(number(source), expected);
+    }
+
+    #[test]
+    fn test_finite() {
+        assert!(Number("1e400".to_string()).is_finite());
+        assert!(!Number("-Infinity".to_string()).is_finite());
+        assert!(!Number("NaN".to_string()).is_finite());
+    }
+
+    #[test_case(r#""a\tb""#, "a\tb")]
+    #[test_case(r#"'it\'s "quoted"'"#, "it's \"quoted\"")]
+    #[test_case(r#"'\x41B\0'"#, "AB\0")]
+    #[test_case(r#""😀""#, "😀")]
+    #[test_case("'line \\\ncontinued'", "line continued")]
+    #[test_case("'line \\\r\ncontinued'", "line continued")]
+    #[test_case(r#""\q\/""#, "q/")]
+    fn test_strings(source: &str, expected: &str) {
+        let value = from_str(source).expect("Valid JSON5");
+        assert_eq!(value.kind(), &Kind::String(expected.to_string()));
+    }
+
+    #[test]
+    fn test_document() {
+        let source = "// tsconfig\n{\n  /* options */\n  compilerOptions: {\n    \"strict\": true,\n    'paths': {\"@/*\": ['src/*',],},\n  },\n  $schema: null,\n}\n";
+        let value = from_str(source).expect("Valid JSONC");
+        let paths = value
+            .pointer("/compilerOptions/paths/@~1*/0")
+            .expect("Pointer exists");
+        assert_eq!(paths.kind(), &Kind::String("src/*".to_string()));
+        assert_eq!(
+            (paths.span().start.line, paths.span().start.column),
+            (6, 23)
+        );
+        assert_eq!(
+            &source[paths.span().start.offset..paths.span().end.offset],
+            "'src/*'"
+        );
+        assert_eq!(
+            value.pointer("/$schema").map(|v| v.kind()),
+            Some(&Kind::Null)
+        );
+        assert!(value.pointer("/missing").is_none());
+        assert!(value.pointer("").is_some());
+    }
+
+    #[test]
+    fn test_positions() {
+        let value = from_str("[1,\r\n  true, 'é', 2]").expect("Valid JSON5");
+        let Kind::Array(items) = value.kind() else {
+            panic!("Not an array");
+        };
+        let starts: Vec<_> = items
+            .iter()
+            .map(|item| (item.span().start.line, item.span().start.column))
+            .collect();
+        assert_eq!(starts, [(1, 2), (2, 3), (2, 9), (2, 14)]);
+        assert_eq!(items[2].span().end.offset - items[2].span().start.offset, 4);
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let value = from_str("{a: 1, b: 2, 'a': 3}").expect("Valid JSON5");
+        let Kind::Object(members) = value.kind() else {
+            panic!("Should be an object");
+        };
+        let keys: Vec<_> = members.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(
+            value.pointer("/a").map(|v| v.kind()),
+            Some(&Kind::Number(Number("3".to_string())))
+        );
+        assert_eq!(value.pointer("/a").map(|v| v.span().start.column), Some(19));
+    }
+
+    #[test]
+    fn test_equality_ignores_spans() {
+        assert_eq!(
+            from_str("{a: [1, 'x']}").expect("Valid JSON5"),
+            from_str("{\n  \"a\": [\n    1,\n    \"x\",\n  ],\n}").expect("Valid JSON5")
+        );
+    }
+
+    #[test_case("", "Expected a value, found the end of input at line 1 column 1")]
+    #[test_case("[1 2]", "Expected ',' or ']', found '2' at line 1 column 4")]
+    #[test_case("{a 1}", "Expected ':', found '1' at line 1 column 4")]
+    #[test_case("[,]", "Expected a value, found ',' at line 1 column 2")]
+    #[test_case("{,}", "Expected a key or '}', found ',' at line 1 column 2")]
+    #[test_case("'abc", "Unterminated string at line 1 column 1")]
+    #[test_case("\"a\nb\"", "Unescaped line break in string at line 2 column 1")]
+    #[test_case("/* x", "Unterminated comment at line 1 column 1")]
+    #[test_case("01", "Leading zeros are not allowed at line 1 column 1")]
+    #[test_case(
+        "0x",
+        "Expected a hexadecimal digit, found the end of input at line 1 column 3"
+    )]
+    #[test_case("undefined", "Unexpected identifier `undefined` at line 1 column 1")]
+    #[test_case("1 2", "Unexpected '2' after the document at line 1 column 3")]
+    #[test_case("'\\1'", "Invalid escape '\\1' at line 1 column 4")]
+    fn test_errors(source: &str, expected: &str) {
+        let error = from_str(source).expect_err("Invalid JSON5");
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn test_recursion_limit() {
+        let source = "[".repeat(200) + &"]".repeat(200);
+        let error = from_str(&source).expect_err("Too deep");
+        assert_eq!(error.message, "Recursion limit exceeded");
+        assert!(from_str(&("[".repeat(100) + &"]".repeat(100))).is_ok());
+    }
+}
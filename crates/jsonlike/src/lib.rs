@@ -1,7 +1,10 @@
 mod impls;
 
 use core::fmt;
+use std::borrow::Cow;
 mod error;
+#[cfg(any(feature = "json5", test))]
+pub mod json5;
 
 pub use error::JsonError;
 
@@ -103,6 +106,12 @@ pub trait JsonObject {
     fn contains_key(&self, key: &str) -> bool {
         self.get(key).is_some()
     }
+    fn len(&self) -> usize {
+        self.iter().count()
+    }
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 pub trait JsonArray {
@@ -121,6 +130,13 @@ pub trait JsonArray {
             .unwrap_or_else(|err| panic!("Failed to get an element at index {idx}: {err}"))
     }
     fn try_get(&self, idx: usize) -> Result<Option<&Self::Element>, JsonError>;
+
+    fn len(&self) -> usize {
+        self.iter().count()
+    }
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
 pub trait JsonNumber<'a> {
@@ -133,8 +149,17 @@ pub trait JsonNumber<'a> {
             .try_into()
     }
     fn as_float(&self) -> Option<f64>;
+    /// Exact decimal representation of this number, e.g. `18446744073709551617` or `0.1`.
+    ///
+    /// Returns `None` if `as_float` already yields the exact value of this number.
+    fn as_decimal(&self) -> Option<Cow<'_, str>> {
+        None
+    }
 }
 
+/// Integers with a larger magnitude may not be exactly representable as `f64`.
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
 #[cfg(test)]
 mod tests {
     use std::panic;
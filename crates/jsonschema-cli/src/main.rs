@@ -0,0 +1,536 @@
+//! Command-line validation of JSON instances against a JSON Schema.
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, ValueEnum};
+use jsonschema::{
+    blocking::ValidatorBuilder, lines::LineErrorKind, resolver::FileSystemResolver, Draft,
+    Validator,
+};
+use serde_json::Value;
+
+/// Validate JSON instances against a JSON Schema.
+///
+/// Exits with 0 if all instances are valid, 1 if any is invalid and 2 if the schema or an
+/// instance can not be read or the schema is invalid.
+#[derive(Debug, Parser)]
+#[command(name = "jsonschema", version)]
+struct Cli {
+    /// The schema file.
+    schema: PathBuf,
+    /// The instance files to validate, `-` for standard input.
+    #[arg(required = true)]
+    instances: Vec<PathBuf>,
+    /// The draft to validate with instead of the one the schema declares via `$schema`.
+    #[arg(short, long, value_enum)]
+    draft: Option<DraftArg>,
+    /// The output format. JSON formats print one line per instance, in the given order.
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+    /// The directory references to local files are resolved in, defaults to the directory of
+    /// the schema.
+    #[arg(long)]
+    base_dir: Option<PathBuf>,
+    /// Validate every line of the instance files as a separate instance (JSON Lines). Only
+    /// the text output is supported.
+    #[arg(long)]
+    jsonl: bool,
+    /// Stop reading an instance file after this many lines with errors, with `--jsonl`.
+    #[arg(long, requires = "jsonl")]
+    max_errors: Option<NonZeroUsize>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DraftArg {
+    #[value(name = "4")]
+    Draft04,
+    #[value(name = "6")]
+    Draft06,
+    #[value(name = "7")]
+    Draft07,
+    #[value(name = "2019-09")]
+    Draft201909,
+    #[value(name = "2020-12")]
+    Draft202012,
+}
+
+impl From<DraftArg> for Draft {
+    fn from(draft: DraftArg) -> Draft {
+        match draft {
+            DraftArg::Draft04 => Draft::Draft04,
+            DraftArg::Draft06 => Draft::Draft06,
+            DraftArg::Draft07 => Draft::Draft07,
+            DraftArg::Draft201909 => Draft::Draft201909,
+            DraftArg::Draft202012 => Draft::Draft202012,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// A readable report with every error.
+    Text,
+    /// The "Flag" output format of JSON Schema.
+    Flag,
+    /// The "List" output format of JSON Schema.
+    List,
+    /// The "Hierarchical" output format of JSON Schema.
+    Hierarchical,
+}
+
+/// The outcome of a run, ordered by severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    Valid = 0,
+    Invalid = 1,
+    Error = 2,
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> ExitCode {
+        ExitCode::from(status as u8)
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    run(&cli, &mut io::stdout().lock(), &mut io::stderr().lock()).into()
+}
+
+fn run(cli: &Cli, out: &mut impl Write, err: &mut impl Write) -> Status {
+    if cli.jsonl && cli.output != OutputFormat::Text {
+        let _ = writeln!(err, "--jsonl only supports the text output");
+        return Status::Error;
+    }
+    let validator = match load(cli) {
+        Ok(validator) => validator,
+        Err(message) => {
+            let _ = writeln!(err, "{}: {message}", cli.schema.display());
+            return Status::Error;
+        }
+    };
+    let mut status = Status::Valid;
+    for path in &cli.instances {
+        if cli.jsonl {
+            match report_lines(&validator, path, cli.max_errors, out) {
+                Ok(true) => {}
+                Ok(false) => status = status.max(Status::Invalid),
+                Err(error) => {
+                    let _ = writeln!(err, "{}: {error}", path.display());
+                    status = status.max(Status::Error);
+                }
+            }
+            continue;
+        }
+        let instance = match read_json(path) {
+            Ok(instance) => instance,
+            Err(message) => {
+                let _ = writeln!(err, "{}: {message}", path.display());
+                status = status.max(Status::Error);
+                continue;
+            }
+        };
+        let valid = match report(&validator, &instance, path, cli.output, out) {
+            Ok(valid) => valid,
+            Err(error) => {
+                let _ = writeln!(err, "{error}");
+                return Status::Error;
+            }
+        };
+        if !valid {
+            status = status.max(Status::Invalid);
+        }
+    }
+    status
+}
+
+fn load(cli: &Cli) -> Result<Validator<Value>, String> {
+    let schema = read_json(&cli.schema)?;
+    let draft = cli
+        .draft
+        .map_or_else(|| Draft::detect(&schema), Draft::from);
+    let base_dir = match &cli.base_dir {
+        Some(base_dir) => base_dir.clone(),
+        None => match cli.schema.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        },
+    };
+    ValidatorBuilder::default()
+        .draft(draft)
+        .resolver(FileSystemResolver::new(base_dir))
+        .build(&schema)
+        .map_err(|error| error.to_string())
+}
+
+fn open(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    if path.as_os_str() == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+fn read_json(path: &Path) -> Result<Value, String> {
+    let mut text = String::new();
+    open(path)
+        .and_then(|mut reader| reader.read_to_string(&mut text))
+        .map_err(|error| error.to_string())?;
+    serde_json::from_str(&text).map_err(|error| format!("Invalid JSON: {error}"))
+}
+
+/// Write the errors of every line of a JSON Lines file and return whether all lines are valid.
+fn report_lines(
+    validator: &Validator<Value>,
+    path: &Path,
+    max_errors: Option<NonZeroUsize>,
+    out: &mut impl Write,
+) -> io::Result<bool> {
+    let mut lines = validator.lines();
+    if let Some(limit) = max_errors {
+        lines = lines.max_errors(limit.get());
+    }
+    let report = lines.validate(open(path)?)?;
+    let invalid = report.errors.len();
+    for error in &report.errors {
+        match &error.kind {
+            LineErrorKind::Malformed { message } => {
+                writeln!(
+                    out,
+                    "{}:{}: malformed: {message}",
+                    path.display(),
+                    error.line
+                )?;
+            }
+            LineErrorKind::Invalid { errors } => {
+                for violation in errors {
+                    writeln!(
+                        out,
+                        "{}:{}: invalid: at '{}': {} (schema path '{}')",
+                        path.display(),
+                        error.line,
+                        violation.instance_path(),
+                        violation,
+                        violation.schema_path()
+                    )?;
+                }
+            }
+        }
+    }
+    let suffix = if report.stopped {
+        ", stopped after reaching the error limit"
+    } else {
+        ""
+    };
+    writeln!(
+        out,
+        "{}: {} lines, {invalid} with errors{suffix}",
+        path.display(),
+        report.lines
+    )?;
+    Ok(invalid == 0)
+}
+
+/// Write the result for one instance and return whether it is valid.
+fn report(
+    validator: &Validator<Value>,
+    instance: &Value,
+    path: &Path,
+    format: OutputFormat,
+    out: &mut impl Write,
+) -> io::Result<bool> {
+    let valid = match format {
+        OutputFormat::Text => {
+            let errors: Vec<_> = validator.iter_errors(instance).collect();
+            if errors.is_empty() {
+                writeln!(out, "{}: valid", path.display())?;
+            } else {
+                writeln!(out, "{}: invalid", path.display())?;
+                for error in &errors {
+                    writeln!(
+                        out,
+                        "  at '{}': {} (schema path '{}')",
+                        error.instance_path(),
+                        error,
+                        error.schema_path()
+                    )?;
+                }
+            }
+            errors.is_empty()
+        }
+        OutputFormat::Flag => {
+            let flag = validator.evaluate(instance).flag();
+            writeln!(out, "{}", serde_json::to_string(&flag)?)?;
+            flag.valid
+        }
+        OutputFormat::List => {
+            let list = validator.evaluate(instance).list();
+            writeln!(out, "{}", serde_json::to_string(&list)?)?;
+            list.valid
+        }
+        OutputFormat::Hierarchical => {
+            let hierarchical = validator.evaluate(instance).hierarchical();
+            writeln!(out, "{}", serde_json::to_string(&hierarchical)?)?;
+            hierarchical.valid
+        }
+    };
+    Ok(valid)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use clap::Parser;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{run, Cli, Status};
+
+    /// A fresh directory with the given JSON files, named after the running test.
+    fn directory(files: &[(&str, &str)]) -> PathBuf {
+        let test = std::thread::current()
+            .name()
+            .unwrap_or("test")
+            .replace("::", "-");
+        let directory =
+            std::env::temp_dir().join(format!("jsonschema-cli-{}-{test}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("Can create directories");
+        for (file, contents) in files {
+            fs::write(directory.join(file), contents).expect("Can write files");
+        }
+        directory
+    }
+
+    fn execute(directory: &Path, args: &[&str]) -> (Status, String, String) {
+        let args = args.iter().map(|arg| {
+            if arg.ends_with(".json") || arg.ends_with(".jsonl") {
+                directory.join(arg).display().to_string()
+            } else {
+                (*arg).to_string()
+            }
+        });
+        let cli = Cli::try_parse_from(std::iter::once("jsonschema".to_string()).chain(args))
+            .expect("Valid arguments");
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let status = run(&cli, &mut out, &mut err);
+        (
+            status,
+            String::from_utf8(out).expect("UTF-8 output"),
+            String::from_utf8(err).expect("UTF-8 output"),
+        )
+    }
+
+    const SCHEMA: &str = r#"{"properties": {"a": {"$ref": "defs.json#/integer"}}}"#;
+    const DEFS: &str = r#"{"integer": {"type": "integer"}}"#;
+
+    #[test]
+    fn test_text() {
+        let directory = directory(&[
+            ("schema.json", SCHEMA),
+            ("defs.json", DEFS),
+            ("valid.json", r#"{"a": 1}"#),
+            ("invalid.json", r#"{"a": "x"}"#),
+        ]);
+        let (status, out, err) =
+            execute(&directory, &["schema.json", "valid.json", "invalid.json"]);
+        assert_eq!(status, Status::Invalid);
+        let valid = directory.join("valid.json");
+        let invalid = directory.join("invalid.json");
+        assert_eq!(
+            out,
+            format!(
+                "{}: valid\n{}: invalid\n  at '/a': Value is not of type \"integer\" (schema path '/integer/type')\n",
+                valid.display(),
+                invalid.display()
+            )
+        );
+        assert_eq!(err, "");
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+
+    #[test_case("flag", json!({"valid": false}))]
+    #[test_case("list", json!({"valid": false, "nested": [{
+        "valid": false,
+        "evaluationPath": "/type",
+        "schemaLocation": "#/type",
+        "instanceLocation": "",
+        "nested": null,
+        "annotations": null,
+        "droppedAnnotations": null,
+        "errors": {"type": "Value is not of type \"string\""},
+    }]}))]
+    #[test_case("hierarchical", json!({
+        "valid": false,
+        "evaluationPath": "",
+        "schemaLocation": "#",
+        "instanceLocation": "",
+        "nested": null,
+        "annotations": null,
+        "droppedAnnotations": null,
+        "errors": {"type": "Value is not of type \"string\""},
+    }))]
+    fn test_json_output(format: &str, expected: Value) {
+        let directory = directory(&[
+            ("schema.json", r#"{"type": "string"}"#),
+            ("instance.json", "1"),
+        ]);
+        let (status, out, _) = execute(&directory, &["schema.json", "instance.json", "-o", format]);
+        assert_eq!(status, Status::Invalid);
+        let lines: Vec<Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("JSON lines"))
+            .collect();
+        assert_eq!(lines, [expected]);
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+
+    #[test_case(&[], Status::Invalid; "declared draft")]
+    #[test_case(&["--draft", "7"], Status::Valid; "draft flag")]
+    fn test_draft(args: &[&str], expected: Status) {
+        // `$ref` siblings are ignored before 2019-09
+        let directory = directory(&[
+            (
+                "schema.json",
+                r##"{"$schema": "https://json-schema.org/draft/2020-12/schema", "$ref": "#/$defs/any", "$defs": {"any": true}, "type": "string"}"##,
+            ),
+            ("instance.json", "1"),
+        ]);
+        let mut all = vec!["schema.json", "instance.json"];
+        all.extend(args);
+        assert_eq!(execute(&directory, &all).0, expected);
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+
+    #[test_case("schema.json", "{", "Invalid JSON"; "malformed schema")]
+    #[test_case("schema.json", r#"{"type": 1}"#, "Invalid schema"; "invalid schema")]
+    #[test_case("instance.json", "{", "Invalid JSON"; "malformed instance")]
+    fn test_errors(file: &str, contents: &str, expected: &str) {
+        let mut files = vec![("schema.json", "{}"), ("instance.json", "1")];
+        files.retain(|(name, _)| *name != file);
+        files.push((file, contents));
+        let directory = directory(&files);
+        let (status, _, err) = execute(&directory, &["schema.json", "instance.json"]);
+        assert_eq!(status, Status::Error);
+        assert!(err.contains(expected), "{err}");
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let directory = directory(&[("schema.json", "{}")]);
+        let (status, _, err) = execute(&directory, &["schema.json", "missing.json"]);
+        assert_eq!(status, Status::Error);
+        assert!(err.contains("missing.json"));
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+
+    const LINES: &str = "{\"a\": 1}\n\n{\"a\": \"x\"}\n{\"a\": \n{\"a\": 2}\n";
+
+    #[test]
+    fn test_jsonl() {
+        let directory = directory(&[
+            ("schema.json", SCHEMA),
+            ("defs.json", DEFS),
+            ("instances.jsonl", LINES),
+        ]);
+        let (status, out, err) =
+            execute(&directory, &["schema.json", "--jsonl", "instances.jsonl"]);
+        assert_eq!(status, Status::Invalid);
+        assert_eq!(err, "");
+        let path = directory.join("instances.jsonl").display().to_string();
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            format!("{path}:3: invalid: at '/a': Value is not of type \"integer\" (schema path '/integer/type')")
+        );
+        assert!(lines[1].starts_with(&format!("{path}:4: malformed: ")));
+        assert_eq!(lines[2], format!("{path}: 5 lines, 2 with errors"));
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+
+    #[test_case(&[], Status::Valid, "3 lines, 0 with errors")]
+    #[test_case(&["--max-errors", "5"], Status::Valid, "3 lines, 0 with errors")]
+    fn test_jsonl_valid(args: &[&str], expected: Status, summary: &str) {
+        let directory = directory(&[
+            ("schema.json", SCHEMA),
+            ("defs.json", DEFS),
+            ("instances.jsonl", "{\"a\": 1}\n{}\n{\"a\": 3}\n"),
+        ]);
+        let mut all = vec!["schema.json", "--jsonl", "instances.jsonl"];
+        all.extend_from_slice(args);
+        let (status, out, _) = execute(&directory, &all);
+        assert_eq!(status, expected);
+        assert!(out.ends_with(&format!("{summary}\n")), "{out}");
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+
+    #[test]
+    fn test_jsonl_max_errors() {
+        let directory = directory(&[
+            ("schema.json", SCHEMA),
+            ("defs.json", DEFS),
+            ("instances.jsonl", LINES),
+        ]);
+        let (status, out, _) = execute(
+            &directory,
+            &[
+                "schema.json",
+                "--jsonl",
+                "--max-errors",
+                "1",
+                "instances.jsonl",
+            ],
+        );
+        assert_eq!(status, Status::Invalid);
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(
+            lines[1].ends_with(": 3 lines, 1 with errors, stopped after reaching the error limit")
+        );
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+
+    #[test]
+    fn test_jsonl_max_errors_zero() {
+        let error = Cli::try_parse_from([
+            "jsonschema",
+            "schema.json",
+            "--jsonl",
+            "--max-errors",
+            "0",
+            "instances.jsonl",
+        ])
+        .expect_err("Zero is rejected");
+        assert_eq!(error.kind(), clap::error::ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn test_jsonl_errors() {
+        let directory = directory(&[("schema.json", SCHEMA), ("defs.json", DEFS)]);
+        let (status, _, err) = execute(&directory, &["schema.json", "--jsonl", "missing.jsonl"]);
+        assert_eq!(status, Status::Error);
+        assert!(err.contains("missing.jsonl: "));
+        let (status, out, err) = execute(
+            &directory,
+            &["schema.json", "--jsonl", "-o", "flag", "missing.jsonl"],
+        );
+        assert_eq!(status, Status::Error);
+        assert_eq!(out, "");
+        assert_eq!(err, "--jsonl only supports the text output\n");
+        assert!(
+            Cli::try_parse_from(["jsonschema", "s.json", "--max-errors", "1", "i.json"]).is_err()
+        );
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+}
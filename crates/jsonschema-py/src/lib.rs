@@ -0,0 +1,317 @@
+//! Python bindings.
+//!
+//! Instances are validated directly as Python objects through the `jsonlike` pyo3 backend, so
+//! dicts and lists are never serialized. Schemas are converted once, when the validator is built.
+//! Integers in schemas keep their exact value with the default `arbitrary_precision` feature.
+//! Without it, integers that do not fit into `u64` are converted to floats.
+//!
+//! ```python
+//! import jsonschema_rs
+//!
+//! validator = jsonschema_rs.validator_for({"properties": {"id": {"type": "integer"}}})
+//! try:
+//!     validator.validate({"id": "1"})
+//! except jsonschema_rs.ValidationError as error:
+//!     print(error.instance_path, error.schema_path)  # ['id'] ['properties', 'id', 'type']
+//! ```
+// `#[pymethods]` in pyo3 0.20 expands to impls that newer compilers flag
+#![allow(non_local_definitions)]
+
+use jpointer::{JsonPointer, Segment};
+use jsonlike::{Json, JsonArray, JsonError, JsonNumber, JsonObject};
+use pyo3::{
+    create_exception,
+    exceptions::PyValueError,
+    prelude::*,
+    types::{PyIterator, PyList, PyLong},
+};
+
+create_exception!(
+    jsonschema_rs,
+    ValidationError,
+    PyValueError,
+    "The instance is invalid. `message`, `instance_path` and `schema_path` describe the failure."
+);
+create_exception!(
+    jsonschema_rs,
+    SchemaError,
+    PyValueError,
+    "The schema is invalid or can not be compiled."
+);
+
+/// Convert a Python schema into `serde_json`, as compiled schemas are owned by the validator.
+fn to_schema(value: &PyAny) -> Result<serde_json::Value, JsonError> {
+    if let Some(object) = value.try_as_object()? {
+        let mut map = serde_json::Map::with_capacity(object.len());
+        for (key, value) in JsonObject::iter(object) {
+            map.insert(key?.to_owned(), to_schema(value)?);
+        }
+        Ok(serde_json::Value::Object(map))
+    } else if let Some(array) = value.try_as_array()? {
+        array
+            .try_iter()?
+            .map(|item| to_schema(item?))
+            .collect::<Result<_, _>>()
+            .map(serde_json::Value::Array)
+    } else if let Some(string) = value.try_as_string()? {
+        Ok(serde_json::Value::from(string))
+    } else if let Some(boolean) = value.try_as_boolean()? {
+        Ok(serde_json::Value::Bool(boolean))
+    } else if value.try_as_null()?.is_some() {
+        Ok(serde_json::Value::Null)
+    } else if let Some(number) = value.try_as_number()? {
+        if let Ok(integer) = value.downcast_exact::<PyLong>() {
+            // The decimal text is exact for integers of any size, whichever integer type the
+            // backend uses
+            serde_json::from_str(integer.str()?.to_str()?)
+                .map(serde_json::Value::Number)
+                .map_err(|_| JsonError::new(format!("Integer {value} is out of range").into()))
+        } else {
+            number
+                .as_float()
+                .and_then(serde_json::Number::from_f64)
+                .map(serde_json::Value::Number)
+                .ok_or_else(|| {
+                    JsonError::new(format!("Float {value} has no JSON equivalent").into())
+                })
+        }
+    } else {
+        let name = value.get_type().name()?;
+        Err(JsonError::new(
+            format!("Python object of type `{name}` has no JSON equivalent").into(),
+        ))
+    }
+}
+
+fn pointer_to_list<'py>(py: Python<'py>, pointer: &JsonPointer) -> &'py PyList {
+    PyList::new(
+        py,
+        pointer.iter().map(|segment| match segment {
+            Segment::Key(key) => key.to_object(py),
+            Segment::Index(idx) => idx.to_object(py),
+        }),
+    )
+}
+
+fn to_py_error(py: Python<'_>, error: &jsonschema::ValidationError) -> PyResult<PyErr> {
+    let message = error.to_string();
+    let exception = ValidationError::new_err(message.clone());
+    let value = exception.value(py);
+    value.setattr("message", message)?;
+    value.setattr("instance_path", pointer_to_list(py, error.instance_path()))?;
+    value.setattr("schema_path", pointer_to_list(py, error.schema_path()))?;
+    Ok(exception)
+}
+
+/// A compiled schema.
+#[pyclass(module = "jsonschema_rs", name = "Validator", frozen)]
+struct PyValidator {
+    inner: jsonschema::Validator<serde_json::Value>,
+}
+
+#[pymethods]
+impl PyValidator {
+    #[new]
+    fn new(schema: &PyAny) -> PyResult<Self> {
+        let schema = to_schema(schema).map_err(|error| SchemaError::new_err(error.to_string()))?;
+        let inner = jsonschema::blocking::validator_for(&schema)
+            .map_err(|error| SchemaError::new_err(error.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    fn is_valid(&self, instance: &PyAny) -> bool {
+        self.inner.is_valid(instance)
+    }
+
+    /// Raise `ValidationError` for the first error.
+    fn validate(&self, py: Python<'_>, instance: &PyAny) -> PyResult<()> {
+        match self.inner.validate(instance) {
+            Ok(()) => Ok(()),
+            Err(error) => Err(to_py_error(py, &error)?),
+        }
+    }
+
+    /// An iterator over `ValidationError` instances, without raising them.
+    fn iter_errors<'py>(&self, py: Python<'py>, instance: &PyAny) -> PyResult<&'py PyIterator> {
+        let errors = self
+            .inner
+            .iter_errors(instance)
+            .map(|error| Ok(to_py_error(py, &error)?.into_value(py)))
+            .collect::<PyResult<Vec<_>>>()?;
+        PyIterator::from_object(PyList::new(py, errors))
+    }
+}
+
+#[pyfunction]
+fn validator_for(schema: &PyAny) -> PyResult<PyValidator> {
+    PyValidator::new(schema)
+}
+
+#[pyfunction]
+fn is_valid(schema: &PyAny, instance: &PyAny) -> PyResult<bool> {
+    Ok(PyValidator::new(schema)?.is_valid(instance))
+}
+
+#[pyfunction]
+fn validate(py: Python<'_>, schema: &PyAny, instance: &PyAny) -> PyResult<()> {
+    PyValidator::new(schema)?.validate(py, instance)
+}
+
+#[pyfunction]
+fn iter_errors<'py>(
+    py: Python<'py>,
+    schema: &PyAny,
+    instance: &PyAny,
+) -> PyResult<&'py PyIterator> {
+    PyValidator::new(schema)?.iter_errors(py, instance)
+}
+
+#[pymodule]
+fn jsonschema_rs(py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PyValidator>()?;
+    module.add_function(wrap_pyfunction!(validator_for, module)?)?;
+    module.add_function(wrap_pyfunction!(is_valid, module)?)?;
+    module.add_function(wrap_pyfunction!(validate, module)?)?;
+    module.add_function(wrap_pyfunction!(iter_errors, module)?)?;
+    module.add("ValidationError", py.get_type::<ValidationError>())?;
+    module.add("SchemaError", py.get_type::<SchemaError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyo3::{prelude::*, types::PyDict};
+
+    /// Run `code` with the module available as `jsonschema_rs`.
+    fn pyrun(code: &str) {
+        Python::with_gil(|py| {
+            let module = PyModule::new(py, "jsonschema_rs").expect("Valid module name");
+            super::jsonschema_rs(py, module).expect("Module initialization failed");
+            let locals = PyDict::new(py);
+            locals
+                .set_item("jsonschema_rs", module)
+                .expect("Valid local");
+            if let Err(error) = py.run(code, None, Some(locals)) {
+                error.print(py);
+                panic!("Python code failed");
+            }
+        });
+    }
+
+    #[test]
+    fn test_validator() {
+        pyrun(
+            r#"
+validator = jsonschema_rs.validator_for({"properties": {"tags": {"items": {"type": "string"}}}})
+assert validator.is_valid({"tags": ["a", "b"]})
+assert not validator.is_valid({"tags": ["a", 2]})
+validator.validate({"tags": []})
+try:
+    validator.validate({"tags": ["a", 2]})
+    raise AssertionError("Should be invalid")
+except jsonschema_rs.ValidationError as error:
+    assert isinstance(error, ValueError)
+    assert error.instance_path == ["tags", 1]
+    assert error.schema_path == ["properties", "tags", "items", "type"]
+    assert error.message == str(error)
+errors = list(validator.iter_errors({"tags": [1, "a", 2]}))
+assert [error.instance_path for error in errors] == [["tags", 0], ["tags", 2]]
+"#,
+        );
+    }
+
+    #[test]
+    fn test_functions() {
+        pyrun(
+            r#"
+schema = {"type": "integer", "maximum": 2 ** 60, "not": {"const": 1.5}}
+assert jsonschema_rs.is_valid(schema, 3)
+assert not jsonschema_rs.is_valid(schema, 2 ** 61)
+assert not jsonschema_rs.is_valid(schema, True)
+jsonschema_rs.validate(schema, 3)
+assert [error.instance_path for error in jsonschema_rs.iter_errors(schema, "3")] == [[]]
+"#,
+        );
+    }
+
+    #[test]
+    fn test_schema_error() {
+        pyrun(
+            r#"
+for schema in [{"type": "unknown"}, {"const": (1, 2)}, {1: True}, {"maximum": float("nan")}]:
+    try:
+        jsonschema_rs.validator_for(schema)
+        raise AssertionError(f"Should be invalid: {schema}")
+    except jsonschema_rs.SchemaError:
+        pass
+"#,
+        );
+    }
+
+    #[test]
+    fn test_big_integers() {
+        pyrun(
+            r#"
+for schema in [{"maximum": 2 ** 64}, {"maximum": 2 ** 70}, {"maximum": -(2 ** 70)}]:
+    assert jsonschema_rs.is_valid(schema, schema["maximum"])
+    assert not jsonschema_rs.is_valid(schema, schema["maximum"] + 1)
+assert jsonschema_rs.is_valid({"const": 10 ** 30}, 10 ** 30)
+assert not jsonschema_rs.is_valid({"const": 10 ** 30}, 10 ** 30 + 1)
+assert jsonschema_rs.is_valid({"multipleOf": 10 ** 400}, 10 ** 401)
+"#,
+        );
+    }
+
+    #[test]
+    fn test_instance_without_json_equivalent() {
+        pyrun(
+            r#"
+schema = {"propertyNames": {"minLength": 1}, "properties": {"a": {"minimum": 1}}}
+for instance in [{1: True}, {"a": float("nan")}, {"a": (1, 2)}]:
+    assert not jsonschema_rs.is_valid(schema, instance)
+    try:
+        jsonschema_rs.validate(schema, instance)
+        raise AssertionError(f"Should be invalid: {instance}")
+    except jsonschema_rs.ValidationError:
+        pass
+"#,
+        );
+    }
+
+    #[derive(Debug)]
+    struct Positive;
+
+    impl jsonschema::Keyword<PyAny> for Positive {
+        fn is_valid(&self, instance: &PyAny) -> bool {
+            instance.extract::<i64>().is_ok_and(|value| value > 0)
+        }
+    }
+
+    #[test]
+    fn test_python_schema_other_backend() {
+        // Custom keywords of a Python schema only see Python objects, which can not be created
+        // from other backends
+        Python::with_gil(|py| {
+            let schema = py
+                .eval("{'positive': True}", None, None)
+                .expect("Valid code");
+            let validator = jsonschema::blocking::ValidatorBuilder::default()
+                .keyword(
+                    "positive",
+                    |_: &_| -> jsonschema::BuildResult<jsonschema::BoxedKeyword<_>> {
+                        Ok(Box::new(Positive))
+                    },
+                )
+                .build(schema)
+                .expect("Valid schema");
+            assert!(validator.is_valid(py.eval("1", None, None).expect("Valid code")));
+            assert!(!validator.is_valid(&serde_json::json!(1)));
+            let errors: Vec<_> = validator.iter_errors(&serde_json::json!(1)).collect();
+            assert!(matches!(
+                errors[0].kind(),
+                jsonschema::ValidationErrorKind::InvalidInstance { reason }
+                    if reason.starts_with("Python objects can not be created from JSON text")
+            ));
+        });
+    }
+}
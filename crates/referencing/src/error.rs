@@ -11,6 +11,8 @@ pub enum ReferencingError {
     NoSuchAnchor { reference: Url, anchor: String },
     /// An anchor which could never exist in a resource was dereferenced.
     InvalidAnchor { reference: Url, anchor: String },
+    /// The specification of a resource is neither declared via `$schema` nor given.
+    CannotDetermineSpecification { dialect_id: Option<String> },
 }
 
 impl ReferencingError {
@@ -33,13 +35,19 @@ impl fmt::Display for ReferencingError {
                 f.write_fmt(format_args!("'{reference}' does not exist"))
             }
             ReferencingError::PointerToNowhere { reference } => {
-                f.write_fmt(format_args!("'{reference}' does not exist"))
-            }
-            ReferencingError::NoSuchAnchor { reference, anchor } => {
-                f.write_fmt(format_args!("'{anchor}' does not exist"))
+                f.write_fmt(format_args!("Pointer '{reference}' does not exist"))
             }
-            ReferencingError::InvalidAnchor { reference, anchor } => {
-                f.write_fmt(format_args!("'{anchor}' is not a valid anchor"))
+            ReferencingError::NoSuchAnchor { reference, anchor } => f.write_fmt(format_args!(
+                "'{anchor}' does not exist within '{reference}'"
+            )),
+            ReferencingError::InvalidAnchor { anchor, .. } => f.write_fmt(format_args!(
+                "'#{anchor}' is neither a plain name anchor nor a JSON Pointer"
+            )),
+            ReferencingError::CannotDetermineSpecification {
+                dialect_id: Some(dialect_id),
+            } => f.write_fmt(format_args!("Unknown dialect '{dialect_id}'")),
+            ReferencingError::CannotDetermineSpecification { dialect_id: None } => {
+                f.write_str("The resource does not declare its dialect via `$schema`")
             }
         }
     }
@@ -1,234 +1,302 @@
+//! Specifications of the JSON Schema drafts.
 use jsonlike::prelude::*;
 
 use crate::{
-    anchors::{Anchor, Resolvable},
-    error::ReferencingError,
-    path::{JsonPath, Segment},
-    resources::SubResource,
+    anchors::Anchor,
+    path::{jsonpath, JsonPath},
     specification::AnchorIter,
-    Resolved, Resolver, Specification,
+    Specification,
 };
 
-fn dollar_id<J: Json>(contents: &J) -> Option<String> {
+/// Return the specification identified by a `$schema` value.
+pub fn specification_with<J: Json>(dialect_id: &str) -> Option<Box<dyn Specification<J>>> {
+    match dialect_id.trim_end_matches('#') {
+        "https://json-schema.org/draft/2020-12/schema" => Some(Draft202012.boxed()),
+        "https://json-schema.org/draft/2019-09/schema" => Some(Draft201909.boxed()),
+        "http://json-schema.org/draft-07/schema" => Some(Draft7.boxed()),
+        "http://json-schema.org/draft-06/schema" => Some(Draft6.boxed()),
+        "http://json-schema.org/draft-04/schema" => Some(Draft4.boxed()),
+        _ => None,
+    }
+}
+
+fn dollar_id<J: Json>(contents: &J) -> Option<&str> {
     contents
         .as_object()
-        .and_then(|obj| obj.get("$id"))
-        .and_then(|id| id.as_string())
-        .map(|id| id.as_ref().to_owned())
+        .and_then(|object| object.get("$id"))
+        .and_then(Json::as_string)
+        .map(AsRef::as_ref)
 }
 
-fn legacy_id<J: Json>(contents: &J) -> Option<&str> {
-    let Some(object) = contents.as_object() else {
-        return None;
-    };
+/// Before 2019-09, the id is ignored next to `$ref` and an id starting with `#` is an anchor.
+fn legacy_id<'a, J: Json>(contents: &'a J, keyword: &str) -> Option<&'a str> {
+    let object = contents.as_object()?;
     if object.contains_key("$ref") {
         return None;
     }
-    if let Some(id) = object.get("id").and_then(Json::as_string) {
-        let id = id.as_ref();
-        if !id.starts_with('#') {
-            return Some(id);
-        }
-    }
-    None
-}
-
-#[derive(Debug)]
-struct DynamicAnchor {
-    name: String,
-    path: JsonPath,
+    let id = object.get(keyword)?.as_string()?.as_ref();
+    (!id.starts_with('#')).then_some(id)
 }
 
-impl DynamicAnchor {
-    fn new(name: String, path: JsonPath) -> Self {
-        Self { name, path }
-    }
-}
-impl<D: Json> Resolvable<D> for DynamicAnchor {
-    fn name(&self) -> &str {
-        &self.name
-    }
-
-    /// Return the resource for this anchor.
-    fn resolve<'a>(&self, resolver: Resolver<'a, D>) -> Result<Resolved<'a, D>, ReferencingError> {
-        todo!()
-    }
-}
-
-fn anchor<J: Json>(contents: &J) -> AnchorIter<'_, J> {
+fn anchors<'a, J: Json>(contents: &'a J, keywords: &[&str]) -> AnchorIter<'a> {
     let Some(object) = contents.as_object() else {
         return Box::new(std::iter::empty());
     };
-    if let Some(anchor) = object.get("$anchor").and_then(Json::as_string) {
-        let anchor: Box<dyn Resolvable<J>> =
-            Box::new(Anchor::new(anchor.as_ref().to_owned(), JsonPath::new()));
-        return Box::new(std::iter::once(anchor));
-    }
-    if let Some(dynamic_anchor) = object.get("$dynamicAnchor").and_then(Json::as_string) {
-        let anchor: Box<dyn Resolvable<J>> = Box::new(DynamicAnchor::new(
-            dynamic_anchor.as_ref().to_owned(),
-            JsonPath::new(),
-        ));
-        return Box::new(std::iter::once(anchor));
-    }
-    Box::new(std::iter::empty())
+    let anchors: Vec<_> = keywords
+        .iter()
+        .filter_map(|keyword| object.get(keyword)?.as_string())
+        .map(|name| Anchor::new(name.as_ref().to_owned(), JsonPath::new()))
+        .collect();
+    Box::new(anchors.into_iter())
 }
 
-fn anchor_2019<J: Json>(contents: &J) -> AnchorIter<'_, J> {
-    let Some(object) = contents.as_object() else {
-        return Box::new(std::iter::empty());
-    };
-    if let Some(anchor) = object.get("$anchor").and_then(Json::as_string) {
-        let anchor: Box<dyn Resolvable<J>> =
-            Box::new(Anchor::new(anchor.as_ref().to_owned(), JsonPath::new()));
-        return Box::new(std::iter::once(anchor));
-    }
-    Box::new(std::iter::empty())
+fn legacy_anchor_in_id<'a, J: Json>(contents: &'a J, keyword: &str) -> AnchorIter<'a> {
+    let anchor = contents
+        .as_object()
+        .and_then(|object| object.get(keyword))
+        .and_then(Json::as_string)
+        .and_then(|id| id.as_ref().strip_prefix('#'))
+        .map(|name| Anchor::new(name.to_owned(), JsonPath::new()));
+    Box::new(anchor.into_iter())
 }
 
-fn legacy_anchor_in_id<J: Json>(contents: &J) -> AnchorIter<'_, J> {
-    let Some(object) = contents.as_object() else {
-        return Box::new(std::iter::empty());
-    };
-    if let Some(id) = object.get("$id").and_then(Json::as_string) {
-        if let Some(id) = id.as_ref().strip_prefix('#') {
-            // TODO: build proper json path
-            let anchor: Box<dyn Resolvable<J>> =
-                Box::new(Anchor::new(id.to_owned(), JsonPath::new()));
-            return Box::new(std::iter::once(anchor));
-        }
-    }
-    Box::new(std::iter::empty())
+/// Keywords whose values contain subschemas.
+struct Keywords {
+    /// Keywords holding a single subschema.
+    in_value: &'static [&'static str],
+    /// Keywords holding an array of subschemas.
+    in_subarray: &'static [&'static str],
+    /// Keywords holding an object of subschemas.
+    in_subvalues: &'static [&'static str],
+    /// Keywords holding a subschema only if it is an object, as booleans are not schemas.
+    in_object: &'static [&'static str],
+    /// Whether `items` holds either a subschema or an array of subschemas.
+    items_array: bool,
+    /// Whether `dependencies` holds subschemas among arrays of property names.
+    dependencies: bool,
 }
 
-macro_rules! iter_values {
-    ($object:expr, $keys:expr) => {
-        $keys.into_iter().filter_map(|key| $object.get(key))
-    };
-}
+const DRAFT202012: Keywords = Keywords {
+    in_value: &[
+        "additionalProperties",
+        "contains",
+        "contentSchema",
+        "else",
+        "if",
+        "items",
+        "not",
+        "propertyNames",
+        "then",
+        "unevaluatedItems",
+        "unevaluatedProperties",
+    ],
+    in_subarray: &["allOf", "anyOf", "oneOf", "prefixItems"],
+    in_subvalues: &[
+        "$defs",
+        "definitions",
+        "dependentSchemas",
+        "patternProperties",
+        "properties",
+    ],
+    in_object: &[],
+    items_array: false,
+    dependencies: false,
+};
 
-macro_rules! iter_subarrays {
-    ($object:expr, $keys:expr) => {
-        $keys
-            .into_iter()
-            .filter_map(|key| $object.get(key))
-            .filter_map(Json::as_array)
-            .flat_map(|value| value.iter())
-            .filter_map(|item| item.ok())
-    };
-}
+const DRAFT201909: Keywords = Keywords {
+    in_value: &[
+        "additionalItems",
+        "additionalProperties",
+        "contains",
+        "contentSchema",
+        "else",
+        "if",
+        "not",
+        "propertyNames",
+        "then",
+        "unevaluatedItems",
+        "unevaluatedProperties",
+    ],
+    in_subarray: &["allOf", "anyOf", "oneOf"],
+    in_subvalues: &[
+        "$defs",
+        "definitions",
+        "dependentSchemas",
+        "patternProperties",
+        "properties",
+    ],
+    in_object: &[],
+    items_array: true,
+    dependencies: false,
+};
 
-macro_rules! iter_subvalues {
-    ($object:expr, $keys:expr) => {
-        $keys
-            .into_iter()
-            .filter_map(|key| $object.get(key))
-            .filter_map(Json::as_object)
-            .flat_map(|value| value.iter())
-            .map(|(_, subvalue)| subvalue)
-    };
-}
+const DRAFT7: Keywords = Keywords {
+    in_value: &[
+        "additionalItems",
+        "additionalProperties",
+        "contains",
+        "else",
+        "if",
+        "not",
+        "propertyNames",
+        "then",
+    ],
+    in_subarray: &["allOf", "anyOf", "oneOf"],
+    in_subvalues: &["definitions", "patternProperties", "properties"],
+    in_object: &[],
+    items_array: true,
+    dependencies: true,
+};
 
-enum ArrayOrSingle<'a, J: Json> {
-    ArrayIter(<J::Array as JsonArray>::Iter<'a>),
-    SingleIter(std::iter::Once<&'a J>),
-}
+const DRAFT6: Keywords = Keywords {
+    in_value: &[
+        "additionalItems",
+        "additionalProperties",
+        "contains",
+        "not",
+        "propertyNames",
+    ],
+    in_subarray: &["allOf", "anyOf", "oneOf"],
+    in_subvalues: &["definitions", "patternProperties", "properties"],
+    in_object: &[],
+    items_array: true,
+    dependencies: true,
+};
 
-impl<'a, J: Json> Iterator for ArrayOrSingle<'a, J> {
-    type Item = Result<&'a J, JsonError>;
+const DRAFT4: Keywords = Keywords {
+    in_value: &["not"],
+    in_subarray: &["allOf", "anyOf", "oneOf"],
+    in_subvalues: &["definitions", "patternProperties", "properties"],
+    in_object: &["additionalItems", "additionalProperties"],
+    items_array: true,
+    dependencies: true,
+};
 
-    fn next(&mut self) -> Option<Self::Item> {
-        match self {
-            ArrayOrSingle::ArrayIter(iter) => iter.next(),
-            ArrayOrSingle::SingleIter(iter) => iter.next().map(Ok),
+fn subresources_of<'a, J: Json>(
+    contents: &'a J,
+    keywords: &Keywords,
+) -> Box<dyn Iterator<Item = (JsonPath, &'a J)> + 'a> {
+    let Some(object) = contents.as_object() else {
+        return Box::new(std::iter::empty());
+    };
+    let mut subresources = Vec::new();
+    for (key, value) in object.iter() {
+        let Ok(key) = key else {
+            continue;
+        };
+        let key = key.as_ref();
+        if keywords.in_value.contains(&key)
+            || (keywords.in_object.contains(&key) && value.is_object())
+        {
+            subresources.push((jsonpath!(key), value));
+        } else if keywords.in_subarray.contains(&key)
+            || (key == "items" && keywords.items_array && value.is_array())
+        {
+            subresources.extend(elements(key, value));
+        } else if key == "items" && keywords.items_array {
+            subresources.push((jsonpath!(key), value));
+        } else if keywords.in_subvalues.contains(&key) {
+            subresources.extend(members(key, value));
+        } else if key == "dependencies" && keywords.dependencies {
+            subresources.extend(members(key, value).filter(|(_, value)| value.is_object()));
         }
     }
+    Box::new(subresources.into_iter())
 }
 
-fn iter_items<'a, O, J>(object: &'a O) -> impl Iterator<Item = &J>
-where
-    J: Json + 'a,
-    O: JsonObject<Value = J>,
-{
-    object
-        .get("items")
-        .map(|items| {
-            if let Some(array) = items.as_array() {
-                ArrayOrSingle::ArrayIter(array.iter())
-            } else {
-                ArrayOrSingle::SingleIter(std::iter::once(items))
-            }
-        })
+fn elements<'a, J: Json>(
+    keyword: &'a str,
+    value: &'a J,
+) -> impl Iterator<Item = (JsonPath, &'a J)> + 'a {
+    value
+        .as_array()
         .into_iter()
-        .flatten()
-        .filter_map(|r| r.ok())
+        .flat_map(JsonArray::iter)
+        .enumerate()
+        .filter_map(move |(idx, element)| Some((jsonpath!(keyword, idx), element.ok()?)))
 }
 
-fn subresources_of_with_ap_items_dependencies<'a, J: Json>(
-    contents: &'a J,
-    in_value: &'static [&'static str],
-    in_subarray: &'static [&'static str],
-    in_subvalues: &'static [&'static str],
-) -> Box<dyn Iterator<Item = &'a J> + 'a> {
-    let Some(object) = contents.as_object() else {
-        return Box::new(std::iter::empty());
-    };
-    let values = iter_values!(object, in_value);
-    let subarrays = iter_subarrays!(object, in_subarray);
-    let subvalues = iter_subvalues!(object, in_subvalues);
-    let items = iter_items(object);
-    let dependencies = iter_subvalues!(object, &["dependencies"]).filter(|dep| dep.is_object());
-    let additional = iter_subvalues!(object, &["additionalItems", "additionalProperties"])
-        .filter(|items| items.is_object());
-    Box::new(
-        values
-            .chain(subarrays)
-            .chain(subvalues)
-            .chain(items)
-            .chain(dependencies)
-            .chain(additional),
-    )
+fn members<'a, J: Json>(
+    keyword: &'a str,
+    value: &'a J,
+) -> impl Iterator<Item = (JsonPath, &'a J)> + 'a {
+    value
+        .as_object()
+        .into_iter()
+        .flat_map(JsonObject::iter)
+        .filter_map(move |(key, value)| Some((jsonpath!(keyword, key.ok()?.as_ref()), value)))
 }
 
-#[derive(Copy, Clone)]
-pub struct Draft4;
-
-impl<J: Json> Specification<J> for Draft4 {
-    fn name(&self) -> &'static str {
-        "draft-04"
-    }
+macro_rules! specification {
+    ($(#[$meta:meta])* $name:ident, $label:literal, $keywords:expr, id_of = $id_of:expr, anchors_in = $anchors_in:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone)]
+        pub struct $name;
 
-    fn id_of<'a>(&self, document: &'a J) -> Option<&'a str> {
-        legacy_id(document)
-    }
+        impl<J: Json> Specification<J> for $name {
+            fn name(&self) -> &'static str {
+                $label
+            }
 
-    fn subresources_of<'a>(
-        &self,
-        document: &'a J,
-    ) -> Box<dyn Iterator<Item = (JsonPath, &'a J)> + 'a> {
-        todo!()
-        //  subresources_of_with_ap_items_dependencies(
-        //      document,
-        //      &["not"],
-        //      &["allOf", "anyOf", "oneOf"],
-        //      &["definitions", "patternProperties", "properties"],
-        //  )
-    }
+            fn id_of<'a>(&self, document: &'a J) -> Option<&'a str> {
+                $id_of(document)
+            }
 
-    fn anchors_in<'a>(&self, document: &'a J) -> AnchorIter<'a, J> {
-        legacy_anchor_in_id(document)
-    }
+            fn subresources_of<'a>(
+                &self,
+                document: &'a J,
+            ) -> Box<dyn Iterator<Item = (JsonPath, &'a J)> + 'a> {
+                subresources_of(document, &$keywords)
+            }
 
-    fn maybe_in_subresource<'a>(
-        &self,
-        segments: &[Segment],
-        resolver: &'a Resolver<'a, J>,
-        subresource: SubResource<'a, J>,
-    ) -> Resolver<'a, J> {
-        todo!()
-    }
+            fn anchors_in<'a>(&self, document: &'a J) -> AnchorIter<'a> {
+                $anchors_in(document)
+            }
 
-    fn box_clone(&self) -> Box<dyn Specification<J>> {
-        Box::new(*self)
-    }
+            fn box_clone(&self) -> Box<dyn Specification<J>> {
+                Box::new(*self)
+            }
+        }
+    };
 }
+
+specification!(
+    /// JSON Schema Draft 2020-12.
+    Draft202012,
+    "draft2020-12",
+    DRAFT202012,
+    id_of = dollar_id,
+    anchors_in = |document| anchors(document, &["$anchor", "$dynamicAnchor"])
+);
+specification!(
+    /// JSON Schema Draft 2019-09.
+    Draft201909,
+    "draft2019-09",
+    DRAFT201909,
+    id_of = dollar_id,
+    anchors_in = |document| anchors(document, &["$anchor"])
+);
+specification!(
+    /// JSON Schema Draft 7.
+    Draft7,
+    "draft-07",
+    DRAFT7,
+    id_of = |document| legacy_id(document, "$id"),
+    anchors_in = |document| legacy_anchor_in_id(document, "$id")
+);
+specification!(
+    /// JSON Schema Draft 6.
+    Draft6,
+    "draft-06",
+    DRAFT6,
+    id_of = |document| legacy_id(document, "$id"),
+    anchors_in = |document| legacy_anchor_in_id(document, "$id")
+);
+specification!(
+    /// JSON Schema Draft 4.
+    Draft4,
+    "draft-04",
+    DRAFT4,
+    id_of = |document| legacy_id(document, "id"),
+    anchors_in = |document| legacy_anchor_in_id(document, "id")
+);
@@ -1,7 +1,7 @@
-use std::{borrow::Cow, collections::VecDeque};
+use std::collections::VecDeque;
 
-use error::ReferencingError;
 use jsonlike::prelude::*;
+use percent_encoding::percent_decode_str;
 use url::Url;
 
 mod anchors;
@@ -11,9 +11,11 @@ mod path;
 mod registry;
 mod resources;
 mod specification;
+pub use anchors::Anchor;
+pub use error::ReferencingError;
+pub use path::{JsonPath, Segment};
 pub use registry::Registry;
-pub use resources::Resource;
-use resources::SubResource;
+pub use resources::{Resource, SubResource};
 pub use specification::Specification;
 
 // TODO:
@@ -22,22 +24,9 @@ pub use specification::Specification;
 //   - Invalidate StoredResource::Referenced when an existing value is replaced
 //   - Better type & variant names
 //   - Rethink the whole structure and properly set pub/private types
-//   - maybe in subresource
-//   - Use `Cow` in retrieved? or just a reference
+//   - Retrieve resources missing from the registry
 //   - Issue - no dyn dispatch for `Json` without specifying all assoc types
 
-#[derive(Debug)]
-pub struct Retrieved<'a, V, D: Json> {
-    value: V,
-    registry: &'a Registry<D>,
-}
-
-impl<'a, V, D: Json> Retrieved<'a, V, D> {
-    pub fn new(value: V, registry: &'a Registry<D>) -> Self {
-        Self { value, registry }
-    }
-}
-
 #[derive(Debug)]
 pub struct Resolved<'a, D: Json> {
     pub contents: &'a D,
@@ -76,51 +65,69 @@ impl<'a, D: Json> Resolver<'a, D> {
         }
     }
 
+    /// The URI relative references are resolved against.
+    pub fn base_uri(&self) -> &Url {
+        &self.base_uri
+    }
+
+    /// URIs of the resources this resolver went through, the most recent first.
+    pub fn dynamic_scope(&self) -> impl Iterator<Item = &Url> {
+        self.previous.iter()
+    }
+
+    /// Resolve a reference to the resource it points to.
+    ///
+    /// The returned resolver resolves references within the target, its base URI reflects every
+    /// `$id` between the target resource and the target itself.
     pub fn lookup(&self, reference: &str) -> Result<Resolved<'a, D>, ReferencingError> {
-        let (uri, fragment) = if let Some(reference) = reference.strip_prefix('#') {
-            (Cow::Borrowed(&self.base_uri), Cow::Borrowed(reference))
+        let (uri, fragment) = if let Some(fragment) = reference.strip_prefix('#') {
+            (self.base_uri.clone(), fragment.to_owned())
         } else {
-            let mut full = self.base_uri.join(reference).expect("TODO");
-            let fragment = full.fragment().unwrap_or_default().to_owned();
-            full.set_fragment(None);
-            (Cow::Owned(full), Cow::Owned(fragment))
+            let mut uri = self
+                .base_uri
+                .join(reference)
+                .map_err(|_| ReferencingError::unresolvable(reference))?;
+            let fragment = uri.fragment().unwrap_or_default().to_owned();
+            uri.set_fragment(None);
+            (uri, fragment)
         };
-        let retrieved = self.registry.get_or_retrieve(&uri);
+        let resource = self
+            .registry
+            .get(&uri)
+            .ok_or_else(|| ReferencingError::unresolvable(reference))?;
+        let resolver = self.evolve(&uri);
         if fragment.starts_with('/') {
-            let resolver = self.evolve(retrieved.registry, &uri);
-            todo!("Pointer should be implemented for both, resource & subresource")
-            //  retrieved.value.pointer(&fragment, resolver)
+            resource.pointer(&fragment, resolver)
         } else if !fragment.is_empty() {
-            let retrieved = retrieved.registry.anchor(&uri, &fragment)?;
-            let resolver = self.evolve(retrieved.registry, &uri);
-            retrieved.value.resolve(resolver)
+            let name = percent_decode_str(&fragment).decode_utf8_lossy();
+            let anchor = self.registry.anchor(&uri, &name)?;
+            let resolver = resolver.in_subresource(&anchor);
+            Ok(Resolved::new(anchor.contents, resolver))
         } else {
-            let resolver = self.evolve(retrieved.registry, &uri);
-            Ok(Resolved::new(retrieved.value.contents(), resolver))
+            Ok(Resolved::new(resource.contents, resolver))
         }
     }
 
     /// Evolve, appending to the dynamic scope.
-    fn evolve(&self, registry: &'a Registry<D>, base_uri: &Url) -> Self {
+    fn evolve(&self, base_uri: &Url) -> Self {
         let mut previous = self.previous.clone();
-        // TODO: Re-check if it could be empty
-        if !self.base_uri.as_str().is_empty()
-            && (!previous.is_empty() || base_uri != &self.base_uri)
-        {
+        if !previous.is_empty() || base_uri != &self.base_uri {
             previous.push_front(self.base_uri.clone());
         }
         Resolver {
             base_uri: base_uri.to_owned(),
-            registry,
+            registry: self.registry,
             previous,
         }
     }
 
     /// Create a resolver for a subresource (which may have a new base URI).
-    fn in_subresource(&self, subresource: SubResource<'a, D>) -> Resolver<D> {
-        if let Some(id) = subresource.id() {
+    pub fn in_subresource(&self, subresource: &SubResource<'_, D>) -> Resolver<'a, D> {
+        let base_uri = subresource.id().and_then(|id| self.base_uri.join(id).ok());
+        if let Some(mut base_uri) = base_uri {
+            base_uri.set_fragment(None);
             Resolver {
-                base_uri: self.base_uri.join(id).expect("Is it possible?"),
+                base_uri,
                 registry: self.registry,
                 previous: self.previous.clone(),
             }
@@ -133,13 +140,15 @@ impl<'a, D: Json> Resolver<'a, D> {
 #[cfg(test)]
 mod tests {
     use jsonlike::prelude::*;
+    use serde_json::{json, Value};
+    use test_case::test_case;
+    use url::Url;
 
     use crate::{
-        anchors::{Anchor, Resolvable},
-        path::{jsonpath, JsonPath, Segment},
-        resources::SubResource,
+        jsonschema::{Draft202012, Draft4, Draft7},
+        path::{jsonpath, JsonPath},
         specification::AnchorIter,
-        Resolver, Specification,
+        Anchor, ReferencingError, Registry, Resource, Specification,
     };
 
     #[derive(Debug, Clone, Copy)]
@@ -176,15 +185,14 @@ mod tests {
             Box::new(subresources_of.into_iter())
         }
 
-        fn anchors_in<'a>(&self, document: &'a D) -> AnchorIter<'a, D> {
+        fn anchors_in<'a>(&self, document: &'a D) -> AnchorIter<'a> {
             let mut anchors_in = vec![];
             if let Some(object) = document.as_object() {
                 if let Some(anchors) = object.get("anchors").and_then(Json::as_object) {
                     for (key, _) in anchors.iter() {
                         if let Ok(key) = key {
-                            let anchor = Anchor::new(key.as_ref().to_owned(), jsonpath!("anchors"));
-                            let boxed: Box<dyn Resolvable<D>> = Box::new(anchor);
-                            anchors_in.push(boxed);
+                            let key = key.as_ref();
+                            anchors_in.push(Anchor::new(key.to_owned(), jsonpath!("anchors", key)));
                         }
                     }
                 }
@@ -192,26 +200,117 @@ mod tests {
             Box::new(anchors_in.into_iter())
         }
 
-        fn maybe_in_subresource<'a>(
-            &self,
-            segments: &[Segment],
-            resolver: &'a Resolver<'a, D>,
-            subresource: SubResource<'a, D>,
-        ) -> Resolver<'a, D> {
-            if segments.len() % 2 == 0
-                && segments
-                    .iter()
-                    .step_by(2)
-                    .all(|each| each == &Segment::from("children".to_owned()))
-            {
-                resolver.in_subresource(subresource)
-            } else {
-                resolver.clone()
-            }
-        }
-
         fn box_clone(&self) -> Box<dyn Specification<D>> {
             Box::new(*self)
         }
     }
+
+    fn registry(specification: Box<dyn Specification<Value>>, contents: Value) -> Registry<Value> {
+        let uri = Url::parse("http://example.com/root.json").expect("Invalid URL");
+        let mut registry =
+            Registry::new().with_resource(uri, Resource::new(contents, specification));
+        registry.crawl();
+        registry
+    }
+
+    fn lookup(registry: &Registry<Value>, reference: &str) -> (Value, String) {
+        let base_uri = Url::parse("http://example.com/root.json").expect("Invalid URL");
+        let resolved = registry
+            .resolver(base_uri)
+            .lookup(reference)
+            .expect("Resolvable reference");
+        (
+            resolved.contents.clone(),
+            resolved.resolver.base_uri().to_string(),
+        )
+    }
+
+    #[test_case("#", json!({"$defs": {"a": {"$id": "nested/", "$defs": {"b": {"$anchor": "b", "type": "null"}}}, "c": {"$anchor": "c"}, "d~/e": {"minimum": 1}}}), "http://example.com/root.json"; "empty fragment")]
+    #[test_case("#/$defs/a/$defs/b", json!({"$anchor": "b", "type": "null"}), "http://example.com/nested/"; "pointer through nested id")]
+    #[test_case("#/$defs/d~0~1e", json!({"minimum": 1}), "http://example.com/root.json"; "escaped pointer")]
+    #[test_case("#/$defs/d~0~1e/minimum", json!(1), "http://example.com/root.json"; "pointer into a keyword")]
+    #[test_case("#/$defs/c", json!({"$anchor": "c"}), "http://example.com/root.json"; "pointer")]
+    #[test_case("#c", json!({"$anchor": "c"}), "http://example.com/root.json"; "anchor")]
+    #[test_case("nested/#b", json!({"$anchor": "b", "type": "null"}), "http://example.com/nested/"; "anchor in nested resource")]
+    #[test_case("nested/", json!({"$id": "nested/", "$defs": {"b": {"$anchor": "b", "type": "null"}}}), "http://example.com/nested/"; "nested resource")]
+    #[test_case("http://example.com/nested/#/$defs/b/type", json!("null"), "http://example.com/nested/"; "absolute pointer")]
+    fn test_lookup(reference: &str, expected: Value, base_uri: &str) {
+        let registry = registry(
+            Draft202012.boxed(),
+            json!({
+                "$defs": {
+                    "a": {"$id": "nested/", "$defs": {"b": {"$anchor": "b", "type": "null"}}},
+                    "c": {"$anchor": "c"},
+                    "d~/e": {"minimum": 1}
+                }
+            }),
+        );
+        assert_eq!(
+            lookup(&registry, reference),
+            (expected, base_uri.to_owned())
+        );
+    }
+
+    #[test]
+    fn test_lookup_then() {
+        let registry = registry(
+            Draft202012.boxed(),
+            json!({"$defs": {"a": {"$id": "nested/", "$ref": "#/$defs/b", "$defs": {"b": {"type": "null"}}}}}),
+        );
+        let resolver =
+            registry.resolver(Url::parse("http://example.com/root.json").expect("Invalid URL"));
+        let resolved = resolver.lookup("nested/").expect("Resolvable reference");
+        let resolved = resolved
+            .resolver
+            .lookup("#/$defs/b")
+            .expect("Resolvable reference");
+        assert_eq!(resolved.contents, &json!({"type": "null"}));
+        assert_eq!(
+            resolved
+                .resolver
+                .dynamic_scope()
+                .map(Url::as_str)
+                .collect::<Vec<_>>(),
+            ["http://example.com/nested/", "http://example.com/root.json"]
+        );
+    }
+
+    #[test_case(Draft4.boxed(), json!({"definitions": {"a": {"id": "#foo"}}}), "#foo"; "draft 4 anchor")]
+    #[test_case(Draft7.boxed(), json!({"definitions": {"a": {"$id": "#foo"}}}), "#foo"; "draft 7 anchor")]
+    #[test_case(Draft202012.boxed(), json!({"$defs": {"a": {"$dynamicAnchor": "foo"}}}), "#foo"; "dynamic anchor")]
+    #[test_case(Draft4.boxed(), json!({"items": [{"id": "#foo"}]}), "#/items/0"; "draft 4 items")]
+    #[test_case(Draft7.boxed(), json!({"dependencies": {"a": {"$id": "#foo"}}}), "#/dependencies/a"; "draft 7 dependencies")]
+    fn test_lookup_legacy(
+        specification: Box<dyn Specification<Value>>,
+        contents: Value,
+        reference: &str,
+    ) {
+        let registry = registry(specification, contents);
+        assert!(lookup(&registry, reference).0.is_object());
+    }
+
+    #[test]
+    fn test_legacy_id_next_to_ref() {
+        // The id is ignored next to `$ref`, so `a` stays within the root resource
+        let registry = registry(
+            Draft7.boxed(),
+            json!({"definitions": {"a": {"$id": "nested/", "$ref": "#/definitions/b"}, "b": {}}}),
+        );
+        let (_, base_uri) = lookup(&registry, "#/definitions/a");
+        assert_eq!(base_uri, "http://example.com/root.json");
+    }
+
+    #[test_case("#/$defs/b", "Pointer '/$defs/b' does not exist")]
+    #[test_case("#/$defs/a/0", "Pointer '/$defs/a/0' does not exist")]
+    #[test_case("#/$defs/a/minimum/0", "Pointer '/$defs/a/minimum/0' does not exist")]
+    #[test_case("#b", "'b' does not exist within 'http://example.com/root.json'")]
+    #[test_case("#b/c", "'#b/c' is neither a plain name anchor nor a JSON Pointer")]
+    #[test_case("other.json", "'other.json' does not exist")]
+    fn test_lookup_error(reference: &str, expected: &str) {
+        let registry = registry(Draft202012.boxed(), json!({"$defs": {"a": {"minimum": 1}}}));
+        let resolver =
+            registry.resolver(Url::parse("http://example.com/root.json").expect("Invalid URL"));
+        let error: ReferencingError = resolver.lookup(reference).expect_err("Should fail");
+        assert_eq!(error.to_string(), expected);
+    }
 }
@@ -31,6 +31,17 @@ impl JsonPath {
     pub fn iter(&self) -> std::slice::Iter<'_, Segment> {
         self.0.iter()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Extend this path with the segments of a path relative to it.
+    pub fn join(&self, other: &JsonPath) -> JsonPath {
+        let mut segments = self.0.clone();
+        segments.extend(other.iter().cloned());
+        JsonPath(segments)
+    }
 }
 
 macro_rules! jsonpath {
@@ -4,16 +4,21 @@ use jsonlike::Json;
 use url::Url;
 
 use crate::{
-    anchors::{AnchorMap, Resolvable},
+    anchors::AnchorMap,
     error::ReferencingError,
-    resources::{self, Res},
-    Resolver, Resource, Retrieved,
+    path::JsonPath,
+    resources::{self, ResourceReference, SubResource},
+    Resolver, Resource,
 };
 
+/// A collection of resources, identified by URI.
+///
+/// Subresources and anchors are discovered by `Registry::crawl`, references are only resolvable
+/// against a crawled registry.
 #[derive(Default, Debug)]
 pub struct Registry<D: Json> {
     resources: resources::ResourceMap<D>,
-    anchors: AnchorMap<D>,
+    anchors: AnchorMap,
     uncrawled: HashSet<Url>,
 }
 
@@ -26,7 +31,7 @@ impl<D: Json> Registry<D> {
         }
     }
     /// Return the `Resource` identified by the given URI.
-    pub fn get<'a>(&'a self, uri: &Url) -> Option<Res<'a, D>> {
+    pub fn get<'a>(&'a self, uri: &Url) -> Option<SubResource<'a, D>> {
         self.resources.get(uri)
     }
     /// Count the total number of resources in this registry.
@@ -36,32 +41,17 @@ impl<D: Json> Registry<D> {
     pub fn is_empty(&self) -> bool {
         self.resources.is_empty()
     }
-    /// Crawl all added resources, discovering subresources.
+    /// Crawl all added resources, discovering subresources and anchors.
     pub fn crawl(&mut self) {
-        let mut subresources = Vec::new();
-        // Inspect the known uncrawled resources first
-        while let Some(uri) = self.uncrawled.drain().next() {
-            // INVARIANT: These resources always exist in both, `self.resources` &
-            // `self.uncrawled`, therefore this should never panic.
-            let resource = self
-                .resources
-                .get_resource(&uri)
-                .unwrap_or_else(|| panic!("Resource does not exist: {uri}"));
-            // Push all subresources for further traversal.
-            // NOTE: Subresources are represesented as their parent ID + a path to this subresource
-            for subresource in resource.subresources() {
-                subresources.push(subresource.into_reference_in(uri.clone()))
-            }
-            for anchor in resource.anchors() {
-                // TODO: Implement AnchorKey, so it is easier to implement Borrow for it
-                let name = anchor.name().to_owned();
-                self.anchors.insert(uri.clone(), name, anchor);
-            }
-        }
-        // Then traverse all reachable subresources
-        while let Some(reference) = subresources.pop() {
-            // INVARIANT: Every subresource is built from a path within a valid parent resource
-            // and is created within this function, therefore this should never panic.
+        // Locations to visit, with the base URI of their parent
+        let mut locations: Vec<(Url, ResourceReference)> = self
+            .uncrawled
+            .drain()
+            .map(|uri| (uri.clone(), ResourceReference::new(uri, JsonPath::new())))
+            .collect();
+        while let Some((mut base_uri, reference)) = locations.pop() {
+            // INVARIANT: Every location is either an added resource or built from a path within
+            // one in this function, therefore this should never panic.
             let subresource = self
                 .resources
                 .get_subresource(&reference)
@@ -71,13 +61,38 @@ impl<D: Json> Registry<D> {
                         reference.path, reference.parent_uri
                     )
                 });
-            for subresource in subresource.subresources() {
-                subresources.push(subresource.into_reference_in(reference.parent_uri.clone()))
+            let id = subresource
+                .id()
+                .and_then(|id| base_uri.join(id).ok())
+                .map(|mut uri| {
+                    uri.set_fragment(None);
+                    uri
+                });
+            let anchors: Vec<_> = subresource.anchors().collect();
+            let children: Vec<_> = subresource.subresources().map(|(path, _)| path).collect();
+            if let Some(uri) = id {
+                if uri != reference.parent_uri || !reference.path.is_empty() {
+                    self.resources
+                        .insert_referenced(uri.clone(), reference.clone());
+                }
+                base_uri = uri;
             }
-            if let Some(id) = subresource.id() {
-                let uri = reference.parent_uri.join(id).expect("Can it fail?");
-                self.resources
-                    .insert_referenced(uri, subresource.into_reference_in(reference.parent_uri));
+            for anchor in anchors {
+                let location = ResourceReference::new(
+                    reference.parent_uri.clone(),
+                    reference.path.join(anchor.path()),
+                );
+                self.anchors
+                    .insert(base_uri.clone(), anchor.name().to_owned(), location);
+            }
+            for path in children {
+                locations.push((
+                    base_uri.clone(),
+                    ResourceReference::new(
+                        reference.parent_uri.clone(),
+                        reference.path.join(&path),
+                    ),
+                ));
             }
         }
     }
@@ -96,7 +111,7 @@ impl<D: Json> Registry<D> {
         }
         Registry {
             resources,
-            anchors: AnchorMap::default(),
+            anchors: self.anchors,
             uncrawled,
         }
     }
@@ -105,68 +120,41 @@ impl<D: Json> Registry<D> {
         Resolver::new(base_uri, self)
     }
 
-    pub fn get_or_retrieve(&self, uri: &Url) -> Retrieved<Res<D>, D> {
-        if let Some(resource) = self.resources.get(uri) {
-            todo!()
-            //  return Retrieved {
-            //      value: AnchorOrResource::Resource(resource),
-            //      registry: self,
-            //  };
-        }
-
-        //  registry = self.crawl()
-        //  resource = registry._resources.get(uri)
-        //  if resource is not None:
-        //      return Retrieved(registry=registry, value=resource)
-
-        //  try:
-        //      resource = registry._retrieve(uri)
-        //  except (
-        //      exceptions.CannotDetermineSpecification,
-        //      exceptions.NoSuchResource,
-        //  ):
-        //      raise
-        //  except Exception:
-        //      raise exceptions.Unretrievable(ref=uri)
-        //  else:
-        //      registry = registry.with_resource(uri, resource)
-        //      return Retrieved(registry=registry, value=resource)
-        todo!()
-    }
-
     /// Retrieve a given anchor from a resource which must already be crawled.
-    pub fn anchor(
-        &self,
+    pub fn anchor<'a>(
+        &'a self,
         uri: &Url,
         name: &str,
-    ) -> Result<Retrieved<Box<dyn Resolvable<D> + '_>, D>, ReferencingError> {
-        if let Some(value) = self.anchors.get(uri, name) {
-            return Ok(Retrieved::new(value, self));
+    ) -> Result<SubResource<'a, D>, ReferencingError> {
+        let mut location = self.anchors.get(uri, name);
+        if location.is_none() {
+            // The resource may be known by another URI than its canonical one
+            if let Some(canonical_uri) = self
+                .get(uri)
+                .and_then(|resource| resource.id())
+                .and_then(|id| uri.join(id).ok())
+            {
+                location = self.anchors.get(&canonical_uri, name);
+            }
+        }
+        if let Some(location) = location {
+            // INVARIANT: Anchors are only found within crawled resources
+            return Ok(self
+                .resources
+                .get_subresource(location)
+                .expect("Anchors point to existing subresources"));
+        }
+        if name.contains('/') {
+            Err(ReferencingError::InvalidAnchor {
+                reference: uri.to_owned(),
+                anchor: name.to_owned(),
+            })
+        } else {
+            Err(ReferencingError::NoSuchAnchor {
+                reference: uri.to_owned(),
+                anchor: name.to_owned(),
+            })
         }
-        // TODO: Maybe interior mutability?
-
-        //        registry = self.crawl()
-        //        value = registry._anchors.get((uri, name))
-        //        if value is not None:
-        //            return Retrieved(value=value, registry=registry)
-        //
-        //        resource = self[uri]
-        //        canonical_uri = resource.id()
-        //        if canonical_uri is not None:
-        //            value = registry._anchors.get((canonical_uri, name))
-        //            if value is not None:
-        //                return Retrieved(value=value, registry=registry)
-        //
-        //        if "/" in name:
-        //            raise exceptions.InvalidAnchor(
-        //                ref=uri,
-        //                resource=resource,
-        //                anchor=name,
-        //            )
-        Err(ReferencingError::NoSuchAnchor {
-            reference: uri.to_owned(),
-            anchor: name.to_owned(),
-        })
     }
 }
 
@@ -212,6 +200,48 @@ mod tests {
         let mut registry = Registry::new().with_resource(root_id.clone(), root);
         registry.crawl();
         let anchor = registry.anchor(&root_id, "foo").expect("Anchor not found");
-        //   assert_eq!(anchor.value, 12);
+        assert_eq!(anchor.contents(), &json!(12));
+    }
+
+    #[test]
+    fn test_crawl_finds_anchors_by_canonical_uri() {
+        let root = IdAndChildren.create_resource(json!({"ID": "urn:bar", "anchors": {"foo": 12}}));
+        let uri = Url::parse("urn:retrieved").expect("Invalid URL");
+        let mut registry = Registry::new().with_resource(uri.clone(), root);
+        registry.crawl();
+        let anchor = registry.anchor(&uri, "foo").expect("Anchor not found");
+        assert_eq!(anchor.contents(), &json!(12));
+    }
+
+    #[test]
+    fn test_crawl_finds_anchors_in_subresources() {
+        let root = IdAndChildren.create_resource(json!({
+            "ID": "urn:root",
+            "children": [{"ID": "urn:child", "anchors": {"foo": 12}}]
+        }));
+        let root_id = Url::parse("urn:root").expect("Invalid URL");
+        let mut registry = Registry::new().with_resource(root_id.clone(), root);
+        registry.crawl();
+        let child_id = Url::parse("urn:child").expect("Invalid URL");
+        let anchor = registry.anchor(&child_id, "foo").expect("Anchor not found");
+        assert_eq!(anchor.contents(), &json!(12));
+        assert!(registry.anchor(&root_id, "foo").is_err());
+    }
+
+    #[test]
+    fn test_no_such_anchor() {
+        let root = IdAndChildren.create_resource(json!({"anchors": {"foo": 12}}));
+        let uri = Url::parse("urn:root").expect("Invalid URL");
+        let mut registry = Registry::new().with_resource(uri.clone(), root);
+        registry.crawl();
+        let error = registry.anchor(&uri, "bar").expect_err("Missing anchor");
+        assert_eq!(error.to_string(), "'bar' does not exist within 'urn:root'");
+        let error = registry
+            .anchor(&uri, "foo/bar")
+            .expect_err("Invalid anchor");
+        assert_eq!(
+            error.to_string(),
+            "'#foo/bar' is neither a plain name anchor nor a JSON Pointer"
+        );
     }
 }
@@ -5,28 +5,13 @@ use url::Url;
 use jsonlike::prelude::*;
 
 use crate::{
-    anchors::Resolvable,
+    anchors::Anchor,
     error::ReferencingError,
-    jsonschema::Draft4,
+    jsonschema::specification_with,
     path::{JsonPath, Segment},
     Resolved, Resolver, Specification,
 };
 
-#[derive(Debug)]
-pub enum Res<'a, D: Json> {
-    Owned(&'a Resource<D>),
-    Referenced(SubResource<'a, D>),
-}
-
-impl<'a, D: Json> Res<'a, D> {
-    pub(crate) fn contents(&self) -> &'a D {
-        match self {
-            Res::Owned(resource) => &resource.contents,
-            Res::Referenced(subresource) => subresource.contents,
-        }
-    }
-}
-
 /// A document with a concrete interpretation under a specification.
 #[derive(Debug)]
 pub struct Resource<D: Json> {
@@ -51,26 +36,30 @@ impl<D: Json> Resource<D> {
             specification,
         }
     }
+    /// Create a resource, detecting its specification from `$schema`.
+    ///
+    /// Fails if `$schema` is missing or unknown and no default specification is given.
     pub fn from_contents(
         contents: D,
         default_specification: Option<Box<dyn Specification<D>>>,
-    ) -> Resource<D> {
-        // TODO: Properly handle no spec
-        let specification = if let Some(object) = contents.as_object() {
-            if let Some(dialect_id) = object.get("$schema").and_then(Json::as_string) {
-                match dialect_id.as_ref() {
-                    "http://json-schema.org/draft-04/schema" => Draft4.boxed(),
-                    _ => default_specification.unwrap(),
-                }
-            } else {
-                default_specification.unwrap()
-            }
-        } else {
-            default_specification.unwrap()
-        };
-        Resource {
+    ) -> Result<Resource<D>, ReferencingError> {
+        let dialect_id = dialect_id(&contents);
+        let specification = dialect_id
+            .and_then(specification_with)
+            .or(default_specification)
+            .ok_or_else(|| ReferencingError::CannotDetermineSpecification {
+                dialect_id: dialect_id.map(str::to_owned),
+            })?;
+        Ok(Resource {
             contents,
             specification,
+        })
+    }
+    /// View this resource as a subresource rooted at its document.
+    pub fn as_subresource(&self) -> SubResource<'_, D> {
+        SubResource {
+            contents: &self.contents,
+            specification: self.specification.box_clone(),
         }
     }
     /// Retrieve resource's identifier.
@@ -80,172 +69,141 @@ impl<D: Json> Resource<D> {
             .map(|id| id.trim_end_matches('#'))
     }
     /// Retrieve this resource's subresources.
-    pub fn subresources<'a>(&'a self) -> Box<dyn Iterator<Item = SubResource<'a, D>> + 'a> {
+    pub fn subresources<'a>(
+        &'a self,
+    ) -> Box<dyn Iterator<Item = (JsonPath, SubResource<'a, D>)> + 'a> {
         Box::new(
             self.specification
                 .subresources_of(&self.contents)
                 .map(|(path, subresource)| {
-                    SubResource::from_contents(
-                        path.clone(),
-                        subresource,
-                        Some(self.specification.box_clone()),
+                    (
+                        path,
+                        SubResource::from_contents(subresource, self.specification.box_clone()),
                     )
                 }),
         )
     }
-    /// Retrieve this resource's (specification-specific) identifier.
-    pub fn anchors<'a>(&'a self) -> Box<dyn Iterator<Item = Box<dyn Resolvable<D>>> + 'a> {
+    /// Retrieve this resource's (specification-specific) anchors.
+    pub fn anchors(&self) -> Box<dyn Iterator<Item = Anchor> + '_> {
         self.specification.anchors_in(&self.contents)
     }
+}
 
-    /// Resolve the given JSON pointer.
-    pub(crate) fn pointer<'a>(
-        &self,
-        pointer: &str,
-        resolver: Resolver<'a, D>,
-    ) -> Result<Resolved<'a, D>, ReferencingError> {
-        let mut contents = &self.contents;
-        let mut segments = vec![];
-        let mut pointer_chars = pointer.chars();
-        pointer_chars.next();
-        let trimmed_pointer = pointer_chars.as_str();
-        let automaton =
-            AhoCorasick::new(["~1", "~0"]).expect("Invalid patterns for Aho-Corasick automaton");
-        for segment in percent_encoding::percent_decode_str(trimmed_pointer)
-            .decode_utf8_lossy()
-            .split('/')
-        {
-            let parsed_segment;
-            (contents, parsed_segment) = if let Some(array) = contents.as_array() {
-                let idx = segment.parse::<usize>().expect("Properly return an error");
-                (
-                    array
-                        .get(idx)
-                        .ok_or_else(|| ReferencingError::pointer_to_nowhere(pointer))?,
-                    Segment::from(idx),
-                )
-            } else if let Some(object) = contents.as_object() {
-                if automaton.is_match(segment) {
-                    let key = automaton.replace_all(segment, &["/", "~"]);
-                    (
-                        object
-                            .get(&key)
-                            .ok_or_else(|| ReferencingError::pointer_to_nowhere(pointer))?,
-                        Segment::from(key),
-                    )
-                } else {
-                    (
-                        object
-                            .get(segment)
-                            .ok_or_else(|| ReferencingError::pointer_to_nowhere(pointer))?,
-                        Segment::from(segment.to_owned()),
-                    )
-                }
-            } else {
-                return Err(ReferencingError::unresolvable(pointer));
-            };
-            segments.push(parsed_segment);
-            // last_resolver = &resolver;
-            let _resolver = self.specification.maybe_in_subresource(
-                &segments,
-                &resolver,
-                SubResource {
-                    contents,
-                    path: JsonPath::new(),
-                    specification: self.specification.box_clone(),
-                },
-            );
-            // if resolver is not last:
-            //     segments = []
-        }
-        Ok(Resolved {
-            contents: todo!(),
-            resolver,
-        })
-    }
-
-    fn get_subresource(&self, path: &JsonPath) -> Option<SubResource<D>> {
-        let mut contents = &self.contents;
-        for segment in path.iter() {
-            match segment {
-                Segment::Key(key) => {
-                    contents = contents.as_object().and_then(|object| object.get(key))?;
-                }
-                Segment::Index(index) => {
-                    contents = contents.as_array().and_then(|array| array.get(*index))?;
-                }
-            }
-        }
-        Some(SubResource {
-            contents,
-            path: path.clone(),
-            specification: self.specification.box_clone(),
-        })
-    }
+fn dialect_id<D: Json>(contents: &D) -> Option<&str> {
+    contents
+        .as_object()
+        .and_then(|object| object.get("$schema"))
+        .and_then(Json::as_string)
+        .map(AsRef::as_ref)
 }
 
+/// A part of a document, interpreted under a specification.
 #[derive(Debug)]
 pub struct SubResource<'a, D: Json> {
     pub contents: &'a D,
-    // TODO: Maybe remove it? to avoid cloning `JsonPath` into it
-    pub path: JsonPath,
     pub specification: Box<dyn Specification<D>>,
 }
+
 impl<'a, D: Json> PartialEq for SubResource<'a, D> {
     fn eq(&self, other: &Self) -> bool {
         // TODO: It should not be by name
         self.specification.name() == other.specification.name()
-            && self.path == other.path
             && self.contents.equal(other.contents)
     }
 }
 
 impl<'a, D: Json> SubResource<'a, D> {
+    /// Create a subresource, detecting its specification from `$schema`.
+    ///
+    /// Embedded resources may switch to another dialect, otherwise they share the specification
+    /// of their parent.
     pub fn from_contents(
-        path: JsonPath,
-        contents: &D,
-        default_specification: Option<Box<dyn Specification<D>>>,
-    ) -> SubResource<D> {
-        // TODO: Properly handle no spec
-        let specification = if let Some(object) = contents.as_object() {
-            if let Some(dialect_id) = object.get("$schema").and_then(Json::as_string) {
-                match dialect_id.as_ref() {
-                    "http://json-schema.org/draft-04/schema" => Draft4.boxed(),
-                    _ => default_specification.unwrap(),
-                }
-            } else {
-                default_specification.unwrap()
-            }
-        } else {
-            default_specification.unwrap()
-        };
+        contents: &'a D,
+        default_specification: Box<dyn Specification<D>>,
+    ) -> SubResource<'a, D> {
+        let specification = dialect_id(contents)
+            .and_then(specification_with)
+            .unwrap_or(default_specification);
         SubResource {
             contents,
-            path,
             specification,
         }
     }
+    pub fn contents(&self) -> &'a D {
+        self.contents
+    }
     /// Retrieve resource's identifier.
-    pub fn id(&self) -> Option<&str> {
+    pub fn id(&self) -> Option<&'a str> {
         self.specification
             .id_of(self.contents)
             .map(|id| id.trim_end_matches('#'))
     }
-    pub fn subresources(&'a self) -> Box<dyn Iterator<Item = SubResource<'a, D>> + 'a> {
+    /// Retrieve this resource's subresources.
+    pub fn subresources(&self) -> Box<dyn Iterator<Item = (JsonPath, SubResource<'a, D>)> + '_> {
         Box::new(
             self.specification
                 .subresources_of(self.contents)
                 .map(|(path, subresource)| {
-                    SubResource::from_contents(
-                        path.clone(),
-                        subresource,
-                        Some(self.specification.box_clone()),
+                    (
+                        path,
+                        SubResource::from_contents(subresource, self.specification.box_clone()),
                     )
                 }),
         )
     }
-    pub fn into_reference_in(self, uri: Url) -> ResourceReference {
-        ResourceReference::new(uri, self.path)
+    /// Retrieve this resource's (specification-specific) anchors.
+    pub fn anchors(&self) -> Box<dyn Iterator<Item = Anchor> + 'a> {
+        self.specification.anchors_in(self.contents)
+    }
+
+    /// Resolve the given JSON pointer.
+    ///
+    /// The base URI of the returned resolver changes with every `$id` along the way.
+    pub(crate) fn pointer(
+        &self,
+        pointer: &str,
+        mut resolver: Resolver<'a, D>,
+    ) -> Result<Resolved<'a, D>, ReferencingError> {
+        let automaton =
+            AhoCorasick::new(["~1", "~0"]).expect("Invalid patterns for Aho-Corasick automaton");
+        let mut contents = self.contents;
+        let mut specification = self.specification.box_clone();
+        // The innermost subresource on the way and the path from it
+        let mut parent = self.contents;
+        let mut segments = JsonPath::new();
+        let decoded = percent_encoding::percent_decode_str(pointer).decode_utf8_lossy();
+        for segment in decoded.split('/').skip(1) {
+            if let Some(array) = contents.as_array() {
+                let idx = segment
+                    .parse::<usize>()
+                    .map_err(|_| ReferencingError::pointer_to_nowhere(pointer))?;
+                contents = array
+                    .try_get(idx)
+                    .ok()
+                    .flatten()
+                    .ok_or_else(|| ReferencingError::pointer_to_nowhere(pointer))?;
+                segments.push(idx);
+            } else if let Some(object) = contents.as_object() {
+                let key = automaton.replace_all(segment, &["/", "~"]);
+                contents = object
+                    .get(&key)
+                    .ok_or_else(|| ReferencingError::pointer_to_nowhere(pointer))?;
+                segments.push(key);
+            } else {
+                return Err(ReferencingError::pointer_to_nowhere(pointer));
+            }
+            if specification
+                .subresources_of(parent)
+                .any(|(path, _)| path == segments)
+            {
+                let subresource = SubResource::from_contents(contents, specification);
+                resolver = resolver.in_subresource(&subresource);
+                specification = subresource.specification;
+                parent = contents;
+                segments = JsonPath::new();
+            }
+        }
+        Ok(Resolved::new(contents, resolver))
     }
 }
 
@@ -272,27 +230,36 @@ impl<J: Json> ResourceMap<J> {
         self.owned.is_empty() && self.referenced.is_empty()
     }
 
-    /// Return the `Resource` identified by the given URI.
-    pub fn get(&self, key: &Url) -> Option<Res<J>> {
-        // TODO: Avoid key cloning
+    /// Return the resource identified by the given URI.
+    pub fn get(&self, key: &Url) -> Option<SubResource<'_, J>> {
         if let Some(owned) = self.get_resource(key) {
-            Some(Res::Owned(owned))
-        } else if let Some(reference) = self.referenced.get(key) {
-            self.get_subresource(reference).map(Res::Referenced)
+            Some(owned.as_subresource())
         } else {
-            None
+            let mut key = key.clone();
+            key.set_fragment(None);
+            self.get_subresource(self.referenced.get(&key)?)
         }
     }
     pub fn get_resource(&self, key: &Url) -> Option<&Resource<J>> {
-        let mut key = key.clone();
-        key.set_fragment(None);
-        self.owned.get(&key)
+        if key.fragment().is_some() {
+            let mut key = key.clone();
+            key.set_fragment(None);
+            self.owned.get(&key)
+        } else {
+            self.owned.get(key)
+        }
     }
 
-    pub fn get_subresource(&self, reference: &ResourceReference) -> Option<SubResource<J>> {
-        let mut uri = reference.parent_uri.clone();
-        uri.set_fragment(None);
-        self.owned.get(&uri)?.get_subresource(&reference.path)
+    pub fn get_subresource(&self, reference: &ResourceReference) -> Option<SubResource<'_, J>> {
+        let mut subresource = self.get_resource(&reference.parent_uri)?.as_subresource();
+        for segment in reference.path.iter() {
+            let contents = match segment {
+                Segment::Key(key) => subresource.contents.as_object()?.get(key)?,
+                Segment::Index(idx) => subresource.contents.as_array()?.try_get(*idx).ok()??,
+            };
+            subresource = SubResource::from_contents(contents, subresource.specification);
+        }
+        Some(subresource)
     }
 
     pub fn insert(&mut self, key: Url, resource: Resource<J>) {
@@ -304,7 +271,8 @@ impl<J: Json> ResourceMap<J> {
     }
 }
 
-#[derive(Debug)]
+/// A subresource as the URI of the document containing it and the path to it.
+#[derive(Debug, Clone)]
 pub struct ResourceReference {
     pub parent_uri: Url,
     pub path: JsonPath,
@@ -321,19 +289,78 @@ mod tests {
     use serde_json::{json, Value};
     use test_case::test_case;
 
-    use crate::{jsonschema::Draft4, Specification};
+    use crate::{
+        jsonschema::{Draft201909, Draft202012, Draft4, Draft6, Draft7},
+        Resource, Specification,
+    };
 
     #[test_case(json!({ "id": "foo" }), Some("foo"))]
+    #[test_case(json!({ "id": "foo#" }), Some("foo"))]
+    #[test_case(json!({ "id": "#foo" }), None)]
+    #[test_case(json!({ "id": "foo", "$ref": "bar" }), None)]
+    #[test_case(json!({ "$id": "foo" }), None)]
     #[test_case(json!({}), None)]
     fn test_id(contents: Value, expected: Option<&str>) {
         let resource = Draft4.create_resource(contents);
         assert_eq!(resource.id(), expected);
     }
 
+    #[test_case(Draft6.boxed(), Some("foo"))]
+    #[test_case(Draft7.boxed(), Some("foo"))]
+    #[test_case(Draft201909.boxed(), Some("foo"))]
+    #[test_case(Draft202012.boxed(), Some("foo"))]
+    #[test_case(Draft4.boxed(), None)]
+    fn test_dollar_id(specification: Box<dyn Specification<Value>>, expected: Option<&str>) {
+        let resource = Resource::new(json!({ "$id": "foo" }), specification);
+        assert_eq!(resource.id(), expected);
+    }
+
     #[test]
     fn test_subresource_valid() {
         let resource = Draft4.create_resource(json!({"not": {"type": "integer"}}));
         let subresources = resource.subresources().collect::<Vec<_>>();
         assert_eq!(subresources.len(), 1);
     }
+
+    #[test_case(Draft4.boxed(), json!({"items": [{}, {}], "additionalItems": true}), 2)]
+    #[test_case(Draft4.boxed(), json!({"items": {}, "dependencies": {"a": {}, "b": ["c"]}}), 2)]
+    #[test_case(Draft7.boxed(), json!({"if": {}, "then": {}, "additionalItems": true}), 3)]
+    #[test_case(Draft6.boxed(), json!({"if": {}, "then": {}}), 0)]
+    #[test_case(Draft201909.boxed(), json!({"$defs": {"a": {}}, "dependencies": {"a": {}}}), 1)]
+    #[test_case(Draft202012.boxed(), json!({"prefixItems": [{}], "items": {}}), 2)]
+    #[test_case(Draft202012.boxed(), json!({"enum": [{}], "const": {}}), 0)]
+    fn test_subresources(
+        specification: Box<dyn Specification<Value>>,
+        contents: Value,
+        expected: usize,
+    ) {
+        let resource = Resource::new(contents, specification);
+        assert_eq!(resource.subresources().count(), expected);
+    }
+
+    #[test_case(json!({"$schema": "http://json-schema.org/draft-04/schema#"}), "draft-04")]
+    #[test_case(json!({"$schema": "https://json-schema.org/draft/2019-09/schema"}), "draft2019-09")]
+    #[test_case(json!({"$schema": "urn:unknown"}), "draft2020-12")]
+    #[test_case(json!({}), "draft2020-12")]
+    fn test_from_contents(contents: Value, expected: &str) {
+        let resource =
+            Resource::from_contents(contents, Some(Draft202012.boxed())).expect("Known dialect");
+        assert_eq!(resource.specification.name(), expected);
+    }
+
+    #[test]
+    fn test_from_contents_unknown() {
+        let error = Resource::<Value>::from_contents(json!({"$schema": "urn:unknown"}), None)
+            .expect_err("Unknown dialect");
+        assert_eq!(error.to_string(), "Unknown dialect 'urn:unknown'");
+    }
+
+    #[test]
+    fn test_embedded_dialect() {
+        let resource = Draft202012.create_resource(json!({
+            "$defs": {"a": {"$schema": "http://json-schema.org/draft-07/schema#", "$id": "a"}}
+        }));
+        let (_, subresource) = resource.subresources().next().expect("Has a subresource");
+        assert_eq!(subresource.specification.name(), "draft-07");
+    }
 }
@@ -1,29 +1,20 @@
 use core::fmt;
 
-use crate::{
-    anchors::Resolvable,
-    path::{JsonPath, Segment},
-    resources::SubResource,
-    Resolver, Resource,
-};
+use crate::{anchors::Anchor, path::JsonPath, Resource};
 use jsonlike::prelude::*;
 
-pub(crate) type AnchorIter<'a, J> = Box<dyn Iterator<Item = Box<dyn Resolvable<J>>> + 'a>;
+pub(crate) type AnchorIter<'a> = Box<dyn Iterator<Item = Anchor> + 'a>;
 
 pub trait Specification<D: Json> {
     fn name(&self) -> &'static str;
     fn id_of<'a>(&self, document: &'a D) -> Option<&'a str>;
+    /// Subresources of `document` with their paths relative to it.
     fn subresources_of<'a>(
         &self,
         document: &'a D,
     ) -> Box<dyn Iterator<Item = (JsonPath, &'a D)> + 'a>;
-    fn anchors_in<'a>(&self, document: &'a D) -> AnchorIter<'a, D>;
-    fn maybe_in_subresource<'a>(
-        &self,
-        segments: &[Segment],
-        resolver: &'a Resolver<'a, D>,
-        subresource: SubResource<'a, D>,
-    ) -> Resolver<'a, D>;
+    /// Anchors defined directly by `document`, with paths relative to it.
+    fn anchors_in<'a>(&self, document: &'a D) -> AnchorIter<'a>;
     fn create_resource(&self, contents: D) -> Resource<D>
     where
         Self: Copy + 'static,
@@ -5,7 +5,7 @@ use std::{
 };
 
 use once_cell::sync::Lazy;
-use referencing::{jsonschema, Registry, Resource, Specification};
+use referencing::{jsonschema, Registry, Resource};
 use serde_json::Value;
 use url::Url;
 
@@ -70,11 +70,8 @@ impl TestCase {
             TestCase::Success(Success { base_uri, .. }) => base_uri,
             TestCase::Error { base_uri, .. } => base_uri,
         };
-        if let Some(base_uri) = base_uri {
-            Url::parse(base_uri).expect("Invalid URL")
-        } else {
-            todo!()
-        }
+        // Cases without a base URI only contain absolute references
+        Url::parse(base_uri.as_deref().unwrap_or("urn:referencing-suite")).expect("Invalid URL")
     }
 }
 
@@ -82,22 +79,19 @@ impl TestCase {
 fn test_suite(path: PathBuf) {
     let dialect_id = get_dialect_id(&path);
     let group = TestGroup::from_path(&path);
-    let specification = match dialect_id.trim_matches('#') {
-        //   "https://json-schema.org/draft/2020-12/schema" => {},
-        //   "https://json-schema.org/draft/2019-09/schema" => DRAFT201909,
-        //   "http://json-schema.org/draft-07/schema" => DRAFT7,
-        //   "http://json-schema.org/draft-06/schema" => DRAFT6,
-        "http://json-schema.org/draft-04/schema" => jsonschema::Draft4.boxed(),
-        //   "http://json-schema.org/draft-03/schema" => DRAFT3,
-        _ => todo!(),
+    let Some(specification) = jsonschema::specification_with::<Value>(dialect_id) else {
+        // Draft 3 is not supported
+        return;
     };
-    let registry =
+    let mut registry =
         Registry::new().with_resources(group.registry.into_iter().map(|(key, value)| {
             (
                 Url::parse(&key).expect("Invalid URL"),
-                Resource::new(value, specification.box_clone()),
+                Resource::from_contents(value, Some(specification.box_clone()))
+                    .expect("Known specification"),
             )
         }));
+    registry.crawl();
     for test in group.tests {
         let base_uri = test.base_uri();
         let resolver = registry.resolver(base_uri);
@@ -123,10 +117,7 @@ fn test_suite(path: PathBuf) {
                 }
             }
             TestCase::Error { reference, .. } => {
-                let resolved = resolver.lookup(&reference);
-                assert!(resolved.is_err());
-                let error = resolved.expect_err("Should be an error");
-                assert_eq!(error.to_string(), "BLA");
+                assert!(resolver.lookup(&reference).is_err());
             }
         }
     }
@@ -0,0 +1,238 @@
+//! Blocking work that async code waits for, independent of any async runtime.
+//!
+//! Jobs run on a shared pool of threads. The pool grows while all threads are busy, and threads
+//! exit after being idle for a while, so bursts of jobs do not start a thread each.
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock, PoisonError},
+    thread,
+    time::Instant,
+};
+
+/// How long a thread waits for another job before it exits.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+#[derive(Default)]
+struct PoolState {
+    jobs: VecDeque<Job>,
+    /// Threads waiting for a job.
+    idle: usize,
+}
+
+#[derive(Default)]
+struct Pool {
+    state: Mutex<PoolState>,
+    condvar: Condvar,
+}
+
+impl Pool {
+    fn global() -> &'static Pool {
+        static POOL: OnceLock<Pool> = OnceLock::new();
+        POOL.get_or_init(Pool::default)
+    }
+
+    fn submit(&'static self, job: Job) {
+        let mut state = lock(&self.state);
+        state.jobs.push_back(job);
+        // Every queued job needs a thread of its own, as jobs may block for long
+        if state.idle >= state.jobs.len() {
+            self.condvar.notify_one();
+        } else {
+            thread::spawn(move || self.work());
+        }
+    }
+
+    fn work(&self) {
+        let mut state = lock(&self.state);
+        loop {
+            if let Some(job) = state.jobs.pop_front() {
+                drop(state);
+                job();
+                state = lock(&self.state);
+                continue;
+            }
+            state.idle += 1;
+            let (next, wait) = self
+                .condvar
+                .wait_timeout(state, IDLE_TIMEOUT)
+                .unwrap_or_else(PoisonError::into_inner);
+            state = next;
+            state.idle -= 1;
+            if wait.timed_out() && state.jobs.is_empty() {
+                return;
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// The result of a job running in the background.
+pub(crate) struct Task<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+/// Run `job` on the shared pool.
+pub(crate) fn spawn<T: Send + 'static>(job: impl FnOnce() -> T + Send + 'static) -> Task<T> {
+    let slot = Arc::new(Mutex::new(Slot {
+        value: None,
+        waker: None,
+    }));
+    let background = Arc::clone(&slot);
+    Pool::global().submit(Box::new(move || {
+        let value = job();
+        let mut slot = lock(&background);
+        slot.value = Some(value);
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    }));
+    Task { slot }
+}
+
+impl<T> Future for Task<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut slot = lock(&self.slot);
+        match slot.value.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Cancel {
+    cancelled: Mutex<bool>,
+    condvar: Condvar,
+}
+
+/// A future that completes after a duration.
+///
+/// The deadline is tracked by a pooled thread that is released as soon as the timer is dropped.
+pub(crate) struct Timer {
+    task: Task<()>,
+    cancel: Arc<Cancel>,
+}
+
+impl Timer {
+    pub(crate) fn new(duration: Duration) -> Timer {
+        let cancel = Arc::new(Cancel::default());
+        let background = Arc::clone(&cancel);
+        let deadline = Instant::now() + duration;
+        let task = spawn(move || {
+            let mut cancelled = lock(&background.cancelled);
+            while !*cancelled {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                cancelled = background
+                    .condvar
+                    .wait_timeout(cancelled, deadline - now)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0;
+            }
+        });
+        Timer { task, cancel }
+    }
+}
+
+impl Future for Timer {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        Pin::new(&mut self.task).poll(cx)
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        *lock(&self.cancel.cancelled) = true;
+        self.cancel.condvar.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::{
+        collections::HashSet,
+        sync::{mpsc, Arc, Barrier},
+        thread,
+        time::Instant,
+    };
+
+    use super::{lock, spawn, Pool, Timer};
+
+    #[tokio::test]
+    async fn test_spawn() {
+        assert_eq!(spawn(|| 1 + 1).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_jobs_run_concurrently() {
+        // Both jobs only finish once they run at the same time
+        let barrier = Arc::new(Barrier::new(2));
+        let tasks: Vec<_> = (0..2)
+            .map(|_| {
+                let barrier = Arc::clone(&barrier);
+                spawn(move || {
+                    barrier.wait();
+                    thread::current().id()
+                })
+            })
+            .collect();
+        let mut threads = HashSet::new();
+        for task in tasks {
+            threads.insert(task.await);
+        }
+        assert_eq!(threads.len(), 2);
+    }
+
+    #[test]
+    fn test_threads_are_reused() {
+        let pool: &'static Pool = Box::leak(Box::default());
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..2 {
+            let sender = sender.clone();
+            pool.submit(Box::new(move || {
+                sender
+                    .send(thread::current().id())
+                    .expect("Receiver exists");
+            }));
+            // Wait until the thread is idle again
+            while lock(&pool.state).idle == 0 {
+                thread::yield_now();
+            }
+        }
+        let first = receiver.recv().expect("Job ran");
+        assert_eq!(receiver.recv().expect("Job ran"), first);
+    }
+
+    #[tokio::test]
+    async fn test_timer() {
+        let start = Instant::now();
+        Timer::new(Duration::from_millis(50)).await;
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -1,116 +1,313 @@
+use std::sync::Arc;
+
 use crate::{
-    compiler,
+    bundle, compiler,
+    content::{ContentEncoding, ContentMediaType},
+    dereference,
     drafts::{draft_from_schema, Draft},
-    format::FormatFactory,
+    generate::Generator,
     output::Output,
+    retrieval,
     validation::builder::ValidatorBuilder as AsyncValidatorBuilder,
-    vocabulary::KeywordFactory,
-    BuildResult, ReferenceResolver, ValidationError, ValidationErrorIter, Validator,
+    BoxedFormat, BoxedKeyword, BuildError, BuildResult, ValidationError, ValidationErrorIter,
+    Validator,
 };
+#[cfg(feature = "snapshot")]
+use crate::{snapshot, Snapshot};
 use jsonlike::Json;
 
-pub fn is_valid<J: Json>(schema: &J, instance: &J) -> bool {
+pub fn is_valid<J: Json + 'static, I: Json + 'static>(schema: &J, instance: &I) -> bool {
     try_is_valid(schema, instance).expect("Invalid schema")
 }
 
-pub fn try_is_valid<J: Json>(schema: &J, instance: &J) -> BuildResult<bool> {
+pub fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+    schema: &J,
+    instance: &I,
+) -> BuildResult<bool> {
     Ok(validator_for(schema)?.is_valid(instance))
 }
 
-pub fn validate<J: Json>(schema: &J, instance: &J) -> Result<(), ValidationError> {
+pub fn validate<J: Json + 'static, I: Json + 'static>(
+    schema: &J,
+    instance: &I,
+) -> Result<(), ValidationError> {
     try_validate(schema, instance).expect("Invalid schema")
 }
 
-pub fn try_validate<J: Json>(schema: &J, instance: &J) -> BuildResult<Result<(), ValidationError>> {
+pub fn try_validate<J: Json + 'static, I: Json + 'static>(
+    schema: &J,
+    instance: &I,
+) -> BuildResult<Result<(), ValidationError>> {
     Ok(validator_for(schema)?.validate(instance))
 }
 
-pub fn iter_errors<'i, J: Json>(
+pub fn is_valid_many<'i, J: Json + 'static, I: Json + Sync + 'static>(
+    schema: &J,
+    instances: impl IntoIterator<Item = &'i I>,
+    threads: usize,
+) -> Vec<bool> {
+    try_is_valid_many(schema, instances, threads).expect("Invalid schema")
+}
+
+pub fn try_is_valid_many<'i, J: Json + 'static, I: Json + Sync + 'static>(
+    schema: &J,
+    instances: impl IntoIterator<Item = &'i I>,
+    threads: usize,
+) -> BuildResult<Vec<bool>> {
+    Ok(validator_for(schema)?.is_valid_many(instances, threads))
+}
+
+pub fn validate_many<'i, J: Json + 'static, I: Json + Sync + 'static>(
+    schema: &J,
+    instances: impl IntoIterator<Item = &'i I>,
+    threads: usize,
+) -> Vec<Result<(), ValidationError>> {
+    try_validate_many(schema, instances, threads).expect("Invalid schema")
+}
+
+pub fn try_validate_many<'i, J: Json + 'static, I: Json + Sync + 'static>(
+    schema: &J,
+    instances: impl IntoIterator<Item = &'i I>,
+    threads: usize,
+) -> BuildResult<Vec<Result<(), ValidationError>>> {
+    Ok(validator_for(schema)?.validate_many(instances, threads))
+}
+
+pub fn iter_errors<'i, J: Json + 'static, I: Json + 'static>(
     schema: &J,
-    instance: &'i J,
-) -> ValidationErrorIter<'static, 'i, J> {
+    instance: &'i I,
+) -> ValidationErrorIter<'static, 'i, J, I> {
     try_iter_errors(schema, instance).expect("Invalid schema")
 }
 
-pub fn try_iter_errors<'i, J: Json>(
+pub fn try_iter_errors<'i, J: Json + 'static, I: Json + 'static>(
     schema: &J,
-    instance: &'i J,
-) -> BuildResult<ValidationErrorIter<'static, 'i, J>> {
+    instance: &'i I,
+) -> BuildResult<ValidationErrorIter<'static, 'i, J, I>> {
     let validator = validator_for(schema)?;
     Ok(validator.iter_errors_once(instance))
 }
 
-pub fn evaluate<'i, J: Json>(instance: &'i J, schema: &J) -> Output<'static, 'i, J> {
+pub fn evaluate<'i, J: Json + 'static, I: Json + 'static>(
+    instance: &'i I,
+    schema: &J,
+) -> Output<'static, 'i, J, I> {
     try_evaluate(instance, schema).expect("Invalid schema")
 }
 
-pub fn try_evaluate<'i, J: Json>(
-    instance: &'i J,
+pub fn try_evaluate<'i, J: Json + 'static, I: Json + 'static>(
+    instance: &'i I,
     schema: &J,
-) -> BuildResult<Output<'static, 'i, J>> {
+) -> BuildResult<Output<'static, 'i, J, I>> {
     Ok(validator_for(schema)?.evaluate_once(instance))
 }
 
-pub fn validator_for<J: Json>(schema: &J) -> BuildResult<Validator<J>> {
+pub fn validator_for<J: Json + 'static>(schema: &J) -> BuildResult<Validator<J>> {
     let draft = draft_from_schema(schema);
     ValidatorBuilder::default().draft(draft).build(schema)
 }
 
+/// Retrieves external documents referenced via `$ref` without an async runtime.
+///
+/// The blocking counterpart of `jsonschema::ReferenceResolver`.
+pub trait ReferenceResolver<J: Json>: Send + Sync {
+    /// Retrieve the document at `url`, an absolute URI without fragment.
+    fn resolve_external(&self, url: &str) -> BuildResult<J>;
+}
+
+impl<J: Json, R: ReferenceResolver<J> + ?Sized> ReferenceResolver<J> for Arc<R> {
+    fn resolve_external(&self, url: &str) -> BuildResult<J> {
+        (**self).resolve_external(url)
+    }
+}
+
+impl<J: Json, R: ReferenceResolver<J> + ?Sized> ReferenceResolver<J> for Box<R> {
+    fn resolve_external(&self, url: &str) -> BuildResult<J> {
+        (**self).resolve_external(url)
+    }
+}
+
+/// Refuses every external document, so only references within the schema itself resolve.
+struct DefaultResolver;
+
+impl<J: Json> ReferenceResolver<J> for DefaultResolver {
+    fn resolve_external(&self, url: &str) -> BuildResult<J> {
+        Err(BuildError::retrieval(
+            url,
+            "No reference resolver is configured",
+        ))
+    }
+}
+
 pub struct ValidatorBuilder<'a, J: Json> {
     inner: AsyncValidatorBuilder<'a, J>,
+    resolver: Arc<dyn ReferenceResolver<J>>,
 }
 
 impl<'a, J: Json> Default for ValidatorBuilder<'a, J> {
     fn default() -> Self {
         ValidatorBuilder {
             inner: AsyncValidatorBuilder::default(),
+            resolver: Arc::new(DefaultResolver),
         }
     }
 }
 
 impl<'a, J: Json> ValidatorBuilder<'a, J> {
-    pub fn build(&self, schema: &J) -> BuildResult<Validator<J>> {
-        // TODO: Resolve references
-        compiler::compile::<J>(schema, self.inner.draft)
+    /// Build a validator for `schema`.
+    ///
+    /// Documents referenced via `$ref` are retrieved through the resolver first, one at a time,
+    /// including documents referenced by retrieved ones. Every URI is retrieved once.
+    pub fn build(&self, schema: &'a J) -> BuildResult<Validator<J>> {
+        let documents = retrieval::retrieve_blocking(schema, self.inner.draft, &*self.resolver)?;
+        compiler::compile(schema, &documents, &self.inner)
+    }
+    /// Bundle `schema` and the documents it references into one compound document.
+    ///
+    /// Referenced documents are retrieved like in `build` and embedded under `$defs`
+    /// (`definitions` before 2019-09), with the URI they were retrieved from as their `$id`.
+    /// References are kept as they are, so the result resolves without a resolver.
+    pub fn bundle(&self, schema: &J) -> BuildResult<J> {
+        let documents = retrieval::retrieve_blocking(schema, self.inner.draft, &*self.resolver)?;
+        bundle::bundle(schema, &documents, self.inner.draft)
+    }
+    /// Replace every reference in `schema` with the schema it points to.
+    ///
+    /// Referenced documents are retrieved like in `build`. Recursive references can not be
+    /// inlined and point to the location their target was inlined at instead. Inlined schemas
+    /// lose their `$id` and `$schema`, as they are no longer separate resources.
+    pub fn dereference(&self, schema: &J) -> BuildResult<J> {
+        let documents = retrieval::retrieve_blocking(schema, self.inner.draft, &*self.resolver)?;
+        dereference::dereference(schema, &documents, self.inner.draft)
+    }
+    /// Create a generator of instances that are valid against `schema`.
+    ///
+    /// Referenced documents are retrieved like in `build`. Candidates are checked by a
+    /// validator built with the settings of this builder, including custom keywords and formats.
+    pub fn generator(&self, schema: &'a J) -> BuildResult<Generator<J>>
+    where
+        J: 'static,
+    {
+        let documents = retrieval::retrieve_blocking(schema, self.inner.draft, &*self.resolver)?;
+        let validator = compiler::compile(schema, &documents, &self.inner)?;
+        let dereferenced = dereference::dereference(schema, &documents, self.inner.draft)?;
+        Generator::new(&dereferenced, validator)
+    }
+    /// Create a validator from a snapshot made by `Validator::to_snapshot`.
+    ///
+    /// Custom keywords, formats and content handlers used by the snapshot are looked up by name
+    /// in this builder and initialized again. All other settings are taken from the snapshot.
+    #[cfg(feature = "snapshot")]
+    pub fn load(&self, snapshot: &Snapshot<J>) -> BuildResult<Validator<J>> {
+        snapshot::load(snapshot, &self.inner)
     }
     pub fn draft(&mut self, draft: Draft) -> &mut Self {
         self.inner.draft(draft);
         self
     }
     pub fn resolver(&mut self, resolver: impl ReferenceResolver<J> + 'static) -> &mut Self {
-        self.inner.resolver(resolver);
+        self.resolver = Arc::new(resolver);
         self
     }
     pub fn format<F>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
     where
-        F: FormatFactory<'a, J>,
+        F: Fn(&J) -> BuildResult<BoxedFormat> + Send + Sync + 'a,
     {
         self.inner.format(name, factory);
         self
     }
     pub fn keyword<F>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
     where
-        F: KeywordFactory<'a, J>,
+        F: Fn(&J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a,
     {
         self.inner.keyword(name, factory);
         self
     }
+    pub fn validate_content(&mut self, yes: bool) -> &mut Self {
+        self.inner.validate_content(yes);
+        self
+    }
+    pub fn content_encoding(
+        &mut self,
+        name: impl Into<String>,
+        encoding: impl ContentEncoding,
+    ) -> &mut Self {
+        self.inner.content_encoding(name, encoding);
+        self
+    }
+    pub fn content_media_type(
+        &mut self,
+        name: impl AsRef<str>,
+        media_type: impl ContentMediaType<J>,
+    ) -> &mut Self {
+        self.inner.content_media_type(name, media_type);
+        self
+    }
 }
 
 #[cfg(all(test, feature = "serde_json"))]
 mod tests {
-    use serde_json::json;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use serde_json::{json, Value};
+
+    use super::ReferenceResolver;
+    use crate::{BuildError, BuildResult};
 
     #[test]
     fn test_validator_for_blocking() {
         let schema = json!({"type": "integer"});
-        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let _validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+    }
+
+    #[test]
+    fn test_external_references() {
+        struct Documents(AtomicUsize);
+
+        impl ReferenceResolver<Value> for Documents {
+            fn resolve_external(&self, url: &str) -> BuildResult<Value> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                match url {
+                    "https://example.com/a.json" => Ok(json!({"$ref": "b.json"})),
+                    "https://example.com/b.json" => Ok(json!({"type": "integer"})),
+                    _ => Err(BuildError::retrieval(url, "Not found")),
+                }
+            }
+        }
+
+        let resolver = Arc::new(Documents(AtomicUsize::new(0)));
+        let schema = json!({
+            "properties": {
+                "a": {"$ref": "https://example.com/a.json"},
+                "b": {"$ref": "https://example.com/b.json"}
+            }
+        });
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .resolver(Arc::clone(&resolver))
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": 1, "b": 2})));
+        assert!(!validator.is_valid(&json!({"a": "x"})));
+        assert_eq!(resolver.0.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_retrieval_error() {
+        let schema = json!({"$ref": "https://example.com/a.json"});
+        let error = crate::blocking::validator_for(&schema).expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Failed to retrieve 'https://example.com/a.json': No reference resolver is configured"
+        );
     }
 
     #[test]
     fn test_options_blocking() {
         let schema = json!({"type": "integer"});
-        let validator = crate::blocking::ValidatorBuilder::default()
+        let _validator = crate::blocking::ValidatorBuilder::default()
             .build(&schema)
             .expect("Invalid schema");
     }
@@ -0,0 +1,364 @@
+//! Bundling of a schema and the documents it references into a single compound document.
+//!
+//! Follows the bundling process of JSON Schema 2020-12: every retrieved document becomes an
+//! embedded schema resource under `$defs` (`definitions` before 2019-09), identified by the URI
+//! it was retrieved from. References stay as they are and resolve to the embedded resources.
+//!
+//! Documents are read from a `referencing::Registry`, so each one is identified according to the
+//! draft it declares via `$schema`.
+use jpointer::JsonPointer;
+use jsonlike::prelude::*;
+use referencing::SubResource;
+use url::Url;
+
+use crate::{
+    compiler::{default_base, DEFAULT_BASE},
+    drafts::Draft,
+    retrieval,
+    value::{Array, Object, Value},
+    BuildError, BuildResult,
+};
+
+pub(crate) fn bundle<J: Json>(schema: &J, documents: &[(Url, J)], draft: Draft) -> BuildResult<J> {
+    let registry = retrieval::registry(schema, documents, draft)?;
+    let root = registry
+        .get(&default_base())
+        .expect("The schema is registered under the default base URI");
+    if documents.is_empty() {
+        return Ok(J::from_str(&root.contents().to_string())?);
+    }
+    let keyword = definitions_keyword(draft);
+    let mut root = resource(&root, None, draft);
+    let mut definitions = match root.get(keyword) {
+        Some(Value::Object(definitions)) => definitions.clone(),
+        Some(_) => {
+            return Err(BuildError::invalid_keyword(
+                &JsonPointer::new().join(keyword),
+                "Expected an object",
+            ))
+        }
+        None => Object::default(),
+    };
+    let mut uris: Vec<_> = documents.iter().map(|(uri, _)| uri).collect();
+    uris.sort();
+    for uri in uris {
+        let document = registry
+            .get(uri)
+            .expect("Retrieved documents are registered");
+        // Documents referenced relative to a root without `$id` stay relative to it
+        let id = uri
+            .as_str()
+            .strip_prefix(DEFAULT_BASE)
+            .unwrap_or(uri.as_str());
+        let mut key = id.to_owned();
+        let mut suffix = 1;
+        while definitions.get(&key).is_some() {
+            suffix += 1;
+            key = format!("{id}-{suffix}");
+        }
+        let embedded = resource(&document, Some((uri, id)), draft);
+        definitions.insert(key, Value::Object(embedded));
+    }
+    root.insert(keyword.to_owned(), Value::Object(definitions));
+    Ok(J::from_str(&Value::Object(root).to_string())?)
+}
+
+fn definitions_keyword(draft: Draft) -> &'static str {
+    match draft {
+        Draft::Draft04 | Draft::Draft06 | Draft::Draft07 => "definitions",
+        Draft::Draft201909 | Draft::Draft202012 => "$defs",
+    }
+}
+
+fn is_legacy(draft: Draft) -> bool {
+    matches!(draft, Draft::Draft04 | Draft::Draft06 | Draft::Draft07)
+}
+
+/// `document` as an object that is a schema resource identified by `id`, if given.
+///
+/// Schemas that can not carry `id` as their `$id` are wrapped into `allOf`.
+fn resource(document: &SubResource<'_, Value>, id: Option<(&Url, &str)>, draft: Draft) -> Object {
+    let id_keyword = if let Draft::Draft04 = draft {
+        "id"
+    } else {
+        "$id"
+    };
+    let mut resource = Object::default();
+    let Value::Object(object) = document.contents() else {
+        insert_id(&mut resource, id_keyword, id);
+        resource.insert("allOf".to_string(), array(document.contents().clone()));
+        return resource;
+    };
+    if let Some(dialect) = object.get("$schema") {
+        resource.insert("$schema".to_string(), dialect.clone());
+    }
+    if let (true, Some(reference)) = (is_legacy(draft), object.get("$ref")) {
+        // Siblings of `$ref` are ignored, so the reference moves into `allOf` and only the
+        // definitions it may point to are kept
+        insert_id(&mut resource, id_keyword, id);
+        if let Some(definitions) = object.get("definitions") {
+            resource.insert("definitions".to_string(), definitions.clone());
+        }
+        let reference = Object::from(vec![("$ref".to_string(), reference.clone())]);
+        resource.insert("allOf".to_string(), array(Value::Object(reference)));
+        return resource;
+    }
+    if let Some((uri, _)) = id {
+        match (document.id(), object.get(id_keyword)) {
+            // A relative `$id` would resolve against the root of the bundle, so it is spelled
+            // the same way as documents without one
+            (Some(own), _) if identifies(uri, own) => insert_id(&mut resource, id_keyword, id),
+            (None, None) => insert_id(&mut resource, id_keyword, id),
+            // An own `$id` that identifies the document differently, or a legacy one that is an
+            // anchor, can not be replaced, so the document is nested in a resource with that URI
+            _ => {
+                insert_id(&mut resource, id_keyword, id);
+                resource.insert("allOf".to_string(), array(document.contents().clone()));
+                return resource;
+            }
+        }
+    }
+    for (key, value) in object.entries() {
+        if id.is_none() || key != id_keyword {
+            resource.insert(key.clone(), value.clone());
+        }
+    }
+    resource
+}
+
+/// Whether `id` as the `$id` of the document at `uri` identifies it by `uri`.
+fn identifies(uri: &Url, id: &str) -> bool {
+    uri.join(id).is_ok_and(|mut resolved| {
+        resolved.set_fragment(None);
+        resolved == *uri
+    })
+}
+
+fn insert_id(resource: &mut Object, keyword: &str, id: Option<(&Url, &str)>) {
+    if let Some((_, id)) = id {
+        resource.insert(keyword.to_owned(), Value::String(id.to_owned()));
+    }
+}
+
+fn array(item: Value) -> Value {
+    let mut array = Array::default();
+    array.push(item);
+    Value::Array(array)
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::{resolver::InMemoryResolver, Draft, ValidatorBuilder};
+
+    fn resolver() -> InMemoryResolver<Value> {
+        InMemoryResolver::new()
+            .with(
+                "https://example.com/integer.json",
+                json!({"type": "integer", "minimum": 0}),
+            )
+            .with("json-schema:///string.json", json!({"type": "string"}))
+            .with("https://example.com/never.json", json!(false))
+            .with(
+                "https://example.com/moved.json",
+                json!({"$id": "https://other.com/moved.json", "type": "null"}),
+            )
+            .with(
+                "https://example.com/same.json",
+                json!({"$id": "same.json", "type": "boolean"}),
+            )
+            .with(
+                "https://example.com/legacy.json",
+                json!({
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "$id": "#anchor",
+                    "type": "string",
+                }),
+            )
+            .with(
+                "https://example.com/reference.json",
+                json!({"$ref": "same.json", "definitions": {"a": {}}, "title": "Ignored"}),
+            )
+    }
+
+    async fn bundle(schema: &Value, draft: Draft) -> Value {
+        ValidatorBuilder::default()
+            .draft(draft)
+            .resolver(resolver())
+            .bundle(schema)
+            .await
+            .expect("Documents exist")
+    }
+
+    #[tokio::test]
+    async fn test_bundle() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "properties": {
+                "a": {"$ref": "https://example.com/integer.json"},
+                "b": {"$ref": "string.json"},
+                "c": {"$ref": "https://example.com/never.json"},
+                "d": {"$ref": "https://example.com/moved.json"},
+                "e": {"$ref": "https://example.com/same.json"},
+                "f": {"$ref": "#/$defs/local"},
+            },
+            "$defs": {"local": {"type": "array"}},
+        });
+        let bundled = bundle(&schema, Draft::Draft202012).await;
+        assert_eq!(
+            bundled,
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "properties": schema["properties"],
+                "$defs": {
+                    "local": {"type": "array"},
+                    "https://example.com/integer.json": {
+                        "$id": "https://example.com/integer.json",
+                        "type": "integer",
+                        "minimum": 0,
+                    },
+                    "https://example.com/moved.json": {
+                        "$id": "https://example.com/moved.json",
+                        "allOf": [{"$id": "https://other.com/moved.json", "type": "null"}],
+                    },
+                    "https://example.com/never.json": {
+                        "$id": "https://example.com/never.json",
+                        "allOf": [false],
+                    },
+                    "https://example.com/same.json": {
+                        "$id": "https://example.com/same.json",
+                        "type": "boolean",
+                    },
+                    "string.json": {"$id": "string.json", "type": "string"},
+                },
+            })
+        );
+    }
+
+    #[test_case(json!({"a": 1, "b": "x", "d": null, "e": true, "f": []}), true)]
+    #[test_case(json!({"a": "x"}), false)]
+    #[test_case(json!({"a": -1}), false)]
+    #[test_case(json!({"b": 1}), false)]
+    #[test_case(json!({"c": 1}), false)]
+    #[test_case(json!({"d": 1}), false)]
+    #[test_case(json!({"e": 1}), false)]
+    #[test_case(json!({"f": 1}), false)]
+    #[tokio::test]
+    async fn test_bundle_is_self_contained(instance: Value, expected: bool) {
+        let schema = json!({
+            "properties": {
+                "a": {"$ref": "https://example.com/integer.json"},
+                "b": {"$ref": "string.json"},
+                "c": {"$ref": "https://example.com/never.json"},
+                "d": {"$ref": "https://example.com/moved.json"},
+                "e": {"$ref": "https://example.com/same.json"},
+                "f": {"$ref": "#/$defs/local"},
+            },
+            "$defs": {"local": {"type": "array"}},
+        });
+        let bundled = bundle(&schema, Draft::Draft202012).await;
+        let validator = ValidatorBuilder::default()
+            .build(&bundled)
+            .await
+            .expect("Bundle resolves without a resolver");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[test_case(Draft::Draft04, "id")]
+    #[test_case(Draft::Draft06, "$id")]
+    #[test_case(Draft::Draft07, "$id")]
+    #[tokio::test]
+    async fn test_bundle_legacy(draft: Draft, id: &str) {
+        let schema = json!({
+            "$ref": "https://example.com/reference.json",
+            "definitions": {"local": {}},
+            "title": "Ignored",
+        });
+        let bundled = bundle(&schema, draft).await;
+        let same = if let Draft::Draft04 = draft {
+            // Draft 4 identifies resources by `id`
+            json!({"id": "https://example.com/same.json", "$id": "same.json", "type": "boolean"})
+        } else {
+            json!({"$id": "https://example.com/same.json", "type": "boolean"})
+        };
+        assert_eq!(
+            bundled,
+            json!({
+                "definitions": {
+                    "local": {},
+                    "https://example.com/reference.json": {
+                        id: "https://example.com/reference.json",
+                        "definitions": {"a": {}},
+                        "allOf": [{"$ref": "same.json"}],
+                    },
+                    "https://example.com/same.json": same,
+                },
+                "allOf": [{"$ref": "https://example.com/reference.json"}],
+            })
+        );
+        let validator = ValidatorBuilder::default()
+            .draft(draft)
+            .build(&bundled)
+            .await
+            .expect("Bundle resolves without a resolver");
+        assert!(validator.is_valid(&json!(true)));
+        assert!(!validator.is_valid(&json!(1)));
+    }
+
+    #[tokio::test]
+    async fn test_bundle_other_draft() {
+        // The document is read as Draft 7, where its `$id` is an anchor rather than an identifier
+        let schema = json!({"$ref": "https://example.com/legacy.json"});
+        let bundled = bundle(&schema, Draft::Draft202012).await;
+        assert_eq!(
+            bundled["$defs"]["https://example.com/legacy.json"],
+            json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "$id": "https://example.com/legacy.json",
+                "allOf": [{
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "$id": "#anchor",
+                    "type": "string",
+                }],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_without_references() {
+        let schema = json!({"type": "integer"});
+        assert_eq!(bundle(&schema, Draft::Draft202012).await, schema);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_definitions() {
+        let schema = json!({"$ref": "string.json", "$defs": []});
+        let error = ValidatorBuilder::default()
+            .resolver(resolver())
+            .bundle(&schema)
+            .await
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Invalid schema at '/$defs': Expected an object"
+        );
+    }
+
+    #[test]
+    fn test_blocking() {
+        let schema = json!({"$ref": "string.json"});
+        let bundled = crate::blocking::ValidatorBuilder::default()
+            .resolver(resolver())
+            .bundle(&schema)
+            .expect("Documents exist");
+        assert_eq!(
+            bundled,
+            json!({
+                "$ref": "string.json",
+                "$defs": {"string.json": {"$id": "string.json", "type": "string"}},
+            })
+        );
+    }
+}
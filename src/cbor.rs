@@ -0,0 +1,115 @@
+//! Loading instances from CBOR files.
+//!
+//! `ciborium::Value` implements `Json`, so CBOR documents are validated without converting
+//! them to JSON first. Byte strings, tags and non-finite floats have no JSON equivalent: a
+//! keyword that needs to read them reports `ValidationErrorKind::InvalidInstance`.
+//!
+//! ```rust,no_run
+//! # fn test() -> Result<(), Box<dyn std::error::Error>> {
+//! let schema = serde_json::json!({"required": ["id"]});
+//! let validator = jsonschema::blocking::validator_for(&schema)?;
+//! let event = jsonschema::cbor::load("event.cbor")?;
+//! validator.validate(&event)?;
+//! # Ok(())
+//! # }
+//! ```
+use std::path::Path;
+
+use ciborium::Value;
+use jsonlike::JsonError;
+
+use crate::files;
+
+/// Decode a single CBOR item.
+pub fn from_slice(bytes: &[u8]) -> Result<Value, JsonError> {
+    ciborium::from_reader(bytes).map_err(|err| JsonError::new(Box::new(err)))
+}
+
+/// Read a CBOR document from `path`.
+pub fn load(path: impl AsRef<Path>) -> Result<Value, JsonError> {
+    files::load_bytes(path.as_ref(), from_slice)
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use std::fs;
+
+    use ciborium::{cbor, Value};
+    use serde_json::json;
+    use test_case::test_case;
+
+    use super::{from_slice, load};
+    use crate::{blocking::validator_for, ValidationErrorKind};
+
+    #[test_case(&[0xa1, 0x62, 0x69, 0x64, 0x01], true; "integer id")]
+    #[test_case(&[0xa1, 0x62, 0x69, 0x64, 0x61, 0x31], false; "string id")]
+    #[test_case(&[0xa0], false; "missing id")]
+    fn test_validate(bytes: &[u8], expected: bool) {
+        let schema = json!({"properties": {"id": {"type": "integer"}}, "required": ["id"]});
+        let validator = validator_for(&schema).expect("Valid schema");
+        let instance = from_slice(bytes).expect("Valid CBOR");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[test]
+    fn test_schema_without_json_equivalent() {
+        // {"const": h'01'}
+        let schema = from_slice(&[0xa1, 0x65, 0x63, 0x6f, 0x6e, 0x73, 0x74, 0x41, 0x01])
+            .expect("Valid CBOR");
+        assert!(validator_for(&schema).is_err());
+    }
+
+    #[test_case(json!({"type": "integer"}), Value::Bytes(vec![1]))]
+    #[test_case(json!({"type": "string"}), Value::Tag(1, Box::new(Value::Integer(0.into()))))]
+    #[test_case(json!({"minimum": 1}), Value::Float(f64::NAN))]
+    #[test_case(json!({"multipleOf": 2}), Value::Float(f64::INFINITY))]
+    #[test_case(json!({"maxLength": 1}), Value::Bytes(vec![1]))]
+    #[test_case(json!({"pattern": "^a"}), Value::Tag(1, Box::new(Value::Text("a".into()))))]
+    #[test_case(json!({"const": 1}), Value::Float(f64::NAN))]
+    #[test_case(json!({"enum": [[1]]}), Value::Array(vec![Value::Bytes(vec![1])]))]
+    #[test_case(json!({"uniqueItems": true}), Value::Array(vec![Value::Bytes(vec![1]), Value::Bytes(vec![1])]))]
+    #[test_case(json!({"required": ["a"]}), Value::Bytes(vec![1]))]
+    #[test_case(json!({"properties": {"a": true}}), Value::Tag(1, Box::new(Value::Map(vec![]))))]
+    #[test_case(json!({"items": {"minimum": 1}}), Value::Bytes(vec![1]))]
+    fn test_instance_without_json_equivalent(schema: serde_json::Value, instance: Value) {
+        let validator = validator_for(&schema).expect("Valid schema");
+        assert!(!validator.is_valid(&instance));
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind(),
+            ValidationErrorKind::InvalidInstance { .. }
+        ));
+    }
+
+    #[test]
+    fn test_nested_instance_without_json_equivalent() {
+        let schema = json!({"properties": {"a": {"type": "string"}}});
+        let validator = validator_for(&schema).expect("Valid schema");
+        let instance = Value::Map(vec![(Value::Text("a".into()), Value::Bytes(vec![1]))]);
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].instance_path().to_string(), "/a");
+        assert!(matches!(
+            errors[0].kind(),
+            ValidationErrorKind::InvalidInstance { .. }
+        ));
+    }
+
+    #[test_case(&[]; "empty")]
+    #[test_case(&[0xa1, 0x62, 0x69]; "truncated")]
+    fn test_from_slice_invalid(bytes: &[u8]) {
+        assert!(from_slice(bytes).is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join(format!("jsonschema-cbor-{}", std::process::id()));
+        fs::write(&path, [0xa1, 0x62, 0x69, 0x64, 0x01]).expect("Can write files");
+        let instance = load(&path).expect("Valid CBOR");
+        fs::remove_file(&path).expect("Can remove files");
+        assert_eq!(instance, cbor!({"id" => 1}).expect("Valid CBOR"));
+        let error = load(&path).expect_err("Missing file");
+        assert!(error.to_string().starts_with(&path.display().to_string()));
+    }
+}
@@ -0,0 +1,1004 @@
+//! Detection of breaking changes between two versions of a schema.
+//!
+//! A change is backward compatible if all data valid against the old schema is still valid
+//! against the new one, and forward compatible if all data valid against the new schema is
+//! accepted by consumers that still use the old one.
+//!
+//! ```rust
+//! # #[cfg(feature = "serde_json")]
+//! # fn main() -> Result<(), jsonschema::BuildError> {
+//! use serde_json::json;
+//!
+//! let old = json!({"properties": {"id": {"type": "integer"}}});
+//! let new = json!({"properties": {"id": {"type": "integer"}}, "required": ["id"]});
+//! let report = jsonschema::compat::compare(&old, &new)?;
+//! assert!(!report.is_backward_compatible());
+//! assert!(report.is_forward_compatible());
+//! assert_eq!(
+//!     report.changes()[0].to_string(),
+//!     "breaks backward compatibility at '/required': Property 'id' became required"
+//! );
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "serde_json"))]
+//! # fn main() {}
+//! ```
+//!
+//! Schemas are compared keyword by keyword at the same locations, so references are compared
+//! as written rather than followed. Annotations, such as `title` or `format`, and unknown
+//! keywords are not compared. Changes that can not be classified precisely are reported as
+//! breaking both ways.
+use core::fmt;
+
+use fancy_regex::Regex;
+use jpointer::{JsonPointer, Segment};
+use jsonlike::prelude::*;
+
+use crate::{error::PrimitiveType, value, vocabulary::object_entries, BuildResult};
+
+/// The compatibility a change breaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breaks {
+    /// Some data valid against the old schema is invalid against the new one.
+    Backward,
+    /// Some data valid against the new schema is invalid against the old one.
+    Forward,
+    /// The change may break compatibility either way.
+    Both,
+}
+
+impl Breaks {
+    fn flip(self) -> Breaks {
+        match self {
+            Breaks::Backward => Breaks::Forward,
+            Breaks::Forward => Breaks::Backward,
+            Breaks::Both => Breaks::Both,
+        }
+    }
+    fn backward(self) -> bool {
+        matches!(self, Breaks::Backward | Breaks::Both)
+    }
+    fn forward(self) -> bool {
+        matches!(self, Breaks::Forward | Breaks::Both)
+    }
+}
+
+impl fmt::Display for Breaks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Breaks::Backward => "breaks backward compatibility",
+            Breaks::Forward => "breaks forward compatibility",
+            Breaks::Both => "breaks backward and forward compatibility",
+        })
+    }
+}
+
+/// What changed between the two versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// Types no longer allowed by `type`.
+    TypesRemoved { types: Vec<PrimitiveType> },
+    /// Types newly allowed by `type`.
+    TypesAdded { types: Vec<PrimitiveType> },
+    /// A property was added to `required`.
+    RequiredAdded { property: String },
+    /// A property was removed from `required`.
+    RequiredRemoved { property: String },
+    /// A bound like `minimum` or `maxLength` allows fewer values.
+    BoundTightened { keyword: String },
+    /// A bound like `minimum` or `maxLength` allows more values.
+    BoundLoosened { keyword: String },
+    /// Values removed from `enum`, as JSON.
+    EnumValuesRemoved { values: Vec<String> },
+    /// Values added to `enum`, as JSON.
+    EnumValuesAdded { values: Vec<String> },
+    /// `additionalProperties` became `false`.
+    AdditionalPropertiesClosed,
+    /// `additionalProperties` is no longer `false`.
+    AdditionalPropertiesOpened,
+    /// A property was added to `properties`.
+    PropertyAdded { property: String },
+    /// A property was removed from `properties`.
+    PropertyRemoved { property: String },
+    /// A keyword, or an entry of a keyword like `patternProperties`, was added.
+    KeywordAdded { keyword: String },
+    /// A keyword, or an entry of a keyword like `patternProperties`, was removed.
+    KeywordRemoved { keyword: String },
+    /// A keyword value changed in a way no other kind describes.
+    KeywordChanged { keyword: String },
+    /// A boolean schema was replaced, or replaced another schema.
+    SchemaReplaced,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::TypesRemoved { types } => {
+                write!(f, "Type {} is no longer allowed", join(types))
+            }
+            ChangeKind::TypesAdded { types } => write!(f, "Type {} is now allowed", join(types)),
+            ChangeKind::RequiredAdded { property } => {
+                write!(f, "Property '{property}' became required")
+            }
+            ChangeKind::RequiredRemoved { property } => {
+                write!(f, "Property '{property}' is no longer required")
+            }
+            ChangeKind::BoundTightened { keyword } => write!(f, "`{keyword}` was tightened"),
+            ChangeKind::BoundLoosened { keyword } => write!(f, "`{keyword}` was loosened"),
+            ChangeKind::EnumValuesRemoved { values } => {
+                write!(f, "Values {} were removed from `enum`", values.join(", "))
+            }
+            ChangeKind::EnumValuesAdded { values } => {
+                write!(f, "Values {} were added to `enum`", values.join(", "))
+            }
+            ChangeKind::AdditionalPropertiesClosed => {
+                f.write_str("Additional properties are no longer allowed")
+            }
+            ChangeKind::AdditionalPropertiesOpened => {
+                f.write_str("Additional properties are now allowed")
+            }
+            ChangeKind::PropertyAdded { property } => write!(f, "Property '{property}' was added"),
+            ChangeKind::PropertyRemoved { property } => {
+                write!(f, "Property '{property}' was removed")
+            }
+            ChangeKind::KeywordAdded { keyword } => write!(f, "`{keyword}` was added"),
+            ChangeKind::KeywordRemoved { keyword } => write!(f, "`{keyword}` was removed"),
+            ChangeKind::KeywordChanged { keyword } => write!(f, "`{keyword}` was changed"),
+            ChangeKind::SchemaReplaced => f.write_str("The schema was replaced"),
+        }
+    }
+}
+
+fn join(types: &[PrimitiveType]) -> String {
+    types
+        .iter()
+        .map(PrimitiveType::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A change at a location within the new schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub location: JsonPointer,
+    pub kind: ChangeKind,
+    pub breaks: Breaks,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at '{}': {}", self.breaks, self.location, self.kind)
+    }
+}
+
+/// The breaking changes between two versions of a schema.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    changes: Vec<Change>,
+}
+
+impl Report {
+    /// Whether all data valid against the old schema is valid against the new one.
+    pub fn is_backward_compatible(&self) -> bool {
+        !self.changes.iter().any(|change| change.breaks.backward())
+    }
+    /// Whether all data valid against the new schema is valid against the old one.
+    pub fn is_forward_compatible(&self) -> bool {
+        !self.changes.iter().any(|change| change.breaks.forward())
+    }
+    /// Breaking changes in the order of their locations.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+}
+
+/// Compare two versions of a schema.
+pub fn compare<J: Json>(old: &J, new: &J) -> BuildResult<Report> {
+    let mut comparison = Comparison {
+        location: JsonPointer::new(),
+        negated: false,
+        report: Report::default(),
+    };
+    comparison.schema(old, new)?;
+    comparison
+        .report
+        .changes
+        .sort_by_cached_key(|change| change.location.sort_key());
+    Ok(comparison.report)
+}
+
+/// Keywords that bound the size or value of instances from below.
+const LOWER_BOUNDS: [&str; 6] = [
+    "minimum",
+    "exclusiveMinimum",
+    "minLength",
+    "minItems",
+    "minProperties",
+    "minContains",
+];
+/// Keywords that bound the size or value of instances from above.
+const UPPER_BOUNDS: [&str; 6] = [
+    "maximum",
+    "exclusiveMaximum",
+    "maxLength",
+    "maxItems",
+    "maxProperties",
+    "maxContains",
+];
+/// Keywords with a single subschema.
+const SUBSCHEMA: [&str; 10] = [
+    "additionalItems",
+    "contains",
+    "contentSchema",
+    "else",
+    "if",
+    "not",
+    "propertyNames",
+    "then",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+/// Keywords with subschemas by name.
+const NAMED_SUBSCHEMAS: [&str; 4] = [
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "patternProperties",
+];
+/// Keywords with subschemas by position.
+const INDEXED_SUBSCHEMAS: [&str; 3] = ["allOf", "anyOf", "prefixItems"];
+/// Assertions compared by value.
+const OPAQUE: [&str; 6] = [
+    "$dynamicRef",
+    "$recursiveRef",
+    "$ref",
+    "dependencies",
+    "dependentRequired",
+    "oneOf",
+];
+
+struct Comparison {
+    location: JsonPointer,
+    /// Whether the current schema is negated via `not`, which swaps the compatibility a change
+    /// breaks.
+    negated: bool,
+    report: Report,
+}
+
+impl Comparison {
+    fn report(&mut self, location: JsonPointer, kind: ChangeKind, breaks: Breaks) {
+        let breaks = if self.negated { breaks.flip() } else { breaks };
+        self.report.changes.push(Change {
+            location,
+            kind,
+            breaks,
+        });
+    }
+
+    fn keyword_change(&mut self, keyword: &str, kind: ChangeKind, breaks: Breaks) {
+        self.report(self.location.join(keyword), kind, breaks);
+    }
+
+    fn at(
+        &mut self,
+        segment: impl Into<Segment>,
+        compare: impl FnOnce(&mut Self) -> BuildResult<()>,
+    ) -> BuildResult<()> {
+        self.location.push(segment);
+        let result = compare(self);
+        self.location.pop();
+        result
+    }
+
+    fn schema<J: Json>(&mut self, old: &J, new: &J) -> BuildResult<()> {
+        let (old, new) = match (old.as_object(), new.as_object()) {
+            (Some(old), Some(new)) => (old, new),
+            _ => {
+                if !value::equal(old, new) {
+                    // Any schema allows less than `true` and more than `false`
+                    let breaks = if old.as_boolean() == Some(true)
+                        || new.as_boolean() == Some(false)
+                    {
+                        Breaks::Backward
+                    } else if old.as_boolean() == Some(false) || new.as_boolean() == Some(true) {
+                        Breaks::Forward
+                    } else {
+                        Breaks::Both
+                    };
+                    self.report(self.location.clone(), ChangeKind::SchemaReplaced, breaks);
+                }
+                return Ok(());
+            }
+        };
+        self.types(old, new);
+        self.required(old, new);
+        for keyword in LOWER_BOUNDS {
+            self.bound(old, new, keyword, true);
+        }
+        for keyword in UPPER_BOUNDS {
+            self.bound(old, new, keyword, false);
+        }
+        self.enumeration(old, new)?;
+        self.properties(old, new)?;
+        for keyword in ["const", "pattern", "uniqueItems", "multipleOf"] {
+            self.assertion(old, new, keyword);
+        }
+        for keyword in OPAQUE {
+            match (old.get(keyword), new.get(keyword)) {
+                (Some(old), Some(new)) if !value::equal(old, new) => self.keyword_change(
+                    keyword,
+                    ChangeKind::KeywordChanged {
+                        keyword: keyword.to_owned(),
+                    },
+                    Breaks::Both,
+                ),
+                (Some(_), None) | (None, Some(_)) => self.keyword_change(
+                    keyword,
+                    ChangeKind::KeywordChanged {
+                        keyword: keyword.to_owned(),
+                    },
+                    Breaks::Both,
+                ),
+                _ => {}
+            }
+        }
+        for keyword in SUBSCHEMA {
+            self.subschema(old, new, keyword)?;
+        }
+        match (old.get("items"), new.get("items")) {
+            (Some(old_items), Some(new_items))
+                if old_items.as_array().is_some() || new_items.as_array().is_some() =>
+            {
+                self.indexed(old, new, "items")?;
+            }
+            _ => self.subschema(old, new, "items")?,
+        }
+        for keyword in INDEXED_SUBSCHEMAS {
+            self.indexed(old, new, keyword)?;
+        }
+        for keyword in NAMED_SUBSCHEMAS {
+            self.named(old, new, keyword)?;
+        }
+        Ok(())
+    }
+
+    fn types<O: JsonObject>(&mut self, old: &O, new: &O)
+    where
+        O::Value: Json,
+    {
+        let old_types = allowed_types(old);
+        let new_types = allowed_types(new);
+        let removed: Vec<_> = old_types
+            .iter()
+            .filter(|ty| !allows(&new_types, **ty))
+            .copied()
+            .collect();
+        let added: Vec<_> = new_types
+            .iter()
+            .filter(|ty| !allows(&old_types, **ty))
+            .copied()
+            .collect();
+        if !removed.is_empty() {
+            self.keyword_change(
+                "type",
+                ChangeKind::TypesRemoved { types: removed },
+                Breaks::Backward,
+            );
+        }
+        if !added.is_empty() {
+            self.keyword_change(
+                "type",
+                ChangeKind::TypesAdded { types: added },
+                Breaks::Forward,
+            );
+        }
+    }
+
+    fn required<O: JsonObject>(&mut self, old: &O, new: &O)
+    where
+        O::Value: Json,
+    {
+        let old_required = strings(old.get("required"));
+        let new_required = strings(new.get("required"));
+        for property in &new_required {
+            if !old_required.contains(property) {
+                self.keyword_change(
+                    "required",
+                    ChangeKind::RequiredAdded {
+                        property: property.clone(),
+                    },
+                    Breaks::Backward,
+                );
+            }
+        }
+        for property in old_required {
+            if !new_required.contains(&property) {
+                self.keyword_change(
+                    "required",
+                    ChangeKind::RequiredRemoved { property },
+                    Breaks::Forward,
+                );
+            }
+        }
+    }
+
+    fn bound<O: JsonObject>(&mut self, old: &O, new: &O, keyword: &str, is_lower: bool)
+    where
+        O::Value: Json,
+    {
+        let number = |schema: &O| {
+            schema
+                .get(keyword)
+                .and_then(Json::as_number)
+                .and_then(JsonNumber::as_float)
+        };
+        let tighter = match (number(old), number(new)) {
+            (Some(old), Some(new)) if old == new => return,
+            (Some(old), Some(new)) => (new > old) == is_lower,
+            (None, Some(_)) => true,
+            (Some(_), None) => false,
+            (None, None) => {
+                // E.g. boolean `exclusiveMaximum` in Draft 4
+                self.assertion(old, new, keyword);
+                return;
+            }
+        };
+        let (kind, breaks) = if tighter {
+            let kind = ChangeKind::BoundTightened {
+                keyword: keyword.to_owned(),
+            };
+            (kind, Breaks::Backward)
+        } else {
+            let kind = ChangeKind::BoundLoosened {
+                keyword: keyword.to_owned(),
+            };
+            (kind, Breaks::Forward)
+        };
+        self.keyword_change(keyword, kind, breaks);
+    }
+
+    fn enumeration<O: JsonObject>(&mut self, old: &O, new: &O) -> BuildResult<()>
+    where
+        O::Value: Json,
+    {
+        let (old_values, new_values) = match (
+            old.get("enum").and_then(Json::as_array),
+            new.get("enum").and_then(Json::as_array),
+        ) {
+            (Some(old), Some(new)) => (
+                old.try_iter()?.collect::<Result<Vec<_>, _>>()?,
+                new.try_iter()?.collect::<Result<Vec<_>, _>>()?,
+            ),
+            _ => {
+                self.assertion(old, new, "enum");
+                return Ok(());
+            }
+        };
+        let difference = |left: &[&O::Value], right: &[&O::Value]| -> BuildResult<Vec<String>> {
+            left.iter()
+                .filter(|value| !right.iter().any(|other| value::equal(**value, *other)))
+                .map(|value| Ok(value::Value::from_json(*value)?.to_string()))
+                .collect()
+        };
+        let removed = difference(&old_values, &new_values)?;
+        let added = difference(&new_values, &old_values)?;
+        if !removed.is_empty() {
+            self.keyword_change(
+                "enum",
+                ChangeKind::EnumValuesRemoved { values: removed },
+                Breaks::Backward,
+            );
+        }
+        if !added.is_empty() {
+            self.keyword_change(
+                "enum",
+                ChangeKind::EnumValuesAdded { values: added },
+                Breaks::Forward,
+            );
+        }
+        Ok(())
+    }
+
+    /// Compare an assertion that has no better comparison than equality.
+    fn assertion<O: JsonObject>(&mut self, old: &O, new: &O, keyword: &str)
+    where
+        O::Value: Json,
+    {
+        let (kind, breaks) = match (old.get(keyword), new.get(keyword)) {
+            (Some(old), Some(new)) if value::equal(old, new) => return,
+            (Some(old), Some(new)) => {
+                if let Some(breaks) = compare_assertion(keyword, old, new) {
+                    let kind = ChangeKind::KeywordChanged {
+                        keyword: keyword.to_owned(),
+                    };
+                    (kind, breaks)
+                } else {
+                    return;
+                }
+            }
+            (None, Some(_)) => (
+                ChangeKind::KeywordAdded {
+                    keyword: keyword.to_owned(),
+                },
+                Breaks::Backward,
+            ),
+            (Some(_), None) => (
+                ChangeKind::KeywordRemoved {
+                    keyword: keyword.to_owned(),
+                },
+                Breaks::Forward,
+            ),
+            (None, None) => return,
+        };
+        self.keyword_change(keyword, kind, breaks);
+    }
+
+    fn properties<O: JsonObject>(&mut self, old: &O, new: &O) -> BuildResult<()>
+    where
+        O::Value: Json,
+    {
+        let old_closed = is_closed(old);
+        let new_closed = is_closed(new);
+        match (old_closed, new_closed) {
+            (false, true) => self.keyword_change(
+                "additionalProperties",
+                ChangeKind::AdditionalPropertiesClosed,
+                Breaks::Backward,
+            ),
+            (true, false) => self.keyword_change(
+                "additionalProperties",
+                ChangeKind::AdditionalPropertiesOpened,
+                Breaks::Forward,
+            ),
+            _ => {
+                if let (Some(old), Some(new)) = (
+                    old.get("additionalProperties"),
+                    new.get("additionalProperties"),
+                ) {
+                    self.at("additionalProperties", |comparison| {
+                        comparison.schema(old, new)
+                    })?;
+                }
+            }
+        }
+        let old_properties = old.get("properties").and_then(Json::as_object);
+        let new_properties = new.get("properties").and_then(Json::as_object);
+        if let Some(new_properties) = new_properties {
+            for entry in object_entries::<O::Value>(new_properties) {
+                let (name, new_schema) = entry?;
+                match old_properties.and_then(|old| old.get(name)) {
+                    Some(old_schema) => self.at("properties", |comparison| {
+                        comparison.at(name, |comparison| comparison.schema(old_schema, new_schema))
+                    })?,
+                    None => self.unlisted_property(name, new_schema, unlisted(old, name), true)?,
+                }
+            }
+        }
+        if let Some(old_properties) = old_properties {
+            for entry in object_entries::<O::Value>(old_properties) {
+                let (name, old_schema) = entry?;
+                if new_properties.is_some_and(|new| new.contains_key(name)) {
+                    continue;
+                }
+                self.unlisted_property(name, old_schema, unlisted(new, name), false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compare a property that only one side lists in `properties` with how the other side
+    /// treats it as an unlisted property.
+    fn unlisted_property<J: Json>(
+        &mut self,
+        name: &str,
+        listed: &J,
+        other: Unlisted<'_, J>,
+        added: bool,
+    ) -> BuildResult<()> {
+        let breaks = match other {
+            Unlisted::Additional(Some(additional)) if value::equal(listed, additional) => {
+                return Ok(())
+            }
+            // Previously rejected when added, or rejected from now on when removed
+            Unlisted::Additional(Some(additional)) if additional.as_boolean() == Some(false) => {
+                if added {
+                    Breaks::Forward
+                } else {
+                    Breaks::Backward
+                }
+            }
+            Unlisted::Additional(Some(additional)) if !is_trivial(additional) => {
+                let (old, new) = if added {
+                    (additional, listed)
+                } else {
+                    (listed, additional)
+                };
+                return self.at("properties", |comparison| {
+                    comparison.at(name, |comparison| comparison.schema(old, new))
+                });
+            }
+            // Apart from `patternProperties`, which apply either way, the property is
+            // unconstrained on the other side
+            _ if is_trivial(listed) => return Ok(()),
+            _ if added => Breaks::Backward,
+            _ => Breaks::Forward,
+        };
+        let kind = if added {
+            ChangeKind::PropertyAdded {
+                property: name.to_owned(),
+            }
+        } else {
+            ChangeKind::PropertyRemoved {
+                property: name.to_owned(),
+            }
+        };
+        self.report(self.location.join("properties").join(name), kind, breaks);
+        Ok(())
+    }
+
+    fn subschema<O: JsonObject>(&mut self, old: &O, new: &O, keyword: &str) -> BuildResult<()>
+    where
+        O::Value: Json,
+    {
+        match (old.get(keyword), new.get(keyword)) {
+            // A looser `if` applies `then` to more instances and `else` to fewer, so the
+            // direction of a change depends on both
+            (Some(old), Some(new)) if keyword == "if" => {
+                if !value::equal(old, new) {
+                    self.keyword_change(
+                        keyword,
+                        ChangeKind::KeywordChanged {
+                            keyword: keyword.to_owned(),
+                        },
+                        Breaks::Both,
+                    );
+                }
+                Ok(())
+            }
+            (Some(old), Some(new)) => {
+                let negated = self.negated;
+                self.negated ^= keyword == "not";
+                let result = self.at(keyword, |comparison| comparison.schema(old, new));
+                self.negated = negated;
+                result
+            }
+            // Keywords like `if` and `then` only apply together, their effect is unclear alone
+            (None, Some(schema)) | (Some(schema), None) if is_trivial(schema) => Ok(()),
+            (None, Some(_)) if keyword_narrows(keyword) => {
+                self.added(keyword, Breaks::Backward);
+                Ok(())
+            }
+            (Some(_), None) if keyword_narrows(keyword) => {
+                self.removed(keyword, Breaks::Forward);
+                Ok(())
+            }
+            (None, Some(_)) => {
+                self.added(keyword, Breaks::Both);
+                Ok(())
+            }
+            (Some(_), None) => {
+                self.removed(keyword, Breaks::Both);
+                Ok(())
+            }
+            (None, None) => Ok(()),
+        }
+    }
+
+    fn added(&mut self, keyword: &str, breaks: Breaks) {
+        self.keyword_change(
+            keyword,
+            ChangeKind::KeywordAdded {
+                keyword: keyword.to_owned(),
+            },
+            breaks,
+        );
+    }
+
+    fn removed(&mut self, keyword: &str, breaks: Breaks) {
+        self.keyword_change(
+            keyword,
+            ChangeKind::KeywordRemoved {
+                keyword: keyword.to_owned(),
+            },
+            breaks,
+        );
+    }
+
+    fn indexed<O: JsonObject>(&mut self, old: &O, new: &O, keyword: &str) -> BuildResult<()>
+    where
+        O::Value: Json,
+    {
+        let (old_items, new_items) = match (
+            old.get(keyword).and_then(Json::as_array),
+            new.get(keyword).and_then(Json::as_array),
+        ) {
+            (Some(old), Some(new)) if old.len() == new.len() => (old, new),
+            (None, None) => return Ok(()),
+            _ => {
+                if let (Some(old), Some(new)) = (old.get(keyword), new.get(keyword)) {
+                    if value::equal(old, new) {
+                        return Ok(());
+                    }
+                }
+                self.keyword_change(
+                    keyword,
+                    ChangeKind::KeywordChanged {
+                        keyword: keyword.to_owned(),
+                    },
+                    Breaks::Both,
+                );
+                return Ok(());
+            }
+        };
+        for (idx, (old, new)) in old_items.try_iter()?.zip(new_items.try_iter()?).enumerate() {
+            let (old, new) = (old?, new?);
+            self.at(keyword, |comparison| {
+                comparison.at(idx, |comparison| comparison.schema(old, new))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn named<O: JsonObject>(&mut self, old: &O, new: &O, keyword: &str) -> BuildResult<()>
+    where
+        O::Value: Json,
+    {
+        let (Some(old_schemas), Some(new_schemas)) = (
+            old.get(keyword).and_then(Json::as_object),
+            new.get(keyword).and_then(Json::as_object),
+        ) else {
+            if let "patternProperties" | "dependentSchemas" = keyword {
+                self.assertion(old, new, keyword);
+            }
+            return Ok(());
+        };
+        for entry in object_entries::<O::Value>(new_schemas) {
+            let (name, new_schema) = entry?;
+            match old_schemas.get(name) {
+                Some(old_schema) => self.at(keyword, |comparison| {
+                    comparison.at(name, |comparison| comparison.schema(old_schema, new_schema))
+                })?,
+                // Definitions only matter through references, which are compared themselves
+                None if matches!(keyword, "$defs" | "definitions") => {}
+                None => self.report(
+                    self.location.join(keyword).join(name),
+                    ChangeKind::KeywordAdded {
+                        keyword: keyword.to_owned(),
+                    },
+                    Breaks::Backward,
+                ),
+            }
+        }
+        if !matches!(keyword, "$defs" | "definitions") {
+            for entry in object_entries::<O::Value>(old_schemas) {
+                let (name, _) = entry?;
+                if !new_schemas.contains_key(name) {
+                    self.report(
+                        self.location.join(keyword).join(name),
+                        ChangeKind::KeywordRemoved {
+                            keyword: keyword.to_owned(),
+                        },
+                        Breaks::Forward,
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether adding `keyword` with a subschema only ever rejects more instances.
+fn keyword_narrows(keyword: &str) -> bool {
+    !matches!(keyword, "if" | "then" | "else")
+}
+
+/// The compatibility a changed value of an assertion breaks, `None` if it is not an assertion.
+fn compare_assertion<J: Json>(keyword: &str, old: &J, new: &J) -> Option<Breaks> {
+    match keyword {
+        "uniqueItems" => match (old.as_boolean(), new.as_boolean()) {
+            (Some(false), Some(true)) => Some(Breaks::Backward),
+            (Some(true), Some(false)) => Some(Breaks::Forward),
+            _ => Some(Breaks::Both),
+        },
+        "multipleOf" => {
+            let old = old.as_number().and_then(JsonNumber::as_float)?;
+            let new = new.as_number().and_then(JsonNumber::as_float)?;
+            if (new / old).fract() == 0.0 {
+                Some(Breaks::Backward)
+            } else if (old / new).fract() == 0.0 {
+                Some(Breaks::Forward)
+            } else {
+                Some(Breaks::Both)
+            }
+        }
+        _ => Some(Breaks::Both),
+    }
+}
+
+/// Types allowed by `type`, all if absent.
+fn allowed_types<O: JsonObject>(schema: &O) -> Vec<PrimitiveType>
+where
+    O::Value: Json,
+{
+    let all = vec![
+        PrimitiveType::Array,
+        PrimitiveType::Boolean,
+        PrimitiveType::Null,
+        PrimitiveType::Number,
+        PrimitiveType::Object,
+        PrimitiveType::String,
+    ];
+    let Some(value) = schema.get("type") else {
+        return all;
+    };
+    if let Some(name) = value.as_string() {
+        return PrimitiveType::from_str(name.as_ref()).map_or(all, |ty| vec![ty]);
+    }
+    value.as_array().map_or(all, |types| {
+        types
+            .iter()
+            .filter_map(|item| {
+                item.ok()
+                    .and_then(Json::as_string)
+                    .and_then(|name| PrimitiveType::from_str(name.as_ref()))
+            })
+            .collect()
+    })
+}
+
+fn allows(types: &[PrimitiveType], ty: PrimitiveType) -> bool {
+    types.contains(&ty) || (ty == PrimitiveType::Integer && types.contains(&PrimitiveType::Number))
+}
+
+fn strings<J: Json>(value: Option<&J>) -> Vec<String> {
+    value
+        .and_then(Json::as_array)
+        .map(|array| {
+            array
+                .iter()
+                .filter_map(|item| item.ok().and_then(Json::as_string))
+                .map(|item| item.as_ref().to_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// How a schema constrains a property it does not list in `properties`.
+enum Unlisted<'a, J> {
+    /// Some `patternProperties` apply, so `additionalProperties` does not.
+    Patterns,
+    /// `additionalProperties`, if any.
+    Additional(Option<&'a J>),
+}
+
+fn unlisted<'a, O: JsonObject>(schema: &'a O, name: &str) -> Unlisted<'a, O::Value>
+where
+    O::Value: Json,
+{
+    let patterns = schema.get("patternProperties").and_then(Json::as_object);
+    // Patterns that can not be evaluated are assumed to match
+    let matches = |pattern: &str| {
+        Regex::new(pattern).map_or(true, |regex| regex.is_match(name).unwrap_or(true))
+    };
+    if patterns.is_some_and(|patterns| {
+        patterns
+            .iter()
+            .any(|(pattern, _)| pattern.map_or(true, |pattern| matches(pattern.as_ref())))
+    }) {
+        Unlisted::Patterns
+    } else {
+        Unlisted::Additional(schema.get("additionalProperties"))
+    }
+}
+
+fn is_closed<O: JsonObject>(schema: &O) -> bool
+where
+    O::Value: Json,
+{
+    schema
+        .get("additionalProperties")
+        .and_then(Json::as_boolean)
+        == Some(false)
+}
+
+/// Whether `schema` allows everything.
+fn is_trivial<J: Json>(schema: &J) -> bool {
+    schema.as_boolean() == Some(true) || schema.as_object().is_some_and(|object| object.is_empty())
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::compare;
+
+    fn changes(old: &Value, new: &Value) -> Vec<String> {
+        compare(old, new)
+            .expect("Valid JSON")
+            .changes()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test_case(json!({}), json!({"required": ["a"]}), "breaks backward compatibility at '/required': Property 'a' became required"; "new required property")]
+    #[test_case(json!({"required": ["a"]}), json!({}), "breaks forward compatibility at '/required': Property 'a' is no longer required"; "removed required property")]
+    #[test_case(json!({"type": ["string", "null"]}), json!({"type": "string"}), "breaks backward compatibility at '/type': Type null is no longer allowed"; "narrowed type")]
+    #[test_case(json!({"type": "integer"}), json!({"type": "number"}), "breaks forward compatibility at '/type': Type number is now allowed"; "widened type")]
+    #[test_case(json!({"type": "number"}), json!({"type": "integer"}), "breaks backward compatibility at '/type': Type number is no longer allowed"; "integer only")]
+    #[test_case(json!({}), json!({"type": "object"}), "breaks backward compatibility at '/type': Type array, boolean, null, number, string is no longer allowed"; "added type")]
+    #[test_case(json!({"maxLength": 10}), json!({"maxLength": 5}), "breaks backward compatibility at '/maxLength': `maxLength` was tightened"; "tightened upper bound")]
+    #[test_case(json!({"minimum": 1}), json!({"minimum": 0}), "breaks forward compatibility at '/minimum': `minimum` was loosened"; "loosened lower bound")]
+    #[test_case(json!({}), json!({"minItems": 1}), "breaks backward compatibility at '/minItems': `minItems` was tightened"; "added bound")]
+    #[test_case(json!({"enum": ["a", "b"]}), json!({"enum": ["a"]}), "breaks backward compatibility at '/enum': Values \"b\" were removed from `enum`"; "removed enum value")]
+    #[test_case(json!({"enum": [1]}), json!({"enum": [1.0, 2]}), "breaks forward compatibility at '/enum': Values 2 were added to `enum`"; "added enum value")]
+    #[test_case(json!({}), json!({"additionalProperties": false}), "breaks backward compatibility at '/additionalProperties': Additional properties are no longer allowed"; "closed")]
+    #[test_case(json!({"additionalProperties": false}), json!({"additionalProperties": true}), "breaks forward compatibility at '/additionalProperties': Additional properties are now allowed"; "opened")]
+    #[test_case(json!({"additionalProperties": false}), json!({"additionalProperties": false, "properties": {"a": {}}}), "breaks forward compatibility at '/properties/a': Property 'a' was added"; "property added to closed object")]
+    #[test_case(json!({}), json!({"properties": {"a": {"type": "string"}}}), "breaks backward compatibility at '/properties/a': Property 'a' was added"; "constrained property")]
+    #[test_case(json!({"properties": {"a": {"type": "string"}}}), json!({}), "breaks forward compatibility at '/properties/a': Property 'a' was removed"; "unconstrained property")]
+    #[test_case(json!({"items": {"properties": {"a": {"maximum": 5}}}}), json!({"items": {"properties": {"a": {"maximum": 3}}}}), "breaks backward compatibility at '/items/properties/a/maximum': `maximum` was tightened"; "nested")]
+    #[test_case(json!({"not": {"enum": [1]}}), json!({"not": {"enum": [1, 2]}}), "breaks backward compatibility at '/not/enum': Values 2 were added to `enum`"; "negated")]
+    #[test_case(json!({"multipleOf": 2}), json!({"multipleOf": 4}), "breaks backward compatibility at '/multipleOf': `multipleOf` was changed"; "multiple of")]
+    #[test_case(json!({"$ref": "#/$defs/a"}), json!({"$ref": "#/$defs/b"}), "breaks backward and forward compatibility at '/$ref': `$ref` was changed"; "reference")]
+    #[test_case(json!({"anyOf": [{}, {}]}), json!({"anyOf": [{}]}), "breaks backward and forward compatibility at '/anyOf': `anyOf` was changed"; "branches")]
+    #[test_case(json!(true), json!({"type": "string"}), "breaks backward compatibility at '': The schema was replaced"; "boolean schema")]
+    #[test_case(json!({"additionalProperties": {"type": "string"}}), json!({"properties": {"a": {}}, "additionalProperties": {"type": "string"}}), "breaks forward compatibility at '/properties/a/type': Type array, boolean, null, number, object is now allowed"; "property added over additional schema")]
+    #[test_case(json!({"properties": {"a": {"type": "integer"}}, "additionalProperties": {"type": "number"}}), json!({"additionalProperties": {"type": "number"}}), "breaks forward compatibility at '/properties/a/type': Type number is now allowed"; "property removed under additional schema")]
+    #[test_case(json!({"patternProperties": {"^a": {}}, "additionalProperties": false}), json!({"patternProperties": {"^a": {}}, "properties": {"ab": {"type": "string"}}, "additionalProperties": false}), "breaks backward compatibility at '/properties/ab': Property 'ab' was added"; "property added over pattern")]
+    #[test_case(json!({"if": {"required": ["a"]}, "then": {"required": ["b"]}}), json!({"if": {}, "then": {"required": ["b"]}}), "breaks backward and forward compatibility at '/if': `if` was changed"; "condition")]
+    fn test_change(old: Value, new: Value, expected: &str) {
+        assert_eq!(changes(&old, &new), [expected]);
+    }
+
+    #[test_case(json!({"type": "string", "title": "Old"}), json!({"type": "string", "title": "New", "description": "Added"}); "annotations")]
+    #[test_case(json!({"properties": {"a": {}}}), json!({"properties": {"a": {}, "b": {}}}); "unconstrained property")]
+    #[test_case(json!({"$defs": {"a": {}}}), json!({"$defs": {"b": {"type": "string"}}}); "definitions")]
+    #[test_case(json!({"enum": [1, "a"]}), json!({"enum": ["a", 1]}); "reordered enum")]
+    #[test_case(json!({"type": ["string", "integer"]}), json!({"type": ["integer", "string"]}); "reordered types")]
+    fn test_compatible(old: Value, new: Value) {
+        let report = compare(&old, &new).expect("Valid JSON");
+        assert!(report.is_backward_compatible());
+        assert!(report.is_forward_compatible());
+        assert!(report.changes().is_empty());
+    }
+
+    #[test]
+    fn test_event() {
+        let old = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string"},
+                "kind": {"enum": ["created", "deleted"]},
+                "count": {"type": "integer", "minimum": 0},
+            },
+            "required": ["id"],
+        });
+        let new = json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string"},
+                "kind": {"enum": ["created", "deleted", "updated"]},
+                "count": {"type": "integer", "minimum": 1},
+                "source": {"type": "string"},
+            },
+            "required": ["id", "source"],
+            "additionalProperties": false,
+        });
+        let report = compare(&old, &new).expect("Valid JSON");
+        assert!(!report.is_backward_compatible());
+        assert!(!report.is_forward_compatible());
+        assert_eq!(
+            changes(&old, &new),
+            [
+                "breaks backward compatibility at '/additionalProperties': Additional properties are no longer allowed",
+                "breaks backward compatibility at '/properties/count/minimum': `minimum` was tightened",
+                "breaks forward compatibility at '/properties/kind/enum': Values \"updated\" were added to `enum`",
+                "breaks backward compatibility at '/properties/source': Property 'source' was added",
+                "breaks backward compatibility at '/required': Property 'source' became required",
+            ]
+        );
+    }
+}
@@ -1,17 +1,245 @@
-use crate::{drafts::Draft, graph, BuildError, Validator};
-use jsonlike::{Json, JsonObject};
-
-pub(crate) fn compile<J: Json>(schema: &J, draft: Draft) -> Result<Validator<J>, BuildError> {
-    let mut graph = graph::Graph::new();
-    if let Some(obj) = schema.as_object() {
-        for (key, value) in obj.iter() {
-            if let Some(keyword) = draft.get_keyword(key.unwrap().as_ref(), value) {
-                graph.push_node(keyword);
+mod resources;
+
+use std::{collections::HashMap, sync::Arc};
+
+use jpointer::{JsonPointer, Segment};
+use jsonlike::prelude::*;
+use url::Url;
+
+use crate::{
+    drafts::Draft,
+    extensions::{self, Extension, Extensions},
+    format::FormatFactory,
+    graph::{Graph, NodeId},
+    value::Value,
+    vocabulary::{self, CompiledKeyword, KeywordFactory, KeywordValue, SchemaNode},
+    BoxedFormat, BoxedKeyword, BuildError, BuildResult, Validator, ValidatorBuilder,
+};
+pub(crate) use resources::{default_base, Resources, DATA_KEYWORDS, DEFAULT_BASE};
+
+/// The result of compiling a single keyword, `None` if the keyword has no effect on validation.
+pub(crate) type CompileResult = Option<BuildResult<KeywordValue>>;
+
+/// Shared state for compiling a schema into a graph of nodes.
+pub(crate) struct Context<'a, 'b, J: Json> {
+    builder: &'b ValidatorBuilder<'b, J>,
+    draft: Draft,
+    graph: Graph<SchemaNode>,
+    extensions: Extensions<J>,
+    /// Location of the (sub)schema that is currently compiled.
+    location: JsonPointer,
+    /// The (sub)schema that is currently compiled.
+    schema: &'a J,
+    /// The base URI that applies to the current (sub)schema, before its own `$id`.
+    base: Url,
+    resources: Resources<'a, J>,
+    /// Nodes of reference targets, so every target is compiled only once.
+    references: HashMap<*const J, NodeId>,
+}
+
+impl<'a, 'b, J: Json> Context<'a, 'b, J> {
+    pub(crate) fn location(&self) -> &JsonPointer {
+        &self.location
+    }
+    pub(crate) fn schema(&self) -> &'a J {
+        self.schema
+    }
+    pub(crate) fn validate_content(&self) -> bool {
+        self.builder.validate_content
+    }
+    pub(crate) fn custom_keyword(&self, name: &str) -> Option<Arc<dyn KeywordFactory<J> + 'b>> {
+        self.builder.keywords.get(name).cloned()
+    }
+    pub(crate) fn custom_format(&self, name: &str) -> Option<Arc<dyn FormatFactory<J> + 'b>> {
+        self.builder.formats.get(name).cloned()
+    }
+    /// Add an initialized custom keyword and return its index within the extensions.
+    pub(crate) fn add_keyword(
+        &mut self,
+        name: &str,
+        schema: &J,
+        keyword: BoxedKeyword<J>,
+    ) -> BuildResult<usize> {
+        self.extensions.keywords.push(Extension {
+            name: name.to_owned(),
+            schema: Some(Value::from_json(schema)?),
+            value: Arc::from(keyword),
+        });
+        Ok(self.extensions.keywords.len() - 1)
+    }
+    /// Add an initialized custom format and return its index within the extensions.
+    pub(crate) fn add_format(
+        &mut self,
+        name: &str,
+        schema: &J,
+        format: BoxedFormat,
+    ) -> BuildResult<usize> {
+        self.extensions.formats.push(Extension {
+            name: name.to_owned(),
+            schema: Some(Value::from_json(schema)?),
+            value: Arc::from(format),
+        });
+        Ok(self.extensions.formats.len() - 1)
+    }
+    /// Index of a registered content encoding, `None` if it is unknown.
+    pub(crate) fn content_encoding(&mut self, name: &str) -> Option<usize> {
+        let encoding = self.builder.content_encodings.get(name)?;
+        Some(extensions::intern(
+            &mut self.extensions.content_encodings,
+            name,
+            Arc::clone(encoding),
+        ))
+    }
+    /// Index of a registered content media type, `None` if it is unknown.
+    pub(crate) fn content_media_type(&mut self, name: &str) -> Option<usize> {
+        let media_type = self.builder.content_media_types.get(name)?;
+        Some(extensions::intern(
+            &mut self.extensions.content_media_types,
+            name,
+            Arc::clone(media_type),
+        ))
+    }
+    /// Build an error for an invalid keyword of the current (sub)schema.
+    pub(crate) fn invalid(&self, keyword: &str, reason: impl Into<String>) -> BuildError {
+        BuildError::invalid_keyword(&self.location.join(keyword), reason)
+    }
+    /// Compile a subschema that is the value of `keyword`, e.g. `not`.
+    pub(crate) fn subschema(&mut self, keyword: &str, schema: &'a J) -> BuildResult<NodeId> {
+        self.compile_at(vec![keyword.into()], schema)
+    }
+    /// Compile a subschema nested within `keyword`, e.g. `properties/foo`.
+    pub(crate) fn subschema_in(
+        &mut self,
+        keyword: &str,
+        segment: impl Into<Segment>,
+        schema: &'a J,
+    ) -> BuildResult<NodeId> {
+        self.compile_at(vec![keyword.into(), segment.into()], schema)
+    }
+    /// Add a node consisting of a single keyword located under `keyword` of the current
+    /// (sub)schema.
+    pub(crate) fn push_keyword_node(&mut self, keyword: &str, value: KeywordValue) -> NodeId {
+        self.graph
+            .push_node(SchemaNode::Keywords(vec![CompiledKeyword {
+                location: self.location.join(keyword),
+                value,
+            }]))
+    }
+
+    /// Compile the target of a `$ref` within the current (sub)schema.
+    ///
+    /// Targets are compiled at their own location, which makes recursive references possible.
+    pub(crate) fn reference(&mut self, reference: &str) -> BuildResult<NodeId> {
+        let base = self.resources.scope(&self.base, self.schema);
+        let Some(target) = self.resources.lookup(&base, reference) else {
+            return Err(self.invalid("$ref", format!("Unresolvable reference '{reference}'")));
+        };
+        let key: *const J = target.schema;
+        if let Some(node) = self.references.get(&key) {
+            return Ok(*node);
+        }
+        // Reserve the node first, so references back to the target see it
+        let node = self.graph.push_node(SchemaNode::Boolean {
+            valid: true,
+            location: target.location.clone(),
+        });
+        self.references.insert(key, node);
+        let base = core::mem::replace(&mut self.base, target.base);
+        let location = core::mem::replace(&mut self.location, target.location);
+        let schema = core::mem::replace(&mut self.schema, target.schema);
+        let result = self.compile_node();
+        self.base = base;
+        self.location = location;
+        self.schema = schema;
+        self.graph.replace(node, result?);
+        Ok(node)
+    }
+
+    fn compile_at(&mut self, segments: Vec<Segment>, schema: &'a J) -> BuildResult<NodeId> {
+        let depth = segments.len();
+        let base = self.resources.scope(&self.base, self.schema);
+        let base = core::mem::replace(&mut self.base, base);
+        let parent = core::mem::replace(&mut self.schema, schema);
+        for segment in segments {
+            self.location.push(segment);
+        }
+        let result = self.compile();
+        for _ in 0..depth {
+            self.location.pop();
+        }
+        self.schema = parent;
+        self.base = base;
+        result
+    }
+
+    fn compile(&mut self) -> BuildResult<NodeId> {
+        let node = self.compile_node()?;
+        Ok(self.graph.push_node(node))
+    }
+
+    fn compile_node(&mut self) -> BuildResult<SchemaNode> {
+        let schema = self.schema;
+        if let Some(valid) = schema.as_boolean() {
+            let location = self.location.clone();
+            return Ok(SchemaNode::Boolean { valid, location });
+        }
+        let Some(object) = schema.as_object() else {
+            return Err(BuildError::invalid_keyword(
+                &self.location,
+                "Expected an object or a boolean",
+            ));
+        };
+        let draft = self.draft;
+        // Before 2019-09 `$ref` overrides all of its siblings
+        let only_ref = matches!(draft, Draft::Draft04 | Draft::Draft06 | Draft::Draft07)
+            && object.contains_key("$ref");
+        let mut keywords = Vec::new();
+        for (key, value) in object.iter() {
+            let key = key?.as_ref();
+            if only_ref && key != "$ref" {
+                continue;
             }
+            let compiled = match vocabulary::compile_custom(self, key, value) {
+                Some(compiled) => compiled,
+                None => match draft.get_keyword(self, key, object, value) {
+                    Some(compiled) => compiled,
+                    None => continue,
+                },
+            };
+            keywords.push(CompiledKeyword {
+                location: self.location.join(key),
+                value: compiled?,
+            });
         }
-    } else if let Some(b) = schema.as_boolean() {
-    } else {
-        return Err(todo!());
+        Ok(SchemaNode::Keywords(keywords))
+    }
+}
+
+/// Compile `schema` into a validator.
+///
+/// References are resolved within `schema` itself and `documents`, which are retrieved
+/// beforehand together with the URIs they were retrieved from.
+pub(crate) fn compile<'a, J: Json>(
+    schema: &'a J,
+    documents: &'a [(Url, J)],
+    builder: &ValidatorBuilder<'_, J>,
+) -> BuildResult<Validator<J>> {
+    let mut resources = Resources::new(builder.draft);
+    resources.add(default_base(), schema)?;
+    for (uri, document) in documents {
+        resources.add(uri.clone(), document)?;
+    }
+    let mut ctx = Context {
+        builder,
+        draft: builder.draft,
+        graph: Graph::new(),
+        extensions: Extensions::new(builder.validate_content),
+        location: JsonPointer::new(),
+        schema,
+        base: default_base(),
+        resources,
+        references: HashMap::new(),
     };
-    Ok(Validator::new(graph))
+    let root = ctx.compile()?;
+    Ok(Validator::new(ctx.graph, root, ctx.extensions))
 }
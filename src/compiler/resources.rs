@@ -0,0 +1,290 @@
+//! Identification of schema resources and resolution of `$ref` values against them.
+use std::collections::{HashMap, HashSet};
+
+use jpointer::JsonPointer;
+use jsonlike::{prelude::*, JsonError};
+use percent_encoding::percent_decode_str;
+use url::Url;
+
+use crate::{drafts::Draft, value, vocabulary::object_entries};
+
+/// The base URI of a document that has no `$id`.
+pub(crate) const DEFAULT_BASE: &str = "json-schema:///";
+
+/// Keywords whose values are plain data rather than schemas.
+pub(crate) const DATA_KEYWORDS: [&str; 4] = ["const", "enum", "examples", "default"];
+
+pub(crate) fn default_base() -> Url {
+    Url::parse(DEFAULT_BASE).expect("The default base URI is valid")
+}
+
+/// A schema that can be the target of a reference.
+#[derive(Debug)]
+pub(crate) struct Resource<'a, J: Json> {
+    /// The base URI the schema is nested in, before its own `$id` applies.
+    pub(crate) base: Url,
+    /// Location of the schema within its document.
+    pub(crate) location: JsonPointer,
+    pub(crate) schema: &'a J,
+}
+
+impl<J: Json> Clone for Resource<'_, J> {
+    fn clone(&self) -> Self {
+        Resource {
+            base: self.base.clone(),
+            location: self.location.clone(),
+            schema: self.schema,
+        }
+    }
+}
+
+/// Resources and anchors of a set of documents, together with the documents they reference.
+#[derive(Debug)]
+pub(crate) struct Resources<'a, J: Json> {
+    draft: Draft,
+    resources: HashMap<Url, Resource<'a, J>>,
+    anchors: HashMap<(Url, String), Resource<'a, J>>,
+    /// URIs referenced via `$ref`, without fragments.
+    references: HashSet<Url>,
+}
+
+impl<'a, J: Json> Resources<'a, J> {
+    pub(crate) fn new(draft: Draft) -> Resources<'a, J> {
+        Resources {
+            draft,
+            resources: HashMap::new(),
+            anchors: HashMap::new(),
+            references: HashSet::new(),
+        }
+    }
+
+    /// Index a document available under `uri`, including the resources embedded in it.
+    ///
+    /// Fails if the document contains values without a JSON equivalent.
+    pub(crate) fn add(&mut self, uri: Url, document: &'a J) -> Result<(), JsonError> {
+        value::check(document)?;
+        let resource = Resource {
+            base: uri.clone(),
+            location: JsonPointer::new(),
+            schema: document,
+        };
+        self.resources.insert(uri.clone(), resource);
+        self.walk(&uri, &mut JsonPointer::new(), document)
+    }
+
+    /// URIs of all known resources.
+    pub(crate) fn uris(&self) -> impl Iterator<Item = &Url> {
+        self.resources.keys()
+    }
+
+    /// Documents referenced by the indexed schemas, without fragments.
+    pub(crate) fn references(&self) -> &HashSet<Url> {
+        &self.references
+    }
+
+    /// The base URI that applies within `schema` when it is nested in `base`.
+    pub(crate) fn scope(&self, base: &Url, schema: &'a J) -> Url {
+        let Some(id) = schema.as_object().and_then(|object| self.id_of(object)) else {
+            return base.clone();
+        };
+        match base.join(id) {
+            Ok(mut scope) => {
+                scope.set_fragment(None);
+                scope
+            }
+            Err(_) => base.clone(),
+        }
+    }
+
+    /// The `$id` of a schema if it changes the base URI.
+    fn id_of(&self, object: &'a J::Object) -> Option<&'a str> {
+        let id = match self.draft {
+            Draft::Draft04 => object.get("id"),
+            _ => object.get("$id"),
+        }?
+        .as_string()?
+        .as_ref();
+        match self.draft {
+            // Before 2019-09 `$ref` overrides all of its siblings, including `$id`
+            Draft::Draft04 | Draft::Draft06 | Draft::Draft07 if object.contains_key("$ref") => None,
+            Draft::Draft04 | Draft::Draft06 | Draft::Draft07 if id.starts_with('#') => None,
+            _ => Some(id),
+        }
+    }
+
+    fn add_anchor(&mut self, scope: &Url, name: &str, resource: Resource<'a, J>) {
+        if !name.is_empty() {
+            self.anchors
+                .insert((scope.clone(), name.to_owned()), resource);
+        }
+    }
+
+    fn walk(
+        &mut self,
+        base: &Url,
+        location: &mut JsonPointer,
+        schema: &'a J,
+    ) -> Result<(), JsonError> {
+        if let Some(object) = schema.as_object() {
+            let scope = self.scope(base, schema);
+            let resource = || Resource {
+                base: base.clone(),
+                location: location.clone(),
+                schema,
+            };
+            if scope != *base {
+                self.resources.insert(scope.clone(), resource());
+            }
+            if let Draft::Draft04 | Draft::Draft06 | Draft::Draft07 = self.draft {
+                // Plain name fragments were written as `$id` before `$anchor` existed
+                let id = match self.draft {
+                    Draft::Draft04 => object.get("id"),
+                    _ => object.get("$id"),
+                };
+                if let Some(id) = id.and_then(Json::as_string) {
+                    if let Some((_, name)) = id.as_ref().split_once('#') {
+                        if !name.starts_with('/') {
+                            self.add_anchor(&scope, &decode(name), resource());
+                        }
+                    }
+                }
+            } else {
+                for keyword in ["$anchor", "$dynamicAnchor"] {
+                    if let Some(name) = object.get(keyword).and_then(Json::as_string) {
+                        self.add_anchor(&scope, name.as_ref(), resource());
+                    }
+                }
+            }
+            if let Some(reference) = object.get("$ref").and_then(Json::as_string) {
+                if let Ok(mut uri) = scope.join(reference.as_ref()) {
+                    uri.set_fragment(None);
+                    self.references.insert(uri);
+                }
+            }
+            for entry in object_entries::<J>(object) {
+                let (key, value) = entry?;
+                if DATA_KEYWORDS.contains(&key) {
+                    continue;
+                }
+                location.push(key);
+                let result = self.walk(&scope, location, value);
+                location.pop();
+                result?;
+            }
+        } else if let Some(array) = schema.as_array() {
+            for (idx, item) in array.try_iter()?.enumerate() {
+                location.push(idx);
+                let result = self.walk(base, location, item?);
+                location.pop();
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the schema `reference` points to when it appears within `base`.
+    pub(crate) fn lookup(&self, base: &Url, reference: &str) -> Option<Resource<'a, J>> {
+        let mut uri = base.join(reference).ok()?;
+        let fragment = decode(uri.fragment().unwrap_or_default());
+        uri.set_fragment(None);
+        if fragment.is_empty() || fragment.starts_with('/') {
+            let mut resource = self.resources.get(&uri)?.clone();
+            for token in fragment.split('/').skip(1) {
+                let token = token.replace("~1", "/").replace("~0", "~");
+                resource.base = self.scope(&resource.base, resource.schema);
+                if let Some(object) = resource.schema.as_object() {
+                    resource.schema = object.get(&token)?;
+                    resource.location.push(token);
+                } else {
+                    let idx = token.parse::<usize>().ok()?;
+                    resource.schema = resource.schema.as_array()?.try_get(idx).ok()??;
+                    resource.location.push(idx);
+                }
+            }
+            Some(resource)
+        } else {
+            self.anchors.get(&(uri, fragment)).cloned()
+        }
+    }
+}
+
+fn decode(fragment: &str) -> String {
+    percent_decode_str(fragment)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{default_base, Resources};
+    use crate::drafts::Draft;
+
+    #[test_case(json!({"$defs": {"a": {"type": "string"}}}), "#/$defs/a", "/$defs/a"; "pointer")]
+    #[test_case(json!({"$defs": {"a b": {}}}), "#/$defs/a%20b", "/$defs/a b"; "percent-encoded pointer")]
+    #[test_case(json!({"$defs": {"a/b": {}}}), "#/$defs/a~1b", "/$defs/a~1b"; "escaped pointer")]
+    #[test_case(json!({"allOf": [{}, {}]}), "#/allOf/1", "/allOf/1"; "array index")]
+    #[test_case(json!({"$defs": {"a": {"$anchor": "x"}}}), "#x", "/$defs/a"; "anchor")]
+    #[test_case(json!({"$defs": {"a": {"$id": "a.json"}}}), "a.json", "/$defs/a"; "embedded resource")]
+    #[test_case(json!({"$defs": {"a": {"$id": "a.json", "$defs": {"b": {}}}}}), "a.json#/$defs/b", "/$defs/a/$defs/b"; "pointer within embedded resource")]
+    #[test_case(json!({"$defs": {"a": {"$id": "a.json", "$anchor": "x"}}}), "a.json#x", "/$defs/a"; "anchor within embedded resource")]
+    fn test_lookup(schema: Value, reference: &str, expected: &str) {
+        let mut resources = Resources::new(Draft::Draft202012);
+        resources.add(default_base(), &schema).expect("Valid JSON");
+        let resource = resources
+            .lookup(&default_base(), reference)
+            .expect("Resolvable reference");
+        assert_eq!(resource.location.to_string(), expected);
+    }
+
+    #[test_case(json!({"$ref": "#/$defs/missing"}), "#/$defs/missing"; "missing pointer")]
+    #[test_case(json!({}), "#missing"; "missing anchor")]
+    #[test_case(json!({}), "other.json"; "unknown document")]
+    fn test_lookup_missing(schema: Value, reference: &str) {
+        let mut resources = Resources::new(Draft::Draft202012);
+        resources.add(default_base(), &schema).expect("Valid JSON");
+        assert!(resources.lookup(&default_base(), reference).is_none());
+    }
+
+    #[test]
+    fn test_legacy_anchor() {
+        let schema = json!({"definitions": {"a": {"$id": "#x"}}});
+        let mut resources = Resources::new(Draft::Draft07);
+        resources.add(default_base(), &schema).expect("Valid JSON");
+        let resource = resources
+            .lookup(&default_base(), "#x")
+            .expect("Resolvable reference");
+        assert_eq!(resource.location.to_string(), "/definitions/a");
+    }
+
+    #[test]
+    fn test_references() {
+        let schema = json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "a": {"$ref": "a.json#/$defs/x"},
+                "b": {"$ref": "https://other.com/b.json"},
+                "c": {"$ref": "#/properties/a"},
+                "d": {"const": {"$ref": "ignored.json"}}
+            }
+        });
+        let mut resources = Resources::new(Draft::Draft202012);
+        resources.add(default_base(), &schema).expect("Valid JSON");
+        let mut references: Vec<_> = resources
+            .references()
+            .iter()
+            .map(|uri| uri.as_str())
+            .collect();
+        references.sort_unstable();
+        assert_eq!(
+            references,
+            [
+                "https://example.com/a.json",
+                "https://example.com/root.json",
+                "https://other.com/b.json"
+            ]
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Decoders for the `contentEncoding` and `contentMediaType` keywords.
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use jsonlike::Json;
+
+/// Decoder for a `contentEncoding` value, e.g. `base64`.
+pub trait ContentEncoding: Send + Sync + 'static {
+    /// Decode the given string, returning `None` if it is not valid in this encoding.
+    fn decode(&self, value: &str) -> Option<Vec<u8>>;
+}
+
+/// Parser for a `contentMediaType` value, e.g. `application/json`.
+pub trait ContentMediaType<J: Json>: Send + Sync + 'static {
+    /// Check whether the decoded content is a valid document of this media type.
+    fn is_valid(&self, content: &[u8]) -> bool;
+    /// Parse the decoded content into a value that `contentSchema` is applied to.
+    ///
+    /// Parsed content is valid without calling `is_valid`, which is only used when there is no
+    /// `contentSchema` or this method returns `None`. Media types that can not be represented as
+    /// JSON should keep the default implementation.
+    fn parse(&self, content: &[u8]) -> Option<J> {
+        let _ = content;
+        None
+    }
+}
+
+const LENIENT_PADDING: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+
+/// `base64` encoding as defined in RFC 4648, Section 4.
+pub(crate) struct Base64;
+
+impl ContentEncoding for Base64 {
+    fn decode(&self, value: &str) -> Option<Vec<u8>> {
+        GeneralPurpose::new(&alphabet::STANDARD, LENIENT_PADDING)
+            .decode(value)
+            .ok()
+    }
+}
+
+/// `base64url` encoding as defined in RFC 4648, Section 5.
+pub(crate) struct Base64Url;
+
+impl ContentEncoding for Base64Url {
+    fn decode(&self, value: &str) -> Option<Vec<u8>> {
+        GeneralPurpose::new(&alphabet::URL_SAFE, LENIENT_PADDING)
+            .decode(value)
+            .ok()
+    }
+}
+
+/// `quoted-printable` encoding as defined in RFC 2045, Section 6.7.
+pub(crate) struct QuotedPrintable;
+
+impl ContentEncoding for QuotedPrintable {
+    fn decode(&self, value: &str) -> Option<Vec<u8>> {
+        quoted_printable::decode(value, quoted_printable::ParseMode::Strict).ok()
+    }
+}
+
+/// `application/json` media type.
+pub(crate) struct ApplicationJson;
+
+impl<J: Json> ContentMediaType<J> for ApplicationJson {
+    fn is_valid(&self, content: &[u8]) -> bool {
+        <Self as ContentMediaType<J>>::parse(self, content).is_some()
+    }
+    fn parse(&self, content: &[u8]) -> Option<J> {
+        let content = std::str::from_utf8(content).ok()?;
+        J::from_str(content).ok()
+    }
+}
+
+/// Strip media type parameters, so `application/json; charset=utf-8` becomes `application/json`.
+pub(crate) fn media_type_essence(media_type: &str) -> String {
+    media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(&Base64, "eyJmb28iOiAiYmFyIn0=", Some(br#"{"foo": "bar"}"#.as_slice()))]
+    #[test_case(&Base64, "eyJmb28iOiAiYmFyIn0", Some(br#"{"foo": "bar"}"#.as_slice()))]
+    #[test_case(&Base64, "eyJmb28iOi%iYmFyIn0=", None)]
+    #[test_case(&Base64Url, "P_8-", Some(b"\x3f\xff\x3e".as_slice()))]
+    #[test_case(&Base64Url, "P/8+", None)]
+    #[test_case(&QuotedPrintable, "a=3Db", Some(b"a=b".as_slice()))]
+    #[test_case(&QuotedPrintable, "a=ZZb", None)]
+    fn test_decode(encoding: &dyn ContentEncoding, value: &str, expected: Option<&[u8]>) {
+        assert_eq!(encoding.decode(value).as_deref(), expected);
+    }
+
+    #[test_case("application/json", "application/json")]
+    #[test_case("Application/JSON; charset=utf-8", "application/json")]
+    fn test_media_type_essence(value: &str, expected: &str) {
+        assert_eq!(media_type_essence(value), expected);
+    }
+}
@@ -0,0 +1,403 @@
+//! Inlining of the schemas references point to.
+//!
+//! References are resolved by a `referencing::Resolver`, which tracks base URI changes from
+//! nested `$id`s and finds anchors according to the draft of each resource.
+use jpointer::JsonPointer;
+use jsonlike::prelude::*;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use referencing::{Resolver, Specification, SubResource};
+use url::Url;
+
+use crate::{
+    compiler::{default_base, DATA_KEYWORDS},
+    drafts::Draft,
+    retrieval,
+    value::{Array, Object, Value},
+    BuildError, BuildResult,
+};
+
+/// Characters that are not allowed in a URI fragment.
+const FRAGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'`');
+
+pub(crate) fn dereference<J: Json>(
+    schema: &J,
+    documents: &[(Url, J)],
+    draft: Draft,
+) -> BuildResult<J> {
+    let registry = retrieval::registry(schema, documents, draft)?;
+    let root = registry
+        .get(&default_base())
+        .expect("The schema is registered under the default base URI");
+    let resolver = registry.resolver(default_base()).in_subresource(&root);
+    let mut inliner = Inliner {
+        draft,
+        location: JsonPointer::new(),
+        targets: Vec::new(),
+        subresources: Vec::new(),
+    };
+    let value = inliner.schema(&resolver, &root, Kind::Root)?;
+    Ok(J::from_str(&value.to_string())?)
+}
+
+/// How an object relates to the schemas around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Root,
+    /// A subschema or a reference target.
+    Schema,
+    /// An object within a keyword that does not hold schemas.
+    Value,
+}
+
+struct Inliner {
+    draft: Draft,
+    /// Location within the output.
+    location: JsonPointer,
+    /// Schemas that are being inlined with their locations within the output, outermost first.
+    /// References to them are recursive.
+    targets: Vec<(*const Value, JsonPointer)>,
+    /// Subschemas of the schemas that are being inlined, innermost last.
+    subresources: Vec<Vec<*const Value>>,
+}
+
+impl Inliner {
+    fn id_keyword(&self) -> &'static str {
+        if let Draft::Draft04 = self.draft {
+            "id"
+        } else {
+            "$id"
+        }
+    }
+
+    /// Inline the references within `schema`, which `resolver` is already scoped to.
+    fn schema<'a>(
+        &mut self,
+        resolver: &Resolver<'a, Value>,
+        schema: &SubResource<'a, Value>,
+        kind: Kind,
+    ) -> BuildResult<Value> {
+        let Value::Object(object) = schema.contents() else {
+            return self.value(resolver, &*schema.specification, schema.contents());
+        };
+        if kind != Kind::Value {
+            let subresources = schema
+                .specification
+                .subresources_of(schema.contents())
+                .map(|(_, subresource)| subresource as *const Value)
+                .collect();
+            self.subresources.push(subresources);
+        }
+        self.targets
+            .push((schema.contents(), self.location.clone()));
+        let result = self.object(resolver, schema, object, kind);
+        self.targets.pop();
+        if kind != Kind::Value {
+            self.subresources.pop();
+        }
+        result
+    }
+
+    fn object<'a>(
+        &mut self,
+        resolver: &Resolver<'a, Value>,
+        schema: &SubResource<'a, Value>,
+        object: &'a Object,
+        kind: Kind,
+    ) -> BuildResult<Value> {
+        let specification = &*schema.specification;
+        if let Some(Value::String(reference)) = object.get("$ref") {
+            let siblings = object.len() > 1
+                && !matches!(self.draft, Draft::Draft04 | Draft::Draft06 | Draft::Draft07);
+            if !siblings {
+                // Before 2019-09 `$ref` overrides all of its siblings
+                return self.reference(resolver, specification, reference, self.location.clone());
+            }
+            let mut output = self.entries(resolver, specification, object, kind)?;
+            let mut all_of = match output.get("allOf") {
+                Some(Value::Array(all_of)) => all_of.clone(),
+                Some(_) => {
+                    return Err(BuildError::invalid_keyword(
+                        &self.location.join("allOf"),
+                        "Expected an array",
+                    ))
+                }
+                None => Array::default(),
+            };
+            let target = self.location.join("allOf").join(all_of.len());
+            all_of.push(self.reference(resolver, specification, reference, target)?);
+            output.insert("allOf".to_string(), Value::Array(all_of));
+            return Ok(Value::Object(output));
+        }
+        Ok(Value::Object(self.entries(
+            resolver,
+            specification,
+            object,
+            kind,
+        )?))
+    }
+
+    /// The schema `reference` points to, inlined at `location`.
+    fn reference<'a>(
+        &mut self,
+        resolver: &Resolver<'a, Value>,
+        specification: &dyn Specification<Value>,
+        reference: &str,
+        location: JsonPointer,
+    ) -> BuildResult<Value> {
+        let Ok(resolved) = resolver.lookup(reference) else {
+            return Err(BuildError::invalid_keyword(
+                &self.location.join("$ref"),
+                format!("Unresolvable reference '{reference}'"),
+            ));
+        };
+        let key: *const Value = resolved.contents;
+        if let Some((_, recursive)) = self.targets.iter().rfind(|(schema, _)| *schema == key) {
+            // Inlining a recursive reference never ends, so it points to the inlined target
+            let fragment = utf8_percent_encode(&recursive.to_string(), FRAGMENT).to_string();
+            let reference = Object::from(vec![(
+                "$ref".to_string(),
+                Value::String(format!("#{fragment}")),
+            )]);
+            return Ok(Value::Object(reference));
+        }
+        let target = SubResource::from_contents(resolved.contents, specification.box_clone());
+        let outer = core::mem::replace(&mut self.location, location);
+        let result = self.schema(&resolved.resolver, &target, Kind::Schema);
+        self.location = outer;
+        result
+    }
+
+    /// Entries of a schema except `$ref`, with all nested references inlined.
+    fn entries<'a>(
+        &mut self,
+        resolver: &Resolver<'a, Value>,
+        specification: &dyn Specification<Value>,
+        object: &'a Object,
+        kind: Kind,
+    ) -> BuildResult<Object> {
+        let mut output = Object::default();
+        for (key, value) in object.entries() {
+            if key == "$ref" {
+                continue;
+            }
+            // Inlined schemas are no longer separate resources, and references left in place
+            // are relative to the root
+            if kind == Kind::Schema && (key == self.id_keyword() || key == "$schema") {
+                continue;
+            }
+            let value = if DATA_KEYWORDS.contains(&key.as_str()) {
+                value.clone()
+            } else {
+                self.location.push(key.as_str());
+                let result = self.value(resolver, specification, value);
+                self.location.pop();
+                result?
+            };
+            output.insert(key.clone(), value);
+        }
+        Ok(output)
+    }
+
+    /// A keyword value that may contain schemas.
+    fn value<'a>(
+        &mut self,
+        resolver: &Resolver<'a, Value>,
+        specification: &dyn Specification<Value>,
+        value: &'a Value,
+    ) -> BuildResult<Value> {
+        match value {
+            Value::Object(_) => {
+                let schema = SubResource::from_contents(value, specification.box_clone());
+                let key: *const Value = value;
+                if self
+                    .subresources
+                    .last()
+                    .is_some_and(|subresources| subresources.contains(&key))
+                {
+                    // Subschemas may change the base URI
+                    let resolver = resolver.in_subresource(&schema);
+                    self.schema(&resolver, &schema, Kind::Schema)
+                } else {
+                    self.schema(resolver, &schema, Kind::Value)
+                }
+            }
+            Value::Array(array) => {
+                let mut output = Array::default();
+                for (idx, item) in array.items().iter().enumerate() {
+                    self.location.push(idx);
+                    let result = self.value(resolver, specification, item);
+                    self.location.pop();
+                    output.push(result?);
+                }
+                Ok(Value::Array(output))
+            }
+            _ => Ok(value.clone()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::{resolver::InMemoryResolver, Draft, ValidatorBuilder};
+
+    async fn dereference(schema: &Value, draft: Draft) -> Value {
+        let resolver = InMemoryResolver::new()
+            .with(
+                "https://example.com/a.json",
+                json!({"$defs": {"positive": {"minimum": 0}}, "$ref": "#/$defs/positive"}),
+            )
+            .with(
+                "https://example.com/tree.json",
+                json!({
+                    "type": "object",
+                    "properties": {"children": {"items": {"$ref": "#"}}},
+                }),
+            );
+        ValidatorBuilder::default()
+            .draft(draft)
+            .resolver(resolver)
+            .dereference(schema)
+            .await
+            .expect("Resolvable references")
+    }
+
+    #[test_case(
+        json!({"$ref": "#/$defs/a", "$defs": {"a": {"type": "string"}}}),
+        json!({"$defs": {"a": {"type": "string"}}, "allOf": [{"type": "string"}]});
+        "sibling definitions"
+    )]
+    #[test_case(
+        json!({"properties": {"a": {"$ref": "#x"}}, "$defs": {"x": {"$anchor": "x", "type": "null"}}}),
+        json!({"properties": {"a": {"$anchor": "x", "type": "null"}}, "$defs": {"x": {"$anchor": "x", "type": "null"}}});
+        "anchor"
+    )]
+    #[test_case(
+        json!({"$id": "https://example.com/root.json", "items": {"$ref": "a.json"}}),
+        json!({"$id": "https://example.com/root.json", "items": {"$defs": {"positive": {"minimum": 0}}, "allOf": [{"minimum": 0}]}});
+        "external"
+    )]
+    #[test_case(
+        json!({"$defs": {"b": {"$id": "https://example.com/b/", "items": {"$ref": "../a.json"}}}, "$ref": "#/$defs/b"}),
+        json!({"$defs": {"b": {"items": {"$defs": {"positive": {"minimum": 0}}, "allOf": [{"minimum": 0}]}}}, "allOf": [{"items": {"$defs": {"positive": {"minimum": 0}}, "allOf": [{"minimum": 0}]}}]});
+        "nested base URI"
+    )]
+    #[test_case(
+        json!({"$defs": {"b": {"$id": "https://example.com/b/", "$defs": {"x": {"$anchor": "x", "type": "null"}}, "items": {"$ref": "#x"}}}, "$ref": "#/$defs/b"}),
+        json!({"$defs": {"b": {"$defs": {"x": {"$anchor": "x", "type": "null"}}, "items": {"$anchor": "x", "type": "null"}}}, "allOf": [{"$defs": {"x": {"$anchor": "x", "type": "null"}}, "items": {"$anchor": "x", "type": "null"}}]});
+        "anchor in nested resource"
+    )]
+    #[test_case(
+        json!({"properties": {"$id": {"type": "string"}}}),
+        json!({"properties": {"$id": {"type": "string"}}});
+        "property named like a keyword"
+    )]
+    #[test_case(
+        json!({"properties": {"node": {"$ref": "https://example.com/tree.json"}}}),
+        json!({"properties": {"node": {
+            "type": "object",
+            "properties": {"children": {"items": {"$ref": "#/properties/node"}}},
+        }}});
+        "recursive"
+    )]
+    #[test_case(
+        json!({"$defs": {"a": {"items": {"$ref": "#/$defs/a"}}}, "properties": {"a b": {"$ref": "#/$defs/a"}}}),
+        json!({"$defs": {"a": {"items": {"$ref": "#/$defs/a"}}}, "properties": {"a b": {"items": {"$ref": "#/properties/a%20b"}}}});
+        "recursive within the root"
+    )]
+    #[test_case(
+        json!({"items": {"$ref": "#"}}),
+        json!({"items": {"$ref": "#"}});
+        "recursive root"
+    )]
+    #[test_case(
+        json!({"const": {"$ref": "missing.json"}, "enum": [{"$ref": "#"}]}),
+        json!({"const": {"$ref": "missing.json"}, "enum": [{"$ref": "#"}]});
+        "data keywords"
+    )]
+    #[tokio::test]
+    async fn test_dereference(schema: Value, expected: Value) {
+        assert_eq!(dereference(&schema, Draft::Draft202012).await, expected);
+    }
+
+    #[test_case(Draft::Draft04, "id")]
+    #[test_case(Draft::Draft07, "$id")]
+    #[tokio::test]
+    async fn test_legacy_siblings(draft: Draft, id: &str) {
+        let schema = json!({
+            "definitions": {
+                "a": {id: "https://example.com/b/", "type": "string"},
+                "b": {"$ref": "#/definitions/a", id: "https://example.com/ignored/"},
+            },
+            "properties": {"x": {"$ref": "#/definitions/b", "minLength": 1}},
+        });
+        assert_eq!(
+            dereference(&schema, draft).await,
+            json!({
+                "definitions": {
+                    "a": {"type": "string"},
+                    "b": {"type": "string"},
+                },
+                "properties": {"x": {"type": "string"}},
+            })
+        );
+    }
+
+    #[test_case(Draft::Draft04, "id")]
+    #[test_case(Draft::Draft06, "$id")]
+    #[test_case(Draft::Draft07, "$id")]
+    #[tokio::test]
+    async fn test_legacy_anchor(draft: Draft, id: &str) {
+        let schema = json!({
+            "definitions": {"a": {id: "#foo", "type": "null"}},
+            "properties": {"x": {"$ref": "#foo"}},
+        });
+        assert_eq!(
+            dereference(&schema, draft).await,
+            json!({
+                "definitions": {"a": {"type": "null"}},
+                "properties": {"x": {"type": "null"}},
+            })
+        );
+    }
+
+    #[test_case(json!({"a": {"b": {"a": {}}}}), true)]
+    #[test_case(json!({"a": {"b": {"a": 1}}}), false)]
+    #[tokio::test]
+    async fn test_recursive_output_is_valid(instance: Value, expected: bool) {
+        let schema = json!({
+            "$defs": {
+                "a": {"type": "object", "properties": {"b": {"$ref": "#/$defs/b"}}},
+                "b": {"properties": {"a": {"$ref": "#/$defs/a"}}},
+            },
+            "properties": {"a": {"$ref": "#/$defs/a"}},
+        });
+        let dereferenced = dereference(&schema, Draft::Draft202012).await;
+        let validator = ValidatorBuilder::default()
+            .build(&dereferenced)
+            .await
+            .expect("Valid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[test]
+    fn test_unresolvable() {
+        let schema = json!({"properties": {"a": {"$ref": "#/$defs/missing"}}});
+        let error = crate::blocking::ValidatorBuilder::default()
+            .dereference(&schema)
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Invalid schema at '/properties/a/$ref': Unresolvable reference '#/$defs/missing'"
+        );
+    }
+}
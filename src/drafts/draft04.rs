@@ -1,6 +1,42 @@
-use crate::vocabulary::KeywordValue;
+use crate::{
+    compiler::{CompileResult, Context},
+    vocabulary::{applicator, core, format, validation},
+};
 use jsonlike::Json;
 
-pub(crate) fn get_keyword<J: Json>(key: &str, value: &J) -> Option<KeywordValue<J>> {
-    None
+pub(crate) fn get_keyword<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    key: &str,
+    parent: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    match key {
+        "$ref" => core::compile_ref(ctx, parent, value),
+        "type" => validation::compile_type(ctx, parent, value),
+        "enum" => validation::compile_enum(ctx, parent, value),
+        "multipleOf" => validation::compile_multiple_of(ctx, parent, value),
+        "maxLength" => validation::compile_max_length(ctx, parent, value),
+        "minLength" => validation::compile_min_length(ctx, parent, value),
+        "pattern" => validation::compile_pattern(ctx, parent, value),
+        "maxItems" => validation::compile_max_items(ctx, parent, value),
+        "minItems" => validation::compile_min_items(ctx, parent, value),
+        "uniqueItems" => validation::compile_unique_items(ctx, parent, value),
+        "maxProperties" => validation::compile_max_properties(ctx, parent, value),
+        "minProperties" => validation::compile_min_properties(ctx, parent, value),
+        "required" => validation::compile_required(ctx, parent, value),
+        "allOf" => applicator::compile_all_of(ctx, parent, value),
+        "anyOf" => applicator::compile_any_of(ctx, parent, value),
+        "oneOf" => applicator::compile_one_of(ctx, parent, value),
+        "not" => applicator::compile_not(ctx, parent, value),
+        "properties" => applicator::compile_properties(ctx, parent, value),
+        "patternProperties" => applicator::compile_pattern_properties(ctx, parent, value),
+        "additionalProperties" => applicator::compile_additional_properties(ctx, parent, value),
+        "format" => format::compile_format(ctx, parent, value),
+        "maximum" => validation::compile_maximum_draft4(ctx, parent, value),
+        "minimum" => validation::compile_minimum_draft4(ctx, parent, value),
+        "items" => applicator::compile_items_legacy(ctx, parent, value),
+        "additionalItems" => applicator::compile_additional_items(ctx, parent, value),
+        "dependencies" => applicator::compile_dependencies(ctx, parent, value),
+        _ => None,
+    }
 }
@@ -4,8 +4,9 @@ mod draft07;
 mod draft201909;
 mod draft202012;
 
-use crate::vocabulary::KeywordValue;
+use crate::compiler::{CompileResult, Context};
 use jsonlike::prelude::*;
+use referencing::{jsonschema, Specification};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Draft {
@@ -20,13 +21,44 @@ impl Draft {
     pub fn latest() -> Self {
         Self::Draft202012
     }
-    pub(crate) fn get_keyword<J: Json>(&self, key: &str, value: &J) -> Option<KeywordValue<J>> {
+    /// The draft `schema` declares via `$schema`, the latest one if it declares none or an
+    /// unknown one.
+    pub fn detect(schema: &impl Json) -> Self {
+        draft_from_schema(schema)
+    }
+    /// The meta-schema URI used as `$schema` for this draft.
+    pub(crate) fn uri(&self) -> &'static str {
+        match self {
+            Draft::Draft04 => "http://json-schema.org/draft-04/schema#",
+            Draft::Draft06 => "http://json-schema.org/draft-06/schema#",
+            Draft::Draft07 => "http://json-schema.org/draft-07/schema#",
+            Draft::Draft201909 => "https://json-schema.org/draft/2019-09/schema",
+            Draft::Draft202012 => "https://json-schema.org/draft/2020-12/schema",
+        }
+    }
+    /// How this draft identifies resources and anchors, and where it embeds subschemas.
+    pub(crate) fn specification<J: Json>(&self) -> Box<dyn Specification<J>> {
+        match self {
+            Draft::Draft04 => jsonschema::Draft4.boxed(),
+            Draft::Draft06 => jsonschema::Draft6.boxed(),
+            Draft::Draft07 => jsonschema::Draft7.boxed(),
+            Draft::Draft201909 => jsonschema::Draft201909.boxed(),
+            Draft::Draft202012 => jsonschema::Draft202012.boxed(),
+        }
+    }
+    pub(crate) fn get_keyword<'a, J: Json>(
+        &self,
+        ctx: &mut Context<'a, '_, J>,
+        key: &str,
+        parent: &'a J::Object,
+        value: &'a J,
+    ) -> CompileResult {
         match self {
-            Draft::Draft04 => draft04::get_keyword(key, value),
-            Draft::Draft06 => draft06::get_keyword(key, value),
-            Draft::Draft07 => draft07::get_keyword(key, value),
-            Draft::Draft201909 => draft201909::get_keyword(key, value),
-            Draft::Draft202012 => draft202012::get_keyword(key, value),
+            Draft::Draft04 => draft04::get_keyword(ctx, key, parent, value),
+            Draft::Draft06 => draft06::get_keyword(ctx, key, parent, value),
+            Draft::Draft07 => draft07::get_keyword(ctx, key, parent, value),
+            Draft::Draft201909 => draft201909::get_keyword(ctx, key, parent, value),
+            Draft::Draft202012 => draft202012::get_keyword(ctx, key, parent, value),
         }
     }
 }
@@ -54,8 +86,9 @@ pub(crate) fn from_url(mut url: &str) -> Option<Draft> {
     }
 }
 pub(crate) fn draft_from_schema(schema: &impl Json) -> Draft {
-    if let Some(object) = schema.as_object() {
-        if let Some(url) = object.get("$schema").and_then(Json::as_string) {
+    // Unreadable values are reported once the schema is indexed
+    if let Ok(Some(object)) = schema.try_as_object() {
+        if let Some(Ok(Some(url))) = object.get("$schema").map(Json::try_as_string) {
             from_url(url.as_ref()).unwrap_or_else(Draft::latest)
         } else {
             Draft::latest()
@@ -1,3 +1,7 @@
+use core::{fmt, time::Duration};
+
+use jpointer::{JsonPointer, JsonPointerNode};
+
 /// An error that occured during the building of a validator.
 #[derive(Debug)]
 pub struct BuildError {
@@ -7,12 +11,98 @@ pub struct BuildError {
 #[derive(Debug)]
 enum BuildErrorKind {
     Json(jsonlike::JsonError),
+    InvalidKeyword {
+        location: JsonPointer,
+        reason: String,
+    },
+    Retrieval {
+        url: String,
+        reason: String,
+    },
+    Timeout {
+        duration: Duration,
+    },
+    #[cfg(feature = "http")]
+    Http {
+        url: String,
+        error: crate::resolver::HttpError,
+    },
+    #[cfg(feature = "snapshot")]
+    Snapshot {
+        reason: String,
+    },
+}
+
+impl BuildError {
+    pub(crate) fn invalid_keyword(location: &JsonPointer, reason: impl Into<String>) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::InvalidKeyword {
+                location: location.clone(),
+                reason: reason.into(),
+            },
+        }
+    }
+    /// The document at `url` could not be retrieved, e.g. by a `ReferenceResolver`.
+    pub fn retrieval(url: impl Into<String>, reason: impl Into<String>) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::Retrieval {
+                url: url.into(),
+                reason: reason.into(),
+            },
+        }
+    }
+    pub(crate) fn timeout(duration: Duration) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::Timeout { duration },
+        }
+    }
+    #[cfg(feature = "http")]
+    pub(crate) fn http(url: impl Into<String>, error: crate::resolver::HttpError) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::Http {
+                url: url.into(),
+                error,
+            },
+        }
+    }
+    /// The reason why an `HttpResolver` failed to retrieve a document.
+    #[cfg(feature = "http")]
+    pub fn http_error(&self) -> Option<&crate::resolver::HttpError> {
+        match &self.kind {
+            BuildErrorKind::Http { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn snapshot(reason: impl Into<String>) -> BuildError {
+        BuildError {
+            kind: BuildErrorKind::Snapshot {
+                reason: reason.into(),
+            },
+        }
+    }
 }
 
-impl core::fmt::Display for BuildError {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.kind {
             BuildErrorKind::Json(error) => error.fmt(f),
+            BuildErrorKind::InvalidKeyword { location, reason } => {
+                write!(f, "Invalid schema at '{location}': {reason}")
+            }
+            BuildErrorKind::Retrieval { url, reason } => {
+                write!(f, "Failed to retrieve '{url}': {reason}")
+            }
+            BuildErrorKind::Timeout { duration } => {
+                write!(
+                    f,
+                    "Retrieving referenced documents timed out after {duration:?}"
+                )
+            }
+            #[cfg(feature = "http")]
+            BuildErrorKind::Http { url, error } => write!(f, "Failed to retrieve '{url}': {error}"),
+            #[cfg(feature = "snapshot")]
+            BuildErrorKind::Snapshot { reason } => write!(f, "Invalid snapshot: {reason}"),
         }
     }
 }
@@ -21,6 +111,13 @@ impl std::error::Error for BuildError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match &self.kind {
             BuildErrorKind::Json(error) => Some(error),
+            BuildErrorKind::InvalidKeyword { .. }
+            | BuildErrorKind::Retrieval { .. }
+            | BuildErrorKind::Timeout { .. } => None,
+            #[cfg(feature = "http")]
+            BuildErrorKind::Http { error, .. } => Some(error),
+            #[cfg(feature = "snapshot")]
+            BuildErrorKind::Snapshot { .. } => None,
         }
     }
 }
@@ -35,14 +132,311 @@ impl From<jsonlike::JsonError> for BuildError {
 
 /// An error that occured during JSON Schema validation.
 #[derive(Clone, Debug)]
-pub struct ValidationError(Box<ValidationErrorKind>);
+pub struct ValidationError(Box<ValidationErrorInner>);
 
 #[derive(Clone, Debug)]
-pub enum ValidationErrorKind {}
+struct ValidationErrorInner {
+    kind: ValidationErrorKind,
+    instance_path: JsonPointer,
+    schema_path: JsonPointer,
+}
+
+impl ValidationError {
+    pub(crate) fn new(
+        kind: ValidationErrorKind,
+        instance_path: &JsonPointerNode<'_>,
+        schema_path: &JsonPointer,
+    ) -> ValidationError {
+        ValidationError(Box::new(ValidationErrorInner {
+            kind,
+            instance_path: instance_path.into(),
+            schema_path: schema_path.clone(),
+        }))
+    }
+    /// Nest the instance path under `prefix`, e.g. the location of a value within a stream.
+    pub(crate) fn prefixed(mut self, prefix: &JsonPointer) -> ValidationError {
+        if !prefix.is_empty() {
+            let mut path = prefix.clone();
+            path.extend(&self.0.instance_path);
+            self.0.instance_path = path;
+        }
+        self
+    }
+    /// The reason of this error.
+    pub fn kind(&self) -> &ValidationErrorKind {
+        &self.0.kind
+    }
+    /// Location of the invalid value within the instance.
+    pub fn instance_path(&self) -> &JsonPointer {
+        &self.0.instance_path
+    }
+    /// Location of the failed keyword within the schema.
+    pub fn schema_path(&self) -> &JsonPointer {
+        &self.0.schema_path
+    }
+}
+
+/// A primitive JSON type as used by the `type` keyword.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub enum PrimitiveType {
+    Array,
+    Boolean,
+    Integer,
+    Null,
+    Number,
+    Object,
+    String,
+}
+
+impl PrimitiveType {
+    pub(crate) fn from_str(name: &str) -> Option<PrimitiveType> {
+        match name {
+            "array" => Some(PrimitiveType::Array),
+            "boolean" => Some(PrimitiveType::Boolean),
+            "integer" => Some(PrimitiveType::Integer),
+            "null" => Some(PrimitiveType::Null),
+            "number" => Some(PrimitiveType::Number),
+            "object" => Some(PrimitiveType::Object),
+            "string" => Some(PrimitiveType::String),
+            _ => None,
+        }
+    }
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrimitiveType::Array => "array",
+            PrimitiveType::Boolean => "boolean",
+            PrimitiveType::Integer => "integer",
+            PrimitiveType::Null => "null",
+            PrimitiveType::Number => "number",
+            PrimitiveType::Object => "object",
+            PrimitiveType::String => "string",
+        }
+    }
+}
+
+impl fmt::Display for PrimitiveType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
-impl core::fmt::Display for ValidationError {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        todo!()
+/// The reason why an instance failed validation.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ValidationErrorKind {
+    /// The schema is `false`, so every instance is invalid.
+    FalseSchema,
+    /// The instance is not of any of the expected types.
+    Type {
+        expected: Vec<PrimitiveType>,
+    },
+    /// The instance is not equal to the `const` value.
+    Const {
+        expected: String,
+    },
+    /// The instance is not equal to any of the `enum` values.
+    Enum {
+        options: String,
+    },
+    MultipleOf {
+        multiple_of: f64,
+    },
+    Maximum {
+        limit: f64,
+    },
+    ExclusiveMaximum {
+        limit: f64,
+    },
+    Minimum {
+        limit: f64,
+    },
+    ExclusiveMinimum {
+        limit: f64,
+    },
+    MaxLength {
+        limit: u64,
+    },
+    MinLength {
+        limit: u64,
+    },
+    Pattern {
+        pattern: String,
+    },
+    MaxItems {
+        limit: u64,
+    },
+    MinItems {
+        limit: u64,
+    },
+    UniqueItems,
+    /// No item matches the `contains` subschema.
+    Contains,
+    MinContains {
+        limit: u64,
+    },
+    MaxContains {
+        limit: u64,
+    },
+    MaxProperties {
+        limit: u64,
+    },
+    MinProperties {
+        limit: u64,
+    },
+    Required {
+        property: String,
+    },
+    AdditionalProperties {
+        unexpected: Vec<String>,
+    },
+    AdditionalItems {
+        limit: usize,
+    },
+    AnyOf,
+    OneOfNotValid,
+    OneOfMultipleValid,
+    Not,
+    Format {
+        format: String,
+    },
+    /// The string could not be decoded with the `contentEncoding` value.
+    ContentEncoding {
+        encoding: String,
+    },
+    /// The decoded string is not a valid document of the `contentMediaType` value.
+    ContentMediaType {
+        media_type: String,
+    },
+    /// A custom keyword reported the instance as invalid.
+    Custom {
+        keyword: String,
+    },
+    /// The instance could not be read by its JSON backend.
+    InvalidInstance {
+        reason: String,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind() {
+            ValidationErrorKind::FalseSchema => {
+                f.write_str("False schema does not allow any value")
+            }
+            ValidationErrorKind::Type { expected } => {
+                f.write_str("Value is not of type ")?;
+                for (idx, ty) in expected.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "\"{ty}\"")?;
+                }
+                Ok(())
+            }
+            ValidationErrorKind::Const { expected } => write!(f, "{expected} was expected"),
+            ValidationErrorKind::Enum { options } => write!(f, "Value is not one of {options}"),
+            ValidationErrorKind::MultipleOf { multiple_of } => {
+                write!(f, "Value is not a multiple of {multiple_of}")
+            }
+            ValidationErrorKind::Maximum { limit } => {
+                write!(f, "Value is greater than the maximum of {limit}")
+            }
+            ValidationErrorKind::ExclusiveMaximum { limit } => write!(
+                f,
+                "Value is greater than or equal to the maximum of {limit}"
+            ),
+            ValidationErrorKind::Minimum { limit } => {
+                write!(f, "Value is less than the minimum of {limit}")
+            }
+            ValidationErrorKind::ExclusiveMinimum { limit } => {
+                write!(f, "Value is less than or equal to the minimum of {limit}")
+            }
+            ValidationErrorKind::MaxLength { limit } => {
+                write!(f, "Value is longer than {limit} characters")
+            }
+            ValidationErrorKind::MinLength { limit } => {
+                write!(f, "Value is shorter than {limit} characters")
+            }
+            ValidationErrorKind::Pattern { pattern } => {
+                write!(f, "Value does not match \"{pattern}\"")
+            }
+            ValidationErrorKind::MaxItems { limit } => {
+                write!(f, "Value has more than {limit} items")
+            }
+            ValidationErrorKind::MinItems { limit } => {
+                write!(f, "Value has less than {limit} items")
+            }
+            ValidationErrorKind::UniqueItems => f.write_str("Value has non-unique elements"),
+            ValidationErrorKind::Contains => {
+                f.write_str("None of the items are valid under the given schema")
+            }
+            ValidationErrorKind::MinContains { limit } => write!(
+                f,
+                "Less than {limit} items are valid under the given schema"
+            ),
+            ValidationErrorKind::MaxContains { limit } => write!(
+                f,
+                "More than {limit} items are valid under the given schema"
+            ),
+            ValidationErrorKind::MaxProperties { limit } => {
+                write!(f, "Value has more than {limit} properties")
+            }
+            ValidationErrorKind::MinProperties { limit } => {
+                write!(f, "Value has less than {limit} properties")
+            }
+            ValidationErrorKind::Required { property } => {
+                write!(f, "\"{property}\" is a required property")
+            }
+            ValidationErrorKind::AdditionalProperties { unexpected } => {
+                f.write_str("Additional properties are not allowed (")?;
+                for (idx, property) in unexpected.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "\"{property}\"")?;
+                }
+                f.write_str(if unexpected.len() == 1 {
+                    " was unexpected)"
+                } else {
+                    " were unexpected)"
+                })
+            }
+            ValidationErrorKind::AdditionalItems { limit } => write!(
+                f,
+                "Additional items are not allowed (more than {limit} items)"
+            ),
+            ValidationErrorKind::AnyOf => {
+                f.write_str("Value is not valid under any of the given schemas")
+            }
+            ValidationErrorKind::OneOfNotValid => {
+                f.write_str("Value is not valid under any of the given schemas")
+            }
+            ValidationErrorKind::OneOfMultipleValid => {
+                f.write_str("Value is valid under more than one of the given schemas")
+            }
+            ValidationErrorKind::Not => {
+                f.write_str("Value should not be valid under the given schema")
+            }
+            ValidationErrorKind::Format { format } => {
+                write!(f, "Value is not a \"{format}\"")
+            }
+            ValidationErrorKind::ContentEncoding { encoding } => {
+                write!(
+                    f,
+                    "Value is not compliant with \"{encoding}\" content encoding"
+                )
+            }
+            ValidationErrorKind::ContentMediaType { media_type } => {
+                write!(f, "Value is not compliant with \"{media_type}\" media type")
+            }
+            ValidationErrorKind::Custom { keyword } => {
+                write!(f, "Value is not valid under the \"{keyword}\" keyword")
+            }
+            ValidationErrorKind::InvalidInstance { reason } => {
+                write!(f, "Value can not be read: {reason}")
+            }
+        }
     }
 }
 
@@ -0,0 +1,106 @@
+//! User-provided keywords, formats and content handlers a compiled validator refers to.
+//!
+//! Compiled keywords store an index into these tables instead of the extension itself, so the
+//! graph of compiled nodes is plain data that does not depend on the schema backend.
+use core::fmt;
+use std::sync::Arc;
+
+use jsonlike::Json;
+
+use crate::{
+    content::{ContentEncoding, ContentMediaType},
+    value::Value,
+    Format, Keyword,
+};
+
+/// An initialized extension together with the data needed to initialize it again.
+pub(crate) struct Extension<T: ?Sized> {
+    /// Name the extension is registered under in `ValidatorBuilder`.
+    pub(crate) name: String,
+    /// The schema passed to the extension factory, if any.
+    pub(crate) schema: Option<Value>,
+    pub(crate) value: Arc<T>,
+}
+
+impl<T: ?Sized> Clone for Extension<T> {
+    fn clone(&self) -> Self {
+        Extension {
+            name: self.name.clone(),
+            schema: self.schema.clone(),
+            value: Arc::clone(&self.value),
+        }
+    }
+}
+
+pub(crate) struct Extensions<J: Json> {
+    pub(crate) keywords: Vec<Extension<dyn Keyword<J>>>,
+    pub(crate) formats: Vec<Extension<dyn Format>>,
+    pub(crate) content_encodings: Vec<Extension<dyn ContentEncoding>>,
+    pub(crate) content_media_types: Vec<Extension<dyn ContentMediaType<J>>>,
+    /// Whether content keywords were compiled as assertions.
+    pub(crate) validate_content: bool,
+}
+
+impl<J: Json> Extensions<J> {
+    pub(crate) fn new(validate_content: bool) -> Extensions<J> {
+        Extensions {
+            keywords: Vec::new(),
+            formats: Vec::new(),
+            content_encodings: Vec::new(),
+            content_media_types: Vec::new(),
+            validate_content,
+        }
+    }
+}
+
+/// Add an extension that has no per-schema state, reusing an existing entry with the same name.
+pub(crate) fn intern<T: ?Sized>(
+    extensions: &mut Vec<Extension<T>>,
+    name: &str,
+    value: Arc<T>,
+) -> usize {
+    match extensions
+        .iter()
+        .position(|extension| extension.name == name)
+    {
+        Some(idx) => idx,
+        None => {
+            extensions.push(Extension {
+                name: name.to_owned(),
+                schema: None,
+                value,
+            });
+            extensions.len() - 1
+        }
+    }
+}
+
+impl<J: Json> Clone for Extensions<J> {
+    fn clone(&self) -> Self {
+        Extensions {
+            keywords: self.keywords.clone(),
+            formats: self.formats.clone(),
+            content_encodings: self.content_encodings.clone(),
+            content_media_types: self.content_media_types.clone(),
+            validate_content: self.validate_content,
+        }
+    }
+}
+
+impl<J: Json> fmt::Debug for Extensions<J> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn names<T: ?Sized>(extensions: &[Extension<T>]) -> Vec<&str> {
+            extensions
+                .iter()
+                .map(|extension| extension.name.as_str())
+                .collect()
+        }
+        f.debug_struct("Extensions")
+            .field("keywords", &names(&self.keywords))
+            .field("formats", &names(&self.formats))
+            .field("content_encodings", &names(&self.content_encodings))
+            .field("content_media_types", &names(&self.content_media_types))
+            .field("validate_content", &self.validate_content)
+            .finish()
+    }
+}
@@ -0,0 +1,56 @@
+//! Reading documents from files, shared by the modules for other formats.
+use core::fmt::Display;
+use std::{fs, path::Path};
+
+use jsonlike::JsonError;
+
+/// Read the text at `path` and parse it. Errors of both steps are prefixed with the path.
+#[cfg(any(feature = "json5", feature = "serde_yaml", feature = "toml"))]
+pub(crate) fn load<T, E: Display>(
+    path: &Path,
+    parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<T, JsonError> {
+    let text = fs::read_to_string(path).map_err(|error| with_path(path, error))?;
+    parse(&text).map_err(|error| with_path(path, error))
+}
+
+/// Read the bytes at `path` and decode them. Errors of both steps are prefixed with the path.
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+pub(crate) fn load_bytes<T, E: Display>(
+    path: &Path,
+    decode: impl FnOnce(&[u8]) -> Result<T, E>,
+) -> Result<T, JsonError> {
+    let bytes = fs::read(path).map_err(|error| with_path(path, error))?;
+    decode(&bytes).map_err(|error| with_path(path, error))
+}
+
+fn with_path(path: &Path, error: impl Display) -> JsonError {
+    JsonError::new(format!("{}: {error}", path.display()).into())
+}
+
+#[cfg(all(test, any(feature = "json5", feature = "serde_yaml", feature = "toml")))]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::load;
+
+    #[test]
+    fn test_missing_file() {
+        let error = load(Path::new("missing.txt"), |text| Ok::<_, String>(text.len()))
+            .expect_err("Missing file");
+        assert!(error.to_string().starts_with("missing.txt: "));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let path = std::env::temp_dir().join(format!("jsonschema-files-{}", std::process::id()));
+        fs::write(&path, "text").expect("Can write files");
+        let error = load(&path, |text| Err::<(), _>(format!("Invalid '{text}'")))
+            .expect_err("Invalid file");
+        assert_eq!(
+            error.to_string(),
+            format!("{}: Invalid 'text'", path.display())
+        );
+        fs::remove_file(&path).expect("Can remove files");
+    }
+}
@@ -10,20 +10,17 @@ mod sealed {
     pub trait Sealed<J> {}
 }
 
-pub trait FormatFactory<'a, J: Json>: Send + Sync + sealed::Sealed<J> + 'a {
-    fn init(&self, schema: &'a J) -> BuildResult<BoxedFormat>;
+pub trait FormatFactory<J: Json>: Send + Sync + sealed::Sealed<J> {
+    fn init(&self, schema: &J) -> BuildResult<BoxedFormat>;
 }
 
-impl<'a, F, J: Json + 'a> sealed::Sealed<J> for F where
-    F: Fn(&'a J) -> BuildResult<BoxedFormat> + Send + Sync + 'a
-{
-}
+impl<F, J: Json> sealed::Sealed<J> for F where F: Fn(&J) -> BuildResult<BoxedFormat> + Send + Sync {}
 
-impl<'a, F, J: Json + 'a> FormatFactory<'a, J> for F
+impl<F, J: Json> FormatFactory<J> for F
 where
-    F: Fn(&'a J) -> BuildResult<BoxedFormat> + Send + Sync + 'a,
+    F: Fn(&J) -> BuildResult<BoxedFormat> + Send + Sync,
 {
-    fn init(&self, schema: &'a J) -> BuildResult<BoxedFormat> {
+    fn init(&self, schema: &J) -> BuildResult<BoxedFormat> {
         self(schema)
     }
 }
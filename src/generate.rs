@@ -0,0 +1,1201 @@
+//! Generation of instances that are valid against a schema.
+//!
+//! ```rust
+//! # #[cfg(feature = "serde_json")]
+//! # fn main() -> Result<(), jsonschema::BuildError> {
+//! use serde_json::json;
+//!
+//! let schema = json!({
+//!     "type": "object",
+//!     "properties": {
+//!         "id": {"type": "integer", "minimum": 1},
+//!         "sku": {"type": "string", "pattern": "^[A-Z]{3}-[0-9]{4}$"},
+//!     },
+//!     "required": ["id", "sku"],
+//! });
+//! let mut generator = jsonschema::blocking::ValidatorBuilder::default()
+//!     .generator(&schema)?
+//!     .seed(42);
+//! let instance = generator.generate().expect("The schema is satisfiable");
+//! assert!(jsonschema::blocking::is_valid(&schema, &instance));
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "serde_json"))]
+//! # fn main() {}
+//! ```
+//!
+//! Candidates are sampled from the schema with its references inlined and then checked by the
+//! compiled validator, so every generated instance is valid, including against custom keywords
+//! and formats. Keywords that are not sampled from, such as `not` or `uniqueItems` on nested
+//! values, only cause candidates to be rejected. Patterns are sampled on a best effort basis:
+//! lookarounds and backreferences are not supported.
+use jsonlike::prelude::*;
+use percent_encoding::percent_decode_str;
+
+use crate::{
+    value::{Array, Number, Object, Value},
+    BuildResult, Validator,
+};
+
+/// Produces valid instances of a schema from a seedable pseudo-random sequence.
+///
+/// Create one via `ValidatorBuilder::generator`. The same seed produces the same instances.
+pub struct Generator<J: Json> {
+    schema: Value,
+    validator: Validator<J>,
+    rng: Rng,
+    attempts: usize,
+    max_depth: usize,
+}
+
+impl<J: Json + 'static> Generator<J> {
+    pub(crate) fn new(dereferenced: &J, validator: Validator<J>) -> BuildResult<Generator<J>> {
+        Ok(Generator {
+            schema: Value::from_json(dereferenced)?,
+            validator,
+            rng: Rng(0),
+            attempts: 100,
+            max_depth: 5,
+        })
+    }
+    /// Restart the pseudo-random sequence from `seed`. The default seed is `0`.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Generator<J> {
+        self.rng = Rng(seed);
+        self
+    }
+    /// Give up after `attempts` invalid candidates in a row. Defaults to `100`.
+    #[must_use]
+    pub fn attempts(mut self, attempts: usize) -> Generator<J> {
+        self.attempts = attempts;
+        self
+    }
+    /// Only add optional properties and items up to `depth` levels deep. Defaults to `5`.
+    ///
+    /// Deeper values are as small as the schema allows, which ends recursive schemas.
+    #[must_use]
+    pub fn max_depth(mut self, depth: usize) -> Generator<J> {
+        self.max_depth = depth;
+        self
+    }
+    /// Generate the next valid instance, `None` if no candidate was valid within the attempts.
+    pub fn generate(&mut self) -> Option<J> {
+        for _ in 0..self.attempts {
+            let mut sampler = Sampler {
+                root: &self.schema,
+                rng: &mut self.rng,
+                max_depth: self.max_depth,
+            };
+            if let Some(candidate) = sampler.value(&self.schema, 0) {
+                if self.validator.is_valid(&candidate) {
+                    return J::from_str(&candidate.to_string()).ok();
+                }
+            }
+        }
+        None
+    }
+    /// Valid instances that are one step smaller than `instance`, simplest first.
+    ///
+    /// Steps remove properties and items, or shorten strings and move numbers towards zero. For
+    /// property-based testing, repeatedly replace a failing instance with the first of its
+    /// shrinks that still fails.
+    pub fn shrink(&self, instance: &J) -> Vec<J> {
+        let Ok(instance) = Value::from_json(instance) else {
+            return Vec::new();
+        };
+        let mut shrinks: Vec<Value> = Vec::new();
+        for candidate in smaller(&instance) {
+            if !shrinks.contains(&candidate) && self.validator.is_valid(&candidate) {
+                shrinks.push(candidate);
+            }
+        }
+        shrinks
+            .iter()
+            .filter_map(|shrink| J::from_str(&shrink.to_string()).ok())
+            .collect()
+    }
+}
+
+/// SplitMix64, which is small and good enough for sampling.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// A number in `0..bound`, `bound` must not be zero.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+    /// A number in `low..=high`.
+    fn between(&mut self, low: usize, high: usize) -> usize {
+        low + self.below(high.saturating_sub(low) + 1)
+    }
+    fn chance(&mut self) -> bool {
+        self.next() & 1 == 1
+    }
+    /// A float in `0.0..1.0`.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+    fn pick<'v, T>(&mut self, items: &'v [T]) -> Option<&'v T> {
+        if items.is_empty() {
+            None
+        } else {
+            items.get(self.below(items.len()))
+        }
+    }
+}
+
+/// How many optional items or characters are added beyond the lower bounds at most.
+const SPREAD: usize = 4;
+/// How often references and combinators are resolved within a single schema at most.
+const RESOLUTIONS: usize = 16;
+
+const LOWER_BOUNDS: [&str; 5] = [
+    "minimum",
+    "minLength",
+    "minItems",
+    "minProperties",
+    "minContains",
+];
+const UPPER_BOUNDS: [&str; 5] = [
+    "maximum",
+    "maxLength",
+    "maxItems",
+    "maxProperties",
+    "maxContains",
+];
+const MAPS: [&str; 5] = [
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "patternProperties",
+    "properties",
+];
+const SUBSCHEMAS: [&str; 7] = [
+    "additionalItems",
+    "additionalProperties",
+    "contains",
+    "items",
+    "propertyNames",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+struct Sampler<'a> {
+    root: &'a Value,
+    rng: &'a mut Rng,
+    max_depth: usize,
+}
+
+impl Sampler<'_> {
+    fn value(&mut self, schema: &Value, depth: usize) -> Option<Value> {
+        let schema = match schema {
+            Value::Bool(false) => return None,
+            Value::Object(object) => self.resolve(object.clone(), RESOLUTIONS),
+            _ => return Some(self.any(depth)),
+        };
+        if let Some(value) = schema.get("const") {
+            return Some(value.clone());
+        }
+        if let Some(Value::Array(values)) = schema.get("enum") {
+            return self.rng.pick(values.items()).cloned();
+        }
+        let types = types(&schema);
+        let ty = *self.rng.pick(&types)?;
+        match ty {
+            "null" => Some(Value::Null),
+            "boolean" => Some(Value::Bool(self.rng.chance())),
+            "integer" => self.number(&schema, true),
+            "number" => {
+                let is_integer = self.rng.chance();
+                self.number(&schema, is_integer)
+            }
+            "string" => Some(Value::String(self.string(&schema))),
+            "array" => self.array(&schema, depth),
+            _ => self.object(&schema, depth),
+        }
+    }
+
+    /// `schema` with references, `allOf` and one branch of `anyOf`, `oneOf` and `if` merged in.
+    fn resolve(&mut self, mut schema: Object, budget: usize) -> Object {
+        if budget == 0 {
+            return schema;
+        }
+        for keyword in ["$ref", "$recursiveRef", "$dynamicRef"] {
+            if let Some(Value::String(reference)) = schema.remove(keyword) {
+                if let Some(Value::Object(target)) = pointer(self.root, &reference) {
+                    let target = self.resolve(target.clone(), budget - 1);
+                    schema = merge(schema, &target);
+                } else if let Some(Value::Bool(false)) = pointer(self.root, &reference) {
+                    schema.insert("enum".to_string(), Value::Array(Array::default()));
+                }
+            }
+        }
+        if let Some(Value::Array(branches)) = schema.remove("allOf") {
+            for branch in branches.items() {
+                schema = self.merge_branch(schema, branch, budget);
+            }
+        }
+        for keyword in ["anyOf", "oneOf"] {
+            if let Some(Value::Array(branches)) = schema.remove(keyword) {
+                if let Some(branch) = self.rng.pick(branches.items()) {
+                    schema = self.merge_branch(schema, branch, budget);
+                }
+            }
+        }
+        if let Some(condition) = schema.remove("if") {
+            let then = schema.remove("then");
+            let otherwise = schema.remove("else");
+            if self.rng.chance() {
+                schema = self.merge_branch(schema, &condition, budget);
+                if let Some(then) = then {
+                    schema = self.merge_branch(schema, &then, budget);
+                }
+            } else if let Some(otherwise) = otherwise {
+                schema = self.merge_branch(schema, &otherwise, budget);
+            }
+        }
+        schema
+    }
+
+    fn merge_branch(&mut self, schema: Object, branch: &Value, budget: usize) -> Object {
+        match branch {
+            Value::Object(branch) => {
+                let branch = self.resolve(branch.clone(), budget - 1);
+                merge(schema, &branch)
+            }
+            Value::Bool(false) => {
+                let mut schema = schema;
+                schema.insert("enum".to_string(), Value::Array(Array::default()));
+                schema
+            }
+            _ => schema,
+        }
+    }
+
+    /// A small value of any type.
+    fn any(&mut self, depth: usize) -> Value {
+        let choices = if depth < self.max_depth { 6 } else { 4 };
+        match self.rng.below(choices) {
+            0 => Value::Null,
+            1 => Value::Bool(self.rng.chance()),
+            2 => Value::Number(Number::from_f64(self.rng.between(0, 100) as f64)),
+            3 => Value::String(self.letters(1, SPREAD)),
+            4 => Value::Array(Array::default()),
+            _ => Value::Object(Object::default()),
+        }
+    }
+
+    fn number(&mut self, schema: &Object, is_integer: bool) -> Option<Value> {
+        let mut low = number(schema, "minimum").map(|value| (value, false));
+        let mut high = number(schema, "maximum").map(|value| (value, false));
+        match schema.get("exclusiveMinimum") {
+            Some(Value::Number(value)) => {
+                low = stricter(Some((value.as_f64(), true)), low, true);
+            }
+            Some(Value::Bool(true)) => low = low.map(|(value, _)| (value, true)),
+            _ => {}
+        }
+        match schema.get("exclusiveMaximum") {
+            Some(Value::Number(value)) => {
+                high = stricter(Some((value.as_f64(), true)), high, false);
+            }
+            Some(Value::Bool(true)) => high = high.map(|(value, _)| (value, true)),
+            _ => {}
+        }
+        let (low, low_exclusive) = low.unwrap_or_else(|| {
+            let value = high.map_or(-100.0, |(high, _)| high.min(0.0) - 100.0);
+            (value, false)
+        });
+        let (high, high_exclusive) = high.unwrap_or((low.max(0.0) + 100.0, false));
+        let step = number(schema, "multipleOf").filter(|step| *step > 0.0);
+        let value = if let Some(step) = step {
+            let first = (low / step).ceil()
+                + f64::from(u8::from((low / step).fract() == 0.0 && low_exclusive));
+            let last = (high / step).floor()
+                - f64::from(u8::from((high / step).fract() == 0.0 && high_exclusive));
+            if first > last {
+                return None;
+            }
+            let count = (last - first).min(1e6) as usize;
+            (first + self.rng.between(0, count) as f64) * step
+        } else if is_integer {
+            let first = if low_exclusive {
+                low.floor() + 1.0
+            } else {
+                low.ceil()
+            };
+            let last = if high_exclusive {
+                high.ceil() - 1.0
+            } else {
+                high.floor()
+            };
+            if first > last {
+                return None;
+            }
+            let count = (last - first).min(1e6) as usize;
+            first + self.rng.between(0, count) as f64
+        } else {
+            if low > high {
+                return None;
+            }
+            let value = ((low + (high - low) * self.rng.unit()) * 100.0).round() / 100.0;
+            let within = (value > low || (!low_exclusive && value == low))
+                && (value < high || (!high_exclusive && value == high));
+            if within {
+                value
+            } else {
+                (low + high) / 2.0
+            }
+        };
+        Some(Value::Number(Number::from_f64(value)))
+    }
+
+    fn string(&mut self, schema: &Object) -> String {
+        let min = count(schema, "minLength").unwrap_or(0);
+        let max = count(schema, "maxLength").unwrap_or(min + SPREAD).max(min);
+        if let Some(Value::String(format)) = schema.get("format") {
+            if let Some(sample) = self.format(format) {
+                return sample;
+            }
+        }
+        if let Some(Value::String(pattern)) = schema.get("pattern") {
+            if let Some(sample) = Pattern::parse(pattern).map(|pattern| pattern.sample(self.rng)) {
+                return sample;
+            }
+        }
+        self.letters(min, max.min(min + SPREAD))
+    }
+
+    fn letters(&mut self, min: usize, max: usize) -> String {
+        let length = self.rng.between(min, max);
+        (0..length)
+            .map(|_| char::from(b'a' + self.rng.below(26) as u8))
+            .collect()
+    }
+
+    fn format(&mut self, format: &str) -> Option<String> {
+        let sample = match format {
+            "date-time" => format!("2024-01-{:02}T12:00:00Z", self.rng.between(1, 28)),
+            "date" => format!("2024-01-{:02}", self.rng.between(1, 28)),
+            "time" => format!("12:{:02}:00Z", self.rng.below(60)),
+            "duration" => format!("P{}D", self.rng.between(1, 30)),
+            "email" | "idn-email" => format!("{}@example.com", self.letters(1, SPREAD)),
+            "hostname" | "idn-hostname" => format!("{}.example.com", self.letters(1, SPREAD)),
+            "ipv4" => format!("192.0.2.{}", self.rng.between(1, 254)),
+            "ipv6" => format!("2001:db8::{:x}", self.rng.between(1, 0xffff)),
+            "uri" | "iri" => format!("https://example.com/{}", self.letters(0, SPREAD)),
+            "uri-reference" | "iri-reference" => format!("/{}", self.letters(0, SPREAD)),
+            "uri-template" => "https://example.com/{id}".to_string(),
+            "uuid" => {
+                let hex = |rng: &mut Rng, length: usize| {
+                    (0..length)
+                        .map(|_| char::from_digit(rng.below(16) as u32, 16).unwrap_or('0'))
+                        .collect::<String>()
+                };
+                format!(
+                    "{}-{}-4{}-a{}-{}",
+                    hex(self.rng, 8),
+                    hex(self.rng, 4),
+                    hex(self.rng, 3),
+                    hex(self.rng, 3),
+                    hex(self.rng, 12)
+                )
+            }
+            "json-pointer" => format!("/{}", self.letters(0, SPREAD)),
+            "relative-json-pointer" => self.rng.below(10).to_string(),
+            "regex" => ".*".to_string(),
+            _ => return None,
+        };
+        Some(sample)
+    }
+
+    fn array(&mut self, schema: &Object, depth: usize) -> Option<Value> {
+        let (prefix, rest): (&[Value], Option<&Value>) =
+            match (schema.get("prefixItems"), schema.get("items")) {
+                (Some(Value::Array(prefix)), rest) => (prefix.items(), rest),
+                (None, Some(Value::Array(prefix))) => {
+                    (prefix.items(), schema.get("additionalItems"))
+                }
+                (_, rest) => (&[], rest),
+            };
+        let min = count(schema, "minItems").unwrap_or(0);
+        let mut max = count(schema, "maxItems").unwrap_or(usize::MAX).max(min);
+        if let Some(Value::Bool(false)) = rest {
+            max = max.min(prefix.len());
+        }
+        let length = if depth < self.max_depth {
+            self.rng.between(min, max.min(min + SPREAD))
+        } else {
+            min
+        };
+        let unique = matches!(schema.get("uniqueItems"), Some(Value::Bool(true)));
+        let mut items: Vec<Value> = Vec::with_capacity(length);
+        let item_schema = |idx: usize| prefix.get(idx).or(rest).unwrap_or(&Value::Bool(true));
+        for idx in 0..length {
+            items.push(self.item(item_schema(idx), &items, unique, depth)?);
+        }
+        if let Some(contains) = schema.get("contains") {
+            let needed = count(schema, "minContains").unwrap_or(1);
+            for offset in 0..needed {
+                let idx = (items.len() + offset)
+                    .saturating_sub(needed)
+                    .min(items.len());
+                let merged = merge_schemas(item_schema(idx), contains);
+                let item = self.item(&merged, &items, unique, depth)?;
+                if idx < items.len() {
+                    items[idx] = item;
+                } else {
+                    items.push(item);
+                }
+            }
+        }
+        Some(Value::Array(Array::from(items)))
+    }
+
+    fn item(
+        &mut self,
+        schema: &Value,
+        items: &[Value],
+        unique: bool,
+        depth: usize,
+    ) -> Option<Value> {
+        for _ in 0..SPREAD {
+            let item = self.value(schema, depth + 1)?;
+            if !unique || !items.contains(&item) {
+                return Some(item);
+            }
+        }
+        None
+    }
+
+    fn object(&mut self, schema: &Object, depth: usize) -> Option<Value> {
+        let properties = match schema.get("properties") {
+            Some(Value::Object(properties)) => properties.entries(),
+            _ => &[],
+        };
+        let additional = schema
+            .get("additionalProperties")
+            .unwrap_or(&Value::Bool(true));
+        let property_schema = |name: &str| {
+            properties
+                .iter()
+                .find_map(|(key, schema)| (key == name).then_some(schema))
+                .unwrap_or(additional)
+        };
+        let mut names: Vec<String> = strings(schema.get("required"));
+        let min = count(schema, "minProperties").unwrap_or(0);
+        let max = count(schema, "maxProperties")
+            .unwrap_or(usize::MAX)
+            .max(min);
+        let optional: Vec<&String> = properties
+            .iter()
+            .map(|(name, _)| name)
+            .filter(|name| !names.contains(name))
+            .collect();
+        for name in optional {
+            let wanted = names.len() < min || (depth < self.max_depth && self.rng.chance());
+            if wanted && names.len() < max {
+                names.push(name.clone());
+            }
+        }
+        if let Some(Value::Object(dependencies)) = schema
+            .get("dependentRequired")
+            .or_else(|| schema.get("dependencies"))
+        {
+            for (name, dependents) in dependencies.entries() {
+                if names.contains(name) {
+                    for dependent in strings(Some(dependents)) {
+                        if !names.contains(&dependent) {
+                            names.push(dependent);
+                        }
+                    }
+                }
+            }
+        }
+        while names.len() < min {
+            let name = match schema.get("propertyNames") {
+                Some(names @ Value::Object(_)) => match self.value(names, depth + 1) {
+                    Some(Value::String(name)) => name,
+                    _ => return None,
+                },
+                _ => format!("property{}", names.len() + 1),
+            };
+            if names.contains(&name) {
+                return None;
+            }
+            names.push(name);
+        }
+        let mut object = Object::default();
+        for name in names {
+            let value = self.value(property_schema(&name), depth + 1)?;
+            object.insert(name, value);
+        }
+        Some(Value::Object(object))
+    }
+}
+
+/// The stricter of two lower or upper bounds, which may be exclusive.
+fn stricter(
+    left: Option<(f64, bool)>,
+    right: Option<(f64, bool)>,
+    is_lower: bool,
+) -> Option<(f64, bool)> {
+    match (left, right) {
+        (Some(left), Some(right)) => {
+            let left_is_stricter = if is_lower {
+                left.0 > right.0 || (left.0 == right.0 && left.1)
+            } else {
+                left.0 < right.0 || (left.0 == right.0 && left.1)
+            };
+            Some(if left_is_stricter { left } else { right })
+        }
+        (left, right) => left.or(right),
+    }
+}
+
+fn number(schema: &Object, keyword: &str) -> Option<f64> {
+    match schema.get(keyword) {
+        Some(Value::Number(value)) => Some(value.as_f64()),
+        _ => None,
+    }
+}
+
+fn count(schema: &Object, keyword: &str) -> Option<usize> {
+    number(schema, keyword).map(|value| value.max(0.0).min(usize::MAX as f64) as usize)
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(items)) => items
+            .items()
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(item) => Some(item.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Types an instance of `schema` may have, implied by its keywords if there is no `type`.
+fn types(schema: &Object) -> Vec<&'static str> {
+    const ALL: [&str; 7] = [
+        "null", "boolean", "integer", "number", "string", "array", "object",
+    ];
+    let named: Vec<&str> = match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types
+            .items()
+            .iter()
+            .filter_map(|ty| match ty {
+                Value::String(ty) => Some(ty.as_str()),
+                _ => None,
+            })
+            .collect(),
+        _ => {
+            let implied = |keywords: &[&str]| keywords.iter().any(|key| schema.get(key).is_some());
+            let mut implied_types = Vec::new();
+            if implied(&[
+                "minimum",
+                "maximum",
+                "exclusiveMinimum",
+                "exclusiveMaximum",
+                "multipleOf",
+            ]) {
+                implied_types.push("number");
+            }
+            if implied(&["minLength", "maxLength", "pattern", "format"]) {
+                implied_types.push("string");
+            }
+            if implied(&[
+                "items",
+                "prefixItems",
+                "minItems",
+                "maxItems",
+                "contains",
+                "uniqueItems",
+            ]) {
+                implied_types.push("array");
+            }
+            if implied(&[
+                "properties",
+                "required",
+                "additionalProperties",
+                "minProperties",
+                "maxProperties",
+                "patternProperties",
+            ]) {
+                implied_types.push("object");
+            }
+            if implied_types.is_empty() {
+                return ALL.to_vec();
+            }
+            implied_types
+        }
+    };
+    ALL.into_iter().filter(|ty| named.contains(ty)).collect()
+}
+
+/// The schema at `reference`, a URI fragment with a JSON pointer, within `root`.
+fn pointer<'v>(root: &'v Value, reference: &str) -> Option<&'v Value> {
+    let fragment = reference.strip_prefix('#')?;
+    let fragment = percent_decode_str(fragment).decode_utf8().ok()?;
+    if fragment.is_empty() {
+        return Some(root);
+    }
+    let mut target = root;
+    for token in fragment.strip_prefix('/')?.split('/') {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        target = match target {
+            Value::Object(object) => object.get(&token)?,
+            Value::Array(array) => array.items().get(token.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(target)
+}
+
+/// A schema that only allows instances valid against both schemas, as far as the keywords can
+/// be combined. Conflicting values of other keywords are kept from `left`.
+fn merge(mut left: Object, right: &Object) -> Object {
+    for (key, value) in right.entries() {
+        let Some(existing) = left.get(key) else {
+            left.insert(key.clone(), value.clone());
+            continue;
+        };
+        let merged = match (key.as_str(), existing, value) {
+            (key, Value::Object(existing), Value::Object(value)) if MAPS.contains(&key) => {
+                let mut merged = existing.clone();
+                for (name, schema) in value.entries() {
+                    let schema = match merged.get(name) {
+                        Some(existing) => merge_schemas(existing, schema),
+                        None => schema.clone(),
+                    };
+                    merged.insert(name.clone(), schema);
+                }
+                Value::Object(merged)
+            }
+            ("required" | "allOf", Value::Array(existing), Value::Array(value)) => {
+                let mut merged = existing.items().to_vec();
+                for item in value.items() {
+                    if !merged.contains(item) {
+                        merged.push(item.clone());
+                    }
+                }
+                Value::Array(Array::from(merged))
+            }
+            ("enum", Value::Array(existing), Value::Array(value)) => Value::Array(Array::from(
+                existing
+                    .items()
+                    .iter()
+                    .filter(|item| value.items().contains(item))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            )),
+            ("type", _, _) => {
+                let left_types = types(&Object::from(vec![(key.clone(), existing.clone())]));
+                let right_types = types(&Object::from(vec![(key.clone(), value.clone())]));
+                let allows = |types: &[&str], ty: &str| {
+                    types.contains(&ty) || (ty == "integer" && types.contains(&"number"))
+                };
+                Value::Array(Array::from(
+                    left_types
+                        .iter()
+                        .chain(right_types.iter())
+                        .filter(|ty| allows(&left_types, ty) && allows(&right_types, ty))
+                        .map(|ty| Value::String((*ty).to_string()))
+                        .fold(Vec::new(), |mut types, ty| {
+                            if !types.contains(&ty) {
+                                types.push(ty);
+                            }
+                            types
+                        }),
+                ))
+            }
+            (key, Value::Number(existing), Value::Number(value))
+                if LOWER_BOUNDS.contains(&key) || UPPER_BOUNDS.contains(&key) =>
+            {
+                let is_lower = LOWER_BOUNDS.contains(&key);
+                if (value.as_f64() > existing.as_f64()) == is_lower {
+                    Value::Number(value.clone())
+                } else {
+                    Value::Number(existing.clone())
+                }
+            }
+            (key, existing, value)
+                if SUBSCHEMAS.contains(&key)
+                    && !matches!(existing, Value::Array(_))
+                    && !matches!(value, Value::Array(_)) =>
+            {
+                merge_schemas(existing, value)
+            }
+            _ => continue,
+        };
+        left.insert(key.clone(), merged);
+    }
+    left
+}
+
+fn merge_schemas(left: &Value, right: &Value) -> Value {
+    match (left, right) {
+        (Value::Bool(false), _) | (_, Value::Bool(false)) => Value::Bool(false),
+        (Value::Object(left), Value::Object(right)) => Value::Object(merge(left.clone(), right)),
+        (Value::Object(_), _) => left.clone(),
+        _ => right.clone(),
+    }
+}
+
+/// Candidates that are one step smaller than `value`, simplest first.
+fn smaller(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Null | Value::Bool(false) => Vec::new(),
+        Value::Bool(true) => vec![Value::Bool(false)],
+        Value::Number(number) => {
+            let number = number.as_f64();
+            let step = if number.fract() == 0.0 {
+                number - number.signum()
+            } else {
+                number
+            };
+            [0.0, number.trunc(), (number / 2.0).trunc(), step]
+                .into_iter()
+                .filter(|candidate| *candidate != number)
+                .map(|candidate| Value::Number(Number::from_f64(candidate)))
+                .collect()
+        }
+        Value::String(string) => {
+            let chars: Vec<char> = string.chars().collect();
+            [0, chars.len() / 2, chars.len().saturating_sub(1)]
+                .into_iter()
+                .filter(|length| *length < chars.len())
+                .map(|length| Value::String(chars[..length].iter().collect()))
+                .collect()
+        }
+        Value::Array(array) => {
+            let items = array.items();
+            let mut candidates = Vec::new();
+            for length in [0, items.len() / 2] {
+                if length < items.len() {
+                    candidates.push(Value::Array(Array::from(items[..length].to_vec())));
+                }
+            }
+            for idx in 0..items.len() {
+                let mut removed = items.to_vec();
+                removed.remove(idx);
+                candidates.push(Value::Array(Array::from(removed)));
+            }
+            for (idx, item) in items.iter().enumerate() {
+                for candidate in smaller(item) {
+                    let mut replaced = items.to_vec();
+                    replaced[idx] = candidate;
+                    candidates.push(Value::Array(Array::from(replaced)));
+                }
+            }
+            candidates
+        }
+        Value::Object(object) => {
+            let mut candidates = Vec::new();
+            for (key, _) in object.entries() {
+                let mut removed = object.clone();
+                removed.remove(key);
+                candidates.push(Value::Object(removed));
+            }
+            for (key, value) in object.entries() {
+                for candidate in smaller(value) {
+                    let mut replaced = object.clone();
+                    replaced.insert(key.clone(), candidate);
+                    candidates.push(Value::Object(replaced));
+                }
+            }
+            candidates
+        }
+    }
+}
+
+/// A regular expression that strings can be sampled from.
+enum Pattern {
+    Literal(char),
+    /// Inclusive character ranges, or everything printable outside of them if negated.
+    Class(Vec<(char, char)>, bool),
+    Any,
+    /// Alternatives of sequences.
+    Group(Vec<Vec<Pattern>>),
+    Repeat(Box<Pattern>, usize, usize),
+}
+
+/// Upper bound for unbounded repetitions.
+const REPEAT: usize = 3;
+
+impl Pattern {
+    /// Parse the supported subset of ECMA 262 regular expressions.
+    fn parse(pattern: &str) -> Option<Pattern> {
+        let mut chars = pattern.chars().peekable();
+        let group = parse_alternatives(&mut chars)?;
+        chars.next().is_none().then_some(group)
+    }
+
+    fn sample(&self, rng: &mut Rng) -> String {
+        let mut output = String::new();
+        self.write(rng, &mut output);
+        output
+    }
+
+    fn write(&self, rng: &mut Rng, output: &mut String) {
+        match self {
+            Pattern::Literal(char) => output.push(*char),
+            Pattern::Class(ranges, false) => {
+                if let Some((start, end)) = rng.pick(ranges) {
+                    let (start, end) = (u32::from(*start), u32::from(*end));
+                    let code = start + rng.below((end.saturating_sub(start) + 1) as usize) as u32;
+                    output
+                        .push(char::from_u32(code).unwrap_or(char::from_u32(start).unwrap_or('a')));
+                }
+            }
+            Pattern::Class(ranges, true) => {
+                let allowed: Vec<char> = (' '..='~')
+                    .filter(|char| {
+                        !ranges
+                            .iter()
+                            .any(|(start, end)| (start..=end).contains(&char))
+                    })
+                    .collect();
+                if let Some(char) = rng.pick(&allowed) {
+                    output.push(*char);
+                }
+            }
+            Pattern::Any => output.push(char::from(b'a' + rng.below(26) as u8)),
+            Pattern::Group(alternatives) => {
+                if let Some(sequence) = rng.pick(alternatives) {
+                    for pattern in sequence {
+                        pattern.write(rng, output);
+                    }
+                }
+            }
+            Pattern::Repeat(pattern, min, max) => {
+                for _ in 0..rng.between(*min, *max) {
+                    pattern.write(rng, output);
+                }
+            }
+        }
+    }
+}
+
+type Chars<'p> = core::iter::Peekable<core::str::Chars<'p>>;
+
+fn parse_alternatives(chars: &mut Chars<'_>) -> Option<Pattern> {
+    let mut alternatives = vec![Vec::new()];
+    while let Some(&next) = chars.peek() {
+        let atom = match next {
+            ')' => break,
+            '|' => {
+                chars.next();
+                alternatives.push(Vec::new());
+                continue;
+            }
+            // Anchors match the empty string
+            '^' | '$' => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                if chars.peek() == Some(&'?') {
+                    chars.next();
+                    // Only non-capturing groups are supported
+                    if chars.next() != Some(':') {
+                        return None;
+                    }
+                }
+                let group = parse_alternatives(chars)?;
+                if chars.next() != Some(')') {
+                    return None;
+                }
+                group
+            }
+            '[' => {
+                chars.next();
+                parse_class(chars)?
+            }
+            '.' => {
+                chars.next();
+                Pattern::Any
+            }
+            '\\' => {
+                chars.next();
+                parse_escape(chars)?
+            }
+            '*' | '+' | '?' | '{' => return None,
+            _ => {
+                chars.next();
+                Pattern::Literal(next)
+            }
+        };
+        let atom = parse_quantifier(chars, atom)?;
+        alternatives.last_mut()?.push(atom);
+    }
+    Some(Pattern::Group(alternatives))
+}
+
+fn parse_quantifier(chars: &mut Chars<'_>, atom: Pattern) -> Option<Pattern> {
+    let (min, max) = match chars.peek() {
+        Some('*') => (0, REPEAT),
+        Some('+') => (1, REPEAT + 1),
+        Some('?') => (0, 1),
+        Some('{') => {
+            chars.next();
+            let mut bounds = String::new();
+            for char in chars.by_ref() {
+                if char == '}' {
+                    break;
+                }
+                bounds.push(char);
+            }
+            let (min, max) = match bounds.split_once(',') {
+                Some((min, "")) => {
+                    let min = min.trim().parse().ok()?;
+                    (min, min + REPEAT)
+                }
+                Some((min, max)) => (min.trim().parse().ok()?, max.trim().parse().ok()?),
+                None => {
+                    let exact = bounds.trim().parse().ok()?;
+                    (exact, exact)
+                }
+            };
+            if chars.peek() == Some(&'?') {
+                chars.next();
+            }
+            return Some(Pattern::Repeat(Box::new(atom), min, max));
+        }
+        _ => return Some(atom),
+    };
+    chars.next();
+    // Lazy quantifiers produce the same strings
+    if chars.peek() == Some(&'?') {
+        chars.next();
+    }
+    Some(Pattern::Repeat(Box::new(atom), min, max))
+}
+
+fn parse_escape(chars: &mut Chars<'_>) -> Option<Pattern> {
+    const DIGITS: &[(char, char)] = &[('0', '9')];
+    const WORD: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+    const SPACE: &[(char, char)] = &[(' ', ' ')];
+    Some(match chars.next()? {
+        'd' => Pattern::Class(DIGITS.to_vec(), false),
+        'D' => Pattern::Class(DIGITS.to_vec(), true),
+        'w' => Pattern::Class(WORD.to_vec(), false),
+        'W' => Pattern::Class(WORD.to_vec(), true),
+        's' => Pattern::Class(SPACE.to_vec(), false),
+        'S' => Pattern::Class(SPACE.to_vec(), true),
+        'n' => Pattern::Literal('\n'),
+        't' => Pattern::Literal('\t'),
+        'r' => Pattern::Literal('\r'),
+        'u' => {
+            let code: String = (0..4).filter_map(|_| chars.next()).collect();
+            Pattern::Literal(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?)
+        }
+        // Word boundaries and backreferences
+        'b' | 'B' | '1'..='9' => return None,
+        escaped => Pattern::Literal(escaped),
+    })
+}
+
+fn parse_class(chars: &mut Chars<'_>) -> Option<Pattern> {
+    let negated = chars.peek() == Some(&'^');
+    if negated {
+        chars.next();
+    }
+    let mut ranges = Vec::new();
+    loop {
+        let start = match chars.next()? {
+            ']' => break,
+            '\\' => match parse_escape(chars)? {
+                Pattern::Literal(char) => char,
+                Pattern::Class(class, false) => {
+                    ranges.extend(class);
+                    continue;
+                }
+                _ => return None,
+            },
+            char => char,
+        };
+        let mut lookahead = chars.clone();
+        if lookahead.next() == Some('-') && lookahead.peek().is_some_and(|next| *next != ']') {
+            chars.next();
+            let end = match chars.next()? {
+                '\\' => match parse_escape(chars)? {
+                    Pattern::Literal(char) => char,
+                    _ => return None,
+                },
+                char => char,
+            };
+            ranges.push((start, end));
+        } else {
+            ranges.push((start, start));
+        }
+    }
+    Some(Pattern::Class(ranges, negated))
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{Generator, Pattern, Rng};
+    use crate::blocking::{self, ValidatorBuilder};
+
+    fn generator(schema: &Value) -> Generator<Value> {
+        ValidatorBuilder::default()
+            .generator(schema)
+            .expect("Valid schema")
+    }
+
+    #[test_case(json!({"type": "integer", "minimum": 10, "maximum": 12}))]
+    #[test_case(json!({"type": "number", "exclusiveMinimum": 0, "exclusiveMaximum": 1}))]
+    #[test_case(json!({"type": "integer", "multipleOf": 7, "minimum": -20}))]
+    #[test_case(json!({"type": "string", "minLength": 3, "maxLength": 5}))]
+    #[test_case(json!({"type": "string", "pattern": "^[A-Z]{2}-\\d{3}(x|y)?$"}))]
+    #[test_case(json!({"type": "string", "format": "date"}))]
+    #[test_case(json!({"enum": ["a", 1, null]}))]
+    #[test_case(json!({"const": {"a": [1]}}))]
+    #[test_case(json!({"type": ["string", "null"]}))]
+    #[test_case(json!({"type": "array", "items": {"type": "boolean"}, "minItems": 2, "uniqueItems": true}))]
+    #[test_case(json!({"type": "array", "prefixItems": [{"type": "string"}], "items": false, "minItems": 1}))]
+    #[test_case(json!({"type": "array", "contains": {"const": 5}, "minContains": 2}))]
+    #[test_case(json!({
+        "type": "object",
+        "properties": {"a": {"type": "integer"}, "b": {"type": "string"}},
+        "required": ["a"],
+        "additionalProperties": false,
+    }))]
+    #[test_case(json!({"type": "object", "minProperties": 3, "propertyNames": {"pattern": "^x[0-9]$"}}))]
+    #[test_case(json!({"allOf": [{"type": "integer"}, {"minimum": 5}, {"maximum": 6}]}))]
+    #[test_case(json!({"anyOf": [{"type": "string"}, {"type": "array", "maxItems": 1}]}))]
+    #[test_case(json!({"oneOf": [{"type": "integer", "multipleOf": 2}, {"type": "integer", "multipleOf": 3}]}))]
+    #[test_case(json!({
+        "if": {"properties": {"kind": {"const": "a"}}, "required": ["kind"]},
+        "then": {"required": ["a"]},
+        "else": {"properties": {"kind": {"const": "b"}}},
+        "type": "object",
+    }))]
+    #[test_case(json!({"$defs": {"positive": {"type": "integer", "minimum": 1}}, "items": {"$ref": "#/$defs/positive"}, "type": "array"}))]
+    #[test_case(json!({
+        "$defs": {"node": {"type": "object", "properties": {"children": {"type": "array", "items": {"$ref": "#/$defs/node"}}}, "required": ["children"]}},
+        "$ref": "#/$defs/node",
+    }))]
+    #[test_case(json!(true))]
+    fn test_generate(schema: Value) {
+        let mut generator = generator(&schema).seed(7);
+        for _ in 0..20 {
+            let instance = generator.generate().expect("Satisfiable schema");
+            assert!(
+                blocking::is_valid(&schema, &instance),
+                "{instance} is invalid against {schema}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_seed() {
+        let schema = json!({"type": "array", "items": {"type": "string"}});
+        let sample = |seed| {
+            let mut generator = generator(&schema).seed(seed);
+            (0..5)
+                .map(|_| generator.generate().expect("Satisfiable schema"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sample(1), sample(1));
+        assert_ne!(sample(1), sample(2));
+    }
+
+    #[test]
+    fn test_unsatisfiable() {
+        let schema = json!({"type": "integer", "minimum": 2, "maximum": 1});
+        assert_eq!(generator(&schema).attempts(5).generate(), None);
+        assert_eq!(generator(&json!(false)).generate(), None);
+    }
+
+    #[test]
+    fn test_external_reference() {
+        let resolver = crate::resolver::InMemoryResolver::new().with(
+            "https://example.com/id.json",
+            json!({"type": "string", "format": "uuid"}),
+        );
+        let schema = json!({"properties": {"id": {"$ref": "https://example.com/id.json"}}, "required": ["id"]});
+        let mut generator = ValidatorBuilder::default()
+            .resolver(resolver)
+            .generator(&schema)
+            .expect("Valid schema");
+        let instance = generator.generate().expect("Satisfiable schema");
+        assert_eq!(instance["id"].as_str().map(str::len), Some(36));
+    }
+
+    #[test]
+    fn test_shrink() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"a": {"type": "integer", "minimum": 3}, "b": {"type": "string"}},
+            "required": ["a"],
+        });
+        let generator = generator(&schema);
+        assert_eq!(
+            generator.shrink(&json!({"a": 8, "b": "xy"})),
+            [
+                json!({"a": 8}),
+                json!({"a": 4, "b": "xy"}),
+                json!({"a": 7, "b": "xy"}),
+                json!({"a": 8, "b": ""}),
+                json!({"a": 8, "b": "x"}),
+            ]
+        );
+        assert!(generator.shrink(&json!({"a": 3})).is_empty());
+    }
+
+    #[test]
+    fn test_shrink_to_minimal_failure() {
+        // Shrink towards the smallest array that contains a number above 10
+        let schema = json!({"type": "array", "items": {"type": "integer"}});
+        let generator = generator(&schema);
+        let fails = |instance: &Value| {
+            instance
+                .as_array()
+                .is_some_and(|items| items.iter().any(|item| item.as_i64() > Some(10)))
+        };
+        let mut instance = json!([1, 50, 3, 44]);
+        while let Some(smaller) = generator.shrink(&instance).into_iter().find(fails) {
+            instance = smaller;
+        }
+        assert_eq!(instance, json!([11]));
+    }
+
+    #[test_case("^abc$", &["abc"])]
+    #[test_case("a|b", &["a", "b"])]
+    #[test_case("[x-z]", &["x", "y", "z"])]
+    #[test_case("(?:ab){2}", &["abab"])]
+    #[test_case("\\.\\u0041", &[".A"])]
+    fn test_pattern(pattern: &str, expected: &[&str]) {
+        let pattern = Pattern::parse(pattern).expect("Supported pattern");
+        let mut rng = Rng(3);
+        for _ in 0..10 {
+            let sample = pattern.sample(&mut rng);
+            assert!(expected.contains(&sample.as_str()), "{sample}");
+        }
+    }
+
+    #[test_case("(?=a)")]
+    #[test_case("(a)\\1")]
+    #[test_case("a)")]
+    #[test_case("*")]
+    fn test_unsupported_pattern(pattern: &str) {
+        assert!(Pattern::parse(pattern).is_none());
+    }
+}
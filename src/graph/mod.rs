@@ -2,7 +2,9 @@ mod node;
 
 pub(crate) use node::{Node, NodeId};
 
+/// An arena of compiled nodes that refer to each other via `NodeId`.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Graph<T> {
     nodes: Vec<Node<T>>,
 }
@@ -12,9 +14,29 @@ impl<T> Graph<T> {
         Graph { nodes: Vec::new() }
     }
 
-    pub(super) fn push_node(&mut self, node: T) -> NodeId {
+    pub(crate) fn push_node(&mut self, node: T) -> NodeId {
         let next_index = self.nodes.len();
         self.nodes.push(Node::new(node));
         NodeId::new(next_index)
     }
+
+    /// Replace the value of a node that was pushed in advance, e.g. to refer to it recursively.
+    pub(crate) fn replace(&mut self, id: NodeId, node: T) {
+        self.nodes[id.index()].value = node;
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, id: NodeId) -> &T {
+        &self.nodes[id.index()].value
+    }
+
+    /// Whether `id` refers to a node of this graph.
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn contains(&self, id: NodeId) -> bool {
+        id.index() < self.nodes.len()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.nodes.iter().map(|node| &node.value)
+    }
 }
@@ -0,0 +1,431 @@
+//! Inference of schemas from sample instances.
+//!
+//! ```rust
+//! # #[cfg(feature = "serde_json")]
+//! # fn main() -> Result<(), jsonschema::BuildError> {
+//! use jsonschema::{infer::SchemaInferrer, Draft};
+//! use serde_json::json;
+//!
+//! let samples = [
+//!     json!({"id": 1, "status": "active", "note": null}),
+//!     json!({"id": 2, "status": "active", "note": "late"}),
+//!     json!({"id": 3, "status": "closed"}),
+//! ];
+//! let schema = SchemaInferrer::new(Draft::Draft07).infer(&samples)?;
+//! assert_eq!(
+//!     schema,
+//!     json!({
+//!         "$schema": "http://json-schema.org/draft-07/schema#",
+//!         "type": "object",
+//!         "properties": {
+//!             "id": {"type": "integer", "minimum": 1, "maximum": 3},
+//!             "status": {"type": "string", "enum": ["active", "closed"]},
+//!             "note": {"type": ["string", "null"]},
+//!         },
+//!         "required": ["id", "status"],
+//!     })
+//! );
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "serde_json"))]
+//! # fn main() {}
+//! ```
+//!
+//! Every sample is valid against the inferred schema with the default settings. Stricter
+//! settings, like closing objects, still accept the samples; looser ones, like disabling
+//! enums and ranges, accept more of the data the samples were taken from.
+use jsonlike::prelude::*;
+
+use crate::{
+    drafts::Draft,
+    value::{Array, Number, Object, Value},
+    BuildResult,
+};
+
+/// Infers a schema from sample instances.
+#[derive(Debug, Clone)]
+pub struct SchemaInferrer {
+    draft: Draft,
+    max_enum_values: usize,
+    ranges: bool,
+    required: bool,
+    additional_properties: bool,
+}
+
+impl Default for SchemaInferrer {
+    fn default() -> Self {
+        SchemaInferrer::new(Draft::latest())
+    }
+}
+
+impl SchemaInferrer {
+    /// Infer schemas in `draft`.
+    pub fn new(draft: Draft) -> SchemaInferrer {
+        SchemaInferrer {
+            draft,
+            max_enum_values: 5,
+            ranges: true,
+            required: true,
+            additional_properties: true,
+        }
+    }
+    /// Infer an `enum` for strings with at most `limit` distinct values that repeat across the
+    /// samples, none if `0`. Defaults to `5`.
+    #[must_use]
+    pub fn max_enum_values(mut self, limit: usize) -> SchemaInferrer {
+        self.max_enum_values = limit;
+        self
+    }
+    /// Infer `minimum` and `maximum` from the numbers in the samples. Enabled by default.
+    #[must_use]
+    pub fn ranges(mut self, yes: bool) -> SchemaInferrer {
+        self.ranges = yes;
+        self
+    }
+    /// Require properties that are present in every sample of an object. Enabled by default.
+    #[must_use]
+    pub fn required(mut self, yes: bool) -> SchemaInferrer {
+        self.required = yes;
+        self
+    }
+    /// Allow properties that do not occur in the samples. Enabled by default.
+    #[must_use]
+    pub fn additional_properties(mut self, yes: bool) -> SchemaInferrer {
+        self.additional_properties = yes;
+        self
+    }
+    /// Infer a schema that `instances` are valid against.
+    pub fn infer<'j, J: Json + 'j>(
+        &self,
+        instances: impl IntoIterator<Item = &'j J>,
+    ) -> BuildResult<J> {
+        let mut shape = Shape::default();
+        for instance in instances {
+            shape.add(instance, self.max_enum_values)?;
+        }
+        let mut schema = Object::default();
+        schema.insert(
+            "$schema".to_string(),
+            Value::String(self.draft.uri().to_string()),
+        );
+        for (key, value) in self.schema(&shape).entries() {
+            schema.insert(key.clone(), value.clone());
+        }
+        Ok(J::from_str(&Value::Object(schema).to_string())?)
+    }
+
+    fn schema(&self, shape: &Shape) -> Object {
+        let mut schema = Object::default();
+        let mut types = Vec::new();
+        if let Some(object) = &shape.object {
+            types.push("object");
+            self.object(object, &mut schema);
+        }
+        if let Some(array) = &shape.array {
+            types.push("array");
+            if array.items.count > 0 {
+                let items = self.schema(&array.items);
+                schema.insert("items".to_string(), Value::Object(items));
+            }
+        }
+        if let Some(strings) = &shape.strings {
+            types.push("string");
+            let is_only_type = shape.object.is_none()
+                && shape.array.is_none()
+                && shape.numbers.is_none()
+                && !shape.boolean;
+            if let (true, Some(values)) = (is_only_type, &strings.values) {
+                if values.len() < strings.count {
+                    let mut values: Vec<Value> =
+                        values.iter().cloned().map(Value::String).collect();
+                    if shape.null {
+                        values.push(Value::Null);
+                    }
+                    schema.insert("enum".to_string(), Value::Array(Array::from(values)));
+                }
+            }
+        }
+        if let Some(numbers) = &shape.numbers {
+            types.push(if numbers.is_integer {
+                "integer"
+            } else {
+                "number"
+            });
+            if self.ranges {
+                schema.insert("minimum".to_string(), numbers.min.1.clone());
+                schema.insert("maximum".to_string(), numbers.max.1.clone());
+            }
+        }
+        if shape.boolean {
+            types.push("boolean");
+        }
+        if shape.null {
+            types.push("null");
+        }
+        let ty = match types.as_slice() {
+            [] => return schema,
+            [ty] => Value::String((*ty).to_string()),
+            types => Value::Array(Array::from(
+                types
+                    .iter()
+                    .map(|ty| Value::String((*ty).to_string()))
+                    .collect::<Vec<_>>(),
+            )),
+        };
+        let mut ordered = Object::from(vec![("type".to_string(), ty)]);
+        for (key, value) in schema.entries() {
+            ordered.insert(key.clone(), value.clone());
+        }
+        ordered
+    }
+
+    fn object(&self, shape: &ObjectShape, schema: &mut Object) {
+        if !shape.properties.is_empty() {
+            let properties = shape
+                .properties
+                .iter()
+                .map(|(name, property)| (name.clone(), Value::Object(self.schema(property))))
+                .collect::<Vec<_>>();
+            schema.insert(
+                "properties".to_string(),
+                Value::Object(Object::from(properties)),
+            );
+        }
+        let required: Vec<Value> = shape
+            .properties
+            .iter()
+            .filter(|(_, property)| property.count == shape.count)
+            .map(|(name, _)| Value::String(name.clone()))
+            .collect();
+        if self.required && !required.is_empty() {
+            schema.insert("required".to_string(), Value::Array(Array::from(required)));
+        }
+        if !self.additional_properties {
+            schema.insert("additionalProperties".to_string(), Value::Bool(false));
+        }
+    }
+}
+
+/// What the values at one location of the samples look like.
+#[derive(Default)]
+struct Shape {
+    /// How many values were seen.
+    count: usize,
+    null: bool,
+    boolean: bool,
+    numbers: Option<Numbers>,
+    strings: Option<Strings>,
+    array: Option<Box<ArrayShape>>,
+    object: Option<Box<ObjectShape>>,
+}
+
+struct Numbers {
+    is_integer: bool,
+    min: (f64, Value),
+    max: (f64, Value),
+}
+
+struct Strings {
+    count: usize,
+    /// Distinct values in the order of appearance, `None` once there are too many of them.
+    values: Option<Vec<String>>,
+}
+
+#[derive(Default)]
+struct ArrayShape {
+    items: Shape,
+}
+
+#[derive(Default)]
+struct ObjectShape {
+    count: usize,
+    /// Properties in the order of appearance.
+    properties: Vec<(String, Shape)>,
+}
+
+impl Shape {
+    fn add<J: Json>(&mut self, value: &J, max_enum_values: usize) -> BuildResult<()> {
+        self.count += 1;
+        if let Some(object) = value.try_as_object()? {
+            let shape = self.object.get_or_insert_with(Box::default);
+            shape.count += 1;
+            for (key, value) in object.iter() {
+                let key = key?.as_ref();
+                let idx = match shape.properties.iter().position(|(name, _)| name == key) {
+                    Some(idx) => idx,
+                    None => {
+                        shape.properties.push((key.to_owned(), Shape::default()));
+                        shape.properties.len() - 1
+                    }
+                };
+                shape.properties[idx].1.add(value, max_enum_values)?;
+            }
+        } else if let Some(array) = value.try_as_array()? {
+            let shape = self.array.get_or_insert_with(Box::default);
+            for item in array.try_iter()? {
+                shape.items.add(item?, max_enum_values)?;
+            }
+        } else if let Some(string) = value.try_as_string()? {
+            let strings = self.strings.get_or_insert_with(|| Strings {
+                count: 0,
+                values: (max_enum_values > 0).then(Vec::new),
+            });
+            strings.count += 1;
+            if let Some(values) = &mut strings.values {
+                let string = string.as_ref();
+                if !values.iter().any(|value| value == string) {
+                    if values.len() < max_enum_values {
+                        values.push(string.to_owned());
+                    } else {
+                        strings.values = None;
+                    }
+                }
+            }
+        } else if let Some(number) = value.try_as_number()? {
+            let number = Number::from_json(number);
+            let float = number.as_f64();
+            let value = Value::Number(number);
+            let is_integer = float.fract() == 0.0;
+            match &mut self.numbers {
+                Some(numbers) => {
+                    numbers.is_integer &= is_integer;
+                    if float < numbers.min.0 {
+                        numbers.min = (float, value);
+                    } else if float > numbers.max.0 {
+                        numbers.max = (float, value);
+                    }
+                }
+                None => {
+                    self.numbers = Some(Numbers {
+                        is_integer,
+                        min: (float, value.clone()),
+                        max: (float, value),
+                    });
+                }
+            }
+        } else if value.try_as_boolean()?.is_some() {
+            self.boolean = true;
+        } else {
+            self.null = true;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::SchemaInferrer;
+    use crate::Draft;
+
+    fn infer(inferrer: &SchemaInferrer, samples: &[Value]) -> Value {
+        let mut schema = inferrer.infer(samples).expect("Valid JSON");
+        schema
+            .as_object_mut()
+            .expect("Inferred schemas are objects")
+            .remove("$schema");
+        schema
+    }
+
+    #[test_case(&[json!(1), json!(5), json!(3)], json!({"type": "integer", "minimum": 1, "maximum": 5}); "integers")]
+    #[test_case(&[json!(1), json!(2.5)], json!({"type": "number", "minimum": 1, "maximum": 2.5}); "numbers")]
+    #[test_case(&[json!("a"), json!("b"), json!("a")], json!({"type": "string", "enum": ["a", "b"]}); "enum")]
+    #[test_case(&[json!("a"), json!("b")], json!({"type": "string"}); "distinct strings")]
+    #[test_case(&[json!("a"), json!(null), json!("a")], json!({"type": ["string", "null"], "enum": ["a", null]}); "nullable enum")]
+    #[test_case(&[json!("a"), json!(1), json!("a")], json!({"type": ["string", "integer"], "minimum": 1, "maximum": 1}); "mixed types")]
+    #[test_case(&[json!(true), json!(null)], json!({"type": ["boolean", "null"]}); "nullable")]
+    #[test_case(&[json!([1, 2]), json!([])], json!({"type": "array", "items": {"type": "integer", "minimum": 1, "maximum": 2}}); "arrays")]
+    #[test_case(&[json!([])], json!({"type": "array"}); "empty arrays")]
+    #[test_case(&[], json!({}); "no samples")]
+    #[test_case(
+        &[json!({"a": 1, "b": {"c": "x"}}), json!({"a": 2, "b": {"c": "y", "d": false}})],
+        json!({
+            "type": "object",
+            "properties": {
+                "a": {"type": "integer", "minimum": 1, "maximum": 2},
+                "b": {
+                    "type": "object",
+                    "properties": {"c": {"type": "string"}, "d": {"type": "boolean"}},
+                    "required": ["c"],
+                },
+            },
+            "required": ["a", "b"],
+        });
+        "objects"
+    )]
+    fn test_infer(samples: &[Value], expected: Value) {
+        assert_eq!(infer(&SchemaInferrer::default(), samples), expected);
+    }
+
+    #[test]
+    fn test_strictness() {
+        let samples = [
+            json!({"kind": "a", "size": 1}),
+            json!({"kind": "a", "size": 7}),
+            json!({"kind": "b"}),
+        ];
+        let loose = SchemaInferrer::default()
+            .max_enum_values(0)
+            .ranges(false)
+            .required(false);
+        assert_eq!(
+            infer(&loose, &samples),
+            json!({
+                "type": "object",
+                "properties": {"kind": {"type": "string"}, "size": {"type": "integer"}},
+            })
+        );
+        let strict = SchemaInferrer::default().additional_properties(false);
+        assert_eq!(
+            infer(&strict, &samples),
+            json!({
+                "type": "object",
+                "properties": {
+                    "kind": {"type": "string", "enum": ["a", "b"]},
+                    "size": {"type": "integer", "minimum": 1, "maximum": 7},
+                },
+                "required": ["kind"],
+                "additionalProperties": false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_too_many_enum_values() {
+        let samples: Vec<Value> = ["a", "b", "c", "a", "b", "c"]
+            .into_iter()
+            .map(Value::from)
+            .collect();
+        let inferrer = SchemaInferrer::default().max_enum_values(2);
+        assert_eq!(infer(&inferrer, &samples), json!({"type": "string"}));
+    }
+
+    #[test_case(Draft::Draft04, "http://json-schema.org/draft-04/schema#")]
+    #[test_case(Draft::Draft201909, "https://json-schema.org/draft/2019-09/schema")]
+    fn test_draft(draft: Draft, expected: &str) {
+        let schema = SchemaInferrer::new(draft)
+            .infer(&[json!(null)])
+            .expect("Valid JSON");
+        assert_eq!(schema["$schema"], expected);
+    }
+
+    #[test]
+    fn test_samples_are_valid() {
+        let samples = [
+            json!({"id": 1, "tags": ["x"], "meta": {"score": 0.5}, "kind": "a"}),
+            json!({"id": 2, "tags": [], "meta": null, "kind": "a"}),
+            json!({"id": 3, "tags": ["y", "z"], "kind": "b", "extra": true}),
+        ];
+        let schema = SchemaInferrer::default()
+            .additional_properties(false)
+            .infer(&samples)
+            .expect("Valid JSON");
+        let validator = crate::blocking::validator_for(&schema).expect("Valid schema");
+        for sample in &samples {
+            assert!(validator.is_valid(sample), "{sample}");
+        }
+        assert!(!validator.is_valid(&json!({"id": 4, "tags": [], "kind": "c"})));
+    }
+}
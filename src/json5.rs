@@ -0,0 +1,101 @@
+//! Loading instances from JSON5 and JSONC files.
+//!
+//! Documents are parsed as they are written: comments, trailing commas, single-quoted strings
+//! and unquoted keys are accepted, and every value keeps its line and column. Validation runs
+//! directly on the parsed document, and [`locate`] maps an error back to the source.
+//!
+//! ```rust,no_run
+//! # fn test() -> Result<(), Box<dyn std::error::Error>> {
+//! let schema = serde_json::json!({"properties": {"compilerOptions": {"type": "object"}}});
+//! let validator = jsonschema::blocking::validator_for(&schema)?;
+//! let config = jsonschema::json5::load("tsconfig.json")?;
+//! for error in validator.iter_errors(&config) {
+//!     if let Some(span) = jsonschema::json5::locate(&config, &error) {
+//!         eprintln!("tsconfig.json:{}:{}: {error}", span.start.line, span.start.column);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::path::Path;
+
+pub use jsonlike::json5::{from_str, Kind, Number, ParseError, Position, Span, Value};
+use jsonlike::JsonError;
+
+use crate::{files, ValidationError};
+
+/// Read a JSON5 or JSONC document from `path`.
+pub fn load(path: impl AsRef<Path>) -> Result<Value, JsonError> {
+    files::load(path.as_ref(), from_str)
+}
+
+/// The source range of the value an error refers to.
+pub fn locate(document: &Value, error: &ValidationError) -> Option<Span> {
+    document
+        .pointer(&error.instance_path().to_string())
+        .map(Value::span)
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::json;
+    use test_case::test_case;
+
+    use super::{from_str, locate};
+    use crate::{blocking::validator_for, ValidationErrorKind};
+
+    #[test]
+    fn test_locate() {
+        let schema = json!({
+            "properties": {
+                "compilerOptions": {
+                    "properties": {
+                        "strict": {"type": "boolean"},
+                        "paths": {"additionalProperties": {"items": {"type": "string"}}}
+                    }
+                }
+            }
+        });
+        let validator = validator_for(&schema).expect("Valid schema");
+        let source = "// tsconfig\n{\n  compilerOptions: {\n    strict: 'yes', /* boolean */\n    paths: {'@/*': ['src/*', 42,],},\n  },\n}\n";
+        let config = from_str(source).expect("Valid JSONC");
+        let mut locations: Vec<_> = validator
+            .iter_errors(&config)
+            .map(|error| {
+                let span = locate(&config, &error).expect("Error location");
+                (span.start.line, span.start.column)
+            })
+            .collect();
+        locations.sort_unstable();
+        assert_eq!(locations, [(4, 13), (5, 30)]);
+        assert!(validator
+            .is_valid(&from_str("{compilerOptions: {strict: true}}").expect("Valid JSON5")));
+    }
+
+    #[test_case(json!({"minimum": 1}), "{retries: Infinity}")]
+    #[test_case(json!({"maximum": 1}), "{retries: -Infinity}")]
+    #[test_case(json!({"type": "number"}), "{retries: NaN}")]
+    #[test_case(json!({"multipleOf": 2}), "{retries: NaN}")]
+    fn test_non_finite_number(retries: serde_json::Value, config: &str) {
+        let schema = json!({"properties": {"retries": retries}});
+        let validator = validator_for(&schema).expect("Valid schema");
+        let config = from_str(config).expect("Valid JSON5");
+        assert!(!validator.is_valid(&config));
+        let errors: Vec<_> = validator.iter_errors(&config).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].instance_path().to_string(), "/retries");
+        assert!(matches!(
+            errors[0].kind(),
+            ValidationErrorKind::InvalidInstance { .. }
+        ));
+    }
+
+    #[test_case("{a: 1, a: 2}", true)]
+    #[test_case("{a: 1, b: 2, a: 3}", false)]
+    fn test_duplicate_keys(config: &str, expected: bool) {
+        let schema = json!({"maxProperties": 1});
+        let validator = validator_for(&schema).expect("Valid schema");
+        let config = from_str(config).expect("Valid JSON5");
+        assert_eq!(validator.is_valid(&config), expected);
+    }
+}
@@ -40,11 +40,32 @@
 //!     let validator = jsonschema::blocking::ValidatorBuilder::default()
 //!         .draft(Draft::Draft04)
 //!         .build(&schema)?;
+//!     // Fail if retrieving the documents referenced via `$ref` takes longer than 10 seconds
+//!     let validator = jsonschema::ValidatorBuilder::default()
+//!         .timeout(std::time::Duration::from_secs(10))
+//!         .build(&schema)
+//!         .await?;
+//!     // Assert `contentEncoding`, `contentMediaType` and `contentSchema` instead of treating them as annotations
+//!     let validator = jsonschema::ValidatorBuilder::default()
+//!         .validate_content(true)
+//!         .build(&schema)
+//!         .await?;
+//!     // Serialize a compiled validator and load it later without compiling the schema again (requires the `snapshot` feature)
+//!     #[cfg(feature = "snapshot")]
+//!     {
+//!         let bytes = validator.to_snapshot();
+//!         let snapshot = jsonschema::Snapshot::<serde_json::Value>::from_bytes(&bytes)?;
+//!         let validator = jsonschema::ValidatorBuilder::default().load(&snapshot)?;
+//!     }
 //!
 //!     // Boolean result
 //!     assert!(!validator.is_valid(&instance));
 //!     // First error as `Result<(), jsonschema::Error>`
 //!     assert!(validator.validate(&instance).is_err());
+//!     // Many instances across up to 4 threads, with results in input order
+//!     assert_eq!(validator.is_valid_many([&instance, &instance], 4), vec![false, false]);
+//!     // A document read incrementally from any `std::io::Read`, without loading it into memory
+//!     assert!(!validator.streaming().is_valid(&b"\"a\""[..])?);
 //!
 //!     // Iterate over errors
 //!     for error in validator.iter_errors(&instance) {
@@ -62,8 +83,14 @@
 //!     struct Resolver;
 //!
 //!     impl<J: Json> jsonschema::ReferenceResolver<J> for Resolver {
-//!         fn resolve_external(&self, url: &str) -> impl core::future::Future<Output = BuildResult<J>> {
-//!             async { Ok(J::from_str("{}")?) }
+//!         fn resolve_external<'a>(&'a self, url: &'a str) -> jsonschema::resolver::BoxedFuture<'a, BuildResult<J>> {
+//!             Box::pin(async { Ok(J::from_str("{}")?) })
+//!         }
+//!     };
+//!
+//!     impl<J: Json> jsonschema::blocking::ReferenceResolver<J> for Resolver {
+//!         fn resolve_external(&self, url: &str) -> BuildResult<J> {
+//!             Ok(J::from_str("{}")?)
 //!         }
 //!     };
 //!
@@ -130,21 +157,58 @@
 //!     Ok(())
 //! }
 //! ```
+mod background;
 pub mod blocking;
+mod bundle;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+pub mod compat;
 mod compiler;
+mod content;
+mod dereference;
 mod drafts;
 mod error;
+mod extensions;
+#[cfg(any(
+    feature = "cbor",
+    feature = "json5",
+    feature = "msgpack",
+    feature = "serde_yaml",
+    feature = "toml"
+))]
+mod files;
 mod format;
+pub mod generate;
 mod graph;
+pub mod infer;
+#[cfg(feature = "json5")]
+pub mod json5;
+pub mod lines;
+pub mod lint;
 mod maybe_owned;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+mod number;
 pub mod output;
-mod resolver;
+pub mod resolver;
+mod retrieval;
+#[cfg(feature = "snapshot")]
+mod snapshot;
+pub mod streaming;
+#[cfg(feature = "toml")]
+pub mod toml;
 mod validation;
+mod value;
 mod vocabulary;
+#[cfg(feature = "serde_yaml")]
+pub mod yaml;
 
+#[cfg(feature = "snapshot")]
+pub use crate::snapshot::Snapshot;
 pub use crate::{
+    content::{ContentEncoding, ContentMediaType},
     drafts::Draft,
-    error::{BuildError, ValidationError},
+    error::{BuildError, PrimitiveType, ValidationError, ValidationErrorKind},
     format::Format,
     output::Output,
     resolver::ReferenceResolver,
@@ -172,4 +236,13 @@ mod tests {
         assert_send_sync::<crate::Validator<serde_json::Value>>();
         assert_send_sync::<crate::ValidationError>();
     }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn test_build_is_send() {
+        fn assert_send<T: Send>(_: T) {}
+        let schema = serde_json::json!({"$ref": "https://example.com/a.json"});
+        let builder = crate::ValidatorBuilder::default();
+        assert_send(builder.build(&schema));
+    }
 }
@@ -0,0 +1,247 @@
+//! Validating newline-delimited JSON (JSON Lines), one instance per line.
+//!
+//! ```rust
+//! # #[cfg(feature = "serde_json")]
+//! # fn test() -> Result<(), Box<dyn std::error::Error>> {
+//! use jsonschema::lines::LineErrorKind;
+//!
+//! let schema = serde_json::json!({"required": ["id"]});
+//! let validator = jsonschema::blocking::validator_for(&schema)?;
+//! let input: &[u8] = b"{\"id\": 1}\n{\"name\": \"a\"}\n{\"id\": \n";
+//! let report = validator.lines().validate(input)?;
+//! assert_eq!(report.lines, 3);
+//! assert_eq!(report.errors[0].line, 2);
+//! assert!(matches!(report.errors[0].kind, LineErrorKind::Invalid { .. }));
+//! assert_eq!(report.errors[1].line, 3);
+//! assert!(matches!(report.errors[1].kind, LineErrorKind::Malformed { .. }));
+//! # Ok(())
+//! # }
+//! ```
+use core::{fmt, num::NonZeroUsize};
+use std::io::{self, BufRead};
+
+use jsonlike::Json;
+
+use crate::{ValidationError, Validator};
+
+/// A validator for JSON Lines read from a `BufRead`.
+///
+/// Created via `Validator::lines`. Blank lines are skipped, but still counted for line numbers.
+#[derive(Debug)]
+pub struct LinesValidator<'v, J: Json> {
+    validator: &'v Validator<J>,
+    max_errors: Option<NonZeroUsize>,
+}
+
+impl<'v, J: Json + 'static> LinesValidator<'v, J> {
+    /// Stop reading after `limit` lines with errors. A limit of 0 means no limit.
+    #[must_use]
+    pub fn max_errors(mut self, limit: usize) -> LinesValidator<'v, J> {
+        self.max_errors = NonZeroUsize::new(limit);
+        self
+    }
+    /// Validate every line of `reader`.
+    ///
+    /// Lines that are not valid JSON are reported as malformed instead of failing the whole
+    /// input; only errors of `reader` itself are returned as `Err`.
+    pub fn validate(&self, mut reader: impl BufRead) -> io::Result<LinesReport> {
+        let mut report = LinesReport::default();
+        let mut buffer = Vec::new();
+        loop {
+            buffer.clear();
+            if reader.read_until(b'\n', &mut buffer)? == 0 {
+                return Ok(report);
+            }
+            report.lines += 1;
+            let kind = match core::str::from_utf8(&buffer) {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => match J::from_str(line.trim_end_matches(['\n', '\r'])) {
+                    Ok(instance) => {
+                        let errors: Vec<_> = self.validator.iter_errors(&instance).collect();
+                        if errors.is_empty() {
+                            continue;
+                        }
+                        LineErrorKind::Invalid { errors }
+                    }
+                    Err(error) => LineErrorKind::Malformed {
+                        message: error.to_string(),
+                    },
+                },
+                Err(error) => LineErrorKind::Malformed {
+                    message: error.to_string(),
+                },
+            };
+            report.errors.push(LineError {
+                line: report.lines,
+                kind,
+            });
+            if self
+                .max_errors
+                .is_some_and(|limit| report.errors.len() >= limit.get())
+            {
+                report.stopped = !reader.fill_buf()?.is_empty();
+                return Ok(report);
+            }
+        }
+    }
+    /// Whether every line of `reader` is a valid instance.
+    pub fn is_valid(&self, reader: impl BufRead) -> io::Result<bool> {
+        let first_error = LinesValidator {
+            validator: self.validator,
+            max_errors: Some(NonZeroUsize::MIN),
+        };
+        Ok(first_error.validate(reader)?.errors.is_empty())
+    }
+}
+
+/// The outcome of validating JSON Lines.
+#[derive(Debug, Default)]
+pub struct LinesReport {
+    /// How many lines were read, including blank ones.
+    pub lines: usize,
+    /// Lines with errors, in input order.
+    pub errors: Vec<LineError>,
+    /// Whether the limit of `LinesValidator::max_errors` was reached before the end of the
+    /// input, so the rest of it was not read.
+    pub stopped: bool,
+}
+
+/// Errors of a single line.
+#[derive(Debug)]
+pub struct LineError {
+    /// The line number, starting at 1.
+    pub line: usize,
+    pub kind: LineErrorKind,
+}
+
+#[derive(Debug)]
+pub enum LineErrorKind {
+    /// The line is not a JSON document.
+    Malformed { message: String },
+    /// The line is a JSON document that is invalid against the schema.
+    Invalid { errors: Vec<ValidationError> },
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            LineErrorKind::Malformed { message } => {
+                write!(f, "Line {}: Invalid JSON: {message}", self.line)
+            }
+            LineErrorKind::Invalid { errors } => {
+                write!(f, "Line {}: ", self.line)?;
+                for (idx, error) in errors.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(f, "{error} at '{}'", error.instance_path())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<J: Json + 'static> Validator<J> {
+    /// Validate JSON Lines, one instance per line.
+    pub fn lines(&self) -> LinesValidator<'_, J> {
+        LinesValidator {
+            validator: self,
+            max_errors: None,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::json;
+    use test_case::test_case;
+
+    use super::LineErrorKind;
+    use crate::blocking::validator_for;
+
+    const INPUT: &[u8] = b"{\"id\": 1}\n\n{\"id\": \"a\"}\r\nnot json\n{\"id\": 2}\n{}";
+
+    #[test]
+    fn test_validate() {
+        let validator =
+            validator_for(&json!({"properties": {"id": {"type": "integer"}}, "required": ["id"]}))
+                .expect("Valid schema");
+        let report = validator.lines().validate(INPUT).expect("In-memory input");
+        assert_eq!(report.lines, 6);
+        assert!(!report.stopped);
+        let errors: Vec<_> = report.errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            errors,
+            [
+                "Line 3: Value is not of type \"integer\" at '/id'",
+                "Line 4: Invalid JSON: expected ident at line 1 column 2",
+                "Line 6: \"id\" is a required property at ''",
+            ]
+        );
+        assert!(matches!(
+            report.errors[1].kind,
+            LineErrorKind::Malformed { .. }
+        ));
+    }
+
+    #[test_case(0, &[3, 4, 6], false; "no limit")]
+    #[test_case(1, &[3], true)]
+    #[test_case(2, &[3, 4], true)]
+    #[test_case(3, &[3, 4, 6], false; "limit on the last line")]
+    #[test_case(5, &[3, 4, 6], false)]
+    fn test_max_errors(limit: usize, expected: &[usize], stopped: bool) {
+        let validator =
+            validator_for(&json!({"required": ["id"], "properties": {"id": {"type": "integer"}}}))
+                .expect("Valid schema");
+        let report = validator
+            .lines()
+            .max_errors(limit)
+            .validate(INPUT)
+            .expect("In-memory input");
+        let lines: Vec<_> = report.errors.iter().map(|error| error.line).collect();
+        assert_eq!(lines, expected);
+        assert_eq!(report.stopped, stopped);
+    }
+
+    #[test]
+    fn test_max_errors_trailing_newline() {
+        let validator = validator_for(&json!({"type": "integer"})).expect("Valid schema");
+        let report = validator
+            .lines()
+            .max_errors(1)
+            .validate(&b"1\n\"y\"\n"[..])
+            .expect("In-memory input");
+        assert_eq!(report.lines, 2);
+        assert_eq!(report.errors.len(), 1);
+        assert!(!report.stopped);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let validator = validator_for(&json!({})).expect("Valid schema");
+        let report = validator
+            .lines()
+            .validate(&b"1\n\"\xff\"\n"[..])
+            .expect("In-memory input");
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].line, 2);
+        assert!(matches!(
+            report.errors[0].kind,
+            LineErrorKind::Malformed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_is_valid() {
+        let validator = validator_for(&json!({"type": "integer"})).expect("Valid schema");
+        assert!(validator
+            .lines()
+            .is_valid(&b"1\n2\n"[..])
+            .expect("In-memory input"));
+        assert!(!validator
+            .lines()
+            .is_valid(&b"1\nx\n"[..])
+            .expect("In-memory input"));
+    }
+}
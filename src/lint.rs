@@ -0,0 +1,703 @@
+//! Detection of likely mistakes in schemas.
+//!
+//! ```rust
+//! # #[cfg(feature = "serde_json")]
+//! # fn main() -> Result<(), jsonschema::BuildError> {
+//! use jsonschema::lint::Severity;
+//! use serde_json::json;
+//!
+//! let schema = json!({"type": "object", "requried": ["name"]});
+//! let diagnostics = jsonschema::lint::lint(&schema)?;
+//! assert_eq!(diagnostics[0].severity, Severity::Warning);
+//! assert_eq!(diagnostics[0].location.to_string(), "/requried");
+//! assert_eq!(
+//!     diagnostics[0].to_string(),
+//!     "warning at '/requried': Unknown keyword `requried`, did you mean `required`?"
+//! );
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "serde_json"))]
+//! # fn main() {}
+//! ```
+use core::fmt;
+
+use jpointer::{JsonPointer, Segment};
+use jsonlike::prelude::*;
+use url::Url;
+
+use crate::{
+    compiler::{default_base, Resources},
+    drafts::{draft_from_schema, Draft},
+    error::PrimitiveType,
+    value,
+    vocabulary::object_entries,
+    BuildResult,
+};
+
+/// How likely a diagnostic points to a schema that does not work as intended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The schema can not work as intended.
+    Error,
+    /// The schema is probably not what was intended.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// A likely mistake in a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Lint {
+    /// A keyword the draft does not define, with a similarly spelled keyword if there is one.
+    UnknownKeyword {
+        keyword: String,
+        suggestion: Option<String>,
+    },
+    /// A keyword that has no effect on any of the types allowed by `type`.
+    TypeMismatch {
+        keyword: String,
+        types: Vec<PrimitiveType>,
+    },
+    /// A required property that `properties` and `additionalProperties: false` disallow.
+    RequiredNotAllowed { property: String },
+    /// A `oneOf` branch that can never be the only valid one. If it is identical to another
+    /// branch, `same_as` is the index of that branch.
+    UnreachableBranch {
+        index: usize,
+        same_as: Option<usize>,
+    },
+    /// A definition that no reference within the schema points to.
+    UnusedDefinition { name: String },
+    /// A sibling of `$ref`, which is ignored before 2019-09.
+    IgnoredSibling { keyword: String },
+    /// A reference into the schema itself that points to nothing.
+    UnresolvableReference { reference: String },
+}
+
+impl Lint {
+    /// How serious this lint is.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Lint::RequiredNotAllowed { .. }
+            | Lint::UnreachableBranch { .. }
+            | Lint::UnresolvableReference { .. } => Severity::Error,
+            Lint::UnknownKeyword { .. }
+            | Lint::TypeMismatch { .. }
+            | Lint::UnusedDefinition { .. }
+            | Lint::IgnoredSibling { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lint::UnknownKeyword {
+                keyword,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "Unknown keyword `{keyword}`, did you mean `{suggestion}`?"
+            ),
+            Lint::UnknownKeyword {
+                keyword,
+                suggestion: None,
+            } => write!(f, "Unknown keyword `{keyword}`"),
+            Lint::TypeMismatch { keyword, types } => {
+                write!(f, "`{keyword}` has no effect on instances of type ")?;
+                for (idx, ty) in types.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str(" or ")?;
+                    }
+                    ty.fmt(f)?;
+                }
+                Ok(())
+            }
+            Lint::RequiredNotAllowed { property } => write!(
+                f,
+                "Required property '{property}' is not allowed by `additionalProperties: false`"
+            ),
+            Lint::UnreachableBranch {
+                index,
+                same_as: Some(other),
+            } => write!(
+                f,
+                "`oneOf` branch {index} is identical to branch {other}, so neither can be the only match"
+            ),
+            Lint::UnreachableBranch {
+                index,
+                same_as: None,
+            } => write!(f, "`oneOf` branch {index} never matches"),
+            Lint::UnusedDefinition { name } => write!(f, "Definition '{name}' is never referenced"),
+            Lint::IgnoredSibling { keyword } => {
+                write!(f, "`{keyword}` is ignored next to `$ref` in this draft")
+            }
+            Lint::UnresolvableReference { reference } => {
+                write!(f, "Reference '{reference}' does not resolve within the schema")
+            }
+        }
+    }
+}
+
+/// A lint found at a location within the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub location: JsonPointer,
+    pub lint: Lint,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at '{}': {}", self.severity, self.location, self.lint)
+    }
+}
+
+/// Lint `schema` according to the draft declared by its `$schema`, or the latest draft.
+pub fn lint<J: Json>(schema: &J) -> BuildResult<Vec<Diagnostic>> {
+    lint_draft(schema, draft_from_schema(schema))
+}
+
+/// Lint `schema` according to `draft`.
+///
+/// Diagnostics are ordered by their location within the schema.
+pub fn lint_draft<J: Json>(schema: &J, draft: Draft) -> BuildResult<Vec<Diagnostic>> {
+    let mut resources = Resources::new(draft);
+    resources.add(default_base(), schema)?;
+    let mut linter = Linter {
+        draft,
+        resources,
+        location: JsonPointer::new(),
+        diagnostics: Vec::new(),
+        definitions: Vec::new(),
+        targets: Vec::new(),
+    };
+    linter.schema(&default_base(), schema)?;
+    let Linter {
+        mut diagnostics,
+        definitions,
+        targets,
+        ..
+    } = linter;
+    for (location, name) in definitions {
+        if !targets.iter().any(|target| starts_with(target, &location)) {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                location,
+                lint: Lint::UnusedDefinition { name },
+            });
+        }
+    }
+    diagnostics.sort_by_cached_key(|diagnostic| diagnostic.location.sort_key());
+    Ok(diagnostics)
+}
+
+/// Keywords of Draft 4 that later drafts kept. `definitions` is still widely used to hold
+/// subschemas after `$defs` replaced it, so it is known to all drafts.
+const LEGACY_KEYWORDS: &[&str] = &[
+    "$ref",
+    "$schema",
+    "additionalItems",
+    "additionalProperties",
+    "allOf",
+    "anyOf",
+    "default",
+    "definitions",
+    "dependencies",
+    "description",
+    "enum",
+    "exclusiveMaximum",
+    "exclusiveMinimum",
+    "format",
+    "items",
+    "maxItems",
+    "maxLength",
+    "maxProperties",
+    "maximum",
+    "minItems",
+    "minLength",
+    "minProperties",
+    "minimum",
+    "multipleOf",
+    "not",
+    "oneOf",
+    "pattern",
+    "patternProperties",
+    "properties",
+    "required",
+    "title",
+    "type",
+    "uniqueItems",
+];
+const DRAFT04_KEYWORDS: &[&str] = &["id"];
+const DRAFT06_KEYWORDS: &[&str] = &["$id", "const", "contains", "examples", "propertyNames"];
+const DRAFT07_KEYWORDS: &[&str] = &[
+    "$comment",
+    "contentEncoding",
+    "contentMediaType",
+    "else",
+    "if",
+    "readOnly",
+    "then",
+    "writeOnly",
+];
+const DRAFT2019_KEYWORDS: &[&str] = &[
+    "$anchor",
+    "$defs",
+    "$vocabulary",
+    "contentSchema",
+    "dependentRequired",
+    "dependentSchemas",
+    "deprecated",
+    "maxContains",
+    "minContains",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+const DRAFT201909_KEYWORDS: &[&str] = &["$recursiveAnchor", "$recursiveRef"];
+const DRAFT202012_KEYWORDS: &[&str] = &["$dynamicAnchor", "$dynamicRef", "prefixItems"];
+
+/// All keywords `draft` defines, in groups.
+fn keywords(draft: Draft) -> Vec<&'static [&'static str]> {
+    match draft {
+        Draft::Draft04 => vec![LEGACY_KEYWORDS, DRAFT04_KEYWORDS],
+        Draft::Draft06 => vec![LEGACY_KEYWORDS, DRAFT06_KEYWORDS],
+        Draft::Draft07 => vec![LEGACY_KEYWORDS, DRAFT06_KEYWORDS, DRAFT07_KEYWORDS],
+        Draft::Draft201909 => vec![
+            LEGACY_KEYWORDS,
+            DRAFT06_KEYWORDS,
+            DRAFT07_KEYWORDS,
+            DRAFT2019_KEYWORDS,
+            DRAFT201909_KEYWORDS,
+        ],
+        // `additionalItems` is gone, `items` took its place next to `prefixItems`
+        Draft::Draft202012 => vec![
+            LEGACY_KEYWORDS,
+            DRAFT06_KEYWORDS,
+            DRAFT07_KEYWORDS,
+            DRAFT2019_KEYWORDS,
+            DRAFT202012_KEYWORDS,
+        ],
+    }
+}
+
+fn is_known(draft: Draft, keyword: &str) -> bool {
+    if let (Draft::Draft202012, "additionalItems") = (draft, keyword) {
+        return false;
+    }
+    keywords(draft).iter().any(|group| group.contains(&keyword))
+}
+
+/// The known keyword closest to `keyword`, if it is likely a typo of it.
+fn suggest(draft: Draft, keyword: &str) -> Option<String> {
+    keywords(draft)
+        .iter()
+        .flat_map(|group| group.iter())
+        .filter(|known| is_known(draft, known))
+        .map(|known| (distance(keyword, known), *known))
+        .filter(|(distance, known)| *distance <= 2 && *distance < known.len() / 2)
+        .min()
+        .map(|(_, known)| known.to_owned())
+}
+
+/// The Levenshtein distance between two strings.
+fn distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    for (i, l) in left.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, r) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(l != *r);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[right.len()]
+}
+
+/// The types a keyword applies to, if it is specific to some.
+fn applicable_types(keyword: &str) -> Option<&'static [PrimitiveType]> {
+    match keyword {
+        "maxLength" | "minLength" | "pattern" => Some(&[PrimitiveType::String]),
+        "multipleOf" | "maximum" | "exclusiveMaximum" | "minimum" | "exclusiveMinimum" => {
+            Some(&[PrimitiveType::Number, PrimitiveType::Integer])
+        }
+        "items" | "prefixItems" | "additionalItems" | "maxItems" | "minItems" | "uniqueItems"
+        | "contains" | "maxContains" | "minContains" | "unevaluatedItems" => {
+            Some(&[PrimitiveType::Array])
+        }
+        "properties"
+        | "patternProperties"
+        | "additionalProperties"
+        | "required"
+        | "maxProperties"
+        | "minProperties"
+        | "propertyNames"
+        | "dependencies"
+        | "dependentRequired"
+        | "dependentSchemas"
+        | "unevaluatedProperties" => Some(&[PrimitiveType::Object]),
+        _ => None,
+    }
+}
+
+fn starts_with(pointer: &JsonPointer, prefix: &JsonPointer) -> bool {
+    pointer.len() >= prefix.len() && pointer.iter().zip(prefix.iter()).all(|(l, r)| l == r)
+}
+
+struct Linter<'a, J: Json> {
+    draft: Draft,
+    resources: Resources<'a, J>,
+    location: JsonPointer,
+    diagnostics: Vec<Diagnostic>,
+    /// Locations and names of definitions that may be unused.
+    definitions: Vec<(JsonPointer, String)>,
+    /// Locations references point to.
+    targets: Vec<JsonPointer>,
+}
+
+impl<'a, J: Json> Linter<'a, J> {
+    fn report(&mut self, location: JsonPointer, lint: Lint) {
+        self.diagnostics.push(Diagnostic {
+            severity: lint.severity(),
+            location,
+            lint,
+        });
+    }
+
+    fn is_legacy(&self) -> bool {
+        matches!(self.draft, Draft::Draft04 | Draft::Draft06 | Draft::Draft07)
+    }
+
+    fn schema(&mut self, base: &Url, schema: &'a J) -> BuildResult<()> {
+        let Some(object) = schema.as_object() else {
+            return Ok(());
+        };
+        let scope = self.resources.scope(base, schema);
+        for keyword in ["$ref", "$dynamicRef", "$recursiveRef"] {
+            if let Some(reference) = object.get(keyword).and_then(Json::as_string) {
+                self.reference(&scope, keyword, reference.as_ref());
+            }
+        }
+        if self.is_legacy() && object.contains_key("$ref") {
+            for entry in object_entries::<J>(object) {
+                let (key, value) = entry?;
+                match key {
+                    "$ref" | "$comment" => {}
+                    // Definitions are still reachable via JSON pointers
+                    "definitions" => self.at(key, |linter| linter.definitions(&scope, value))?,
+                    _ => self.report(
+                        self.location.join(key),
+                        Lint::IgnoredSibling {
+                            keyword: key.to_owned(),
+                        },
+                    ),
+                }
+            }
+            return Ok(());
+        }
+        let types = declared_types::<J>(object);
+        for entry in object_entries::<J>(object) {
+            let (key, value) = entry?;
+            if !is_known(self.draft, key) {
+                let suggestion = suggest(self.draft, key);
+                self.report(
+                    self.location.join(key),
+                    Lint::UnknownKeyword {
+                        keyword: key.to_owned(),
+                        suggestion,
+                    },
+                );
+                continue;
+            }
+            if let (Some(types), Some(applicable)) = (&types, applicable_types(key)) {
+                if !types.is_empty() && !types.iter().any(|ty| applicable.contains(ty)) {
+                    self.report(
+                        self.location.join(key),
+                        Lint::TypeMismatch {
+                            keyword: key.to_owned(),
+                            types: types.clone(),
+                        },
+                    );
+                }
+            }
+            self.at(key, |linter| linter.keyword(&scope, key, value))?;
+        }
+        self.closed_properties(object);
+        Ok(())
+    }
+
+    /// Lint the subschemas in the value of a known keyword.
+    fn keyword(&mut self, scope: &Url, keyword: &str, value: &'a J) -> BuildResult<()> {
+        match keyword {
+            "additionalItems"
+            | "additionalProperties"
+            | "contains"
+            | "contentSchema"
+            | "else"
+            | "if"
+            | "not"
+            | "propertyNames"
+            | "then"
+            | "unevaluatedItems"
+            | "unevaluatedProperties" => self.schema(scope, value),
+            "items" if value.as_array().is_none() => self.schema(scope, value),
+            "allOf" | "anyOf" | "items" | "prefixItems" => self.schemas(scope, value),
+            "oneOf" => {
+                self.unreachable_branches(value)?;
+                self.schemas(scope, value)
+            }
+            "dependencies" | "dependentSchemas" | "patternProperties" | "properties" => {
+                self.named_schemas(scope, value)
+            }
+            "$defs" | "definitions" => self.definitions(scope, value),
+            _ => Ok(()),
+        }
+    }
+
+    fn at(
+        &mut self,
+        key: impl Into<Segment>,
+        lint: impl FnOnce(&mut Self) -> BuildResult<()>,
+    ) -> BuildResult<()> {
+        self.location.push(key);
+        let result = lint(self);
+        self.location.pop();
+        result
+    }
+
+    fn schemas(&mut self, scope: &Url, value: &'a J) -> BuildResult<()> {
+        if let Some(array) = value.as_array() {
+            for (idx, item) in array.try_iter()?.enumerate() {
+                let item = item?;
+                self.at(idx, |linter| linter.schema(scope, item))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn named_schemas(&mut self, scope: &Url, value: &'a J) -> BuildResult<()> {
+        if let Some(object) = value.as_object() {
+            for entry in object_entries::<J>(object) {
+                let (name, schema) = entry?;
+                self.at(name, |linter| linter.schema(scope, schema))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn definitions(&mut self, scope: &Url, value: &'a J) -> BuildResult<()> {
+        if let Some(object) = value.as_object() {
+            let id = if let Draft::Draft04 = self.draft {
+                "id"
+            } else {
+                "$id"
+            };
+            for entry in object_entries::<J>(object) {
+                let (name, schema) = entry?;
+                // Resources with an `$id` may be referenced from other documents
+                let is_resource = schema
+                    .as_object()
+                    .is_some_and(|schema| schema.contains_key(id));
+                if !is_resource {
+                    self.definitions
+                        .push((self.location.join(name), name.to_owned()));
+                }
+            }
+        }
+        self.named_schemas(scope, value)
+    }
+
+    fn reference(&mut self, scope: &Url, keyword: &str, reference: &str) {
+        if let Some(target) = self.resources.lookup(scope, reference) {
+            self.targets.push(target.location);
+            return;
+        }
+        let Ok(mut uri) = scope.join(reference) else {
+            return;
+        };
+        uri.set_fragment(None);
+        // References to other documents can only be checked once they are retrieved
+        if self.resources.uris().any(|known| *known == uri) {
+            self.report(
+                self.location.join(keyword),
+                Lint::UnresolvableReference {
+                    reference: reference.to_owned(),
+                },
+            );
+        }
+    }
+
+    fn closed_properties(&mut self, object: &J::Object) {
+        if object
+            .get("additionalProperties")
+            .and_then(Json::as_boolean)
+            != Some(false)
+            || object.contains_key("patternProperties")
+        {
+            return;
+        }
+        let Some(required) = object.get("required").and_then(Json::as_array) else {
+            return;
+        };
+        let properties = object.get("properties").and_then(Json::as_object);
+        for (idx, name) in required.iter().enumerate() {
+            let Some(name) = name.ok().and_then(Json::as_string) else {
+                continue;
+            };
+            let name = name.as_ref();
+            if !properties.is_some_and(|properties| properties.contains_key(name)) {
+                self.report(
+                    self.location.join("required").join(idx),
+                    Lint::RequiredNotAllowed {
+                        property: name.to_owned(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn unreachable_branches(&mut self, value: &J) -> BuildResult<()> {
+        let Some(array) = value.as_array() else {
+            return Ok(());
+        };
+        let branches = array.try_iter()?.collect::<Result<Vec<_>, _>>()?;
+        for (index, branch) in branches.iter().enumerate() {
+            let same_as = branches[..index]
+                .iter()
+                .position(|other| value::equal(*other, *branch));
+            if same_as.is_some() || never_valid(*branch) {
+                self.report(
+                    self.location.join(index),
+                    Lint::UnreachableBranch { index, same_as },
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Types allowed by `type`, if present and well-formed.
+fn declared_types<J: Json>(object: &J::Object) -> Option<Vec<PrimitiveType>> {
+    let value = object.get("type")?;
+    if let Some(name) = value.as_string() {
+        return PrimitiveType::from_str(name.as_ref()).map(|ty| vec![ty]);
+    }
+    value
+        .as_array()?
+        .iter()
+        .map(|item| {
+            item.ok()
+                .and_then(Json::as_string)
+                .and_then(|name| PrimitiveType::from_str(name.as_ref()))
+        })
+        .collect()
+}
+
+/// Whether no instance is valid against `schema`, judging by its form.
+fn never_valid<J: Json>(schema: &J) -> bool {
+    if let Some(valid) = schema.as_boolean() {
+        return !valid;
+    }
+    let Some(object) = schema.as_object() else {
+        return false;
+    };
+    object.len() == 1
+        && object.get("not").is_some_and(|not| {
+            not.as_boolean() == Some(true) || not.as_object().is_some_and(|not| not.is_empty())
+        })
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{lint, lint_draft, Severity};
+    use crate::Draft;
+
+    fn messages(schema: &Value, draft: Draft) -> Vec<String> {
+        lint_draft(schema, draft)
+            .expect("Valid JSON")
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test_case(json!({"requried": ["a"]}), "warning at '/requried': Unknown keyword `requried`, did you mean `required`?"; "typo")]
+    #[test_case(json!({"properties": {"a": {"minLenght": 1}}}), "warning at '/properties/a/minLenght': Unknown keyword `minLenght`, did you mean `minLength`?"; "nested typo")]
+    #[test_case(json!({"x-internal": true}), "warning at '/x-internal': Unknown keyword `x-internal`"; "unknown")]
+    #[test_case(json!({"type": "integer", "minLength": 1}), "warning at '/minLength': `minLength` has no effect on instances of type integer"; "type mismatch")]
+    #[test_case(json!({"type": ["string", "null"], "minimum": 1}), "warning at '/minimum': `minimum` has no effect on instances of type string or null"; "type mismatch with several types")]
+    #[test_case(json!({"properties": {"a": {}}, "required": ["a", "b"], "additionalProperties": false}), "error at '/required/1': Required property 'b' is not allowed by `additionalProperties: false`"; "closed properties")]
+    #[test_case(json!({"oneOf": [{"type": "string"}, false]}), "error at '/oneOf/1': `oneOf` branch 1 never matches"; "false branch")]
+    #[test_case(json!({"oneOf": [{"not": {}}, {}]}), "error at '/oneOf/0': `oneOf` branch 0 never matches"; "negated branch")]
+    #[test_case(json!({"oneOf": [{"type": "string"}, {"type": "integer"}, {"type": "string"}]}), "error at '/oneOf/2': `oneOf` branch 2 is identical to branch 0, so neither can be the only match"; "duplicate branch")]
+    #[test_case(json!({"$defs": {"a": {}, "b": {}}, "$ref": "#/$defs/a"}), "warning at '/$defs/b': Definition 'b' is never referenced"; "unused definition")]
+    #[test_case(json!({"$ref": "#/$defs/missing"}), "error at '/$ref': Reference '#/$defs/missing' does not resolve within the schema"; "unresolvable reference")]
+    fn test_lint(schema: Value, expected: &str) {
+        assert_eq!(messages(&schema, Draft::Draft202012), [expected]);
+    }
+
+    #[test_case(json!({"type": "object", "properties": {"a": {"type": "string", "minLength": 1}}, "required": ["a"], "additionalProperties": false}); "closed object")]
+    #[test_case(json!({"type": ["integer", "string"], "minimum": 1, "minLength": 1}); "several types")]
+    #[test_case(json!({"$defs": {"a": {"properties": {"b": {"$ref": "#/$defs/c"}}}, "c": {}}, "$ref": "#/$defs/a"}); "nested references")]
+    #[test_case(json!({"$defs": {"a": {"$anchor": "x"}, "b": {"$id": "b.json"}}, "$ref": "#x"}); "anchors and resources")]
+    #[test_case(json!({"$ref": "https://example.com/other.json"}); "external reference")]
+    #[test_case(json!({"properties": {"requried": {}}, "const": {"requried": 1}}); "names and data")]
+    #[test_case(json!({"oneOf": [{"type": "string"}, {"type": "integer"}]}); "distinct branches")]
+    #[test_case(json!({"required": ["b"], "additionalProperties": false, "patternProperties": {"^b$": {}}}); "pattern properties")]
+    fn test_clean(schema: Value) {
+        assert_eq!(messages(&schema, Draft::Draft202012), Vec::<String>::new());
+    }
+
+    #[test_case(Draft::Draft04)]
+    #[test_case(Draft::Draft06)]
+    #[test_case(Draft::Draft07)]
+    fn test_ignored_siblings(draft: Draft) {
+        let schema = json!({
+            "$ref": "#/definitions/a",
+            "definitions": {"a": {}, "b": {}},
+            "type": "string",
+            "$comment": "Not ignored, but harmless",
+        });
+        assert_eq!(
+            messages(&schema, draft),
+            [
+                "warning at '/definitions/b': Definition 'b' is never referenced",
+                "warning at '/type': `type` is ignored next to `$ref` in this draft",
+            ]
+        );
+    }
+
+    #[test_case(Draft::Draft04, json!({"const": 1}), "warning at '/const': Unknown keyword `const`")]
+    #[test_case(Draft::Draft07, json!({"$defs": {}}), "warning at '/$defs': Unknown keyword `$defs`")]
+    #[test_case(Draft::Draft202012, json!({"additionalItems": false}), "warning at '/additionalItems': Unknown keyword `additionalItems`")]
+    #[test_case(Draft::Draft201909, json!({"$dynamicRef": "#"}), "warning at '/$dynamicRef': Unknown keyword `$dynamicRef`")]
+    fn test_draft_keywords(draft: Draft, schema: Value, expected: &str) {
+        assert_eq!(messages(&schema, draft), [expected]);
+    }
+
+    #[test]
+    fn test_detected_draft() {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "$ref": "#/definitions/a",
+            "definitions": {"a": {}},
+            "title": "Ignored",
+        });
+        let diagnostics = lint(&schema).expect("Valid JSON");
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Warning));
+    }
+}
@@ -0,0 +1,111 @@
+//! Loading instances from MessagePack files.
+//!
+//! `rmpv::Value` implements `Json`, so MessagePack documents are validated without converting
+//! them to JSON first. Binary data, extension types, strings with invalid UTF-8 and non-finite
+//! floats have no JSON equivalent: a keyword that needs to read them reports
+//! `ValidationErrorKind::InvalidInstance`.
+//!
+//! ```rust,no_run
+//! # fn test() -> Result<(), Box<dyn std::error::Error>> {
+//! let schema = serde_json::json!({"required": ["id"]});
+//! let validator = jsonschema::blocking::validator_for(&schema)?;
+//! let event = jsonschema::msgpack::load("event.msgpack")?;
+//! validator.validate(&event)?;
+//! # Ok(())
+//! # }
+//! ```
+use std::path::Path;
+
+use jsonlike::JsonError;
+use rmpv::Value;
+
+use crate::files;
+
+/// Decode a single MessagePack value.
+pub fn from_slice(mut bytes: &[u8]) -> Result<Value, JsonError> {
+    rmpv::decode::read_value(&mut bytes).map_err(|err| JsonError::new(Box::new(err)))
+}
+
+/// Read a MessagePack document from `path`.
+pub fn load(path: impl AsRef<Path>) -> Result<Value, JsonError> {
+    files::load_bytes(path.as_ref(), from_slice)
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use std::fs;
+
+    use rmpv::Value;
+    use serde_json::json;
+    use test_case::test_case;
+
+    use super::{from_slice, load};
+    use crate::{blocking::validator_for, ValidationErrorKind};
+
+    #[test_case(&[0x81, 0xa2, 0x69, 0x64, 0x01], true; "integer id")]
+    #[test_case(&[0x81, 0xa2, 0x69, 0x64, 0xa1, 0x31], false; "string id")]
+    #[test_case(&[0x80], false; "missing id")]
+    fn test_validate(bytes: &[u8], expected: bool) {
+        let schema = json!({"properties": {"id": {"type": "integer"}}, "required": ["id"]});
+        let validator = validator_for(&schema).expect("Valid schema");
+        let instance = from_slice(bytes).expect("Valid MessagePack");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[test_case(json!({"type": "integer"}), Value::Binary(vec![1]))]
+    #[test_case(json!({"type": "string"}), Value::Ext(1, vec![0]))]
+    #[test_case(json!({"minimum": 1}), Value::F64(f64::NAN))]
+    #[test_case(json!({"multipleOf": 2}), Value::F32(f32::INFINITY))]
+    #[test_case(json!({"maxLength": 1}), Value::Binary(vec![1]))]
+    #[test_case(json!({"pattern": "^a"}), from_slice(&[0xa1, 0xff]).expect("Valid MessagePack"))]
+    #[test_case(json!({"const": 1}), Value::F64(f64::NAN))]
+    #[test_case(json!({"enum": [[1]]}), Value::Array(vec![Value::Binary(vec![1])]))]
+    #[test_case(json!({"uniqueItems": true}), Value::Array(vec![Value::Binary(vec![1]), Value::Binary(vec![1])]))]
+    #[test_case(json!({"required": ["a"]}), Value::Binary(vec![1]))]
+    #[test_case(json!({"properties": {"a": true}}), Value::Ext(1, vec![0]))]
+    #[test_case(json!({"items": {"minimum": 1}}), Value::Binary(vec![1]))]
+    fn test_instance_without_json_equivalent(schema: serde_json::Value, instance: Value) {
+        let validator = validator_for(&schema).expect("Valid schema");
+        assert!(!validator.is_valid(&instance));
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind(),
+            ValidationErrorKind::InvalidInstance { .. }
+        ));
+    }
+
+    #[test]
+    fn test_nested_instance_without_json_equivalent() {
+        let schema = json!({"properties": {"a": {"type": "string"}}});
+        let validator = validator_for(&schema).expect("Valid schema");
+        let instance = Value::Map(vec![(Value::from("a"), Value::Binary(vec![1]))]);
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].instance_path().to_string(), "/a");
+        assert!(matches!(
+            errors[0].kind(),
+            ValidationErrorKind::InvalidInstance { .. }
+        ));
+    }
+
+    #[test_case(&[]; "empty")]
+    #[test_case(&[0x81, 0xa2, 0x69]; "truncated")]
+    fn test_from_slice_invalid(bytes: &[u8]) {
+        assert!(from_slice(bytes).is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join(format!("jsonschema-msgpack-{}", std::process::id()));
+        fs::write(&path, [0x81, 0xa2, 0x69, 0x64, 0x01]).expect("Can write files");
+        let instance = load(&path).expect("Valid MessagePack");
+        fs::remove_file(&path).expect("Can remove files");
+        assert_eq!(
+            instance,
+            Value::Map(vec![(Value::from("id"), Value::from(1))])
+        );
+        let error = load(&path).expect_err("Missing file");
+        assert!(error.to_string().starts_with(&path.display().to_string()));
+    }
+}
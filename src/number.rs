@@ -0,0 +1,260 @@
+//! Exact numeric semantics.
+//!
+//! Numbers are compared via `f64` whenever that gives the exact answer. Only if the floats can
+//! not tell two numbers apart, or for `multipleOf`, numbers are converted to exact decimals.
+use core::cmp::Ordering;
+
+use jsonlike::{JsonNumber, MAX_SAFE_INTEGER};
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+/// An arbitrary precision decimal number: `(-1)^negative * mantissa * 10^exponent`.
+///
+/// The mantissa has no trailing zeros, so every number has exactly one representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Decimal {
+    negative: bool,
+    mantissa: BigUint,
+    exponent: i64,
+    /// Number of decimal digits in the mantissa.
+    digits: i64,
+}
+
+impl Decimal {
+    /// Parse a JSON number literal.
+    pub(crate) fn parse(value: &str) -> Option<Decimal> {
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        let (significand, exponent) = match value.find(['e', 'E']) {
+            Some(idx) => (&value[..idx], value[idx + 1..].parse::<i64>().ok()?),
+            None => (value, 0),
+        };
+        let (integer, fraction) = match significand.split_once('.') {
+            Some((_, "")) => return None,
+            Some(parts) => parts,
+            None => (significand, ""),
+        };
+        if integer.is_empty()
+            || !integer.bytes().all(|byte| byte.is_ascii_digit())
+            || !fraction.bytes().all(|byte| byte.is_ascii_digit())
+        {
+            return None;
+        }
+        let mut digits = String::with_capacity(integer.len() + fraction.len());
+        digits.push_str(integer);
+        digits.push_str(fraction);
+        let significant = digits.trim_start_matches('0');
+        let trimmed = significant.trim_end_matches('0');
+        if trimmed.is_empty() {
+            return Some(Decimal::zero());
+        }
+        let exponent = exponent
+            .checked_sub(i64::try_from(fraction.len()).ok()?)?
+            .checked_add(i64::try_from(significant.len() - trimmed.len()).ok()?)?;
+        Some(Decimal {
+            negative,
+            mantissa: trimmed.parse().ok()?,
+            exponent,
+            digits: i64::try_from(trimmed.len()).ok()?,
+        })
+    }
+
+    /// Exact value of a number from any backend.
+    pub(crate) fn from_number<'n, N: JsonNumber<'n> + ?Sized>(number: &'n N) -> Option<Decimal> {
+        match number.as_decimal() {
+            Some(decimal) => Decimal::parse(&decimal),
+            // `f64` formatting yields the shortest representation that round-trips, so `0.1`
+            // stays `0.1` instead of its binary approximation.
+            None => Decimal::parse(&number.as_float()?.to_string()),
+        }
+    }
+
+    fn zero() -> Decimal {
+        Decimal {
+            negative: false,
+            mantissa: BigUint::zero(),
+            exponent: 0,
+            digits: 0,
+        }
+    }
+
+    pub(crate) fn is_positive(&self) -> bool {
+        !self.negative && !self.mantissa.is_zero()
+    }
+
+    pub(crate) fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    pub(crate) fn is_multiple_of(&self, divisor: &Decimal) -> bool {
+        if self.mantissa.is_zero() {
+            return true;
+        }
+        if divisor.mantissa.is_zero() || self.exponent < divisor.exponent {
+            // Without trailing zeros the mantissa can not be divisible by a power of ten
+            return false;
+        }
+        // self / divisor = (m1 / m2) * 10^(e1 - e2)
+        let scale = BigUint::from(10u8).modpow(
+            &BigUint::from(self.exponent.abs_diff(divisor.exponent)),
+            &divisor.mantissa,
+        );
+        ((&self.mantissa % &divisor.mantissa) * scale % &divisor.mantissa).is_zero()
+    }
+
+    fn cmp_magnitude(&self, other: &Decimal) -> Ordering {
+        // Position of the most significant digit decides unless both are the same
+        let (top, other_top) = (
+            i128::from(self.digits) + i128::from(self.exponent),
+            i128::from(other.digits) + i128::from(other.exponent),
+        );
+        if top != other_top {
+            return top.cmp(&other_top);
+        }
+        // Both exponents differ by at most the number of digits, so scaling is cheap
+        let exponent = self.exponent.min(other.exponent);
+        let scale = |decimal: &Decimal| {
+            &decimal.mantissa * BigUint::from(10u8).pow(decimal.exponent.abs_diff(exponent) as u32)
+        };
+        scale(self).cmp(&scale(other))
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = |decimal: &Decimal| match (decimal.mantissa.is_zero(), decimal.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        };
+        match (sign(self), sign(other)) {
+            (0, 0) => Ordering::Equal,
+            (left, right) if left != right => left.cmp(&right),
+            (-1, _) => other.cmp_magnitude(self),
+            _ => self.cmp_magnitude(other),
+        }
+    }
+}
+
+/// Compare two numbers, possibly from different backends.
+pub(crate) fn compare<L, R>(left: &L, right: &R) -> Option<Ordering>
+where
+    L: for<'a> JsonNumber<'a> + ?Sized,
+    R: for<'a> JsonNumber<'a> + ?Sized,
+{
+    let floats = (left.as_float(), right.as_float());
+    if let (Some(left), Some(right)) = floats {
+        // Rounding to `f64` is monotonic, so distinct floats come from distinct numbers. Numbers
+        // beyond the `f64` range are compared as decimals.
+        if left != right && left.is_finite() && right.is_finite() {
+            return left.partial_cmp(&right);
+        }
+    }
+    if left.as_decimal().is_none() && right.as_decimal().is_none() {
+        return floats.0?.partial_cmp(&floats.1?);
+    }
+    Some(Decimal::from_number(left)?.cmp(&Decimal::from_number(right)?))
+}
+
+pub(crate) fn equal<L, R>(left: &L, right: &R) -> bool
+where
+    L: for<'a> JsonNumber<'a> + ?Sized,
+    R: for<'a> JsonNumber<'a> + ?Sized,
+{
+    compare(left, right) == Some(Ordering::Equal)
+}
+
+/// Whether a number has no fractional part, e.g. `1.0` or `1e100`.
+pub(crate) fn is_integer<N: for<'a> JsonNumber<'a> + ?Sized>(number: &N) -> bool {
+    if let Some(decimal) = number.as_decimal() {
+        return Decimal::parse(&decimal).is_some_and(|decimal| decimal.is_integer());
+    }
+    number.as_float().is_some_and(|value| value.fract() == 0.0)
+}
+
+/// Whether `number` is a multiple of `divisor`, which should be computed once upfront.
+pub(crate) fn is_multiple_of<N: for<'a> JsonNumber<'a> + ?Sized>(
+    number: &N,
+    divisor: &Decimal,
+    divisor_float: f64,
+) -> bool {
+    if number.as_decimal().is_none() {
+        if let Some(value) = number.as_float() {
+            // Integers within the safe range are exact in both representations
+            let is_safe =
+                |value: f64| value.fract() == 0.0 && value.abs() <= MAX_SAFE_INTEGER as f64;
+            if is_safe(value) && is_safe(divisor_float) && divisor.is_integer() {
+                return value % divisor_float == 0.0;
+            }
+        }
+    }
+    Decimal::from_number(number).is_some_and(|value| value.is_multiple_of(divisor))
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::Decimal;
+
+    #[test_case("0", "0.0", true)]
+    #[test_case("-0", "0e10", true)]
+    #[test_case("1.50", "15e-1", true)]
+    #[test_case("100", "1e2", true)]
+    #[test_case("0.1", "0.10000000000000001", false)]
+    fn test_parse_equal(left: &str, right: &str, expected: bool) {
+        assert_eq!(Decimal::parse(left) == Decimal::parse(right), expected);
+    }
+
+    #[test_case("1.")]
+    #[test_case(".1")]
+    #[test_case("1e")]
+    #[test_case("inf")]
+    #[test_case("NaN")]
+    #[test_case("1e99999999999999999999")]
+    fn test_parse_invalid(value: &str) {
+        assert!(Decimal::parse(value).is_none());
+    }
+
+    #[test_case("1", "2")]
+    #[test_case("-2", "-1")]
+    #[test_case("-1", "0")]
+    #[test_case("0.1", "0.11")]
+    #[test_case("99", "1e2")]
+    #[test_case("9007199254740992", "9007199254740993")]
+    #[test_case("1e-400", "1e-399")]
+    fn test_less_than(smaller: &str, larger: &str) {
+        let (smaller, larger) = (
+            Decimal::parse(smaller).expect("Invalid number"),
+            Decimal::parse(larger).expect("Invalid number"),
+        );
+        assert!(smaller < larger);
+        assert!(larger > smaller);
+    }
+
+    #[test_case("0.3", "0.1", true)]
+    #[test_case("19.99", "0.01", true)]
+    #[test_case("19.999", "0.01", false)]
+    #[test_case("1e308", "3", false)]
+    #[test_case("3e1000", "3", true)]
+    #[test_case("0", "0.7", true)]
+    #[test_case("-4.5", "1.5", true)]
+    #[test_case("18446744073709551616", "2", true)]
+    #[test_case("18446744073709551617", "2", false)]
+    fn test_is_multiple_of(value: &str, divisor: &str, expected: bool) {
+        let (value, divisor) = (
+            Decimal::parse(value).expect("Invalid number"),
+            Decimal::parse(divisor).expect("Invalid number"),
+        );
+        assert_eq!(value.is_multiple_of(&divisor), expected);
+    }
+}
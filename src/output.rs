@@ -1,19 +1,20 @@
 use std::collections::BTreeMap;
 
-use crate::{maybe_owned::MaybeOwned, Validator};
-use jpointer::JsonPointer;
+use crate::{maybe_owned::MaybeOwned, ValidationError, Validator};
+use jpointer::{JsonPointer, Segment};
 use jsonlike::Json;
 
-pub struct Output<'v, 'i, J: Json> {
+/// Evaluation result of an instance of type `I` against a schema of type `J`.
+pub struct Output<'v, 'i, J: Json, I: Json = J> {
     validator: MaybeOwned<'v, Validator<J>>,
-    instance: &'i J,
+    instance: &'i I,
 }
 
-impl<'v, 'i, J: Json> Output<'v, 'i, J> {
+impl<'v, 'i, J: Json + 'static, I: Json + 'static> Output<'v, 'i, J, I> {
     pub(crate) fn new(
         validator: MaybeOwned<'v, Validator<J>>,
-        instance: &'i J,
-    ) -> Output<'v, 'i, J> {
+        instance: &'i I,
+    ) -> Output<'v, 'i, J, I> {
         Output {
             validator,
             instance,
@@ -24,11 +25,140 @@ impl<'v, 'i, J: Json> Output<'v, 'i, J> {
             valid: self.validator.is_valid(self.instance),
         }
     }
+    /// Errors as a flat list with one unit per failed keyword.
     pub fn list(&self) -> List<J> {
-        todo!()
+        let errors = self.validator.collect_errors(self.instance);
+        List {
+            valid: errors.is_empty(),
+            nested: errors.iter().map(error_unit).collect(),
+        }
     }
+    /// Errors nested by evaluation path: each failed subschema is a unit under the unit of its
+    /// parent schema, and failed keywords are reported by the unit of their own schema.
     pub fn hierarchical(&self) -> Hierarchical<J> {
-        todo!()
+        let errors = self.validator.collect_errors(self.instance);
+        let mut root = failed_unit(JsonPointer::new(), JsonPointer::new());
+        root.valid = errors.is_empty();
+        for error in &errors {
+            let mut evaluation_path = error.schema_path().clone();
+            let keyword = evaluation_path.pop().map(|segment| segment.to_string());
+            let mut unit = &mut root;
+            for (evaluation_path, instance_location) in
+                subschemas(&evaluation_path, error.instance_path())
+            {
+                let nested = unit.nested.get_or_insert_with(Vec::new);
+                let idx = if let Some(idx) = nested.iter().position(|unit| {
+                    unit.evaluation_path == evaluation_path
+                        && unit.instance_location == instance_location
+                }) {
+                    idx
+                } else {
+                    nested.push(failed_unit(evaluation_path, instance_location));
+                    nested.len() - 1
+                };
+                unit = &mut nested[idx];
+            }
+            unit.errors
+                .get_or_insert_with(BTreeMap::new)
+                .insert(keyword.unwrap_or_default(), error.to_string());
+        }
+        root
+    }
+}
+
+/// Keywords whose subschemas are named by the next segment.
+const NAMED: &[&str] = &[
+    "properties",
+    "patternProperties",
+    "dependentSchemas",
+    "dependencies",
+    "$defs",
+    "definitions",
+];
+/// Keywords whose subschemas apply to a member or an item of the instance.
+const DESCENDING: &[&str] = &[
+    "properties",
+    "patternProperties",
+    "additionalProperties",
+    "unevaluatedProperties",
+    "items",
+    "prefixItems",
+    "additionalItems",
+    "unevaluatedItems",
+];
+
+/// The subschemas along `schema_path`, from the outermost one, with the instance location each
+/// of them applies to.
+fn subschemas(
+    schema_path: &JsonPointer,
+    instance_path: &JsonPointer,
+) -> Vec<(JsonPointer, JsonPointer)> {
+    let segments: Vec<_> = schema_path.iter().cloned().collect();
+    let mut steps = Vec::new();
+    let mut idx = 0;
+    while idx < segments.len() {
+        let keyword = match &segments[idx] {
+            Segment::Key(keyword) => &**keyword,
+            Segment::Index(_) => "",
+        };
+        idx += 1;
+        if matches!(segments.get(idx), Some(Segment::Index(_)))
+            || (NAMED.contains(&keyword) && idx < segments.len())
+        {
+            idx += 1;
+        }
+        steps.push((idx, DESCENDING.contains(&keyword)));
+    }
+    // Every descending keyword below a subschema accounts for one segment of the instance path
+    let mut descending = steps.iter().filter(|(_, descends)| *descends).count();
+    steps
+        .into_iter()
+        .map(|(end, descends)| {
+            descending -= usize::from(descends);
+            let mut instance_location = instance_path.clone();
+            for _ in 0..descending {
+                instance_location.pop();
+            }
+            (
+                JsonPointer::from(segments[..end].to_vec()),
+                instance_location,
+            )
+        })
+        .collect()
+}
+
+fn failed_unit<J: Json>(
+    evaluation_path: JsonPointer,
+    instance_location: JsonPointer,
+) -> OutputUnit<J> {
+    OutputUnit {
+        valid: false,
+        schema_location: format!("#{evaluation_path}"),
+        evaluation_path,
+        instance_location,
+        nested: None,
+        annotations: None,
+        dropped_annotations: None,
+        errors: None,
+    }
+}
+
+fn error_unit<J: Json>(error: &ValidationError) -> OutputUnit<J> {
+    let keyword = error
+        .schema_path()
+        .iter()
+        .last()
+        .map(|segment| segment.to_string())
+        .unwrap_or_default();
+    OutputUnit {
+        valid: false,
+        evaluation_path: error.schema_path().clone(),
+        schema_location: format!("#{}", error.schema_path()),
+        instance_location: error.instance_path().clone(),
+        nested: None,
+        annotations: None,
+        dropped_annotations: None,
+        errors: Some(BTreeMap::from([(keyword, error.to_string())])),
     }
 }
 
@@ -60,3 +190,96 @@ pub struct OutputUnit<J: Json> {
 }
 
 pub type Hierarchical<J> = OutputUnit<J>;
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::OutputUnit;
+
+    #[test]
+    fn test_list() {
+        let schema = json!({"properties": {"a": {"type": "string", "minLength": 2}}});
+        let instance = json!({"a": 1});
+        let output = crate::blocking::evaluate(&instance, &schema).list();
+        assert!(!output.valid);
+        assert_eq!(output.nested.len(), 1);
+        let unit = &output.nested[0];
+        assert_eq!(unit.evaluation_path.to_string(), "/properties/a/type");
+        assert_eq!(unit.schema_location, "#/properties/a/type");
+        assert_eq!(unit.instance_location.to_string(), "/a");
+        assert_eq!(
+            unit.errors.as_ref().and_then(|errors| errors.get("type")),
+            Some(&"Value is not of type \"string\"".to_string())
+        );
+    }
+
+    /// Units in depth-first order as their depth, evaluation path, instance location and
+    /// failed keywords.
+    fn collect_units(
+        unit: &OutputUnit<Value>,
+        depth: usize,
+        units: &mut Vec<(usize, String, String, Vec<String>)>,
+    ) {
+        let keywords = unit.errors.iter().flatten().map(|(key, _)| key.clone());
+        units.push((
+            depth,
+            unit.evaluation_path.to_string(),
+            unit.instance_location.to_string(),
+            keywords.collect(),
+        ));
+        for nested in unit.nested.iter().flatten() {
+            collect_units(nested, depth + 1, units);
+        }
+    }
+
+    #[test_case(
+        json!({"properties": {"a": {"minLength": 2, "pattern": "^x"}}, "required": ["b"]}),
+        json!({"a": "y"}),
+        &[(0, "", "", &["required"]), (1, "/properties/a", "/a", &["minLength", "pattern"])];
+        "siblings"
+    )]
+    #[test_case(
+        json!({"properties": {"a": {"properties": {"b": {"type": "string"}}}}}),
+        json!({"a": {"b": 1}}),
+        &[(0, "", "", &[]), (1, "/properties/a", "/a", &[]), (2, "/properties/a/properties/b", "/a/b", &["type"])];
+        "nested properties"
+    )]
+    #[test_case(
+        json!({"allOf": [{"items": {"maximum": 1}}]}),
+        json!([0, 2]),
+        &[(0, "", "", &[]), (1, "/allOf/0", "", &[]), (2, "/allOf/0/items", "/1", &["maximum"])];
+        "items in branch"
+    )]
+    fn test_hierarchical(
+        schema: Value,
+        instance: Value,
+        expected: &[(usize, &str, &str, &[&str])],
+    ) {
+        let output = crate::blocking::evaluate(&instance, &schema).hierarchical();
+        assert!(!output.valid);
+        let mut actual = Vec::new();
+        collect_units(&output, 0, &mut actual);
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(depth, evaluation_path, instance_location, keywords)| {
+                (
+                    *depth,
+                    (*evaluation_path).to_string(),
+                    (*instance_location).to_string(),
+                    keywords.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_valid() {
+        let instance = json!(1);
+        let output = crate::blocking::evaluate(&instance, &json!({"type": "integer"}));
+        assert!(output.list().valid && output.list().nested.is_empty());
+        assert!(output.hierarchical().valid && output.hierarchical().nested.is_none());
+    }
+}
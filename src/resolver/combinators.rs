@@ -0,0 +1,265 @@
+//! Resolvers that wrap other resolvers.
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+};
+
+use jsonlike::Json;
+
+use super::{BoxedFuture, ReferenceResolver};
+use crate::BuildResult;
+
+/// Keeps retrieved documents, so a resolver shared between builds retrieves every URL once.
+///
+/// Only successful retrievals are cached.
+pub struct CachingResolver<J: Json, R> {
+    inner: R,
+    cache: Mutex<HashMap<String, J>>,
+}
+
+impl<J: Json, R> CachingResolver<J, R> {
+    pub fn new(inner: R) -> CachingResolver<J, R> {
+        CachingResolver {
+            inner,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+    /// Forget all cached documents.
+    pub fn clear(&self) {
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+impl<J, R> ReferenceResolver<J> for CachingResolver<J, R>
+where
+    J: Json + Clone + Send,
+    R: ReferenceResolver<J>,
+{
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        Box::pin(async move {
+            let cached = self
+                .cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(url)
+                .cloned();
+            if let Some(document) = cached {
+                return Ok(document);
+            }
+            let document = self.inner.resolve_external(url).await?;
+            self.cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(url.to_owned(), document.clone());
+            Ok(document)
+        })
+    }
+}
+
+/// Tries resolvers in order and returns the first document retrieved successfully.
+///
+/// If every resolver fails, the error of the last one is returned.
+pub struct FallbackResolver<J: Json> {
+    resolvers: Vec<Box<dyn ReferenceResolver<J>>>,
+}
+
+impl<J: Json> FallbackResolver<J> {
+    pub fn new(first: impl ReferenceResolver<J> + 'static) -> FallbackResolver<J> {
+        FallbackResolver {
+            resolvers: vec![Box::new(first)],
+        }
+    }
+    /// Try `resolver` if all previous resolvers fail.
+    #[must_use]
+    pub fn or(mut self, resolver: impl ReferenceResolver<J> + 'static) -> FallbackResolver<J> {
+        self.resolvers.push(Box::new(resolver));
+        self
+    }
+}
+
+impl<J: Json> ReferenceResolver<J> for FallbackResolver<J> {
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        Box::pin(async move {
+            let (last, rest) = self
+                .resolvers
+                .split_last()
+                .expect("A fallback chain has at least one resolver");
+            for resolver in rest {
+                if let Ok(document) = resolver.resolve_external(url).await {
+                    return Ok(document);
+                }
+            }
+            last.resolve_external(url).await
+        })
+    }
+}
+
+/// Calls `callback` with the URL and the outcome of every retrieval, e.g. for logging.
+pub struct InspectingResolver<R, F> {
+    inner: R,
+    callback: F,
+}
+
+impl<R, F> InspectingResolver<R, F> {
+    pub fn new(inner: R, callback: F) -> InspectingResolver<R, F> {
+        InspectingResolver { inner, callback }
+    }
+}
+
+impl<J, R, F> ReferenceResolver<J> for InspectingResolver<R, F>
+where
+    J: Json,
+    R: ReferenceResolver<J>,
+    F: Fn(&str, &BuildResult<J>) + Send + Sync,
+{
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        Box::pin(async move {
+            let result = self.inner.resolve_external(url).await;
+            (self.callback)(url, &result);
+            result
+        })
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    use serde_json::{json, Value};
+
+    use super::{CachingResolver, FallbackResolver, InspectingResolver};
+    use crate::{resolver::BoxedFuture, BuildError, BuildResult, ReferenceResolver};
+
+    /// Serves a single document and counts requests.
+    struct Single {
+        url: &'static str,
+        document: Value,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Single {
+        fn new(url: &'static str, document: Value) -> Single {
+            Single {
+                url,
+                document,
+                calls: Arc::default(),
+            }
+        }
+    }
+
+    impl ReferenceResolver<Value> for Single {
+        fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<Value>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move {
+                if url == self.url {
+                    Ok(self.document.clone())
+                } else {
+                    Err(BuildError::retrieval(url, "Not found"))
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caching() {
+        let inner = Single::new("https://example.com/a.json", json!({"type": "integer"}));
+        let calls = Arc::clone(&inner.calls);
+        let resolver = CachingResolver::new(inner);
+        for _ in 0..2 {
+            let document = resolver
+                .resolve_external("https://example.com/a.json")
+                .await
+                .expect("Document exists");
+            assert_eq!(document, json!({"type": "integer"}));
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        // Failures are not cached
+        for _ in 0..2 {
+            assert!(resolver
+                .resolve_external("https://example.com/b.json")
+                .await
+                .is_err());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        resolver.clear();
+        resolver
+            .resolve_external("https://example.com/a.json")
+            .await
+            .expect("Document exists");
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_fallback() {
+        let resolver = FallbackResolver::new(Single::new("https://example.com/a.json", json!(1)))
+            .or(Single::new("https://example.com/b.json", json!(2)));
+        for (url, expected) in [
+            ("https://example.com/a.json", json!(1)),
+            ("https://example.com/b.json", json!(2)),
+        ] {
+            let document = resolver
+                .resolve_external(url)
+                .await
+                .expect("Document exists");
+            assert_eq!(document, expected);
+        }
+        let error = resolver
+            .resolve_external("https://example.com/c.json")
+            .await
+            .expect_err("Document does not exist");
+        assert_eq!(
+            error.to_string(),
+            "Failed to retrieve 'https://example.com/c.json': Not found"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inspecting() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let records = Arc::clone(&log);
+        let resolver = InspectingResolver::new(
+            Single::new("https://example.com/a.json", json!({})),
+            move |url: &str, result: &BuildResult<Value>| {
+                records
+                    .lock()
+                    .expect("Lock is not poisoned")
+                    .push(format!("{url}: {}", result.is_ok()));
+            },
+        );
+        let schema = json!({"allOf": [
+            {"$ref": "https://example.com/a.json"},
+        ]});
+        crate::ValidatorBuilder::default()
+            .resolver(resolver)
+            .build(&schema)
+            .await
+            .expect("Invalid schema");
+        assert_eq!(
+            *log.lock().expect("Lock is not poisoned"),
+            ["https://example.com/a.json: true"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shared() {
+        let resolver: Arc<dyn ReferenceResolver<Value>> = Arc::new(Single::new(
+            "https://example.com/a.json",
+            json!({"type": "string"}),
+        ));
+        let schema = json!({"$ref": "https://example.com/a.json"});
+        for _ in 0..2 {
+            let validator = crate::ValidatorBuilder::default()
+                .resolver(Arc::clone(&resolver))
+                .build(&schema)
+                .await
+                .expect("Invalid schema");
+            assert!(!validator.is_valid(&json!(1)));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Retrieval of documents from a directory.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use jsonlike::Json;
+use percent_encoding::percent_decode_str;
+use url::Url;
+
+use super::{BoxedFuture, ReferenceResolver, TextResolver};
+use crate::{blocking, compiler::DEFAULT_BASE, BuildError, BuildResult};
+
+/// Reads documents from files under a root directory.
+///
+/// Accepts `file://` URLs, URIs relative to a schema without `$id` (e.g. `other.json` becomes
+/// `json-schema:///other.json`) and relative paths, which are all looked up within the root.
+/// Paths that escape the root, including via `..` or symlinks, are refused.
+///
+/// Files are read synchronously, also when used as an async resolver.
+#[derive(Debug, Clone)]
+pub struct FileSystemResolver {
+    root: PathBuf,
+}
+
+impl FileSystemResolver {
+    pub fn new(root: impl Into<PathBuf>) -> FileSystemResolver {
+        FileSystemResolver { root: root.into() }
+    }
+
+    fn path(&self, url: &str) -> Result<PathBuf, String> {
+        let path = match Url::parse(url) {
+            Ok(parsed) if parsed.scheme() == "file" => parsed
+                .to_file_path()
+                .map_err(|()| "Invalid file URL".to_string())?,
+            Ok(parsed) if url.starts_with(DEFAULT_BASE) => self
+                .root
+                .join(decode(parsed.path().trim_start_matches('/'))),
+            Ok(parsed) => return Err(format!("Unsupported scheme '{}'", parsed.scheme())),
+            Err(_) => self.root.join(decode(url)),
+        };
+        let root = canonicalize(&self.root)?;
+        let path = canonicalize(&path)?;
+        if path.starts_with(&root) {
+            Ok(path)
+        } else {
+            Err("Path is outside of the root directory".to_string())
+        }
+    }
+
+    fn load<J: Json>(&self, url: &str) -> BuildResult<J> {
+        Ok(J::from_str(&self.resolve_text(url)?)?)
+    }
+}
+
+fn decode(path: &str) -> String {
+    percent_decode_str(path).decode_utf8_lossy().into_owned()
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, String> {
+    path.canonicalize()
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+impl TextResolver for FileSystemResolver {
+    fn resolve_text(&self, url: &str) -> BuildResult<String> {
+        let path = self
+            .path(url)
+            .map_err(|reason| BuildError::retrieval(url, reason))?;
+        fs::read_to_string(&path).map_err(|error| BuildError::retrieval(url, error.to_string()))
+    }
+}
+
+impl<J: Json> ReferenceResolver<J> for FileSystemResolver {
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        Box::pin(async move { self.load(url) })
+    }
+}
+
+impl<J: Json> blocking::ReferenceResolver<J> for FileSystemResolver {
+    fn resolve_external(&self, url: &str) -> BuildResult<J> {
+        self.load(url)
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::FileSystemResolver;
+    use crate::{blocking::ReferenceResolver, BuildResult};
+
+    /// A fresh directory `<tmp>/<name>/root` with a file inside and another one next to it.
+    fn fixture(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join(format!("jsonschema-{}", std::process::id()))
+            .join(name);
+        let root = directory.join("root");
+        fs::create_dir_all(root.join("nested")).expect("Can create directories");
+        fs::write(root.join("nested/a b.json"), r#"{"type": "integer"}"#).expect("Can write files");
+        fs::write(directory.join("secret.json"), "{}").expect("Can write files");
+        root
+    }
+
+    fn resolve(root: &Path, url: &str) -> BuildResult<Value> {
+        ReferenceResolver::resolve_external(&FileSystemResolver::new(root), url)
+    }
+
+    #[test_case("nested/a%20b.json"; "relative path")]
+    #[test_case("json-schema:///nested/a%20b.json"; "default base")]
+    fn test_resolve(url: &str) {
+        let root = fixture("resolve");
+        let document = resolve(&root, url).expect("Document exists");
+        assert_eq!(document, json!({"type": "integer"}));
+    }
+
+    #[test]
+    fn test_file_url() {
+        let root = fixture("file-url").canonicalize().expect("Root exists");
+        let url = url::Url::from_file_path(root.join("nested/a b.json")).expect("Absolute path");
+        let document = resolve(&root, url.as_str()).expect("Document exists");
+        assert_eq!(document, json!({"type": "integer"}));
+    }
+
+    #[test_case("../secret.json"; "parent directory")]
+    #[test_case("nested/../../secret.json"; "nested parent directory")]
+    fn test_escape(url: &str) {
+        let root = fixture("escape");
+        let error = resolve(&root, url).expect_err("Should be refused");
+        assert!(error
+            .to_string()
+            .ends_with("Path is outside of the root directory"));
+    }
+
+    #[test]
+    fn test_escape_file_url() {
+        let root = fixture("escape-file-url");
+        let outside = root
+            .parent()
+            .expect("Root has a parent")
+            .join("secret.json");
+        let url = url::Url::from_file_path(outside).expect("Absolute path");
+        assert!(resolve(&root, url.as_str()).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_scheme() {
+        let root = fixture("scheme");
+        let error = resolve(&root, "https://example.com/a.json").expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Failed to retrieve 'https://example.com/a.json': Unsupported scheme 'https'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_build() {
+        let root = fixture("build");
+        let schema = json!({"$ref": "nested/a%20b.json"});
+        let validator = crate::ValidatorBuilder::default()
+            .resolver(FileSystemResolver::new(&root))
+            .build(&schema)
+            .await
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!("a")));
+    }
+}
@@ -0,0 +1,404 @@
+//! Retrieval of documents over HTTP(S).
+use core::{fmt, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Read},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use jsonlike::Json;
+use url::Url;
+
+use super::{BoxedFuture, ReferenceResolver, TextResolver};
+use crate::{background, blocking, BuildError, BuildResult};
+
+/// The reason why an HTTP(S) retrieval failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HttpError {
+    /// The URL is not a valid absolute `http` or `https` URL.
+    InvalidUrl,
+    /// The host is not in the allowlist, possibly after a redirect.
+    HostNotAllowed { host: String },
+    /// The server responded with an error status.
+    Status { code: u16 },
+    /// The response body exceeds the size limit in bytes.
+    TooLarge { limit: u64 },
+    /// More redirects than allowed.
+    TooManyRedirects { limit: usize },
+    /// The server did not respond within the configured timeouts.
+    Timeout,
+    /// A network or protocol failure.
+    Transport { message: String },
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::InvalidUrl => f.write_str("Expected an absolute HTTP(S) URL"),
+            HttpError::HostNotAllowed { host } => write!(f, "Host '{host}' is not allowed"),
+            HttpError::Status { code } => write!(f, "Unexpected HTTP status {code}"),
+            HttpError::TooLarge { limit } => {
+                write!(f, "Response body exceeds the limit of {limit} bytes")
+            }
+            HttpError::TooManyRedirects { limit } => {
+                write!(f, "More than {limit} redirects")
+            }
+            HttpError::Timeout => f.write_str("Request timed out"),
+            HttpError::Transport { message } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// Fetches documents referenced via `http` and `https` URLs.
+///
+/// Redirects are followed up to a limit, and every hop is checked against the host allowlist.
+/// Retrieved documents are cached by normalized URL for the lifetime of the resolver and its
+/// clones. Failures are reported as `BuildError`s that carry an `HttpError`, see
+/// `BuildError::http_error`.
+///
+/// Requests are blocking. As an async resolver, they run on a shared pool of background threads.
+#[derive(Debug, Clone)]
+pub struct HttpResolver {
+    agent: ureq::Agent,
+    /// Hosts that may be contacted, any host if `None`.
+    allowed_hosts: Option<HashSet<String>>,
+    max_size: u64,
+    max_redirects: usize,
+    cache: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl Default for HttpResolver {
+    fn default() -> Self {
+        HttpResolver::new()
+    }
+}
+
+impl HttpResolver {
+    /// A resolver with a 30 second timeout per request, up to 5 redirects and a 10 MiB limit.
+    pub fn new() -> HttpResolver {
+        HttpResolver {
+            agent: agent(Duration::from_secs(10), Duration::from_secs(30)),
+            allowed_hosts: None,
+            max_size: 10 * 1024 * 1024,
+            max_redirects: 5,
+            cache: Arc::default(),
+        }
+    }
+    /// Set the timeouts for connecting and for a whole request, including the body.
+    #[must_use]
+    pub fn timeouts(mut self, connect: Duration, request: Duration) -> HttpResolver {
+        self.agent = agent(connect, request);
+        self
+    }
+    /// Allow requests to `host`. Once any host is allowed, all other hosts are refused.
+    #[must_use]
+    pub fn allow_host(mut self, host: impl Into<String>) -> HttpResolver {
+        self.allowed_hosts
+            .get_or_insert_with(HashSet::new)
+            .insert(host.into().to_ascii_lowercase());
+        self
+    }
+    /// Refuse response bodies larger than `bytes`.
+    #[must_use]
+    pub fn max_size(mut self, bytes: u64) -> HttpResolver {
+        self.max_size = bytes;
+        self
+    }
+    /// Follow at most `limit` redirects per document, none if `0`.
+    #[must_use]
+    pub fn max_redirects(mut self, limit: usize) -> HttpResolver {
+        self.max_redirects = limit;
+        self
+    }
+
+    fn check(&self, url: &Url) -> Result<(), HttpError> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(HttpError::InvalidUrl);
+        }
+        let host = url.host_str().ok_or(HttpError::InvalidUrl)?;
+        match &self.allowed_hosts {
+            Some(allowed) if !allowed.contains(host) => Err(HttpError::HostNotAllowed {
+                host: host.to_owned(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn fetch(&self, url: &str) -> Result<String, HttpError> {
+        let mut url = Url::parse(url).map_err(|_| HttpError::InvalidUrl)?;
+        url.set_fragment(None);
+        let key = url.to_string();
+        if let Some(body) = self
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+        {
+            return Ok(body.clone());
+        }
+        for _ in 0..=self.max_redirects {
+            self.check(&url)?;
+            let response = self
+                .agent
+                .get(url.as_str())
+                .call()
+                .map_err(|error| match error {
+                    ureq::Error::Status(code, _) => HttpError::Status { code },
+                    ureq::Error::Transport(transport) => transport_error(&transport),
+                })?;
+            let code = response.status();
+            if (300..400).contains(&code) {
+                let location = response
+                    .header("location")
+                    .ok_or(HttpError::Status { code })?;
+                url = url.join(location).map_err(|_| HttpError::InvalidUrl)?;
+                continue;
+            }
+            let body = self.read_body(response)?;
+            self.cache
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert(key, body.clone());
+            return Ok(body);
+        }
+        Err(HttpError::TooManyRedirects {
+            limit: self.max_redirects,
+        })
+    }
+
+    fn read_body(&self, response: ureq::Response) -> Result<String, HttpError> {
+        let too_large = HttpError::TooLarge {
+            limit: self.max_size,
+        };
+        if let Some(length) = response
+            .header("content-length")
+            .and_then(|length| length.parse::<u64>().ok())
+        {
+            if length > self.max_size {
+                return Err(too_large);
+            }
+        }
+        let mut body = Vec::new();
+        response
+            .into_reader()
+            .take(self.max_size.saturating_add(1))
+            .read_to_end(&mut body)
+            .map_err(|error| io_error(&error))?;
+        if body.len() as u64 > self.max_size {
+            return Err(too_large);
+        }
+        String::from_utf8(body).map_err(|_| HttpError::Transport {
+            message: "Response body is not valid UTF-8".to_string(),
+        })
+    }
+}
+
+fn agent(connect: Duration, request: Duration) -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(connect)
+        .timeout(request)
+        // Redirects are followed manually, so every hop is checked against the allowlist
+        .redirects(0)
+        .build()
+}
+
+fn io_error(error: &io::Error) -> HttpError {
+    match error.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => HttpError::Timeout,
+        _ => HttpError::Transport {
+            message: error.to_string(),
+        },
+    }
+}
+
+fn transport_error(transport: &ureq::Transport) -> HttpError {
+    let source =
+        std::error::Error::source(transport).and_then(|source| source.downcast_ref::<io::Error>());
+    match source {
+        Some(error) if matches!(io_error(error), HttpError::Timeout) => HttpError::Timeout,
+        _ => HttpError::Transport {
+            message: transport.to_string(),
+        },
+    }
+}
+
+impl<J: Json> ReferenceResolver<J> for HttpResolver {
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        let resolver = self.clone();
+        let owned = url.to_owned();
+        let body = background::spawn(move || resolver.fetch(&owned));
+        Box::pin(async move {
+            let body = body.await.map_err(|error| BuildError::http(url, error))?;
+            Ok(J::from_str(&body)?)
+        })
+    }
+}
+
+impl TextResolver for HttpResolver {
+    fn resolve_text(&self, url: &str) -> BuildResult<String> {
+        self.fetch(url)
+            .map_err(|error| BuildError::http(url, error))
+    }
+}
+
+impl<J: Json> blocking::ReferenceResolver<J> for HttpResolver {
+    fn resolve_external(&self, url: &str) -> BuildResult<J> {
+        Ok(J::from_str(&self.resolve_text(url)?)?)
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use core::time::Duration;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+    };
+
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::{HttpError, HttpResolver};
+    use crate::{blocking::ReferenceResolver, BuildResult};
+
+    /// A local stand-in for a schema server, answering requests by path.
+    struct Server {
+        address: String,
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl Server {
+        fn start(respond: fn(&str, &str) -> Option<String>) -> Server {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("Can bind");
+            let address = format!("http://{}", listener.local_addr().expect("Has an address"));
+            let requests = Arc::new(AtomicUsize::new(0));
+            let counter = Arc::clone(&requests);
+            let base = address.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else {
+                        continue;
+                    };
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let mut request_line = String::new();
+                    let mut reader = BufReader::new(&stream);
+                    reader
+                        .read_line(&mut request_line)
+                        .expect("Can read the request");
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).expect("Can read headers");
+                        if header.trim().is_empty() {
+                            break;
+                        }
+                    }
+                    let path = request_line.split(' ').nth(1).unwrap_or("/");
+                    if let Some(response) = respond(&base, path) {
+                        let _ = stream.write_all(response.as_bytes());
+                    } else {
+                        // Leave the client waiting
+                        thread::sleep(Duration::from_secs(2));
+                    }
+                }
+            });
+            Server { address, requests }
+        }
+
+        fn url(&self, path: &str) -> String {
+            format!("{}{path}", self.address)
+        }
+    }
+
+    fn response(status: &str, headers: &str, body: &str) -> Option<String> {
+        Some(format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n{headers}\r\n{body}",
+            body.len()
+        ))
+    }
+
+    fn routes(base: &str, path: &str) -> Option<String> {
+        match path {
+            "/a.json" => response("200 OK", "", r#"{"type": "integer"}"#),
+            "/large.json" => response("200 OK", "", &format!("[{}1]", "1, ".repeat(100))),
+            "/moved.json" => response("301 Moved Permanently", "Location: /a.json\r\n", ""),
+            "/loop.json" => response("302 Found", "Location: /loop.json\r\n", ""),
+            "/away.json" => response("302 Found", "Location: http://example.com/a.json\r\n", ""),
+            "/slow.json" => None,
+            _ => response("404 Not Found", "", &format!("No document at {base}{path}")),
+        }
+    }
+
+    fn resolve(resolver: &HttpResolver, url: &str) -> BuildResult<Value> {
+        ReferenceResolver::resolve_external(resolver, url)
+    }
+
+    #[test]
+    fn test_cache() {
+        let server = Server::start(routes);
+        let resolver = HttpResolver::new();
+        for url in ["/a.json", "/a.json#", "/a.json#/type"] {
+            let document = resolve(&resolver, &server.url(url)).expect("Document exists");
+            assert_eq!(document, json!({"type": "integer"}));
+        }
+        // Clones share the cache
+        resolve(&resolver.clone(), &server.url("/a.json")).expect("Document exists");
+        assert_eq!(server.requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_redirect() {
+        let server = Server::start(routes);
+        let document =
+            resolve(&HttpResolver::new(), &server.url("/moved.json")).expect("Document exists");
+        assert_eq!(document, json!({"type": "integer"}));
+    }
+
+    #[test_case("/missing.json", HttpResolver::new(), HttpError::Status { code: 404 })]
+    #[test_case("/large.json", HttpResolver::new().max_size(100), HttpError::TooLarge { limit: 100 })]
+    #[test_case("/loop.json", HttpResolver::new().max_redirects(2), HttpError::TooManyRedirects { limit: 2 })]
+    #[test_case("/moved.json", HttpResolver::new().max_redirects(0), HttpError::TooManyRedirects { limit: 0 })]
+    #[test_case("/a.json", HttpResolver::new().allow_host("example.com"), HttpError::HostNotAllowed { host: "127.0.0.1".to_string() })]
+    #[test_case("/away.json", HttpResolver::new().allow_host("127.0.0.1"), HttpError::HostNotAllowed { host: "example.com".to_string() }; "redirect to another host")]
+    #[test_case("/slow.json", HttpResolver::new().timeouts(Duration::from_secs(1), Duration::from_millis(100)), HttpError::Timeout)]
+    fn test_errors(path: &str, resolver: HttpResolver, expected: HttpError) {
+        let server = Server::start(routes);
+        let error = resolve(&resolver, &server.url(path)).expect_err("Should fail");
+        assert_eq!(error.http_error(), Some(&expected));
+        assert_eq!(
+            error.to_string(),
+            format!("Failed to retrieve '{}': {expected}", server.url(path))
+        );
+    }
+
+    #[test]
+    fn test_invalid_url() {
+        let error =
+            resolve(&HttpResolver::new(), "ftp://example.com/a.json").expect_err("Should fail");
+        assert_eq!(error.http_error(), Some(&HttpError::InvalidUrl));
+    }
+
+    #[tokio::test]
+    async fn test_build() {
+        let server = Server::start(routes);
+        let schema = json!({"properties": {"a": {"$ref": server.url("/moved.json")}}});
+        let validator = crate::ValidatorBuilder::default()
+            .resolver(HttpResolver::new().allow_host("127.0.0.1"))
+            .build(&schema)
+            .await
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!({"a": "x"})));
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .resolver(HttpResolver::new())
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": 1})));
+    }
+}
@@ -0,0 +1,326 @@
+//! Reproducible retrieval via a lockfile.
+use core::fmt::Write;
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use jsonlike::Json;
+use sha2::{Digest, Sha256};
+
+use super::{memory::normalize, BoxedFuture, ReferenceResolver, TextResolver};
+use crate::{blocking, BuildError, BuildResult};
+
+/// The index of locked documents within the lockfile directory.
+const INDEX: &str = "schemas.lock";
+
+/// Records retrieved documents into a lockfile directory or serves them from it.
+///
+/// The directory holds an index with one `<sha256> <uri>` line per document, sorted by URI,
+/// and the content of every document in `<sha256>.json`.
+///
+/// - In record mode, documents are retrieved from a `TextResolver` and added to the lockfile.
+///   A document that is already locked with a different hash is an error; remove its entry
+///   to accept the change.
+/// - In replay mode, documents are only served from the lockfile. URIs that are not locked and
+///   content that does not match its locked hash are errors.
+///
+/// The lockfile is read and written synchronously, also when used as an async resolver.
+pub struct LockfileResolver {
+    directory: PathBuf,
+    source: Option<Box<dyn TextResolver>>,
+    /// Serializes updates of the index.
+    lock: Mutex<()>,
+}
+
+impl LockfileResolver {
+    /// Retrieve documents from `source` and record them in `directory`, which is created if
+    /// needed.
+    pub fn record(
+        directory: impl Into<PathBuf>,
+        source: impl TextResolver + 'static,
+    ) -> LockfileResolver {
+        LockfileResolver {
+            directory: directory.into(),
+            source: Some(Box::new(source)),
+            lock: Mutex::new(()),
+        }
+    }
+    /// Serve documents only from the lockfile in `directory`.
+    pub fn replay(directory: impl Into<PathBuf>) -> LockfileResolver {
+        LockfileResolver {
+            directory: directory.into(),
+            source: None,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn load<J: Json>(&self, url: &str) -> BuildResult<J> {
+        Ok(J::from_str(&self.resolve_text(url)?)?)
+    }
+
+    fn replay_text(&self, uri: &str) -> Result<String, String> {
+        let index = {
+            let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+            read_index(&self.directory)?
+        };
+        let locked = index
+            .get(uri)
+            .ok_or_else(|| "Not in the lockfile".to_string())?;
+        let path = self.directory.join(format!("{locked}.json"));
+        let text = fs::read_to_string(&path).map_err(|error| describe(&path, &error))?;
+        let hash = sha256(&text);
+        if &hash == locked {
+            Ok(text)
+        } else {
+            Err(format!(
+                "Locked content does not match its hash: expected sha256 {locked}, found {hash}"
+            ))
+        }
+    }
+
+    fn record_text(&self, uri: &str, text: &str) -> Result<(), String> {
+        let hash = sha256(text);
+        let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut index = read_index(&self.directory)?;
+        match index.get(uri) {
+            Some(locked) if *locked == hash => return Ok(()),
+            Some(locked) => {
+                return Err(format!(
+                    "Content changed since it was locked: expected sha256 {locked}, found {hash}"
+                ))
+            }
+            None => {}
+        }
+        fs::create_dir_all(&self.directory).map_err(|error| describe(&self.directory, &error))?;
+        let path = self.directory.join(format!("{hash}.json"));
+        fs::write(&path, text).map_err(|error| describe(&path, &error))?;
+        index.insert(uri.to_owned(), hash);
+        write_index(&self.directory, &index)
+    }
+}
+
+impl TextResolver for LockfileResolver {
+    fn resolve_text(&self, url: &str) -> BuildResult<String> {
+        let uri = normalize(url);
+        match &self.source {
+            Some(source) => {
+                let text = source.resolve_text(url)?;
+                self.record_text(&uri, &text)
+                    .map_err(|reason| BuildError::retrieval(url, reason))?;
+                Ok(text)
+            }
+            None => self
+                .replay_text(&uri)
+                .map_err(|reason| BuildError::retrieval(url, reason)),
+        }
+    }
+}
+
+impl<J: Json> ReferenceResolver<J> for LockfileResolver {
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        Box::pin(async move { self.load(url) })
+    }
+}
+
+impl<J: Json> blocking::ReferenceResolver<J> for LockfileResolver {
+    fn resolve_external(&self, url: &str) -> BuildResult<J> {
+        self.load(url)
+    }
+}
+
+fn sha256(text: &str) -> String {
+    Sha256::digest(text.as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut hash, byte| {
+            let _ = write!(hash, "{byte:02x}");
+            hash
+        })
+}
+
+fn describe(path: &Path, error: &io::Error) -> String {
+    format!("{}: {error}", path.display())
+}
+
+/// Locked hashes by URI, empty if there is no lockfile yet.
+fn read_index(directory: &Path) -> Result<BTreeMap<String, String>, String> {
+    let path = directory.join(INDEX);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(error) => return Err(describe(&path, &error)),
+    };
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            line.split_once(' ')
+                .map(|(hash, uri)| (uri.to_owned(), hash.to_owned()))
+                .ok_or_else(|| format!("{}: Malformed line {}", path.display(), number + 1))
+        })
+        .collect()
+}
+
+fn write_index(directory: &Path, index: &BTreeMap<String, String>) -> Result<(), String> {
+    let path = directory.join(INDEX);
+    let contents = index
+        .iter()
+        .fold(String::new(), |mut contents, (uri, hash)| {
+            let _ = writeln!(contents, "{hash} {uri}");
+            contents
+        });
+    fs::write(&path, contents).map_err(|error| describe(&path, &error))
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use std::{
+        fs,
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use serde_json::{json, Value};
+
+    use super::{sha256, LockfileResolver, INDEX};
+    use crate::{blocking::ReferenceResolver, BuildError, BuildResult};
+
+    const A: &str = r#"{"type": "integer"}"#;
+
+    /// A fresh, not yet existing lockfile directory.
+    fn directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join(format!("jsonschema-lockfile-{}", std::process::id()))
+            .join(name);
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    fn source(text: &'static str) -> impl Fn(&str) -> BuildResult<String> {
+        move |url: &str| {
+            if url == "https://example.com/a.json" {
+                Ok(text.to_string())
+            } else {
+                Err(BuildError::retrieval(url, "Not found"))
+            }
+        }
+    }
+
+    fn resolve(resolver: &LockfileResolver, url: &str) -> BuildResult<Value> {
+        ReferenceResolver::resolve_external(resolver, url)
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let directory = directory("replay");
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let recorder = LockfileResolver::record(&directory, move |url: &str| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            source(A)(url)
+        });
+        for _ in 0..2 {
+            assert_eq!(
+                resolve(&recorder, "https://example.com/a.json").expect("Document exists"),
+                json!({"type": "integer"})
+            );
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        let hash = sha256(A);
+        assert_eq!(
+            fs::read_to_string(directory.join(INDEX)).expect("Index exists"),
+            format!("{hash} https://example.com/a.json\n")
+        );
+        assert_eq!(
+            fs::read_to_string(directory.join(format!("{hash}.json"))).expect("Content exists"),
+            A
+        );
+        let replay = LockfileResolver::replay(&directory);
+        assert_eq!(
+            resolve(&replay, "https://example.com/a.json").expect("Document is locked"),
+            json!({"type": "integer"})
+        );
+    }
+
+    #[test]
+    fn test_record_changed() {
+        let directory = directory("changed");
+        resolve(
+            &LockfileResolver::record(&directory, source(A)),
+            "https://example.com/a.json",
+        )
+        .expect("Document exists");
+        let error = resolve(
+            &LockfileResolver::record(&directory, source(r#"{"type": "string"}"#)),
+            "https://example.com/a.json",
+        )
+        .expect_err("Content changed");
+        assert!(error
+            .to_string()
+            .contains("Content changed since it was locked"));
+    }
+
+    #[test]
+    fn test_record_failure() {
+        let directory = directory("failure");
+        let recorder = LockfileResolver::record(&directory, source(A));
+        assert!(resolve(&recorder, "https://example.com/b.json").is_err());
+        assert!(!directory.join(INDEX).exists());
+    }
+
+    #[test]
+    fn test_replay_missing() {
+        let directory = directory("missing");
+        let error = resolve(
+            &LockfileResolver::replay(&directory),
+            "https://example.com/a.json",
+        )
+        .expect_err("Nothing is locked");
+        assert_eq!(
+            error.to_string(),
+            "Failed to retrieve 'https://example.com/a.json': Not in the lockfile"
+        );
+    }
+
+    #[test]
+    fn test_replay_modified() {
+        let directory = directory("modified");
+        resolve(
+            &LockfileResolver::record(&directory, source(A)),
+            "https://example.com/a.json",
+        )
+        .expect("Document exists");
+        fs::write(directory.join(format!("{}.json", sha256(A))), "{}").expect("Can write files");
+        let error = resolve(
+            &LockfileResolver::replay(&directory),
+            "https://example.com/a.json",
+        )
+        .expect_err("Content was modified");
+        assert!(error
+            .to_string()
+            .contains("Locked content does not match its hash"));
+    }
+
+    #[tokio::test]
+    async fn test_build() {
+        let directory = directory("build");
+        let schema = json!({"$ref": "https://example.com/a.json"});
+        crate::ValidatorBuilder::default()
+            .resolver(LockfileResolver::record(&directory, source(A)))
+            .build(&schema)
+            .await
+            .expect("Invalid schema");
+        let validator = crate::ValidatorBuilder::default()
+            .resolver(LockfileResolver::replay(&directory))
+            .build(&schema)
+            .await
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!("a")));
+    }
+}
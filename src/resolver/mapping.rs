@@ -0,0 +1,96 @@
+//! Rewriting of URIs before retrieval.
+use jsonlike::Json;
+
+use super::{BoxedFuture, ReferenceResolver};
+use crate::{blocking, BuildResult};
+
+/// Rewrites URI prefixes before passing URIs to another resolver.
+///
+/// For example, `https://schemas.example.com/` can be mapped to `./schemas/` and served by a
+/// `FileSystemResolver`. If several prefixes match, the longest one applies. URIs without a
+/// matching prefix are passed through unchanged.
+#[derive(Debug, Clone)]
+pub struct MappingResolver<R> {
+    inner: R,
+    mappings: Vec<(String, String)>,
+}
+
+impl<R> MappingResolver<R> {
+    pub fn new(inner: R) -> MappingResolver<R> {
+        MappingResolver {
+            inner,
+            mappings: Vec::new(),
+        }
+    }
+    /// Replace `prefix` with `replacement`.
+    #[must_use]
+    pub fn map(
+        mut self,
+        prefix: impl Into<String>,
+        replacement: impl Into<String>,
+    ) -> MappingResolver<R> {
+        self.mappings.push((prefix.into(), replacement.into()));
+        self
+    }
+
+    fn rewrite(&self, url: &str) -> String {
+        self.mappings
+            .iter()
+            .filter(|(prefix, _)| url.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or_else(
+                || url.to_owned(),
+                |(prefix, replacement)| format!("{replacement}{}", &url[prefix.len()..]),
+            )
+    }
+}
+
+impl<J: Json, R: ReferenceResolver<J>> ReferenceResolver<J> for MappingResolver<R> {
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        Box::pin(async move {
+            let url = self.rewrite(url);
+            self.inner.resolve_external(&url).await
+        })
+    }
+}
+
+impl<J: Json, R: blocking::ReferenceResolver<J>> blocking::ReferenceResolver<J>
+    for MappingResolver<R>
+{
+    fn resolve_external(&self, url: &str) -> BuildResult<J> {
+        self.inner.resolve_external(&self.rewrite(url))
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::json;
+    use test_case::test_case;
+
+    use super::MappingResolver;
+    use crate::resolver::InMemoryResolver;
+
+    #[test_case("https://schemas.example.com/a.json", "./schemas/a.json")]
+    #[test_case("https://schemas.example.com/v2/a.json", "./v2/a.json"; "longest prefix")]
+    #[test_case("https://other.com/a.json", "https://other.com/a.json"; "no match")]
+    fn test_rewrite(url: &str, expected: &str) {
+        let resolver = MappingResolver::new(())
+            .map("https://schemas.example.com/", "./schemas/")
+            .map("https://schemas.example.com/v2/", "./v2/");
+        assert_eq!(resolver.rewrite(url), expected);
+    }
+
+    #[tokio::test]
+    async fn test_build() {
+        let inner = InMemoryResolver::new().with("urn:schemas:a.json", json!({"type": "integer"}));
+        let resolver =
+            MappingResolver::new(inner).map("https://schemas.example.com/", "urn:schemas:");
+        let schema = json!({"$ref": "https://schemas.example.com/a.json"});
+        let validator = crate::ValidatorBuilder::default()
+            .resolver(resolver)
+            .build(&schema)
+            .await
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!("a")));
+    }
+}
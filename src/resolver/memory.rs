@@ -0,0 +1,117 @@
+//! Retrieval of preloaded documents.
+use std::collections::HashMap;
+
+use jsonlike::Json;
+use url::Url;
+
+use super::{BoxedFuture, ReferenceResolver};
+use crate::{blocking, BuildError, BuildResult};
+
+/// Serves documents that are registered upfront under their URIs.
+#[derive(Debug, Clone)]
+pub struct InMemoryResolver<J: Json> {
+    documents: HashMap<String, J>,
+}
+
+impl<J: Json> Default for InMemoryResolver<J> {
+    fn default() -> Self {
+        InMemoryResolver {
+            documents: HashMap::new(),
+        }
+    }
+}
+
+impl<J: Json> InMemoryResolver<J> {
+    pub fn new() -> InMemoryResolver<J> {
+        InMemoryResolver::default()
+    }
+    /// Serve `document` for `uri`. An empty fragment of the URI is ignored.
+    #[must_use]
+    pub fn with(mut self, uri: impl AsRef<str>, document: J) -> InMemoryResolver<J> {
+        self.insert(uri, document);
+        self
+    }
+    /// Serve `document` for `uri`. An empty fragment of the URI is ignored.
+    pub fn insert(&mut self, uri: impl AsRef<str>, document: J) {
+        self.documents.insert(normalize(uri.as_ref()), document);
+    }
+
+    fn load(&self, url: &str) -> BuildResult<J>
+    where
+        J: Clone,
+    {
+        self.documents
+            .get(&normalize(url))
+            .cloned()
+            .ok_or_else(|| BuildError::retrieval(url, "No document is registered for this URI"))
+    }
+}
+
+/// The URI as it is passed to resolvers, so equivalent spellings match.
+pub(super) fn normalize(uri: &str) -> String {
+    match Url::parse(uri) {
+        Ok(mut parsed) => {
+            if parsed.fragment() == Some("") {
+                parsed.set_fragment(None);
+            }
+            parsed.into()
+        }
+        Err(_) => uri.to_owned(),
+    }
+}
+
+impl<J: Json + Clone + Send + Sync> ReferenceResolver<J> for InMemoryResolver<J> {
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        Box::pin(async move { self.load(url) })
+    }
+}
+
+impl<J: Json + Clone + Send + Sync> blocking::ReferenceResolver<J> for InMemoryResolver<J> {
+    fn resolve_external(&self, url: &str) -> BuildResult<J> {
+        self.load(url)
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::json;
+
+    use super::InMemoryResolver;
+
+    #[tokio::test]
+    async fn test_build() {
+        let resolver = InMemoryResolver::new()
+            .with(
+                "https://example.com/a.json#",
+                json!({"$ref": "b.json", "minimum": 0}),
+            )
+            .with("HTTPS://EXAMPLE.COM/b.json", json!({"type": "integer"}));
+        let schema = json!({"$ref": "https://example.com/a.json"});
+        let validator = crate::ValidatorBuilder::default()
+            .resolver(resolver.clone())
+            .build(&schema)
+            .await
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(1)));
+        assert!(!validator.is_valid(&json!(-1)));
+        assert!(!validator.is_valid(&json!("a")));
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .resolver(resolver)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(!validator.is_valid(&json!(-1)));
+    }
+
+    #[test]
+    fn test_missing() {
+        let schema = json!({"$ref": "https://example.com/a.json"});
+        let error = crate::blocking::ValidatorBuilder::default()
+            .resolver(InMemoryResolver::new())
+            .build(&schema)
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Failed to retrieve 'https://example.com/a.json': No document is registered for this URI"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Retrieval of documents referenced via `$ref`.
+mod combinators;
+mod filesystem;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "lockfile")]
+mod lockfile;
+mod mapping;
+mod memory;
+
+use core::{future::Future, pin::Pin};
+use std::sync::Arc;
+
+use jsonlike::Json;
+
+use crate::{BuildError, BuildResult};
+
+pub use combinators::{CachingResolver, FallbackResolver, InspectingResolver};
+pub use filesystem::FileSystemResolver;
+#[cfg(feature = "http")]
+pub use http::{HttpError, HttpResolver};
+#[cfg(feature = "lockfile")]
+pub use lockfile::LockfileResolver;
+pub use mapping::MappingResolver;
+pub use memory::InMemoryResolver;
+
+/// A future returned by a `ReferenceResolver`.
+pub type BoxedFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Retrieves external documents referenced via `$ref` while a validator is built.
+///
+/// Resolvers are stored as trait objects, so they can be wrapped and combined, e.g. with
+/// `CachingResolver` or `FallbackResolver`.
+pub trait ReferenceResolver<J: Json>: Send + Sync {
+    /// Retrieve the document at `url`, an absolute URI without fragment.
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>>;
+}
+
+impl<J: Json, R: ReferenceResolver<J> + ?Sized> ReferenceResolver<J> for Arc<R> {
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        (**self).resolve_external(url)
+    }
+}
+
+impl<J: Json, R: ReferenceResolver<J> + ?Sized> ReferenceResolver<J> for Box<R> {
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        (**self).resolve_external(url)
+    }
+}
+
+/// Retrieves the raw text of external documents, for resolvers that keep documents as they
+/// were retrieved, like `LockfileResolver`.
+///
+/// Retrieval is blocking. Closures taking a URL are text resolvers as well.
+pub trait TextResolver: Send + Sync {
+    /// Retrieve the text of the document at `url`, an absolute URI without fragment.
+    fn resolve_text(&self, url: &str) -> BuildResult<String>;
+}
+
+impl<F: Fn(&str) -> BuildResult<String> + Send + Sync> TextResolver for F {
+    fn resolve_text(&self, url: &str) -> BuildResult<String> {
+        self(url)
+    }
+}
+
+/// Refuses every external document, so only references within the schema itself resolve.
+pub(crate) struct DefaultResolver;
+
+impl<J: Json> ReferenceResolver<J> for DefaultResolver {
+    fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<J>> {
+        Box::pin(async move {
+            Err(BuildError::retrieval(
+                url,
+                "No reference resolver is configured",
+            ))
+        })
+    }
+}
@@ -0,0 +1,422 @@
+//! Retrieval of documents referenced via `$ref` before a schema is compiled.
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use std::collections::HashSet;
+
+use jsonlike::Json;
+use referencing::{Registry, Resource};
+use url::Url;
+
+use crate::{
+    background::Timer,
+    blocking,
+    compiler::{default_base, Resources},
+    drafts::Draft,
+    resolver::BoxedFuture,
+    value::Value,
+    BuildError, BuildResult, ValidatorBuilder,
+};
+
+/// Retrieve all documents that `schema` references directly or via other retrieved documents.
+///
+/// Documents are retrieved through the resolver of `builder`, concurrently and at most once per
+/// URI. Dropping the returned future cancels all retrievals in flight.
+pub(crate) async fn retrieve<J: Json>(
+    schema: &J,
+    builder: &ValidatorBuilder<'_, J>,
+) -> BuildResult<Vec<(Url, J)>> {
+    match builder.timeout {
+        Some(duration) => {
+            Timeout {
+                future: Box::pin(retrieve_all(schema, builder)),
+                timer: Timer::new(duration),
+                duration,
+            }
+            .await
+        }
+        None => retrieve_all(schema, builder).await,
+    }
+}
+
+async fn retrieve_all<J: Json>(
+    schema: &J,
+    builder: &ValidatorBuilder<'_, J>,
+) -> BuildResult<Vec<(Url, J)>> {
+    let mut discovery = Discovery::new(builder.draft, schema)?;
+    loop {
+        let missing = discovery.missing();
+        if missing.is_empty() {
+            return Ok(discovery.documents);
+        }
+        let retrieved = JoinAll::new(
+            missing
+                .iter()
+                .map(|uri| builder.resolver.resolve_external(uri.as_str()))
+                .collect(),
+        )
+        .await?;
+        for (uri, document) in missing.into_iter().zip(retrieved) {
+            discovery.add(uri, document)?;
+        }
+    }
+}
+
+/// Retrieve all documents that `schema` references, one by one through a blocking resolver.
+pub(crate) fn retrieve_blocking<J: Json>(
+    schema: &J,
+    draft: Draft,
+    resolver: &dyn blocking::ReferenceResolver<J>,
+) -> BuildResult<Vec<(Url, J)>> {
+    let mut discovery = Discovery::new(draft, schema)?;
+    loop {
+        let missing = discovery.missing();
+        if missing.is_empty() {
+            return Ok(discovery.documents);
+        }
+        for uri in missing {
+            let document = resolver.resolve_external(uri.as_str())?;
+            discovery.add(uri, document)?;
+        }
+    }
+}
+
+/// A crawled registry of `schema` under the default base URI and the documents retrieved for it.
+///
+/// Retrieved documents may declare another draft via `$schema`.
+pub(crate) fn registry<J: Json>(
+    schema: &J,
+    documents: &[(Url, J)],
+    draft: Draft,
+) -> BuildResult<Registry<Value>> {
+    let mut resources = vec![(
+        default_base(),
+        Resource::new(Value::from_json(schema)?, draft.specification()),
+    )];
+    for (uri, document) in documents {
+        let resource =
+            Resource::from_contents(Value::from_json(document)?, Some(draft.specification()))
+                .expect("A default specification is given");
+        resources.push((uri.clone(), resource));
+    }
+    let mut registry = Registry::new().with_resources(resources.into_iter());
+    registry.crawl();
+    Ok(registry)
+}
+
+/// Referenced documents that are retrieved so far, and the ones still missing.
+struct Discovery<J> {
+    draft: Draft,
+    /// URIs of all resources within the schema and the retrieved documents.
+    known: HashSet<Url>,
+    referenced: HashSet<Url>,
+    documents: Vec<(Url, J)>,
+}
+
+impl<J: Json> Discovery<J> {
+    fn new(draft: Draft, schema: &J) -> BuildResult<Discovery<J>> {
+        let mut discovery = Discovery {
+            draft,
+            known: HashSet::new(),
+            referenced: HashSet::new(),
+            documents: Vec::new(),
+        };
+        discovery.index(default_base(), schema)?;
+        Ok(discovery)
+    }
+
+    /// Referenced URIs that are neither retrieved nor part of a known document.
+    fn missing(&self) -> Vec<Url> {
+        self.referenced.difference(&self.known).cloned().collect()
+    }
+
+    fn add(&mut self, uri: Url, document: J) -> BuildResult<()> {
+        self.index(uri.clone(), &document)?;
+        self.documents.push((uri, document));
+        Ok(())
+    }
+
+    /// Record the resources of a document and the documents it references.
+    fn index(&mut self, uri: Url, document: &J) -> BuildResult<()> {
+        let mut resources = Resources::new(self.draft);
+        resources.add(uri, document)?;
+        self.known.extend(resources.uris().cloned());
+        self.referenced
+            .extend(resources.references().iter().cloned());
+        Ok(())
+    }
+}
+
+/// Poll all futures concurrently and fail as soon as any of them fails.
+struct JoinAll<'f, T> {
+    futures: Vec<Option<BoxedFuture<'f, BuildResult<T>>>>,
+    results: Vec<Option<T>>,
+}
+
+impl<'f, T> JoinAll<'f, T> {
+    fn new(futures: Vec<BoxedFuture<'f, BuildResult<T>>>) -> JoinAll<'f, T> {
+        JoinAll {
+            results: futures.iter().map(|_| None).collect(),
+            futures: futures.into_iter().map(Some).collect(),
+        }
+    }
+}
+
+// Results are never pinned, only the futures, which are boxed
+impl<T> Unpin for JoinAll<'_, T> {}
+
+impl<T> Future for JoinAll<'_, T> {
+    type Output = BuildResult<Vec<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut pending = false;
+        for (slot, result) in this.futures.iter_mut().zip(&mut this.results) {
+            let Some(future) = slot else {
+                continue;
+            };
+            match future.as_mut().poll(cx) {
+                Poll::Ready(Ok(value)) => {
+                    *result = Some(value);
+                    *slot = None;
+                }
+                Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                Poll::Pending => pending = true,
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(this
+                .results
+                .iter_mut()
+                .map(|result| result.take().expect("Every future is complete"))
+                .collect()))
+        }
+    }
+}
+
+/// Fail with a timeout error unless `future` completes within `duration`.
+struct Timeout<F> {
+    future: Pin<Box<F>>,
+    timer: Timer,
+    duration: Duration,
+}
+
+impl<T, F: Future<Output = BuildResult<T>>> Future for Timeout<F> {
+    type Output = BuildResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if let Poll::Ready(output) = this.future.as_mut().poll(cx) {
+            return Poll::Ready(output);
+        }
+        match Pin::new(&mut this.timer).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(BuildError::timeout(this.duration))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use core::time::Duration;
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    use serde_json::{json, Value};
+
+    use crate::{
+        resolver::BoxedFuture, BuildError, BuildResult, ReferenceResolver, ValidatorBuilder,
+    };
+
+    /// Serves preloaded documents and records every request.
+    struct Documents {
+        documents: HashMap<&'static str, Value>,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Documents {
+        fn new(documents: impl IntoIterator<Item = (&'static str, Value)>) -> Documents {
+            Documents {
+                documents: documents.into_iter().collect(),
+                requests: Arc::default(),
+            }
+        }
+    }
+
+    impl ReferenceResolver<Value> for Documents {
+        fn resolve_external<'a>(&'a self, url: &'a str) -> BoxedFuture<'a, BuildResult<Value>> {
+            self.requests
+                .lock()
+                .expect("Lock is not poisoned")
+                .push(url.to_owned());
+            Box::pin(async move {
+                self.documents
+                    .get(url)
+                    .cloned()
+                    .ok_or_else(|| BuildError::retrieval(url, "Not found"))
+            })
+        }
+    }
+
+    /// Never completes and records whether it was dropped.
+    struct Pending {
+        dropped: Arc<AtomicBool>,
+    }
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    impl ReferenceResolver<Value> for Pending {
+        fn resolve_external<'a>(&'a self, _: &'a str) -> BoxedFuture<'a, BuildResult<Value>> {
+            let flag = DropFlag(Arc::clone(&self.dropped));
+            Box::pin(async move {
+                let _flag = flag;
+                core::future::pending().await
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_external_references() {
+        let resolver = Documents::new([
+            (
+                "https://example.com/a.json",
+                json!({"$defs": {"positive": {"$ref": "b.json", "minimum": 0}}}),
+            ),
+            ("https://example.com/b.json", json!({"type": "integer"})),
+        ]);
+        let requests = Arc::clone(&resolver.requests);
+        let schema = json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "a": {"$ref": "a.json#/$defs/positive"},
+                "b": {"$ref": "https://example.com/a.json#/$defs/positive"},
+                "c": {"$ref": "b.json"}
+            }
+        });
+        let validator = ValidatorBuilder::default()
+            .resolver(resolver)
+            .build(&schema)
+            .await
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!({"a": 1, "b": 2, "c": 3})));
+        assert!(!validator.is_valid(&json!({"a": -1})));
+        assert!(!validator.is_valid(&json!({"b": 1.5})));
+        assert!(!validator.is_valid(&json!({"c": "x"})));
+        let mut requests = requests.lock().expect("Lock is not poisoned").clone();
+        requests.sort_unstable();
+        assert_eq!(
+            requests,
+            ["https://example.com/a.json", "https://example.com/b.json"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retrieval_error() {
+        let schema = json!({"$ref": "https://example.com/missing.json"});
+        let error = ValidatorBuilder::default()
+            .resolver(Documents::new([]))
+            .build(&schema)
+            .await
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Failed to retrieve 'https://example.com/missing.json': Not found"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_no_resolver() {
+        let schema = json!({"$ref": "https://example.com/a.json"});
+        let error = crate::validator_for(&schema)
+            .await
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Failed to retrieve 'https://example.com/a.json': No reference resolver is configured"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let schema = json!({"$ref": "https://example.com/a.json"});
+        let error = ValidatorBuilder::default()
+            .resolver(Pending {
+                dropped: Arc::clone(&dropped),
+            })
+            .timeout(Duration::from_millis(50))
+            .build(&schema)
+            .await
+            .expect_err("Should time out");
+        assert_eq!(
+            error.to_string(),
+            "Retrieving referenced documents timed out after 50ms"
+        );
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_cancellation() {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let schema = json!({"$ref": "https://example.com/a.json"});
+        let mut builder = ValidatorBuilder::default();
+        builder.resolver(Pending {
+            dropped: Arc::clone(&dropped),
+        });
+        let result = tokio::time::timeout(Duration::from_millis(50), builder.build(&schema)).await;
+        assert!(result.is_err());
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_retrieval() {
+        struct Counting {
+            active: Arc<AtomicUsize>,
+            peak: Arc<AtomicUsize>,
+        }
+
+        impl ReferenceResolver<Value> for Counting {
+            fn resolve_external<'a>(&'a self, _: &'a str) -> BoxedFuture<'a, BuildResult<Value>> {
+                Box::pin(async move {
+                    let current = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+                    self.peak.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    self.active.fetch_sub(1, Ordering::SeqCst);
+                    Ok(json!({}))
+                })
+            }
+        }
+
+        let peak = Arc::new(AtomicUsize::new(0));
+        let schema = json!({"allOf": [
+            {"$ref": "https://example.com/a.json"},
+            {"$ref": "https://example.com/b.json"},
+            {"$ref": "https://example.com/c.json"}
+        ]});
+        ValidatorBuilder::default()
+            .resolver(Counting {
+                active: Arc::default(),
+                peak: Arc::clone(&peak),
+            })
+            .build(&schema)
+            .await
+            .expect("Invalid schema");
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,627 @@
+//! Compiled validators serialized into a compact binary form.
+//!
+//! A snapshot contains the compiled graph and the names of the extensions it refers to. Custom
+//! keywords and formats are initialized again by the factories registered in the builder that
+//! loads the snapshot, so they don't have to be serializable themselves.
+//!
+//! Snapshots are only compatible with the crate version and the feature set that produced them,
+//! and are loaded by a builder with the same `validate_content` setting. Decoding checks that
+//! every node and extension a snapshot refers to exists, but it does not re-check the compiled
+//! keywords against a schema, so snapshots should still come from a trusted source.
+use std::sync::Arc;
+
+use bincode::Options;
+use jsonlike::Json;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    extensions::{Extension, Extensions},
+    graph::{Graph, NodeId},
+    value::Value,
+    vocabulary::{References, SchemaNode},
+    BuildError, BuildResult, Validator, ValidatorBuilder,
+};
+
+const MAGIC: &str = "jsonschema-snapshot";
+
+/// Variable-length integers keep snapshots compact, as most of them are small indices.
+fn options() -> impl Options + Copy {
+    bincode::DefaultOptions::new()
+}
+
+/// Enabled features that may change how schemas are compiled or how snapshots are encoded.
+///
+/// `http` and `lockfile` only affect how referenced documents are retrieved, and those are
+/// compiled into the snapshot.
+fn features() -> Vec<String> {
+    [
+        ("serde", cfg!(feature = "serde")),
+        ("serde_json", cfg!(feature = "serde_json")),
+        ("arbitrary_precision", cfg!(feature = "arbitrary_precision")),
+        ("serde_yaml", cfg!(feature = "serde_yaml")),
+        ("toml", cfg!(feature = "toml")),
+        ("cbor", cfg!(feature = "cbor")),
+        ("msgpack", cfg!(feature = "msgpack")),
+        ("json5", cfg!(feature = "json5")),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(name, _)| name.to_owned())
+    .collect()
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    magic: String,
+    version: String,
+    features: Vec<String>,
+}
+
+impl Header {
+    fn current() -> Header {
+        Header {
+            magic: MAGIC.to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            features: features(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Payload {
+    graph: Graph<SchemaNode>,
+    root: NodeId,
+    /// Content keywords are only compiled if the builder validates them.
+    validate_content: bool,
+    /// Names of custom keywords with the serialized schema they were initialized from.
+    keywords: Vec<(String, String)>,
+    /// Names of custom formats with the serialized (sub)schema that contains them.
+    formats: Vec<(String, String)>,
+    content_encodings: Vec<String>,
+    content_media_types: Vec<String>,
+}
+
+/// A decoded snapshot of a compiled validator, see `Validator::to_snapshot`.
+///
+/// Turn it into a validator via `ValidatorBuilder::load`, which provides the custom keywords and
+/// formats used by the snapshot.
+pub struct Snapshot<J: Json> {
+    graph: Graph<SchemaNode>,
+    root: NodeId,
+    validate_content: bool,
+    keywords: Vec<(String, J)>,
+    formats: Vec<(String, J)>,
+    content_encodings: Vec<String>,
+    content_media_types: Vec<String>,
+}
+
+impl<J: Json> Snapshot<J> {
+    /// Decode a snapshot produced by `Validator::to_snapshot`.
+    ///
+    /// Fails if the snapshot was made by a different version of this crate or with a different
+    /// set of features, or if it refers to nodes or extensions it does not contain.
+    pub fn from_bytes(bytes: &[u8]) -> BuildResult<Snapshot<J>> {
+        // Corrupted lengths must not lead to allocations beyond the size of the input
+        let options = options().with_limit(bytes.len() as u64);
+        let mut reader = bytes;
+        let header: Header = options
+            .deserialize_from(&mut reader)
+            .map_err(|_| BuildError::snapshot("Not a validator snapshot"))?;
+        let current = Header::current();
+        if header.magic != current.magic {
+            return Err(BuildError::snapshot("Not a validator snapshot"));
+        }
+        if header.version != current.version {
+            return Err(BuildError::snapshot(format!(
+                "Made by version {}, expected {}",
+                header.version, current.version
+            )));
+        }
+        if header.features != current.features {
+            return Err(BuildError::snapshot(format!(
+                "Made with features [{}], expected [{}]",
+                header.features.join(", "),
+                current.features.join(", ")
+            )));
+        }
+        let payload: Payload = options
+            .deserialize(reader)
+            .map_err(|error| BuildError::snapshot(error.to_string()))?;
+        check(&payload)?;
+        let parse = |(name, schema): (String, String)| -> BuildResult<(String, J)> {
+            Ok((name, J::from_str(&schema)?))
+        };
+        Ok(Snapshot {
+            graph: payload.graph,
+            root: payload.root,
+            validate_content: payload.validate_content,
+            keywords: payload
+                .keywords
+                .into_iter()
+                .map(parse)
+                .collect::<BuildResult<_>>()?,
+            formats: payload
+                .formats
+                .into_iter()
+                .map(parse)
+                .collect::<BuildResult<_>>()?,
+            content_encodings: payload.content_encodings,
+            content_media_types: payload.content_media_types,
+        })
+    }
+}
+
+impl<J: Json> Validator<J> {
+    /// Serialize this validator into a compact binary snapshot.
+    ///
+    /// Loading a snapshot via `Snapshot::from_bytes` and `ValidatorBuilder::load` is usually much
+    /// faster than compiling the schema again.
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        fn schemas<T: ?Sized>(extensions: &[Extension<T>]) -> Vec<(String, String)> {
+            extensions
+                .iter()
+                .map(|extension| {
+                    let schema = extension.schema.as_ref().map(Value::to_string);
+                    (extension.name.clone(), schema.unwrap_or_default())
+                })
+                .collect()
+        }
+        fn names<T: ?Sized>(extensions: &[Extension<T>]) -> Vec<String> {
+            extensions
+                .iter()
+                .map(|extension| extension.name.clone())
+                .collect()
+        }
+        let extensions = self.extensions();
+        let payload = Payload {
+            graph: self.graph.clone(),
+            root: self.root,
+            validate_content: extensions.validate_content,
+            keywords: schemas(&extensions.keywords),
+            formats: schemas(&extensions.formats),
+            content_encodings: names(&extensions.content_encodings),
+            content_media_types: names(&extensions.content_media_types),
+        };
+        let mut bytes = Vec::new();
+        options()
+            .serialize_into(&mut bytes, &Header::current())
+            .and_then(|()| options().serialize_into(&mut bytes, &payload))
+            .expect("Serializing into memory never fails");
+        bytes
+    }
+}
+
+/// Ensure that every node and extension index in the payload exists, as a corrupted snapshot
+/// would otherwise panic during validation.
+fn check(payload: &Payload) -> BuildResult<()> {
+    let mut refs = References::default();
+    for node in payload.graph.iter() {
+        if let SchemaNode::Keywords(keywords) = node {
+            for keyword in keywords {
+                keyword.value.references(&mut refs);
+            }
+        }
+    }
+    let graph = &payload.graph;
+    if !graph.contains(payload.root) || !refs.nodes.iter().all(|node| graph.contains(*node)) {
+        return Err(BuildError::snapshot("Refers to a missing schema node"));
+    }
+    for (kind, indices, len) in [
+        ("keyword", &refs.keywords, payload.keywords.len()),
+        ("format", &refs.formats, payload.formats.len()),
+        (
+            "content encoding",
+            &refs.content_encodings,
+            payload.content_encodings.len(),
+        ),
+        (
+            "content media type",
+            &refs.content_media_types,
+            payload.content_media_types.len(),
+        ),
+    ] {
+        if indices.iter().any(|idx| *idx >= len) {
+            return Err(BuildError::snapshot(format!("Refers to a missing {kind}")));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn load<J: Json>(
+    snapshot: &Snapshot<J>,
+    builder: &ValidatorBuilder<'_, J>,
+) -> BuildResult<Validator<J>> {
+    if snapshot.validate_content != builder.validate_content {
+        return Err(BuildError::snapshot(format!(
+            "Made with `validate_content({})`, but the builder uses `validate_content({})`",
+            snapshot.validate_content, builder.validate_content
+        )));
+    }
+    let missing = |kind: &str, name: &str| {
+        BuildError::snapshot(format!("{kind} '{name}' is not registered in the builder"))
+    };
+    let mut extensions = Extensions::new(snapshot.validate_content);
+    for (name, schema) in &snapshot.keywords {
+        let factory = builder
+            .keywords
+            .get(name)
+            .ok_or_else(|| missing("Keyword", name))?;
+        extensions.keywords.push(Extension {
+            name: name.clone(),
+            schema: Some(Value::from_json(schema)?),
+            value: Arc::from(factory.init(schema)?),
+        });
+    }
+    for (name, schema) in &snapshot.formats {
+        let factory = builder
+            .formats
+            .get(name)
+            .ok_or_else(|| missing("Format", name))?;
+        extensions.formats.push(Extension {
+            name: name.clone(),
+            schema: Some(Value::from_json(schema)?),
+            value: Arc::from(factory.init(schema)?),
+        });
+    }
+    for name in &snapshot.content_encodings {
+        let encoding = builder
+            .content_encodings
+            .get(name)
+            .ok_or_else(|| missing("Content encoding", name))?;
+        extensions.content_encodings.push(Extension {
+            name: name.clone(),
+            schema: None,
+            value: Arc::clone(encoding),
+        });
+    }
+    for name in &snapshot.content_media_types {
+        let media_type = builder
+            .content_media_types
+            .get(name)
+            .ok_or_else(|| missing("Content media type", name))?;
+        extensions.content_media_types.push(Extension {
+            name: name.clone(),
+            schema: None,
+            value: Arc::clone(media_type),
+        });
+    }
+    Ok(Validator::new(
+        snapshot.graph.clone(),
+        snapshot.root,
+        extensions,
+    ))
+}
+
+/// Serialize a `JsonPointer` segment by segment, as its string form can't tell keys and indices
+/// apart.
+pub(crate) mod pointer {
+    use jpointer::{JsonPointer, Segment};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    enum Repr {
+        Key(Box<str>),
+        Index(usize),
+    }
+
+    pub(crate) fn serialize<S: Serializer>(
+        pointer: &JsonPointer,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(pointer.iter().map(|segment| match segment {
+            Segment::Key(key) => Repr::Key(key.clone()),
+            Segment::Index(idx) => Repr::Index(*idx),
+        }))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<JsonPointer, D::Error> {
+        let segments = Vec::<Repr>::deserialize(deserializer)?;
+        Ok(JsonPointer::from(
+            segments
+                .into_iter()
+                .map(|segment| match segment {
+                    Repr::Key(key) => Segment::Key(key),
+                    Repr::Index(idx) => Segment::Index(idx),
+                })
+                .collect::<Vec<_>>(),
+        ))
+    }
+}
+
+/// Serialize regular expressions as their patterns and compile them again when loading.
+pub(crate) mod regex {
+    use fancy_regex::Regex;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        regex: &Regex,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(regex.as_str())
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Regex, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Regex::new(&pattern).map_err(D::Error::custom)
+    }
+}
+
+pub(crate) mod regexes {
+    use fancy_regex::Regex;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        regexes: &[Regex],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(regexes.iter().map(Regex::as_str))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Regex>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|pattern| Regex::new(pattern).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+pub(crate) mod regex_pairs {
+    use fancy_regex::Regex;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer, T: Serialize>(
+        pairs: &[(Regex, T)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(pairs.iter().map(|(regex, value)| (regex.as_str(), value)))
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(Regex, T)>, D::Error> {
+        Vec::<(String, T)>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(pattern, value)| {
+                Regex::new(&pattern)
+                    .map(|regex| (regex, value))
+                    .map_err(D::Error::custom)
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use bincode::Options;
+
+    use super::{options, Header, Payload, Snapshot};
+    use crate::graph::Graph;
+    use crate::{blocking::ValidatorBuilder, BoxedFormat, BoxedKeyword, BuildResult};
+
+    fn snapshot_of<'a>(schema: &'a Value, builder: &mut ValidatorBuilder<'a, Value>) -> Vec<u8> {
+        builder.build(schema).expect("Invalid schema").to_snapshot()
+    }
+
+    #[test_case(json!({"type": "integer", "minimum": 1, "multipleOf": 3}), json!(6), json!(4))]
+    #[test_case(json!({"properties": {"a": {"pattern": "^a+$"}}, "additionalProperties": false}), json!({"a": "aa"}), json!({"a": "b"}))]
+    #[test_case(json!({"patternProperties": {"^x-": {"const": [1, {"b": null}]}}}), json!({"x-a": [1, {"b": null}]}), json!({"x-a": 1}))]
+    #[test_case(json!({"contains": {"enum": ["a", 1.5]}, "minContains": 2}), json!(["a", 1.5]), json!(["a"]))]
+    #[test_case(json!({"items": {"maximum": 18446744073709551615_u64}}), json!([1]), json!([1e30]))]
+    #[test_case(json!({"if": true, "then": {"required": ["a"]}, "else": false}), json!({"a": 1}), json!({}))]
+    #[test_case(json!({"contentEncoding": "base64", "contentMediaType": "application/json", "contentSchema": {"type": "object"}}), json!("e30="), json!("WzFd"))]
+    fn test_roundtrip(schema: Value, valid: Value, invalid: Value) {
+        let mut builder = ValidatorBuilder::default();
+        builder.validate_content(true);
+        let bytes = snapshot_of(&schema, &mut builder);
+        let snapshot = Snapshot::from_bytes(&bytes).expect("Invalid snapshot");
+        let validator = ValidatorBuilder::default()
+            .validate_content(true)
+            .load(&snapshot)
+            .expect("Failed to load");
+        assert!(validator.is_valid(&valid));
+        assert!(!validator.is_valid(&invalid));
+        let expected = builder.build(&schema).expect("Invalid schema");
+        let locations = |validator: &crate::Validator<Value>| {
+            validator
+                .iter_errors(&invalid)
+                .map(|error| error.schema_path().clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(locations(&validator), locations(&expected));
+    }
+
+    #[derive(Debug)]
+    struct MaxLength(usize);
+
+    impl crate::Keyword<Value> for MaxLength {
+        fn is_valid(&self, instance: &Value) -> bool {
+            instance.as_str().is_none_or(|value| value.len() <= self.0)
+        }
+    }
+
+    struct Lowercase;
+
+    impl crate::Format for Lowercase {
+        fn is_valid(&self, value: &str) -> bool {
+            value.chars().all(char::is_lowercase)
+        }
+    }
+
+    fn max_length(schema: &Value) -> BuildResult<BoxedKeyword<Value>> {
+        Ok(Box::new(MaxLength(
+            schema.as_u64().unwrap_or_default() as usize
+        )))
+    }
+
+    fn lowercase(_: &Value) -> BuildResult<BoxedFormat> {
+        Ok(Box::new(Lowercase))
+    }
+
+    #[test]
+    fn test_extensions_are_rebound() {
+        let schema = json!({"items": [{"max-length": 2}, {"max-length": 3, "format": "lower"}]});
+        let bytes = snapshot_of(
+            &schema,
+            ValidatorBuilder::default()
+                .draft(crate::Draft::Draft07)
+                .keyword("max-length", max_length)
+                .format("lower", lowercase),
+        );
+        let snapshot = Snapshot::from_bytes(&bytes).expect("Invalid snapshot");
+        let validator = ValidatorBuilder::default()
+            .keyword("max-length", max_length)
+            .format("lower", lowercase)
+            .load(&snapshot)
+            .expect("Failed to load");
+        assert!(validator.is_valid(&json!(["ab", "abc"])));
+        assert!(!validator.is_valid(&json!(["abc"])));
+        assert!(!validator.is_valid(&json!(["a", "abcd"])));
+        assert!(!validator.is_valid(&json!(["a", "aB"])));
+
+        let error = ValidatorBuilder::default()
+            .format("lower", lowercase)
+            .load(&snapshot)
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Invalid snapshot: Keyword 'max-length' is not registered in the builder"
+        );
+    }
+
+    #[test]
+    fn test_reject_other_version() {
+        let mut header = Header::current();
+        header.version = "0.0.0-other".to_owned();
+        let bytes = options().serialize(&header).expect("Failed to serialize");
+        let error = Snapshot::<Value>::from_bytes(&bytes)
+            .err()
+            .expect("Should fail");
+        assert!(error
+            .to_string()
+            .starts_with("Invalid snapshot: Made by version 0.0.0-other"));
+    }
+
+    #[test]
+    fn test_reject_other_features() {
+        let mut header = Header::current();
+        header.features.push("unknown".to_owned());
+        let mut bytes = options().serialize(&header).expect("Failed to serialize");
+        bytes.extend(snapshot_of(&json!({}), &mut ValidatorBuilder::default()));
+        let error = Snapshot::<Value>::from_bytes(&bytes)
+            .err()
+            .expect("Should fail");
+        assert!(error
+            .to_string()
+            .starts_with("Invalid snapshot: Made with features"));
+    }
+
+    #[test_case(b"")]
+    #[test_case(b"not a snapshot at all")]
+    fn test_reject_garbage(bytes: &[u8]) {
+        assert!(Snapshot::<Value>::from_bytes(bytes).is_err());
+    }
+
+    fn split(bytes: &[u8]) -> (Header, Payload) {
+        let mut reader = bytes;
+        let header = options()
+            .deserialize_from(&mut reader)
+            .expect("Invalid header");
+        let payload = options().deserialize(reader).expect("Invalid payload");
+        (header, payload)
+    }
+
+    fn join(header: &Header, payload: &Payload) -> Vec<u8> {
+        let mut bytes = options().serialize(header).expect("Failed to serialize");
+        options()
+            .serialize_into(&mut bytes, payload)
+            .expect("Failed to serialize");
+        bytes
+    }
+
+    #[test]
+    fn test_reject_truncated() {
+        let bytes = snapshot_of(
+            &json!({"properties": {"a": {"pattern": "^a$"}}}),
+            &mut ValidatorBuilder::default(),
+        );
+        for len in 0..bytes.len() {
+            assert!(Snapshot::<Value>::from_bytes(&bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_reject_missing_node() {
+        let (header, nested) = split(&snapshot_of(
+            &json!({"properties": {"a": {"properties": {"b": {}}}}}),
+            &mut ValidatorBuilder::default(),
+        ));
+        let (_, mut payload) = split(&snapshot_of(&json!({}), &mut ValidatorBuilder::default()));
+        payload.root = nested.root;
+        let error = Snapshot::<Value>::from_bytes(&join(&header, &payload))
+            .err()
+            .expect("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Invalid snapshot: Refers to a missing schema node"
+        );
+        // The root exists, but the subschemas of its keywords do not
+        let mut graph = Graph::new();
+        payload.root = graph.push_node(nested.graph.get(nested.root).clone());
+        payload.graph = graph;
+        let error = Snapshot::<Value>::from_bytes(&join(&header, &payload))
+            .err()
+            .expect("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Invalid snapshot: Refers to a missing schema node"
+        );
+    }
+
+    #[test_case(json!({"max-length": 1}), "keyword")]
+    #[test_case(json!({"format": "lower"}), "format")]
+    #[test_case(json!({"contentEncoding": "base64"}), "content encoding")]
+    #[test_case(json!({"contentMediaType": "application/json"}), "content media type")]
+    fn test_reject_missing_extension(schema: Value, kind: &str) {
+        let (header, mut payload) = split(&snapshot_of(
+            &schema,
+            ValidatorBuilder::default()
+                .validate_content(true)
+                .keyword("max-length", max_length)
+                .format("lower", lowercase),
+        ));
+        payload.keywords.clear();
+        payload.formats.clear();
+        payload.content_encodings.clear();
+        payload.content_media_types.clear();
+        let error = Snapshot::<Value>::from_bytes(&join(&header, &payload))
+            .err()
+            .expect("Should fail");
+        assert_eq!(
+            error.to_string(),
+            format!("Invalid snapshot: Refers to a missing {kind}")
+        );
+    }
+
+    #[test]
+    fn test_reject_other_content_setting() {
+        let schema = json!({"contentMediaType": "application/json"});
+        let bytes = snapshot_of(&schema, ValidatorBuilder::default().validate_content(true));
+        let snapshot = Snapshot::<Value>::from_bytes(&bytes).expect("Invalid snapshot");
+        let error = ValidatorBuilder::default()
+            .load(&snapshot)
+            .expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Invalid snapshot: Made with `validate_content(true)`, but the builder uses `validate_content(false)`"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Validating documents as a stream of events, without loading them into memory.
+//!
+//! Objects and arrays are validated incrementally, so memory usage depends on the nesting depth
+//! rather than the size of the document. Keywords that need a whole object or array at once are
+//! either evaluated by buffering just that value, or rejected when the stream validator is built:
+//!
+//! - `anyOf`, `oneOf`, `not`, `if` / `then` / `else`, `dependentSchemas` and the schema form of
+//!   `dependencies`;
+//! - `uniqueItems`;
+//! - `const` and `enum` with arrays or objects;
+//! - custom keywords.
+//!
+//! Besides that, items of arrays with `contains` are buffered one at a time.
+//!
+//! ```rust
+//! # #[cfg(feature = "serde_json")]
+//! # fn test() -> Result<(), Box<dyn std::error::Error>> {
+//! let schema = serde_json::json!({"items": {"required": ["id"]}});
+//! let validator = jsonschema::blocking::validator_for(&schema)?;
+//! let input: &[u8] = br#"[{"id": 1}, {"name": "a"}]"#;
+//! let errors = validator.streaming_strict()?.collect_errors(input)?;
+//! assert_eq!(errors[0].instance_path().to_string(), "/1");
+//! # Ok(())
+//! # }
+//! ```
+mod session;
+mod tokenizer;
+
+use core::fmt;
+use std::io::{self, Read};
+
+use jsonlike::Json;
+
+pub use session::Session;
+pub use tokenizer::Tokenizer;
+
+use crate::{vocabulary::SchemaNode, BuildError, BuildResult, ValidationError, Validator};
+
+/// A single token of a JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    /// An object key, followed by the events of its value.
+    Key(String),
+    String(String),
+    /// A number as written in the document, so no precision is lost.
+    Number(String),
+    Bool(bool),
+    Null,
+}
+
+/// An error that prevents a stream from being validated.
+#[derive(Debug)]
+pub struct StreamError {
+    kind: StreamErrorKind,
+}
+
+#[derive(Debug)]
+enum StreamErrorKind {
+    Io(io::Error),
+    Syntax { message: String, offset: usize },
+    UnexpectedEvent { message: &'static str },
+}
+
+impl StreamError {
+    pub(crate) fn io(error: io::Error) -> StreamError {
+        StreamError {
+            kind: StreamErrorKind::Io(error),
+        }
+    }
+    pub(crate) fn syntax(message: impl Into<String>, offset: usize) -> StreamError {
+        StreamError {
+            kind: StreamErrorKind::Syntax {
+                message: message.into(),
+                offset,
+            },
+        }
+    }
+    pub(crate) fn unexpected(message: &'static str) -> StreamError {
+        StreamError {
+            kind: StreamErrorKind::UnexpectedEvent { message },
+        }
+    }
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            StreamErrorKind::Io(error) => write!(f, "Failed to read the input: {error}"),
+            StreamErrorKind::Syntax { message, offset } => {
+                write!(f, "Invalid JSON at byte {offset}: {message}")
+            }
+            StreamErrorKind::UnexpectedEvent { message } => {
+                write!(f, "Invalid event sequence: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            StreamErrorKind::Io(error) => Some(error),
+            StreamErrorKind::Syntax { .. } | StreamErrorKind::UnexpectedEvent { .. } => None,
+        }
+    }
+}
+
+/// A validator for documents read as a stream of events.
+///
+/// Created via `Validator::streaming` or `Validator::streaming_strict`.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamValidator<'v, J: Json> {
+    validator: &'v Validator<J>,
+}
+
+impl<'v, J: Json + 'static> StreamValidator<'v, J> {
+    /// Start validating a document that is fed event by event.
+    pub fn session(&self) -> Session<'v, J> {
+        Session::new(self.validator)
+    }
+    /// Validate a document from any source of events and return all errors.
+    pub fn collect_events_errors(
+        &self,
+        events: impl IntoIterator<Item = Result<Event, StreamError>>,
+    ) -> Result<Vec<ValidationError>, StreamError> {
+        let mut session = self.session();
+        for event in events {
+            session.push(event?)?;
+        }
+        session.finish()
+    }
+    /// Validate a JSON document read from `reader` and return all errors.
+    pub fn collect_errors(&self, reader: impl Read) -> Result<Vec<ValidationError>, StreamError> {
+        self.collect_events_errors(Tokenizer::new(reader))
+    }
+    /// Check a JSON document read from `reader`, stopping at the first error.
+    pub fn is_valid(&self, reader: impl Read) -> Result<bool, StreamError> {
+        let mut session = self.session();
+        for event in Tokenizer::new(reader) {
+            session.push(event?)?;
+            if !session.is_valid() {
+                return Ok(false);
+            }
+        }
+        Ok(session.finish()?.is_empty())
+    }
+}
+
+impl<J: Json + 'static> Validator<J> {
+    /// Validate documents as streams, buffering values for keywords that need them as a whole.
+    pub fn streaming(&self) -> StreamValidator<'_, J> {
+        StreamValidator { validator: self }
+    }
+    /// Validate documents as streams, failing if any keyword in the schema needs buffering.
+    pub fn streaming_strict(&self) -> BuildResult<StreamValidator<'_, J>> {
+        for node in self.graph.iter() {
+            let SchemaNode::Keywords(keywords) = node else {
+                continue;
+            };
+            if let Some(keyword) = keywords
+                .iter()
+                .find(|keyword| session::needs_buffering(&keyword.value))
+            {
+                return Err(BuildError::invalid_keyword(
+                    &keyword.location,
+                    "Can not be validated without buffering the instance",
+                ));
+            }
+        }
+        Ok(self.streaming())
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use super::Event;
+
+    fn paths(errors: impl IntoIterator<Item = crate::ValidationError>) -> Vec<(String, String)> {
+        let mut paths: Vec<_> = errors
+            .into_iter()
+            .map(|error| {
+                (
+                    error.instance_path().to_string(),
+                    error.schema_path().to_string(),
+                )
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test_case(json!({"type": "object", "required": ["a"]}), json!({"b": 1}))]
+    #[test_case(json!({"type": "string"}), json!([1, {"a": 2}]))]
+    #[test_case(json!({"properties": {"a": {"type": "string"}, "b": {"minimum": 3}}}), json!({"a": 1, "b": 2, "c": 3}))]
+    #[test_case(json!({"patternProperties": {"^x": {"type": "integer"}}, "additionalProperties": false}), json!({"x1": "a", "y": 1, "z": 2}))]
+    #[test_case(json!({"additionalProperties": {"type": "string"}, "properties": {"a": true}}), json!({"a": 1, "b": 2}))]
+    #[test_case(json!({"propertyNames": {"maxLength": 2}, "maxProperties": 1}), json!({"abc": 1, "de": 2}))]
+    #[test_case(json!({"dependentRequired": {"a": ["b"]}, "minProperties": 3}), json!({"a": {"nested": [1]}}))]
+    #[test_case(json!({"prefixItems": [{"type": "string"}], "items": {"type": "integer"}, "maxItems": 2}), json!([1, "a", 2.5]))]
+    #[test_case(json!({"prefixItems": [true], "items": false, "minItems": 3}), json!([1, 2]))]
+    #[test_case(json!({"contains": {"type": "object", "required": ["id"]}, "maxContains": 1}), json!([{"id": 1}, {"id": 2}, 3]))]
+    #[test_case(json!({"contains": {"type": "string"}}), json!([1, [2], {"a": 3}]))]
+    #[test_case(json!({"allOf": [{"items": {"type": "integer"}}, {"items": {"minimum": 2}}]}), json!([1, "a", 3]))]
+    #[test_case(json!({"items": {"anyOf": [{"type": "integer"}, {"required": ["a"]}]}}), json!([1, {"b": 1}, {"a": 1}]))]
+    #[test_case(json!({"properties": {"a": {"uniqueItems": true, "items": {"type": "integer"}}}}), json!({"a": [1, 1, "x"]}))]
+    #[test_case(json!({"properties": {"a": {"oneOf": [{"type": "array"}, {"items": {"type": "string"}}]}}}), json!({"a": [{"x": 1}]}))]
+    #[test_case(json!({"enum": [[1], {"a": 1}]}), json!({"a": 2}))]
+    #[test_case(json!({"const": 1, "properties": {"a": {"const": {"b": [1]}}}}), json!({"a": {"b": [2]}}))]
+    #[test_case(json!({"$defs": {"node": {"properties": {"next": {"$ref": "#/$defs/node"}, "v": {"type": "integer"}}}}, "$ref": "#/$defs/node"}), json!({"v": 1, "next": {"v": "a", "next": {"v": 2.5, "next": {}}}}))]
+    #[test_case(json!({"properties": {"a": false}}), json!({"a": {"deep": [1, 2]}}))]
+    #[test_case(json!(false), json!([1]))]
+    #[test_case(json!({"type": "integer", "maximum": 3}), json!(5))]
+    #[test_case(json!({"items": {"multipleOf": 0.1}}), json!([0.3, 12345678901234567890.1, 1e300]))]
+    fn test_same_errors_as_tree(schema: Value, instance: Value) {
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let expected = paths(validator.iter_errors(&instance));
+        let input = serde_json::to_vec(&instance).expect("Serializable");
+        let errors = validator
+            .streaming()
+            .collect_errors(input.as_slice())
+            .expect("Valid JSON");
+        assert_eq!(paths(errors), expected);
+        assert_eq!(
+            validator
+                .streaming()
+                .is_valid(input.as_slice())
+                .expect("Valid JSON"),
+            expected.is_empty()
+        );
+    }
+
+    #[test]
+    fn test_strict_rejects_buffering() {
+        let schema = json!({"items": {"properties": {"tags": {"uniqueItems": true}}}});
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let error = validator.streaming_strict().expect_err("Should fail");
+        assert_eq!(
+            error.to_string(),
+            "Invalid schema at '/items/properties/tags/uniqueItems': Can not be validated without buffering the instance"
+        );
+        assert!(validator
+            .streaming()
+            .collect_errors(&b"[{\"tags\": [1, 1]}]"[..])
+            .is_ok());
+    }
+
+    #[test]
+    fn test_strict_allows_streamable() {
+        let schema = json!({"items": {"required": ["id"], "properties": {"id": {"enum": [1, 2]}}}});
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let errors = validator
+            .streaming_strict()
+            .expect("Streamable")
+            .collect_errors(&br#"[{"id": 1}, {"id": 3}, {}]"#[..])
+            .expect("Valid JSON");
+        assert_eq!(
+            paths(errors),
+            vec![
+                ("/1/id".to_string(), "/items/properties/id/enum".to_string()),
+                ("/2".to_string(), "/items/required".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_session() {
+        let schema = json!({"items": {"type": "integer"}});
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let streaming = validator.streaming();
+        let mut session = streaming.session();
+        session.push(Event::StartArray).expect("Valid event");
+        session
+            .push(Event::Number("1".to_string()))
+            .expect("Valid event");
+        assert!(session.is_valid());
+        session.push(Event::Null).expect("Valid event");
+        assert!(!session.is_valid());
+        assert_eq!(session.errors()[0].instance_path().to_string(), "/1");
+        session.push(Event::EndArray).expect("Valid event");
+        assert!(session.push(Event::Null).is_err());
+        assert_eq!(session.finish().expect("Complete").len(), 1);
+    }
+
+    #[test_case(&[Event::StartObject, Event::Null]; "value without key")]
+    #[test_case(&[Event::StartArray, Event::EndObject]; "mismatched end")]
+    #[test_case(&[Event::Number("1x".to_string())]; "invalid number")]
+    #[test_case(&[Event::StartArray]; "incomplete")]
+    fn test_invalid_events(events: &[Event]) {
+        let validator = crate::blocking::validator_for(&json!({"items": {"uniqueItems": true}}))
+            .expect("Invalid schema");
+        let result = validator
+            .streaming()
+            .collect_events_errors(events.iter().cloned().map(Ok));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        let validator = crate::blocking::validator_for(&json!({})).expect("Invalid schema");
+        let error = validator
+            .streaming()
+            .collect_errors(&b"[1, }"[..])
+            .expect_err("Invalid JSON");
+        assert_eq!(
+            error.to_string(),
+            "Invalid JSON at byte 4: Expected a value"
+        );
+    }
+}
@@ -0,0 +1,526 @@
+//! Incremental evaluation of a compiled schema over a stream of events.
+//!
+//! Objects and arrays are not built. Instead, each open container has a frame with the keywords
+//! that apply to it: applicators pick schemas for every key or item as it arrives, and keywords
+//! that only need the keys or the number of items run when the container ends. Keywords that need
+//! the whole value (e.g. `anyOf` or `uniqueItems`) make the container be buffered and evaluated
+//! with the tree-based validator instead.
+use std::collections::HashSet;
+
+use jpointer::{JsonPointer, JsonPointerNode};
+use jsonlike::Json;
+
+use super::{Event, StreamError};
+use crate::{
+    error::ValidationErrorKind,
+    graph::NodeId,
+    value::{Array, Object, Value},
+    vocabulary::{CompiledKeyword, KeywordValue, SchemaNode},
+    ValidationError, Validator,
+};
+
+/// Whether the keyword can only be evaluated against the whole value.
+pub(crate) fn needs_buffering(keyword: &KeywordValue) -> bool {
+    match keyword {
+        KeywordValue::AnyOf(_)
+        | KeywordValue::OneOf(_)
+        | KeywordValue::Not(_)
+        | KeywordValue::IfThenElse(_)
+        | KeywordValue::DependentSchemas(_)
+        | KeywordValue::UniqueItems
+        | KeywordValue::Custom(_) => true,
+        KeywordValue::Const(keyword) => !keyword.is_scalar(),
+        KeywordValue::Enum(keyword) => !keyword.is_scalar(),
+        _ => false,
+    }
+}
+
+/// Whether the keyword needs the keys of an object once it ends.
+fn needs_keys(keyword: &KeywordValue) -> bool {
+    match keyword {
+        KeywordValue::Required(_)
+        | KeywordValue::DependentRequired(_)
+        | KeywordValue::MaxProperties(_)
+        | KeywordValue::MinProperties(_)
+        | KeywordValue::PropertyNames(_) => true,
+        KeywordValue::AdditionalProperties(keyword) => keyword.is_forbidden(),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Container {
+    Object,
+    Array,
+}
+
+enum Frame<'v> {
+    Object(ObjectFrame<'v>),
+    Array(ArrayFrame<'v>),
+    Buffer(BufferFrame),
+    /// A container no keyword applies to.
+    Skip(Vec<Container>),
+}
+
+struct ObjectFrame<'v> {
+    keywords: Vec<&'v CompiledKeyword>,
+    /// Distinct keys in their order of appearance, if any keyword needs them.
+    keys: Option<(HashSet<String>, Vec<String>)>,
+    /// Schemas for the value of the last key until that value starts.
+    pending: Option<Vec<NodeId>>,
+}
+
+impl ObjectFrame<'_> {
+    fn schemas_for(&self, key: &str) -> Vec<NodeId> {
+        let mut schemas = Vec::new();
+        for keyword in &self.keywords {
+            match &keyword.value {
+                KeywordValue::Properties(keyword) => schemas.extend(keyword.schema_for(key)),
+                KeywordValue::PatternProperties(keyword) => {
+                    schemas.extend(keyword.schemas_for(key));
+                }
+                KeywordValue::AdditionalProperties(keyword) => {
+                    schemas.extend(keyword.schema_for(key));
+                }
+                _ => {}
+            }
+        }
+        schemas
+    }
+}
+
+struct ArrayFrame<'v> {
+    keywords: Vec<&'v CompiledKeyword>,
+    len: usize,
+    /// `contains` keywords with the number of matching items so far.
+    contains: Vec<(&'v CompiledKeyword, u64)>,
+}
+
+impl ArrayFrame<'_> {
+    fn schemas_at(&self, idx: usize) -> Vec<NodeId> {
+        let mut schemas = Vec::new();
+        for keyword in &self.keywords {
+            match &keyword.value {
+                KeywordValue::PrefixItems(keyword) => schemas.extend(keyword.schema_at(idx)),
+                KeywordValue::Items(keyword) => schemas.extend(keyword.schema_at(idx)),
+                _ => {}
+            }
+        }
+        schemas
+    }
+}
+
+enum Partial {
+    Object(Object, Option<String>),
+    Array(Array),
+}
+
+/// A container built in memory because some keyword needs all of it.
+struct BufferFrame {
+    nodes: Vec<NodeId>,
+    partials: Vec<Partial>,
+}
+
+impl BufferFrame {
+    fn new(nodes: Vec<NodeId>, container: Container) -> BufferFrame {
+        let mut frame = BufferFrame {
+            nodes,
+            partials: Vec::new(),
+        };
+        frame.open(container);
+        frame
+    }
+
+    fn open(&mut self, container: Container) {
+        self.partials.push(match container {
+            Container::Object => Partial::Object(Object::default(), None),
+            Container::Array => Partial::Array(Array::default()),
+        });
+    }
+
+    fn add(&mut self, value: Value) -> Result<(), StreamError> {
+        match self.partials.last_mut() {
+            Some(Partial::Array(array)) => array.push(value),
+            Some(Partial::Object(object, key)) => match key.take() {
+                Some(key) => object.insert(key, value),
+                None => return Err(StreamError::unexpected("Expected a key")),
+            },
+            None => unreachable!("Buffers are removed once complete"),
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, container: Container) -> Result<Option<Value>, StreamError> {
+        let value = match (self.partials.pop(), container) {
+            (Some(Partial::Object(object, None)), Container::Object) => Value::Object(object),
+            (Some(Partial::Array(array)), Container::Array) => Value::Array(array),
+            _ => return Err(StreamError::unexpected("Mismatched end of a container")),
+        };
+        if self.partials.is_empty() {
+            return Ok(Some(value));
+        }
+        self.add(value)?;
+        Ok(None)
+    }
+
+    /// Add an event, returning the whole value once it is complete.
+    fn push(&mut self, event: Event) -> Result<Option<Value>, StreamError> {
+        match event {
+            Event::StartObject => self.open(Container::Object),
+            Event::StartArray => self.open(Container::Array),
+            Event::EndObject => return self.close(Container::Object),
+            Event::EndArray => return self.close(Container::Array),
+            Event::Key(name) => match self.partials.last_mut() {
+                Some(Partial::Object(_, key @ None)) => *key = Some(name),
+                _ => return Err(StreamError::unexpected("Unexpected key")),
+            },
+            event => self.add(scalar(event)?)?,
+        }
+        Ok(None)
+    }
+}
+
+fn scalar(event: Event) -> Result<Value, StreamError> {
+    Ok(match event {
+        Event::String(value) => Value::String(value),
+        Event::Number(literal) => match Value::from_str(&literal) {
+            Ok(Value::Number(number)) => Value::Number(number),
+            _ => return Err(StreamError::unexpected("Invalid number literal")),
+        },
+        Event::Bool(value) => Value::Bool(value),
+        Event::Null => Value::Null,
+        _ => return Err(StreamError::unexpected("Expected a value")),
+    })
+}
+
+/// Validation of a single document that is fed one event at a time.
+///
+/// Errors have the same instance and schema paths as the ones of `Validator::iter_errors` for
+/// the same document, though their order may differ.
+pub struct Session<'v, J: Json> {
+    validator: &'v Validator<J>,
+    stack: Vec<Frame<'v>>,
+    /// Location of the current value.
+    path: JsonPointer,
+    errors: Vec<ValidationError>,
+    finished: bool,
+}
+
+impl<'v, J: Json + 'static> Session<'v, J> {
+    pub(crate) fn new(validator: &'v Validator<J>) -> Session<'v, J> {
+        Session {
+            validator,
+            stack: Vec::new(),
+            path: JsonPointer::default(),
+            errors: Vec::new(),
+            finished: false,
+        }
+    }
+
+    /// Whether no errors were found so far.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Errors found so far.
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    /// Finish the document and return all errors.
+    pub fn finish(self) -> Result<Vec<ValidationError>, StreamError> {
+        if !self.finished {
+            return Err(StreamError::unexpected("Unexpected end of the document"));
+        }
+        Ok(self.errors)
+    }
+
+    /// Process the next event of the document.
+    pub fn push(&mut self, event: Event) -> Result<(), StreamError> {
+        match self.stack.last_mut() {
+            None if self.finished => Err(StreamError::unexpected(
+                "Unexpected event after the end of the document",
+            )),
+            None => self.start_value(vec![self.validator.root], event, false),
+            Some(Frame::Skip(containers)) => {
+                match event {
+                    Event::StartObject => containers.push(Container::Object),
+                    Event::StartArray => containers.push(Container::Array),
+                    Event::EndObject | Event::EndArray => {
+                        let container = if event == Event::EndObject {
+                            Container::Object
+                        } else {
+                            Container::Array
+                        };
+                        if containers.pop() != Some(container) {
+                            return Err(StreamError::unexpected("Mismatched end of a container"));
+                        }
+                        if containers.is_empty() {
+                            self.stack.pop();
+                            self.value_done(None);
+                        }
+                    }
+                    _ => {}
+                }
+                Ok(())
+            }
+            Some(Frame::Buffer(buffer)) => {
+                if let Some(value) = buffer.push(event)? {
+                    let Some(Frame::Buffer(buffer)) = self.stack.pop() else {
+                        unreachable!("The buffer is the last frame")
+                    };
+                    self.evaluate(&buffer.nodes, &value);
+                    self.value_done(Some(value));
+                }
+                Ok(())
+            }
+            Some(Frame::Object(object)) => match event {
+                Event::Key(key) if object.pending.is_none() => {
+                    object.pending = Some(object.schemas_for(&key));
+                    if let Some((seen, keys)) = &mut object.keys {
+                        if seen.insert(key.clone()) {
+                            keys.push(key.clone());
+                        }
+                    }
+                    self.path.push(key);
+                    Ok(())
+                }
+                Event::EndObject if object.pending.is_none() => {
+                    let Some(Frame::Object(object)) = self.stack.pop() else {
+                        unreachable!("The object is the last frame")
+                    };
+                    self.finish_object(object);
+                    self.value_done(None);
+                    Ok(())
+                }
+                event => match object.pending.take() {
+                    Some(schemas) => self.start_value(schemas, event, false),
+                    None => Err(StreamError::unexpected("Expected a key")),
+                },
+            },
+            Some(Frame::Array(array)) => match event {
+                Event::EndArray => {
+                    let Some(Frame::Array(array)) = self.stack.pop() else {
+                        unreachable!("The array is the last frame")
+                    };
+                    self.finish_array(array);
+                    self.value_done(None);
+                    Ok(())
+                }
+                event => {
+                    let schemas = array.schemas_at(array.len);
+                    // `contains` needs every item as a whole
+                    let materialize = !array.contains.is_empty();
+                    self.path.push(array.len);
+                    self.start_value(schemas, event, materialize)
+                }
+            },
+        }
+    }
+
+    /// Evaluate nodes against a complete value at the current location.
+    fn evaluate(&mut self, nodes: &[NodeId], value: &Value) {
+        let mut errors = Vec::new();
+        for node in nodes {
+            self.validator.collect_errors_at(
+                *node,
+                value,
+                &JsonPointer::new(),
+                &JsonPointerNode::new(),
+                &mut errors,
+            );
+        }
+        self.report(errors);
+    }
+
+    fn report(&mut self, errors: Vec<ValidationError>) {
+        self.errors
+            .extend(errors.into_iter().map(|error| error.prefixed(&self.path)));
+    }
+
+    /// Keywords of the given nodes with `allOf` and `$ref` inlined, reporting `false` schemas.
+    fn expand(&mut self, nodes: &[NodeId], keywords: &mut Vec<&'v CompiledKeyword>) {
+        let validator = self.validator;
+        for node in nodes {
+            match validator.graph.get(*node) {
+                SchemaNode::Boolean { valid: true, .. } => {}
+                SchemaNode::Boolean {
+                    valid: false,
+                    location,
+                } => self.report(vec![ValidationError::new(
+                    ValidationErrorKind::FalseSchema,
+                    &JsonPointerNode::new(),
+                    location,
+                )]),
+                SchemaNode::Keywords(compiled) => {
+                    for keyword in compiled {
+                        match &keyword.value {
+                            KeywordValue::AllOf(all_of) => self.expand(all_of.schemas(), keywords),
+                            KeywordValue::Ref(reference) => {
+                                self.expand(&[reference.schema()], keywords)
+                            }
+                            _ => keywords.push(keyword),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn requires_buffer(&self, nodes: &[NodeId]) -> bool {
+        nodes
+            .iter()
+            .any(|node| match self.validator.graph.get(*node) {
+                SchemaNode::Boolean { .. } => false,
+                SchemaNode::Keywords(keywords) => {
+                    keywords.iter().any(|keyword| match &keyword.value {
+                        KeywordValue::AllOf(all_of) => self.requires_buffer(all_of.schemas()),
+                        KeywordValue::Ref(reference) => self.requires_buffer(&[reference.schema()]),
+                        value => needs_buffering(value),
+                    })
+                }
+            })
+    }
+
+    fn start_value(
+        &mut self,
+        nodes: Vec<NodeId>,
+        event: Event,
+        materialize: bool,
+    ) -> Result<(), StreamError> {
+        let container = match event {
+            Event::StartObject => Container::Object,
+            Event::StartArray => Container::Array,
+            Event::Key(_) | Event::EndObject | Event::EndArray => {
+                return Err(StreamError::unexpected("Expected a value"))
+            }
+            event => {
+                let value = scalar(event)?;
+                self.evaluate(&nodes, &value);
+                self.value_done(Some(value));
+                return Ok(());
+            }
+        };
+        if materialize || self.requires_buffer(&nodes) {
+            self.stack
+                .push(Frame::Buffer(BufferFrame::new(nodes, container)));
+            return Ok(());
+        }
+        let mut keywords = Vec::new();
+        self.expand(&nodes, &mut keywords);
+        let frame = if keywords.is_empty() {
+            Frame::Skip(vec![container])
+        } else if container == Container::Object {
+            let needs_keys = keywords.iter().any(|keyword| needs_keys(&keyword.value));
+            Frame::Object(ObjectFrame {
+                keywords,
+                keys: needs_keys.then(|| (HashSet::new(), Vec::new())),
+                pending: None,
+            })
+        } else {
+            let contains = keywords
+                .iter()
+                .filter(|keyword| matches!(keyword.value, KeywordValue::Contains(_)))
+                .map(|keyword| (*keyword, 0))
+                .collect();
+            Frame::Array(ArrayFrame {
+                keywords,
+                len: 0,
+                contains,
+            })
+        };
+        self.stack.push(frame);
+        Ok(())
+    }
+
+    /// Update the parent container once its current value is complete.
+    fn value_done(&mut self, value: Option<Value>) {
+        let validator = self.validator;
+        match self.stack.last_mut() {
+            None => self.finished = true,
+            Some(Frame::Object(_)) => {
+                self.path.pop();
+            }
+            Some(Frame::Array(array)) => {
+                self.path.pop();
+                array.len += 1;
+                if let Some(value) = value {
+                    for (keyword, count) in &mut array.contains {
+                        if let KeywordValue::Contains(contains) = &keyword.value {
+                            if validator.is_valid_at(contains.schema(), &value) {
+                                *count += 1;
+                            }
+                        }
+                    }
+                }
+            }
+            Some(Frame::Buffer(_) | Frame::Skip(_)) => {
+                unreachable!("Values within buffered or skipped containers are not tracked")
+            }
+        }
+    }
+
+    fn finish_object(&mut self, object: ObjectFrame<'v>) {
+        // Values are irrelevant to the remaining keywords, only the keys are
+        let entries: Vec<_> = object
+            .keys
+            .map(|(_, keys)| keys.into_iter().map(|key| (key, Value::Null)).collect())
+            .unwrap_or_default();
+        let skeleton = Value::Object(Object::from(entries));
+        let root = JsonPointerNode::new();
+        let mut errors = Vec::new();
+        for keyword in object.keywords {
+            match &keyword.value {
+                KeywordValue::AdditionalProperties(additional) if !additional.is_forbidden() => {}
+                value @ (KeywordValue::Type(_)
+                | KeywordValue::Const(_)
+                | KeywordValue::Enum(_)
+                | KeywordValue::Required(_)
+                | KeywordValue::DependentRequired(_)
+                | KeywordValue::MaxProperties(_)
+                | KeywordValue::MinProperties(_)
+                | KeywordValue::PropertyNames(_)
+                | KeywordValue::AdditionalProperties(_)) => value.collect_errors(
+                    self.validator,
+                    &skeleton,
+                    &keyword.location,
+                    &root,
+                    &mut errors,
+                ),
+                _ => {}
+            }
+        }
+        self.report(errors);
+    }
+
+    fn finish_array(&mut self, array: ArrayFrame<'v>) {
+        let empty = Value::Array(Array::default());
+        let root = JsonPointerNode::new();
+        let mut errors = Vec::new();
+        for keyword in array.keywords {
+            let location = &keyword.location;
+            match &keyword.value {
+                value
+                @ (KeywordValue::Type(_) | KeywordValue::Const(_) | KeywordValue::Enum(_)) => {
+                    value.collect_errors(self.validator, &empty, location, &root, &mut errors);
+                }
+                KeywordValue::MaxItems(limit) if !limit.allows(array.len) => {
+                    errors.push(ValidationError::new(limit.error_kind(), &root, location));
+                }
+                KeywordValue::MinItems(limit) if !limit.allows(array.len) => {
+                    errors.push(ValidationError::new(limit.error_kind(), &root, location));
+                }
+                KeywordValue::Items(items) => {
+                    items.collect_length_errors(array.len, location, &root, &mut errors);
+                }
+                _ => {}
+            }
+        }
+        for (keyword, count) in array.contains {
+            if let KeywordValue::Contains(contains) = &keyword.value {
+                contains.collect_count_errors(count, &keyword.location, &root, &mut errors);
+            }
+        }
+        self.report(errors);
+    }
+}
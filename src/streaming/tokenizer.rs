@@ -0,0 +1,320 @@
+//! A pull tokenizer turning RFC 8259 JSON bytes into events without building the document.
+use std::io::{self, Read};
+
+use jsonlike::Json;
+
+use super::{Event, StreamError};
+use crate::value::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Container {
+    Object,
+    Array,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    /// A value is required, e.g. at the start or after `:`.
+    Value,
+    /// After `[`.
+    ValueOrEnd,
+    /// After `{`.
+    KeyOrEnd,
+    /// After `,` within an object.
+    Key,
+    /// After a value within a container.
+    CommaOrEnd,
+    /// After the root value, where only whitespace is allowed.
+    Done,
+}
+
+/// Events of a JSON document read from `R`.
+///
+/// Reads are buffered internally, so `R` does not need to be buffered. After an error the
+/// tokenizer yields nothing.
+pub struct Tokenizer<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    position: usize,
+    /// Offset of the next byte within the whole input.
+    offset: usize,
+    stack: Vec<Container>,
+    state: State,
+    failed: bool,
+}
+
+impl<R: Read> Tokenizer<R> {
+    pub fn new(reader: R) -> Tokenizer<R> {
+        Tokenizer {
+            reader,
+            buffer: Vec::new(),
+            position: 0,
+            offset: 0,
+            stack: Vec::new(),
+            state: State::Value,
+            failed: false,
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, StreamError> {
+        if self.position == self.buffer.len() {
+            self.buffer.resize(8 * 1024, 0);
+            let read = loop {
+                match self.reader.read(&mut self.buffer) {
+                    Ok(read) => break read,
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                    Err(error) => return Err(StreamError::io(error)),
+                }
+            };
+            self.buffer.truncate(read);
+            self.position = 0;
+        }
+        Ok(self.buffer.get(self.position).copied())
+    }
+
+    fn bump(&mut self) {
+        self.position += 1;
+        self.offset += 1;
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, StreamError> {
+        let byte = self.peek()?;
+        if byte.is_some() {
+            self.bump();
+        }
+        Ok(byte)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<Option<u8>, StreamError> {
+        loop {
+            match self.peek()? {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.bump(),
+                byte => return Ok(byte),
+            }
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> StreamError {
+        StreamError::syntax(message, self.offset)
+    }
+
+    fn expect_literal(
+        &mut self,
+        literal: &'static [u8],
+        event: Event,
+    ) -> Result<Event, StreamError> {
+        for expected in literal {
+            if self.next_byte()? != Some(*expected) {
+                return Err(self.error("Invalid literal"));
+            }
+        }
+        Ok(event)
+    }
+
+    /// Parse a raw string or number token with the same rules as whole documents.
+    fn parse_token(&self, token: &[u8]) -> Result<Value, StreamError> {
+        let token = std::str::from_utf8(token).map_err(|_| self.error("Invalid UTF-8"))?;
+        Value::from_str(token).map_err(|error| self.error(error.to_string()))
+    }
+
+    fn read_string(&mut self) -> Result<String, StreamError> {
+        let mut token = Vec::new();
+        let mut escaped = false;
+        token.push(b'"');
+        self.bump();
+        loop {
+            let Some(byte) = self.next_byte()? else {
+                return Err(self.error("Unterminated string"));
+            };
+            token.push(byte);
+            match byte {
+                b'"' if !escaped => break,
+                b'\\' => escaped = !escaped,
+                _ => escaped = false,
+            }
+        }
+        match self.parse_token(&token)? {
+            Value::String(string) => Ok(string),
+            _ => unreachable!("A quoted token is always a string"),
+        }
+    }
+
+    fn read_number(&mut self) -> Result<String, StreamError> {
+        let mut token = Vec::new();
+        while let Some(byte @ (b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')) = self.peek()? {
+            token.push(byte);
+            self.bump();
+        }
+        self.parse_token(&token)?;
+        // The token is ASCII, as checked by the parser
+        Ok(String::from_utf8(token).expect("Number literals are ASCII"))
+    }
+
+    fn after_value(&mut self) {
+        self.state = if self.stack.is_empty() {
+            State::Done
+        } else {
+            State::CommaOrEnd
+        };
+    }
+
+    fn end(&mut self, container: Container) -> Result<Event, StreamError> {
+        if self.stack.pop() != Some(container) {
+            return Err(self.error("Mismatched closing bracket"));
+        }
+        self.bump();
+        self.after_value();
+        Ok(match container {
+            Container::Object => Event::EndObject,
+            Container::Array => Event::EndArray,
+        })
+    }
+
+    fn read_value(&mut self, byte: u8) -> Result<Event, StreamError> {
+        let event = match byte {
+            b'{' => {
+                self.bump();
+                self.stack.push(Container::Object);
+                self.state = State::KeyOrEnd;
+                return Ok(Event::StartObject);
+            }
+            b'[' => {
+                self.bump();
+                self.stack.push(Container::Array);
+                self.state = State::ValueOrEnd;
+                return Ok(Event::StartArray);
+            }
+            b'"' => Event::String(self.read_string()?),
+            b'-' | b'0'..=b'9' => Event::Number(self.read_number()?),
+            b't' => self.expect_literal(b"true", Event::Bool(true))?,
+            b'f' => self.expect_literal(b"false", Event::Bool(false))?,
+            b'n' => self.expect_literal(b"null", Event::Null)?,
+            _ => return Err(self.error("Expected a value")),
+        };
+        self.after_value();
+        Ok(event)
+    }
+
+    fn read_key(&mut self) -> Result<Event, StreamError> {
+        let key = self.read_string()?;
+        if self.skip_whitespace()? != Some(b':') {
+            return Err(self.error("Expected ':'"));
+        }
+        self.bump();
+        self.state = State::Value;
+        Ok(Event::Key(key))
+    }
+
+    fn read_event(&mut self) -> Result<Option<Event>, StreamError> {
+        loop {
+            let byte = self.skip_whitespace()?;
+            let Some(byte) = byte else {
+                return match self.state {
+                    State::Done => Ok(None),
+                    _ => Err(self.error("Unexpected end of input")),
+                };
+            };
+            return match (self.state, byte) {
+                (State::Done, _) => Err(self.error("Trailing characters")),
+                (State::ValueOrEnd, b']') => self.end(Container::Array).map(Some),
+                (State::Value | State::ValueOrEnd, byte) => self.read_value(byte).map(Some),
+                (State::KeyOrEnd, b'}') => self.end(Container::Object).map(Some),
+                (State::KeyOrEnd | State::Key, b'"') => self.read_key().map(Some),
+                (State::KeyOrEnd | State::Key, _) => Err(self.error("Expected a string key")),
+                (State::CommaOrEnd, b',') => {
+                    self.bump();
+                    self.state = match self.stack.last() {
+                        Some(Container::Object) => State::Key,
+                        _ => State::Value,
+                    };
+                    continue;
+                }
+                (State::CommaOrEnd, b'}') => self.end(Container::Object).map(Some),
+                (State::CommaOrEnd, b']') => self.end(Container::Array).map(Some),
+                (State::CommaOrEnd, _) => Err(self.error("Expected ',' or a closing bracket")),
+            };
+        }
+    }
+}
+
+impl<R: Read> Iterator for Tokenizer<R> {
+    type Item = Result<Event, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let event = self.read_event();
+        self.failed = event.is_err();
+        event.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::{Event, Tokenizer};
+
+    fn events(input: &str) -> Result<Vec<Event>, String> {
+        Tokenizer::new(input.as_bytes())
+            .collect::<Result<_, _>>()
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn test_events() {
+        let events = events(r#" {"a": [1, -2.5e3, "x\"é"], "b": {}, "c": [true, false, null]} "#)
+            .expect("Valid JSON");
+        assert_eq!(
+            events,
+            vec![
+                Event::StartObject,
+                Event::Key("a".to_string()),
+                Event::StartArray,
+                Event::Number("1".to_string()),
+                Event::Number("-2.5e3".to_string()),
+                Event::String("x\"é".to_string()),
+                Event::EndArray,
+                Event::Key("b".to_string()),
+                Event::StartObject,
+                Event::EndObject,
+                Event::Key("c".to_string()),
+                Event::StartArray,
+                Event::Bool(true),
+                Event::Bool(false),
+                Event::Null,
+                Event::EndArray,
+                Event::EndObject,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scalar_document() {
+        assert_eq!(events("42"), Ok(vec![Event::Number("42".to_string())]));
+    }
+
+    #[test_case(""; "empty")]
+    #[test_case("[1,]"; "trailing comma")]
+    #[test_case("[1 2]"; "missing comma")]
+    #[test_case("{\"a\" 1}"; "missing colon")]
+    #[test_case("{1: 1}"; "non-string key")]
+    #[test_case("[1}"; "mismatched bracket")]
+    #[test_case("01"; "leading zero")]
+    #[test_case("\"abc"; "unterminated string")]
+    #[test_case("[tru]"; "invalid literal")]
+    #[test_case("1 2"; "trailing value")]
+    #[test_case("{\"a\": [1, 2]"; "unexpected end")]
+    fn test_syntax_errors(input: &str) {
+        assert!(events(input).is_err());
+    }
+
+    #[test]
+    fn test_error_offset() {
+        assert_eq!(
+            events("[1,\n x]"),
+            Err("Invalid JSON at byte 5: Expected a value".to_string())
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! Loading instances from TOML files.
+//!
+//! `toml::Value` implements `Json`, so TOML configs are validated against the same schemas as
+//! JSON ones. TOML datetimes are exposed as RFC 3339 strings, e.g. `1979-05-27T07:32:00Z`, so
+//! `format: date-time` applies to them. Integers are exact `i64` values and TOML has no null.
+//!
+//! ```rust,no_run
+//! # fn test() -> Result<(), Box<dyn std::error::Error>> {
+//! let schema = serde_json::json!({"properties": {"package": {"required": ["name"]}}});
+//! let validator = jsonschema::blocking::validator_for(&schema)?;
+//! let manifest = jsonschema::toml::load("Cargo.toml")?;
+//! validator.validate(&manifest)?;
+//! # Ok(())
+//! # }
+//! ```
+use std::path::Path;
+
+use jsonlike::{Json, JsonError};
+use toml::Value;
+
+use crate::files;
+
+/// Read a TOML document from `path`.
+pub fn load(path: impl AsRef<Path>) -> Result<Value, JsonError> {
+    files::load(path.as_ref(), Value::from_str)
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::json;
+    use test_case::test_case;
+    use toml::Value;
+
+    use crate::{blocking::ValidatorBuilder, BoxedFormat, Format, Json};
+
+    struct DateTime;
+
+    impl Format for DateTime {
+        fn is_valid(&self, value: &str) -> bool {
+            value.len() > 10 && value.as_bytes()[10] == b'T' && value.ends_with('Z')
+        }
+    }
+
+    #[test_case("started = 1979-05-27T07:32:00Z\nretries = 3", true)]
+    #[test_case("started = 1979-05-27 07:32:00Z\nretries = 3", true)]
+    #[test_case("started = 1979-05-27\nretries = 3", false)]
+    #[test_case("started = \"yesterday\"\nretries = 3", false)]
+    #[test_case("started = 1979-05-27T07:32:00Z\nretries = 9007199254740993", false)]
+    #[test_case("started = 1979-05-27T07:32:00Z\nretries = 3.5", false)]
+    fn test_validate(config: &str, expected: bool) {
+        let schema = json!({
+            "properties": {
+                "started": {"type": "string", "format": "date-time"},
+                "retries": {"type": "integer", "maximum": 9007199254740992_u64}
+            }
+        });
+        let validator = ValidatorBuilder::default()
+            .format("date-time", |_: &_| -> crate::BuildResult<BoxedFormat> {
+                Ok(Box::new(DateTime))
+            })
+            .build(&schema)
+            .expect("Valid schema");
+        let config = Value::from_str(config).expect("Valid TOML");
+        assert_eq!(validator.is_valid(&config), expected);
+    }
+}
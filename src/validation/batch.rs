@@ -0,0 +1,157 @@
+//! Validating many instances at once across multiple threads.
+use std::{
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+use jsonlike::Json;
+
+use crate::{ValidationError, Validator};
+
+/// Number of instances a thread takes at once, small enough to balance uneven instance sizes.
+const BLOCK_SIZE: usize = 256;
+
+impl<J: Json + 'static> Validator<J> {
+    /// Check many instances using up to `threads` threads.
+    ///
+    /// Results are in the same order as `instances`. With a single thread, or few instances, they
+    /// are validated on the current thread. This blocks until all instances are validated, so in
+    /// async code call it from a blocking task, e.g. `tokio::task::spawn_blocking`.
+    pub fn is_valid_many<'i, I>(
+        &self,
+        instances: impl IntoIterator<Item = &'i I>,
+        threads: usize,
+    ) -> Vec<bool>
+    where
+        I: Json + Sync + 'static,
+    {
+        map_ordered(instances, threads, |instance| self.is_valid(instance))
+    }
+
+    /// Validate many instances using up to `threads` threads, returning the first error of each.
+    ///
+    /// Results are in the same order as `instances`. With a single thread, or few instances, they
+    /// are validated on the current thread. This blocks until all instances are validated, so in
+    /// async code call it from a blocking task, e.g. `tokio::task::spawn_blocking`.
+    pub fn validate_many<'i, I>(
+        &self,
+        instances: impl IntoIterator<Item = &'i I>,
+        threads: usize,
+    ) -> Vec<Result<(), ValidationError>>
+    where
+        I: Json + Sync + 'static,
+    {
+        map_ordered(instances, threads, |instance| self.validate(instance))
+    }
+}
+
+/// Apply `f` to every instance, keeping the order of results.
+///
+/// Instances are read lazily on the current thread and handed out to the workers in blocks, so
+/// only a few blocks are held in memory at once besides the results.
+fn map_ordered<'i, I, R, F>(
+    instances: impl IntoIterator<Item = &'i I>,
+    threads: usize,
+    f: F,
+) -> Vec<R>
+where
+    I: Sync + 'i,
+    R: Send,
+    F: Fn(&I) -> R + Sync,
+{
+    let mut instances = instances.into_iter();
+    let first: Vec<&I> = instances.by_ref().take(BLOCK_SIZE + 1).collect();
+    if threads <= 1 || first.len() <= BLOCK_SIZE {
+        return first.into_iter().chain(instances).map(f).collect();
+    }
+    let f = &f;
+    let mut results: Vec<(usize, Vec<R>)> = thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel::<(usize, Vec<&I>)>(threads);
+        // Shared by the workers only, so sending fails rather than blocks if all of them panic
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                scope.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let next = receiver.lock().expect("Lock is poisoned").recv();
+                        let Ok((block, chunk)) = next else {
+                            return done;
+                        };
+                        done.push((block, chunk.into_iter().map(f).collect()));
+                    }
+                })
+            })
+            .collect();
+        drop(receiver);
+        let mut pending = first.into_iter().chain(instances);
+        for block in 0.. {
+            let chunk: Vec<&I> = pending.by_ref().take(BLOCK_SIZE).collect();
+            if chunk.is_empty() || sender.send((block, chunk)).is_err() {
+                break;
+            }
+        }
+        drop(sender);
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("Validation thread panicked"))
+            .collect()
+    });
+    results.sort_unstable_by_key(|(block, _)| *block);
+    results
+        .into_iter()
+        .flat_map(|(_, results)| results)
+        .collect()
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    fn instances(count: usize) -> Vec<Value> {
+        (0..count)
+            .map(|idx| if idx % 3 == 0 { json!("a") } else { json!(idx) })
+            .collect()
+    }
+
+    #[test_case(0, 4)]
+    #[test_case(10, 1)]
+    #[test_case(10, 0)]
+    #[test_case(256, 4)]
+    #[test_case(257, 2)]
+    #[test_case(1000, 4)]
+    #[test_case(5000, 3)]
+    fn test_is_valid_many(count: usize, threads: usize) {
+        let validator =
+            crate::blocking::validator_for(&json!({"type": "integer"})).expect("Invalid schema");
+        let instances = instances(count);
+        let expected: Vec<_> = instances
+            .iter()
+            .map(|instance| validator.is_valid(instance))
+            .collect();
+        assert_eq!(validator.is_valid_many(&instances, threads), expected);
+    }
+
+    #[test]
+    fn test_is_valid_many_iterator() {
+        let validator =
+            crate::blocking::validator_for(&json!({"type": "integer"})).expect("Invalid schema");
+        let instances = instances(3000);
+        let expected: Vec<_> = (0..3000).step_by(2).map(|idx| idx % 3 != 0).collect();
+        let results = validator.is_valid_many(instances.iter().step_by(2), 4);
+        assert_eq!(results, expected);
+    }
+
+    #[test]
+    fn test_validate_many() {
+        let schema = json!({"type": "integer"});
+        let instances = instances(600);
+        let results = crate::blocking::validate_many(&schema, &instances, 2);
+        assert_eq!(results.len(), instances.len());
+        for (idx, result) in results.iter().enumerate() {
+            assert_eq!(result.is_ok(), idx % 3 != 0);
+        }
+    }
+}
@@ -1,17 +1,24 @@
+use core::time::Duration;
 use std::{collections::HashMap, sync::Arc};
 
 use jsonlike::Json;
 
 use crate::{
-    compiler,
+    bundle, compiler,
+    content::{self, ContentEncoding, ContentMediaType},
+    dereference,
     drafts::{draft_from_schema, Draft},
     format::FormatFactory,
+    generate::Generator,
     resolver::DefaultResolver,
+    retrieval,
     vocabulary::KeywordFactory,
-    BuildResult, ReferenceResolver, Validator,
+    BoxedFormat, BoxedKeyword, BuildResult, ReferenceResolver, Validator,
 };
+#[cfg(feature = "snapshot")]
+use crate::{snapshot, Snapshot};
 
-pub async fn validator_for<J: Json>(schema: &J) -> BuildResult<Validator<J>> {
+pub async fn validator_for<J: Json + 'static>(schema: &J) -> BuildResult<Validator<J>> {
     let draft = draft_from_schema(schema);
     ValidatorBuilder::default().draft(draft).build(schema).await
 }
@@ -19,8 +26,12 @@ pub async fn validator_for<J: Json>(schema: &J) -> BuildResult<Validator<J>> {
 pub struct ValidatorBuilder<'a, J: Json> {
     pub(crate) draft: Draft,
     pub(crate) resolver: Arc<dyn ReferenceResolver<J>>,
-    pub(crate) formats: HashMap<String, Arc<dyn FormatFactory<'a, J>>>,
-    pub(crate) keywords: HashMap<String, Arc<dyn KeywordFactory<'a, J>>>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) formats: HashMap<String, Arc<dyn FormatFactory<J> + 'a>>,
+    pub(crate) keywords: HashMap<String, Arc<dyn KeywordFactory<J> + 'a>>,
+    pub(crate) validate_content: bool,
+    pub(crate) content_encodings: HashMap<String, Arc<dyn ContentEncoding>>,
+    pub(crate) content_media_types: HashMap<String, Arc<dyn ContentMediaType<J>>>,
 }
 
 impl<'a, J: Json> Default for ValidatorBuilder<'a, J> {
@@ -28,16 +39,78 @@ impl<'a, J: Json> Default for ValidatorBuilder<'a, J> {
         ValidatorBuilder {
             draft: Draft::latest(),
             resolver: Arc::new(DefaultResolver),
+            timeout: None,
             formats: HashMap::default(),
             keywords: HashMap::default(),
+            validate_content: false,
+            content_encodings: HashMap::from([
+                (
+                    "base64".to_string(),
+                    Arc::new(content::Base64) as Arc<dyn ContentEncoding>,
+                ),
+                ("base64url".to_string(), Arc::new(content::Base64Url)),
+                (
+                    "quoted-printable".to_string(),
+                    Arc::new(content::QuotedPrintable),
+                ),
+            ]),
+            content_media_types: HashMap::from([(
+                "application/json".to_string(),
+                Arc::new(content::ApplicationJson) as Arc<dyn ContentMediaType<J>>,
+            )]),
         }
     }
 }
 
 impl<'a, J: Json> ValidatorBuilder<'a, J> {
-    pub async fn build(&self, schema: &J) -> BuildResult<Validator<J>> {
-        // TODO: Resolve references
-        compiler::compile::<J>(schema, self.draft)
+    /// Build a validator for `schema`.
+    ///
+    /// Documents referenced via `$ref` are retrieved through the resolver first, including
+    /// documents referenced by retrieved ones. Retrievals run concurrently and every URI is
+    /// retrieved once. Dropping the returned future cancels the retrievals in flight.
+    pub async fn build(&self, schema: &'a J) -> BuildResult<Validator<J>> {
+        let documents = retrieval::retrieve(schema, self).await?;
+        compiler::compile(schema, &documents, self)
+    }
+    /// Bundle `schema` and the documents it references into one compound document.
+    ///
+    /// Referenced documents are retrieved like in `build` and embedded under `$defs`
+    /// (`definitions` before 2019-09), with the URI they were retrieved from as their `$id`.
+    /// References are kept as they are, so the result resolves without a resolver.
+    pub async fn bundle(&self, schema: &J) -> BuildResult<J> {
+        let documents = retrieval::retrieve(schema, self).await?;
+        bundle::bundle(schema, &documents, self.draft)
+    }
+    /// Replace every reference in `schema` with the schema it points to.
+    ///
+    /// Referenced documents are retrieved like in `build`. Recursive references can not be
+    /// inlined and point to the location their target was inlined at instead. Inlined schemas
+    /// lose their `$id` and `$schema`, as they are no longer separate resources.
+    pub async fn dereference(&self, schema: &J) -> BuildResult<J> {
+        let documents = retrieval::retrieve(schema, self).await?;
+        dereference::dereference(schema, &documents, self.draft)
+    }
+    /// Create a generator of instances that are valid against `schema`.
+    ///
+    /// Referenced documents are retrieved like in `build`. Candidates are checked by a
+    /// validator built with the settings of this builder, including custom keywords and formats.
+    pub async fn generator(&self, schema: &'a J) -> BuildResult<Generator<J>>
+    where
+        J: 'static,
+    {
+        let documents = retrieval::retrieve(schema, self).await?;
+        let validator = compiler::compile(schema, &documents, self)?;
+        let dereferenced = dereference::dereference(schema, &documents, self.draft)?;
+        Generator::new(&dereferenced, validator)
+    }
+    /// Create a validator from a snapshot made by `Validator::to_snapshot`.
+    ///
+    /// Custom keywords, formats and content handlers used by the snapshot are looked up by name
+    /// in this builder and initialized again. The builder must use the same `validate_content`
+    /// setting as the one that made the snapshot; all other settings are taken from the snapshot.
+    #[cfg(feature = "snapshot")]
+    pub fn load(&self, snapshot: &Snapshot<J>) -> BuildResult<Validator<J>> {
+        snapshot::load(snapshot, self)
     }
     pub fn draft(&mut self, draft: Draft) -> &mut ValidatorBuilder<'a, J> {
         self.draft = draft;
@@ -47,18 +120,53 @@ impl<'a, J: Json> ValidatorBuilder<'a, J> {
         self.resolver = Arc::new(resolver);
         self
     }
+    /// Fail the build if retrieving referenced documents takes longer than `duration` in total.
+    pub fn timeout(&mut self, duration: Duration) -> &mut Self {
+        self.timeout = Some(duration);
+        self
+    }
     pub fn format<F>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
     where
-        F: FormatFactory<'a, J>,
+        F: Fn(&J) -> BuildResult<BoxedFormat> + Send + Sync + 'a,
     {
         self.formats.insert(name.into(), Arc::new(factory));
         self
     }
     pub fn keyword<F>(&mut self, name: impl Into<String>, factory: F) -> &mut Self
     where
-        F: KeywordFactory<'a, J>,
+        F: Fn(&J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a,
     {
         self.keywords.insert(name.into(), Arc::new(factory));
         self
     }
+    /// Assert `contentEncoding`, `contentMediaType` and `contentSchema` instead of treating them
+    /// as annotations.
+    pub fn validate_content(&mut self, yes: bool) -> &mut Self {
+        self.validate_content = yes;
+        self
+    }
+    /// Register a decoder for a `contentEncoding` value. Encoding names are case-insensitive.
+    pub fn content_encoding(
+        &mut self,
+        name: impl Into<String>,
+        encoding: impl ContentEncoding,
+    ) -> &mut Self {
+        self.content_encodings
+            .insert(name.into().to_ascii_lowercase(), Arc::new(encoding));
+        self
+    }
+    /// Register a parser for a `contentMediaType` value, e.g. `application/xml`.
+    ///
+    /// Media type parameters are not taken into account when looking up a parser.
+    pub fn content_media_type(
+        &mut self,
+        name: impl AsRef<str>,
+        media_type: impl ContentMediaType<J>,
+    ) -> &mut Self {
+        self.content_media_types.insert(
+            content::media_type_essence(name.as_ref()),
+            Arc::new(media_type),
+        );
+        self
+    }
 }
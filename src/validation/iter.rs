@@ -1,27 +1,34 @@
 use crate::{maybe_owned::MaybeOwned, ValidationError, Validator};
 use jsonlike::Json;
 
-pub struct ValidationErrorIter<'v, 'i, J: Json> {
+/// Errors of validating an instance of type `I` against a schema of type `J`.
+pub struct ValidationErrorIter<'v, 'i, J: Json, I: Json = J> {
     validator: MaybeOwned<'v, Validator<J>>,
-    instance: &'i J,
+    instance: &'i I,
+    /// Errors are collected on the first call to `next`.
+    errors: Option<std::vec::IntoIter<ValidationError>>,
 }
 
-impl<'v, 'i, J: Json> ValidationErrorIter<'v, 'i, J> {
+impl<'v, 'i, J: Json, I: Json> ValidationErrorIter<'v, 'i, J, I> {
     pub(crate) fn new(
         validator: MaybeOwned<'v, Validator<J>>,
-        instance: &'i J,
-    ) -> ValidationErrorIter<'v, 'i, J> {
+        instance: &'i I,
+    ) -> ValidationErrorIter<'v, 'i, J, I> {
         ValidationErrorIter {
             validator,
             instance,
+            errors: None,
         }
     }
 }
 
-impl<'v, 'i, J: Json> Iterator for ValidationErrorIter<'v, 'i, J> {
+impl<'v, 'i, J: Json + 'static, I: Json + 'static> Iterator for ValidationErrorIter<'v, 'i, J, I> {
     type Item = ValidationError;
 
     fn next(&mut self) -> Option<Self::Item> {
-        None
+        let (validator, instance) = (&self.validator, self.instance);
+        self.errors
+            .get_or_insert_with(|| validator.collect_errors(instance).into_iter())
+            .next()
     }
 }
@@ -1,115 +1,335 @@
+use std::any::Any;
+
+use jpointer::{JsonPointer, JsonPointerNode};
 use jsonlike::Json;
+pub(crate) mod batch;
 pub(crate) mod builder;
 pub(crate) mod iter;
 use crate::{
-    graph, maybe_owned::MaybeOwned, output::Output, vocabulary::KeywordValue, BuildResult,
-    ValidationError,
+    error::ValidationErrorKind,
+    extensions::Extensions,
+    graph::{self, NodeId},
+    maybe_owned::MaybeOwned,
+    output::Output,
+    value::Value,
+    vocabulary::SchemaNode,
+    BuildResult, ValidationError,
 };
 use builder::validator_for;
 use iter::ValidationErrorIter;
 
-pub async fn is_valid<J: Json>(schema: &J, instance: &J) -> bool {
+pub async fn is_valid<J: Json + 'static, I: Json + 'static>(schema: &J, instance: &I) -> bool {
     try_is_valid(schema, instance)
         .await
         .expect("Invalid schema")
 }
 
-pub async fn try_is_valid<J: Json>(schema: &J, instance: &J) -> BuildResult<bool> {
+pub async fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+    schema: &J,
+    instance: &I,
+) -> BuildResult<bool> {
     Ok(validator_for(schema).await?.is_valid(instance))
 }
 
-pub async fn validate<J: Json>(schema: &J, instance: &J) -> Result<(), ValidationError> {
+pub async fn validate<J: Json + 'static, I: Json + 'static>(
+    schema: &J,
+    instance: &I,
+) -> Result<(), ValidationError> {
     try_validate(schema, instance)
         .await
         .expect("Invalid schema")
 }
 
-pub async fn try_validate<J: Json>(
+pub async fn try_validate<J: Json + 'static, I: Json + 'static>(
     schema: &J,
-    instance: &J,
+    instance: &I,
 ) -> BuildResult<Result<(), ValidationError>> {
     Ok(validator_for(schema).await?.validate(instance))
 }
 
-pub async fn iter_errors<'s, 'i, J: Json>(
-    schema: &'s J,
-    instance: &'i J,
-) -> ValidationErrorIter<'static, 'i, J> {
+pub async fn iter_errors<'i, J: Json + 'static, I: Json + 'static>(
+    schema: &J,
+    instance: &'i I,
+) -> ValidationErrorIter<'static, 'i, J, I> {
     try_iter_errors(schema, instance)
         .await
         .expect("Invalid schema")
 }
 
-pub async fn try_iter_errors<'s, 'i, J: Json>(
-    schema: &'s J,
-    instance: &'i J,
-) -> BuildResult<ValidationErrorIter<'static, 'i, J>> {
+pub async fn try_iter_errors<'i, J: Json + 'static, I: Json + 'static>(
+    schema: &J,
+    instance: &'i I,
+) -> BuildResult<ValidationErrorIter<'static, 'i, J, I>> {
     let validator = validator_for(schema).await?;
     Ok(validator.iter_errors_once(instance))
 }
 
-pub async fn evaluate<'i, J: Json>(instance: &'i J, schema: &J) -> Output<'static, 'i, J> {
+pub async fn evaluate<'i, J: Json + 'static, I: Json + 'static>(
+    instance: &'i I,
+    schema: &J,
+) -> Output<'static, 'i, J, I> {
     try_evaluate(instance, schema)
         .await
         .expect("Invalid schema")
 }
 
-pub async fn try_evaluate<'i, J: Json>(
-    instance: &'i J,
+pub async fn try_evaluate<'i, J: Json + 'static, I: Json + 'static>(
+    instance: &'i I,
     schema: &J,
-) -> BuildResult<Output<'static, 'i, J>> {
+) -> BuildResult<Output<'static, 'i, J, I>> {
     Ok(validator_for(schema).await?.evaluate_once(instance))
 }
 
+/// A compiled schema.
+///
+/// The schema backend `J` is only relevant for compilation, custom keywords and content media
+/// types. Instances may come from any backend, e.g. a schema loaded via `serde_json` can validate
+/// Python objects.
+///
+/// If the schema uses custom keywords, instances of other backends are converted into `J` once per
+/// validation, as the keywords are written against it. Instance types are `'static` so that ones of
+/// the schema backend are recognized and passed to custom keywords without conversion.
 #[derive(Debug, Clone)]
 pub struct Validator<J: Json> {
-    graph: graph::Graph<KeywordValue<J>>,
+    pub(crate) graph: graph::Graph<SchemaNode>,
+    pub(crate) root: NodeId,
+    extensions: Extensions<J>,
 }
 
 impl<J: Json> Validator<J> {
-    pub(crate) fn new(graph: graph::Graph<KeywordValue<J>>) -> Self {
-        Self { graph }
+    pub(crate) fn new(
+        graph: graph::Graph<SchemaNode>,
+        root: NodeId,
+        extensions: Extensions<J>,
+    ) -> Self {
+        Self {
+            graph,
+            root,
+            extensions,
+        }
+    }
+    pub(crate) fn extensions(&self) -> &Extensions<J> {
+        &self.extensions
     }
+}
 
-    pub fn is_valid(&self, instance: &J) -> bool {
-        true
+impl<J: Json + 'static> Validator<J> {
+    pub fn is_valid<I: Json + 'static>(&self, instance: &I) -> bool {
+        self.is_valid_at(self.root, instance)
     }
-    pub fn validate(&self, instance: &J) -> Result<(), ValidationError> {
+    pub fn validate<I: Json + 'static>(&self, instance: &I) -> Result<(), ValidationError> {
         match self.iter_errors(instance).next() {
             None => Ok(()),
             Some(error) => Err(error),
         }
     }
-    pub fn iter_errors<'v, 'i>(&'v self, instance: &'i J) -> ValidationErrorIter<'v, 'i, J> {
+    pub fn iter_errors<'v, 'i, I: Json + 'static>(
+        &'v self,
+        instance: &'i I,
+    ) -> ValidationErrorIter<'v, 'i, J, I> {
         ValidationErrorIter::new(MaybeOwned::Borrowed(self), instance)
     }
-    pub(crate) fn iter_errors_once(self, instance: &J) -> ValidationErrorIter<'static, '_, J> {
+    pub(crate) fn iter_errors_once<I: Json + 'static>(
+        self,
+        instance: &I,
+    ) -> ValidationErrorIter<'static, '_, J, I> {
         ValidationErrorIter::new(MaybeOwned::Owned(self), instance)
     }
-    pub fn evaluate<'v, 'i>(&'v self, instance: &'i J) -> Output<'v, 'i, J> {
+    pub fn evaluate<'v, 'i, I: Json + 'static>(&'v self, instance: &'i I) -> Output<'v, 'i, J, I> {
         Output::new(MaybeOwned::Borrowed(self), instance)
     }
-    pub(crate) fn evaluate_once(self, instance: &J) -> Output<'static, '_, J> {
+    pub(crate) fn evaluate_once<I: Json + 'static>(
+        self,
+        instance: &I,
+    ) -> Output<'static, '_, J, I> {
         Output::new(MaybeOwned::Owned(self), instance)
     }
+
+    pub(crate) fn collect_errors<I: Json + 'static>(&self, instance: &I) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        self.collect_errors_at(
+            self.root,
+            instance,
+            &JsonPointer::new(),
+            &JsonPointerNode::new(),
+            &mut errors,
+        );
+        errors
+    }
+
+    /// Convert an instance of another backend into the schema one if custom keywords need it.
+    ///
+    /// Returns `None` if the instance can be validated as it is.
+    fn convert<I: Json + 'static>(&self, instance: &I) -> Option<BuildResult<J>> {
+        if self.extensions.keywords.is_empty() || (instance as &dyn Any).is::<J>() {
+            return None;
+        }
+        Some(Value::from_json(instance).and_then(|value| Ok(J::from_str(&value.to_string())?)))
+    }
+
+    /// Validate a standalone value, such as the root instance or a property name, against a
+    /// compiled (sub)schema. Unlike `is_valid_node`, it converts the value if needed.
+    pub(crate) fn is_valid_at<I: Json + 'static>(&self, node: NodeId, instance: &I) -> bool {
+        match self.convert(instance) {
+            None => self.is_valid_node(node, instance),
+            Some(Ok(converted)) => self.is_valid_node(node, &converted),
+            Some(Err(_)) => false,
+        }
+    }
+
+    pub(crate) fn collect_errors_at<I: Json + 'static>(
+        &self,
+        node: NodeId,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        match self.convert(instance) {
+            None => self.collect_node_errors(node, instance, path, errors),
+            Some(Ok(converted)) => self.collect_node_errors(node, &converted, path, errors),
+            Some(Err(error)) => errors.push(ValidationError::new(
+                ValidationErrorKind::InvalidInstance {
+                    reason: error.to_string(),
+                },
+                path,
+                location,
+            )),
+        }
+    }
+
+    /// Validate `instance` against a compiled (sub)schema.
+    ///
+    /// The instance type may differ from the schema one, e.g. for property names.
+    pub(crate) fn is_valid_node<I: Json + 'static>(&self, node: NodeId, instance: &I) -> bool {
+        match self.graph.get(node) {
+            SchemaNode::Boolean { valid, .. } => *valid,
+            SchemaNode::Keywords(keywords) => keywords
+                .iter()
+                .all(|keyword| keyword.value.is_valid(self, instance)),
+        }
+    }
+
+    pub(crate) fn collect_node_errors<I: Json + 'static>(
+        &self,
+        node: NodeId,
+        instance: &I,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        match self.graph.get(node) {
+            SchemaNode::Boolean { valid: true, .. } => {}
+            SchemaNode::Boolean {
+                valid: false,
+                location,
+            } => errors.push(ValidationError::new(
+                ValidationErrorKind::FalseSchema,
+                path,
+                location,
+            )),
+            SchemaNode::Keywords(keywords) => {
+                for keyword in keywords {
+                    keyword
+                        .value
+                        .collect_errors(self, instance, &keyword.location, path, errors);
+                }
+            }
+        }
+    }
 }
 
 #[cfg(all(test, feature = "serde_json"))]
 mod tests {
     use serde_json::json;
+    use test_case::test_case;
+
+    use crate::{value::Value, BoxedKeyword, BuildResult};
 
     #[tokio::test]
     async fn test_validator_for() {
         let schema = json!({"type": "integer"});
-        let validator = crate::validator_for(&schema).await.expect("Invalid schema");
+        let _validator = crate::validator_for(&schema).await.expect("Invalid schema");
     }
 
     #[tokio::test]
     async fn test_builder() {
         let schema = json!({"type": "integer"});
-        let validator = crate::ValidatorBuilder::default()
+        let _validator = crate::ValidatorBuilder::default()
             .build(&schema)
             .await
             .expect("Invalid schema");
     }
+
+    #[test_case(json!({"a": [1, "b"]}), true)]
+    #[test_case(json!({"a": [1.5]}), false)]
+    #[test_case(json!({"a": [], "B": null}), false)]
+    #[test_case(json!({"a": [], "c": 18446744073709551616_f64}), false)]
+    fn test_other_instance_backend(instance: serde_json::Value, expected: bool) {
+        let schema = json!({
+            "properties": {"a": {"items": {"type": ["integer", "string"]}}},
+            "propertyNames": {"pattern": "^[a-z]$"},
+            "additionalProperties": {"maximum": 1e19}
+        });
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        // The crate's own value type stands in for another backend
+        let instance = Value::from_json(&instance).expect("Invalid instance");
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.validate(&instance).is_ok(), expected);
+        assert_eq!(crate::blocking::is_valid(&schema, &instance), expected);
+    }
+
+    #[derive(Debug)]
+    struct Short;
+
+    impl crate::Keyword<serde_json::Value> for Short {
+        fn is_valid(&self, instance: &serde_json::Value) -> bool {
+            instance.as_str().is_none_or(|value| value.len() < 3)
+        }
+    }
+
+    #[test]
+    fn test_custom_keyword_other_backend() {
+        let schema = json!({"items": {"short": true}, "propertyNames": {"short": true}});
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .keyword("short", |_: &_| -> BuildResult<BoxedKeyword<_>> {
+                Ok(Box::new(Short))
+            })
+            .build(&schema)
+            .expect("Invalid schema");
+        let valid = Value::from_json(&json!(["ab", 1])).expect("Invalid instance");
+        let invalid = Value::from_json(&json!(["abc"])).expect("Invalid instance");
+        assert!(validator.is_valid(&valid));
+        assert!(!validator.is_valid(&invalid));
+        assert!(validator.is_valid(&json!({"ab": 1})));
+        assert!(!validator.is_valid(&json!({"abc": 1})));
+        let invalid = Value::from_json(&json!({"abc": 1})).expect("Invalid instance");
+        let errors: Vec<_> = validator.iter_errors(&invalid).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "Value is not valid under the \"short\" keyword"
+        );
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn test_custom_keyword_unconvertible_instance() {
+        let schema = json!({"items": {"short": true}});
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .keyword("short", |_: &_| -> BuildResult<BoxedKeyword<_>> {
+                Ok(Box::new(Short))
+            })
+            .build(&schema)
+            .expect("Invalid schema");
+        // [h'01']
+        let instance: ciborium::Value =
+            ciborium::from_reader(&[0x81, 0x41, 0x01][..]).expect("Valid CBOR");
+        assert!(!validator.is_valid(&instance));
+        let errors: Vec<_> = validator.iter_errors(&instance).collect();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0].kind(),
+            crate::ValidationErrorKind::InvalidInstance { .. }
+        ));
+    }
 }
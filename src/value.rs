@@ -0,0 +1,752 @@
+use core::fmt::{self, Write};
+use std::borrow::Cow;
+
+use jsonlike::prelude::*;
+
+use crate::{
+    number::{self, Decimal},
+    BuildResult,
+};
+
+/// An owned, backend-independent JSON value.
+///
+/// Compiled keywords can not borrow from the schema they were built from, therefore values used
+/// by `const` and `enum` are copied into this representation. It also implements `Json`, so
+/// values that do not exist in the instance (e.g. object keys for `propertyNames`) can be
+/// validated like any other instance.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Array),
+    Object(Object),
+}
+
+/// A number with its exact decimal representation if the float is not exact.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Number {
+    float: f64,
+    decimal: Option<Box<str>>,
+}
+
+impl Number {
+    pub(crate) fn from_json<N: for<'n> JsonNumber<'n> + ?Sized>(number: &N) -> Number {
+        let decimal = number.as_decimal();
+        // Numbers beyond the `f64` range have no float, they round to an infinity of their sign
+        let float = number
+            .as_float()
+            .or_else(|| decimal.as_deref()?.parse().ok())
+            .unwrap_or(f64::NAN);
+        Number {
+            float,
+            decimal: decimal.map(Into::into),
+        }
+    }
+
+    fn from_literal(literal: &str) -> Option<Number> {
+        let float = literal.parse::<f64>().ok()?;
+        let is_exact = Decimal::parse(literal) == Decimal::parse(&float.to_string());
+        Some(Number {
+            float,
+            decimal: (!is_exact).then(|| literal.into()),
+        })
+    }
+
+    pub(crate) fn from_f64(float: f64) -> Number {
+        Number {
+            float,
+            decimal: None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> f64 {
+        self.float
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        number::equal(self, other)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Array(Vec<Value>);
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Object(Vec<(String, Value)>);
+
+impl PartialEq for Object {
+    /// Objects are equal if they have the same entries, regardless of their order.
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .all(|(key, value)| other.get(key).is_some_and(|other| value == other))
+    }
+}
+
+impl Array {
+    pub(crate) fn push(&mut self, value: Value) {
+        self.0.push(value);
+    }
+
+    pub(crate) fn items(&self) -> &[Value] {
+        &self.0
+    }
+}
+
+impl From<Vec<Value>> for Array {
+    fn from(items: Vec<Value>) -> Array {
+        Array(items)
+    }
+}
+
+impl Object {
+    /// Add an entry, replacing the value of an existing key as in most JSON parsers.
+    pub(crate) fn insert(&mut self, key: String, value: Value) {
+        if let Some(entry) = self.0.iter_mut().find(|(k, _)| *k == key) {
+            entry.1 = value;
+        } else {
+            self.0.push((key, value));
+        }
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<Value> {
+        let idx = self.0.iter().position(|(k, _)| k == key)?;
+        Some(self.0.remove(idx).1)
+    }
+
+    pub(crate) fn entries(&self) -> &[(String, Value)] {
+        &self.0
+    }
+}
+
+impl From<Vec<(String, Value)>> for Object {
+    /// Build an object from entries with unique keys.
+    fn from(entries: Vec<(String, Value)>) -> Object {
+        Object(entries)
+    }
+}
+
+impl Value {
+    pub(crate) fn from_json<J: Json>(value: &J) -> BuildResult<Value> {
+        if let Some(object) = value.try_as_object()? {
+            let mut entries = Vec::with_capacity(object.len());
+            for (key, value) in object.iter() {
+                entries.push((key?.as_ref().to_owned(), Value::from_json(value)?));
+            }
+            Ok(Value::Object(Object(entries)))
+        } else if let Some(array) = value.try_as_array()? {
+            let mut items = Vec::with_capacity(array.len());
+            for item in array.try_iter()? {
+                items.push(Value::from_json(item?)?);
+            }
+            Ok(Value::Array(Array(items)))
+        } else if let Some(string) = value.try_as_string()? {
+            Ok(Value::String(string.as_ref().to_owned()))
+        } else if let Some(number) = value.try_as_number()? {
+            Ok(Value::Number(Number::from_json(number)))
+        } else if let Some(boolean) = value.try_as_boolean()? {
+            Ok(Value::Bool(boolean))
+        } else {
+            Ok(Value::Null)
+        }
+    }
+
+    /// Compare with an instance according to the JSON Schema equality rules.
+    ///
+    /// Fails if the instance, or any value within it, can not be read by its backend.
+    pub(crate) fn try_equals<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+        match self {
+            Value::Null => instance.try_is_null(),
+            Value::Bool(expected) => Ok(instance.try_as_boolean()? == Some(*expected)),
+            Value::Number(expected) => Ok(instance
+                .try_as_number()?
+                .is_some_and(|value| number::equal(value, expected))),
+            Value::String(expected) => Ok(instance
+                .try_as_string()?
+                .is_some_and(|value| value.as_ref() == expected)),
+            Value::Array(expected) => {
+                let Some(array) = instance.try_as_array()? else {
+                    return Ok(false);
+                };
+                if array.len() != expected.0.len() {
+                    return Ok(false);
+                }
+                for (expected, item) in expected.0.iter().zip(array.try_iter()?) {
+                    if !expected.try_equals(item?)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Value::Object(expected) => {
+                let Some(object) = instance.try_as_object()? else {
+                    return Ok(false);
+                };
+                if object.len() != expected.0.len() {
+                    return Ok(false);
+                }
+                for (key, expected) in &expected.0 {
+                    match object.get(key) {
+                        Some(value) if expected.try_equals(value)? => {}
+                        _ => return Ok(false),
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Number(value) => match &value.decimal {
+                Some(decimal) => f.write_str(decimal),
+                None => write_number(f, value.float),
+            },
+            Value::String(value) => write_string(f, value),
+            Value::Array(items) => {
+                f.write_char('[')?;
+                for (idx, item) in items.0.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_char(',')?;
+                    }
+                    item.fmt(f)?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(entries) => {
+                f.write_char('{')?;
+                for (idx, (key, value)) in entries.0.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    f.write_char(':')?;
+                    value.fmt(f)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+pub(crate) fn write_number(f: &mut impl Write, value: f64) -> fmt::Result {
+    if value.fract() == 0.0 && value.abs() < 1e16 {
+        write!(f, "{}", value as i64)
+    } else {
+        write!(f, "{value}")
+    }
+}
+
+pub(crate) fn write_string(f: &mut impl Write, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for ch in value.chars() {
+        match ch {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => f.write_char(ch)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Check that `value` and everything within it can be read by its backend, so schemas can be
+/// compiled with the infallible accessors afterwards.
+pub(crate) fn check<J: Json>(value: &J) -> Result<(), JsonError> {
+    if let Some(object) = value.try_as_object()? {
+        for (key, value) in object.iter() {
+            key?;
+            check(value)?;
+        }
+    } else if let Some(array) = value.try_as_array()? {
+        for item in array.try_iter()? {
+            check(item?)?;
+        }
+    } else {
+        value.try_as_string()?;
+        value.try_as_number()?;
+        value.try_as_boolean()?;
+        value.try_as_null()?;
+    }
+    Ok(())
+}
+
+/// Compare two instances according to the JSON Schema equality rules.
+///
+/// Unlike `Json::equal`, numbers are compared by their mathematical value, so `1` equals `1.0`.
+/// Values that can not be read by their backend are never equal.
+pub(crate) fn equal<J: Json>(left: &J, right: &J) -> bool {
+    try_equal(left, right).unwrap_or(false)
+}
+
+/// Like `equal`, but fails if either side can not be read by its backend.
+pub(crate) fn try_equal<J: Json>(left: &J, right: &J) -> Result<bool, JsonError> {
+    if let Some(left) = left.try_as_number()? {
+        return Ok(right
+            .try_as_number()?
+            .is_some_and(|right| number::equal(left, right)));
+    }
+    if let Some(left) = left.try_as_array()? {
+        let Some(right) = right.try_as_array()? else {
+            return Ok(false);
+        };
+        if left.len() != right.len() {
+            return Ok(false);
+        }
+        for (left, right) in left.try_iter()?.zip(right.try_iter()?) {
+            if !try_equal(left?, right?)? {
+                return Ok(false);
+            }
+        }
+        return Ok(true);
+    }
+    if let Some(left) = left.try_as_object()? {
+        let Some(right) = right.try_as_object()? else {
+            return Ok(false);
+        };
+        if left.len() != right.len() {
+            return Ok(false);
+        }
+        for (key, left) in left.iter() {
+            match right.get(key?.as_ref()) {
+                Some(right) if try_equal(left, right)? => {}
+                _ => return Ok(false),
+            }
+        }
+        return Ok(true);
+    }
+    if right.try_is_number()? || right.try_is_array()? || right.try_is_object()? {
+        return Ok(false);
+    }
+    left.try_equal(right)
+}
+
+pub(crate) struct ObjectIter<'a>(core::slice::Iter<'a, (String, Value)>);
+
+impl<'a> Iterator for ObjectIter<'a> {
+    type Item = (Result<&'a str, JsonError>, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (Ok(key.as_str()), value))
+    }
+}
+
+impl JsonObject for Object {
+    type Key = str;
+    type Value = Value;
+    type Iter<'a> = ObjectIter<'a>;
+
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find_map(|(k, value)| (k == key).then_some(value))
+    }
+
+    fn iter(&self) -> Self::Iter<'_> {
+        ObjectIter(self.0.iter())
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+pub(crate) struct ArrayIter<'a>(core::slice::Iter<'a, Value>);
+
+impl<'a> Iterator for ArrayIter<'a> {
+    type Item = Result<&'a Value, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Ok)
+    }
+}
+
+impl JsonArray for Array {
+    type Element = Value;
+    type Iter<'a> = ArrayIter<'a>;
+
+    fn try_iter(&self) -> Result<Self::Iter<'_>, JsonError> {
+        Ok(ArrayIter(self.0.iter()))
+    }
+
+    fn try_get(&self, idx: usize) -> Result<Option<&Value>, JsonError> {
+        Ok(self.0.get(idx))
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<'a> JsonNumber<'a> for Number {
+    type Integer = i64;
+
+    fn as_integer<I: TryFrom<i64>>(&'a self) -> Option<i64> {
+        (self.float.fract() == 0.0).then_some(self.float as i64)
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        self.float.is_finite().then_some(self.float)
+    }
+
+    fn as_decimal(&self) -> Option<Cow<'_, str>> {
+        self.decimal.as_deref().map(Cow::Borrowed)
+    }
+}
+
+impl Json for Value {
+    type Object = Object;
+    type Array = Array;
+    type String = str;
+    type Number = Number;
+
+    fn try_as_object(&self) -> Result<Option<&Object>, JsonError> {
+        Ok(match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        })
+    }
+
+    fn try_as_array(&self) -> Result<Option<&Array>, JsonError> {
+        Ok(match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        })
+    }
+
+    fn try_as_string(&self) -> Result<Option<&str>, JsonError> {
+        Ok(match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        })
+    }
+
+    fn try_as_number(&self) -> Result<Option<&Number>, JsonError> {
+        Ok(match self {
+            Value::Number(number) => Some(number),
+            _ => None,
+        })
+    }
+
+    fn try_as_boolean(&self) -> Result<Option<bool>, JsonError> {
+        Ok(match self {
+            Value::Bool(boolean) => Some(*boolean),
+            _ => None,
+        })
+    }
+
+    fn try_as_null(&self) -> Result<Option<()>, JsonError> {
+        Ok(matches!(self, Value::Null).then_some(()))
+    }
+
+    fn try_equal(&self, other: &Self) -> Result<bool, JsonError> {
+        Ok(self == other)
+    }
+
+    fn from_str(s: &str) -> Result<Self, JsonError>
+    where
+        Self: Sized,
+    {
+        let mut parser = Parser {
+            input: s.as_bytes(),
+            position: 0,
+        };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.position != parser.input.len() {
+            return Err(parser.error("Trailing characters"));
+        }
+        Ok(value)
+    }
+}
+
+#[derive(Debug)]
+struct ParseError {
+    message: &'static str,
+    position: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A minimal recursive descent parser for RFC 8259 JSON documents.
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> JsonError {
+        JsonError::new(Box::new(ParseError {
+            message,
+            position: self.position,
+        }))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.position) {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, literal: &'static str, value: Value) -> Result<Value, JsonError> {
+        if self.input[self.position..].starts_with(literal.as_bytes()) {
+            self.position += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error("Invalid literal"))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, JsonError> {
+        self.skip_whitespace();
+        match self.input.get(self.position) {
+            Some(b'n') => self.expect("null", Value::Null),
+            Some(b't') => self.expect("true", Value::Bool(true)),
+            Some(b'f') => self.expect("false", Value::Bool(false)),
+            Some(b'"') => self.parse_string().map(Value::String),
+            Some(b'[') => {
+                self.position += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.input.get(self.position) == Some(&b']') {
+                    self.position += 1;
+                    return Ok(Value::Array(Array(items)));
+                }
+                loop {
+                    items.push(self.parse_value()?);
+                    self.skip_whitespace();
+                    match self.input.get(self.position) {
+                        Some(b',') => self.position += 1,
+                        Some(b']') => {
+                            self.position += 1;
+                            return Ok(Value::Array(Array(items)));
+                        }
+                        _ => return Err(self.error("Expected ',' or ']'")),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.position += 1;
+                let mut entries = Object::default();
+                self.skip_whitespace();
+                if self.input.get(self.position) == Some(&b'}') {
+                    self.position += 1;
+                    return Ok(Value::Object(entries));
+                }
+                loop {
+                    self.skip_whitespace();
+                    if self.input.get(self.position) != Some(&b'"') {
+                        return Err(self.error("Expected a string key"));
+                    }
+                    let key = self.parse_string()?;
+                    self.skip_whitespace();
+                    if self.input.get(self.position) != Some(&b':') {
+                        return Err(self.error("Expected ':'"));
+                    }
+                    self.position += 1;
+                    let value = self.parse_value()?;
+                    entries.insert(key, value);
+                    self.skip_whitespace();
+                    match self.input.get(self.position) {
+                        Some(b',') => self.position += 1,
+                        Some(b'}') => {
+                            self.position += 1;
+                            return Ok(Value::Object(entries));
+                        }
+                        _ => return Err(self.error("Expected ',' or '}'")),
+                    }
+                }
+            }
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            _ => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Value, JsonError> {
+        let start = self.position;
+        if self.input.get(self.position) == Some(&b'-') {
+            self.position += 1;
+        }
+        let digits = |parser: &mut Self| {
+            let begin = parser.position;
+            while let Some(b'0'..=b'9') = parser.input.get(parser.position) {
+                parser.position += 1;
+            }
+            parser.position - begin
+        };
+        let integer_start = self.position;
+        match digits(self) {
+            0 => return Err(self.error("Expected a digit")),
+            length if length > 1 && self.input[integer_start] == b'0' => {
+                return Err(self.error("Leading zeros are not allowed"))
+            }
+            _ => {}
+        }
+        if self.input.get(self.position) == Some(&b'.') {
+            self.position += 1;
+            if digits(self) == 0 {
+                return Err(self.error("Expected a digit"));
+            }
+        }
+        if let Some(b'e' | b'E') = self.input.get(self.position) {
+            self.position += 1;
+            if let Some(b'+' | b'-') = self.input.get(self.position) {
+                self.position += 1;
+            }
+            if digits(self) == 0 {
+                return Err(self.error("Expected a digit"));
+            }
+        }
+        // The slice contains only ASCII characters matched above
+        let text = std::str::from_utf8(&self.input[start..self.position])
+            .map_err(|_| self.error("Invalid number"))?;
+        Number::from_literal(text)
+            .map(Value::Number)
+            .ok_or_else(|| self.error("Invalid number"))
+    }
+
+    fn parse_hex(&mut self) -> Result<u32, JsonError> {
+        let digits = self
+            .input
+            .get(self.position..self.position + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("Invalid unicode escape"))?;
+        self.position += 4;
+        Ok(digits)
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        // Skip the opening quote
+        self.position += 1;
+        let mut buffer = Vec::new();
+        loop {
+            match self.input.get(self.position) {
+                None => return Err(self.error("Unterminated string")),
+                Some(b'"') => {
+                    self.position += 1;
+                    return String::from_utf8(buffer).map_err(|_| self.error("Invalid UTF-8"));
+                }
+                Some(b'\\') => {
+                    self.position += 1;
+                    let escaped = match self.input.get(self.position) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.position += 1;
+                            let mut code = self.parse_hex()?;
+                            if (0xD800..0xDC00).contains(&code)
+                                && self.input[self.position..].starts_with(b"\\u")
+                            {
+                                self.position += 2;
+                                let low = self.parse_hex()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("Invalid surrogate pair"));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            let ch = char::from_u32(code)
+                                .ok_or_else(|| self.error("Invalid unicode escape"))?;
+                            let mut encoded = [0; 4];
+                            buffer.extend_from_slice(ch.encode_utf8(&mut encoded).as_bytes());
+                            continue;
+                        }
+                        _ => return Err(self.error("Invalid escape")),
+                    };
+                    self.position += 1;
+                    let mut encoded = [0; 4];
+                    buffer.extend_from_slice(escaped.encode_utf8(&mut encoded).as_bytes());
+                }
+                Some(byte) if *byte < 0x20 => {
+                    return Err(self.error("Control characters must be escaped"))
+                }
+                Some(byte) => {
+                    buffer.push(*byte);
+                    self.position += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonlike::Json;
+    use test_case::test_case;
+
+    use super::{equal, Value};
+
+    fn parse(value: &str) -> Value {
+        Value::from_str(value).expect("Invalid JSON")
+    }
+
+    #[test_case("1", "1.0", true)]
+    #[test_case(r#"[1, "a"]"#, r#"[1.0, "a"]"#, true)]
+    #[test_case(r#"{"a": [1]}"#, r#"{"a": [1.0]}"#, true)]
+    #[test_case(r#"{"a": 1}"#, r#"{"a": 1, "b": 2}"#, false)]
+    #[test_case("[1]", "[1, 2]", false)]
+    #[test_case("false", "0", false)]
+    #[test_case("null", "null", true)]
+    #[test_case("9007199254740993", "9007199254740992", false)]
+    #[test_case("9007199254740993", "9007199254740993.0", true)]
+    #[test_case("0.1", "0.10000000000000001", false)]
+    #[test_case(r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "a": 1}"#, true)]
+    fn test_equal(left: &str, right: &str, expected: bool) {
+        let (left, right) = (parse(left), parse(right));
+        assert_eq!(equal(&left, &right), expected);
+        assert_eq!(left.try_equals(&right).ok(), Some(expected));
+        assert_eq!(left.equal(&right), expected);
+    }
+
+    #[test_case(r#"{"a":[1,1.5,"x\"y",null,true]}"#)]
+    #[test_case(r#""ä😀\n""#)]
+    #[test_case("[]")]
+    #[test_case("[18446744073709551617,0.10000000000000001]")]
+    #[test_case(r#""\ud83d\ude00""#)]
+    fn test_roundtrip(value: &str) {
+        let parsed = parse(value);
+        assert_eq!(parse(&parsed.to_string()), parsed);
+    }
+
+    #[test_case("")]
+    #[test_case("[1,]")]
+    #[test_case("{\"a\" 1}")]
+    #[test_case("01")]
+    #[test_case("\"abc")]
+    #[test_case("[1] 2")]
+    #[test_case(r#""\ud83d""#)]
+    #[test_case(r#""\ud83d\u0041""#)]
+    #[test_case(r#""\ude00""#)]
+    fn test_invalid(value: &str) {
+        assert!(Value::from_str(value).is_err());
+    }
+}
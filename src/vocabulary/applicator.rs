@@ -0,0 +1,1166 @@
+//! Keywords from the "Applicator" vocabulary.
+use fancy_regex::Regex;
+use jpointer::{JsonPointer, JsonPointerNode};
+use jsonlike::prelude::*;
+
+#[cfg(feature = "snapshot")]
+use crate::vocabulary::References;
+use crate::{
+    compiler::{CompileResult, Context},
+    error::ValidationErrorKind,
+    graph::NodeId,
+    value::Value,
+    vocabulary::{invalid_instance, object_entries, validation, KeywordValue},
+    ValidationError, Validator,
+};
+
+fn compile_array_of_schemas<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    keyword: &str,
+    value: &'a J,
+) -> Result<Vec<NodeId>, crate::BuildError> {
+    let Some(array) = value.as_array() else {
+        return Err(ctx.invalid(keyword, "Expected an array of schemas"));
+    };
+    if array.is_empty() {
+        return Err(ctx.invalid(keyword, "Expected a non-empty array of schemas"));
+    }
+    let mut schemas = Vec::with_capacity(array.len());
+    for (idx, item) in array.try_iter()?.enumerate() {
+        schemas.push(ctx.subschema_in(keyword, idx, item?)?);
+    }
+    Ok(schemas)
+}
+
+fn compile_map_of_schemas<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    keyword: &str,
+    value: &'a J,
+) -> Result<Vec<(String, NodeId)>, crate::BuildError> {
+    let Some(object) = value.as_object() else {
+        return Err(ctx.invalid(keyword, "Expected an object"));
+    };
+    let mut schemas = Vec::with_capacity(object.len());
+    for entry in object_entries::<J>(object) {
+        let (key, value) = entry?;
+        schemas.push((key.to_owned(), ctx.subschema_in(keyword, key, value)?));
+    }
+    Ok(schemas)
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct AllOf {
+    schemas: Vec<NodeId>,
+}
+
+impl AllOf {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.extend_from_slice(&self.schemas);
+    }
+    pub(crate) fn schemas(&self) -> &[NodeId] {
+        &self.schemas
+    }
+    pub(crate) fn is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> bool {
+        self.schemas
+            .iter()
+            .all(|schema| validator.is_valid_node(*schema, instance))
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for schema in &self.schemas {
+            validator.collect_node_errors(*schema, instance, path, errors);
+        }
+    }
+}
+
+pub(crate) fn compile_all_of<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(
+        compile_array_of_schemas(ctx, "allOf", value)
+            .map(|schemas| KeywordValue::AllOf(AllOf { schemas })),
+    )
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct AnyOf {
+    schemas: Vec<NodeId>,
+}
+
+impl AnyOf {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.extend_from_slice(&self.schemas);
+    }
+    pub(crate) fn is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> bool {
+        self.schemas
+            .iter()
+            .any(|schema| validator.is_valid_node(*schema, instance))
+    }
+}
+
+pub(crate) fn compile_any_of<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(
+        compile_array_of_schemas(ctx, "anyOf", value)
+            .map(|schemas| KeywordValue::AnyOf(AnyOf { schemas })),
+    )
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct OneOf {
+    schemas: Vec<NodeId>,
+}
+
+impl OneOf {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.extend_from_slice(&self.schemas);
+    }
+    fn count_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> usize {
+        self.schemas
+            .iter()
+            .filter(|schema| validator.is_valid_node(**schema, instance))
+            .take(2)
+            .count()
+    }
+    pub(crate) fn is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> bool {
+        self.count_valid(validator, instance) == 1
+    }
+    pub(crate) fn error_kind<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> ValidationErrorKind {
+        if self.count_valid(validator, instance) == 0 {
+            ValidationErrorKind::OneOfNotValid
+        } else {
+            ValidationErrorKind::OneOfMultipleValid
+        }
+    }
+}
+
+pub(crate) fn compile_one_of<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(
+        compile_array_of_schemas(ctx, "oneOf", value)
+            .map(|schemas| KeywordValue::OneOf(OneOf { schemas })),
+    )
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Not {
+    schema: NodeId,
+}
+
+impl Not {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.push(self.schema);
+    }
+    pub(crate) fn is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> bool {
+        !validator.is_valid_node(self.schema, instance)
+    }
+}
+
+pub(crate) fn compile_not<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(
+        ctx.subschema("not", value)
+            .map(|schema| KeywordValue::Not(Not { schema })),
+    )
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct IfThenElse {
+    condition: NodeId,
+    then: Option<NodeId>,
+    otherwise: Option<NodeId>,
+}
+
+impl IfThenElse {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.push(self.condition);
+        refs.nodes.extend(self.then);
+        refs.nodes.extend(self.otherwise);
+    }
+    fn branch<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Option<NodeId> {
+        if validator.is_valid_node(self.condition, instance) {
+            self.then
+        } else {
+            self.otherwise
+        }
+    }
+    pub(crate) fn is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> bool {
+        self.branch(validator, instance)
+            .is_none_or(|branch| validator.is_valid_node(branch, instance))
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(branch) = self.branch(validator, instance) {
+            validator.collect_node_errors(branch, instance, path, errors);
+        }
+    }
+}
+
+pub(crate) fn compile_if<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let (then, otherwise) = (parent.get("then"), parent.get("else"));
+    if then.is_none() && otherwise.is_none() {
+        return None;
+    }
+    let mut compile = || {
+        let condition = ctx.subschema("if", value)?;
+        let then = then.map(|then| ctx.subschema("then", then)).transpose()?;
+        let otherwise = otherwise
+            .map(|otherwise| ctx.subschema("else", otherwise))
+            .transpose()?;
+        Ok(KeywordValue::IfThenElse(IfThenElse {
+            condition,
+            then,
+            otherwise,
+        }))
+    };
+    Some(compile())
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Properties {
+    properties: Vec<(String, NodeId)>,
+}
+
+impl Properties {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes
+            .extend(self.properties.iter().map(|(_, schema)| *schema));
+    }
+    /// The schema for the property `key`, if any.
+    pub(crate) fn schema_for(&self, key: &str) -> Option<NodeId> {
+        self.properties
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, schema)| *schema)
+    }
+    pub(crate) fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        let Some(object) = instance.try_as_object()? else {
+            return Ok(true);
+        };
+        Ok(self.properties.iter().all(|(name, schema)| {
+            object
+                .get(name)
+                .is_none_or(|value| validator.is_valid_node(*schema, value))
+        }))
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let object = match instance.try_as_object() {
+            Ok(Some(object)) => object,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        for (name, schema) in &self.properties {
+            if let Some(value) = object.get(name) {
+                validator.collect_node_errors(*schema, value, &path.push(name.as_str()), errors);
+            }
+        }
+    }
+}
+
+pub(crate) fn compile_properties<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(
+        compile_map_of_schemas(ctx, "properties", value)
+            .map(|properties| KeywordValue::Properties(Properties { properties })),
+    )
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PatternProperties {
+    #[cfg_attr(feature = "snapshot", serde(with = "crate::snapshot::regex_pairs"))]
+    patterns: Vec<(Regex, NodeId)>,
+}
+
+impl PatternProperties {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes
+            .extend(self.patterns.iter().map(|(_, schema)| *schema));
+    }
+    /// Schemas of all patterns matching `key`.
+    pub(crate) fn schemas_for<'s>(&'s self, key: &'s str) -> impl Iterator<Item = NodeId> + 's {
+        self.patterns
+            .iter()
+            .filter(|(pattern, _)| pattern.is_match(key).unwrap_or(false))
+            .map(|(_, schema)| *schema)
+    }
+    pub(crate) fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        let Some(object) = instance.try_as_object()? else {
+            return Ok(true);
+        };
+        Ok(object_entries::<I>(object).all(|entry| {
+            let Ok((key, value)) = entry else {
+                return false;
+            };
+            self.patterns.iter().all(|(pattern, schema)| {
+                !pattern.is_match(key).unwrap_or(false) || validator.is_valid_node(*schema, value)
+            })
+        }))
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let object = match instance.try_as_object() {
+            Ok(Some(object)) => object,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        for entry in object_entries::<I>(object) {
+            match entry {
+                Ok((key, value)) => {
+                    for (pattern, schema) in &self.patterns {
+                        if pattern.is_match(key).unwrap_or(false) {
+                            validator.collect_node_errors(*schema, value, &path.push(key), errors);
+                        }
+                    }
+                }
+                Err(error) => errors.push(invalid_instance(&error, path, location)),
+            }
+        }
+    }
+}
+
+fn compile_patterns<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    value: &'a J,
+) -> Result<Vec<(Regex, NodeId)>, crate::BuildError> {
+    let Some(object) = value.as_object() else {
+        return Err(ctx.invalid("patternProperties", "Expected an object"));
+    };
+    let mut patterns = Vec::with_capacity(object.len());
+    for entry in object_entries::<J>(object) {
+        let (pattern, value) = entry?;
+        let regex = validation::compile_regex(ctx, "patternProperties", pattern)?;
+        patterns.push((
+            regex,
+            ctx.subschema_in("patternProperties", pattern, value)?,
+        ));
+    }
+    Ok(patterns)
+}
+
+pub(crate) fn compile_pattern_properties<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(
+        compile_patterns(ctx, value)
+            .map(|patterns| KeywordValue::PatternProperties(PatternProperties { patterns })),
+    )
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct AdditionalProperties {
+    properties: Vec<String>,
+    #[cfg_attr(feature = "snapshot", serde(with = "crate::snapshot::regexes"))]
+    patterns: Vec<Regex>,
+    /// `None` if additional properties are forbidden via `false`.
+    schema: Option<NodeId>,
+}
+
+impl AdditionalProperties {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.extend(self.schema);
+    }
+    fn is_additional(&self, key: &str) -> bool {
+        !self.properties.iter().any(|property| property == key)
+            && !self
+                .patterns
+                .iter()
+                .any(|pattern| pattern.is_match(key).unwrap_or(false))
+    }
+    /// Whether additional properties are forbidden via `false`.
+    pub(crate) fn is_forbidden(&self) -> bool {
+        self.schema.is_none()
+    }
+    /// The schema for `key` if it is an additional property that is not forbidden.
+    pub(crate) fn schema_for(&self, key: &str) -> Option<NodeId> {
+        self.schema.filter(|_| self.is_additional(key))
+    }
+    pub(crate) fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        let Some(object) = instance.try_as_object()? else {
+            return Ok(true);
+        };
+        Ok(object_entries::<I>(object).all(|entry| match entry {
+            Ok((key, value)) => {
+                !self.is_additional(key)
+                    || self
+                        .schema
+                        .is_some_and(|schema| validator.is_valid_node(schema, value))
+            }
+            Err(_) => false,
+        }))
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let object = match instance.try_as_object() {
+            Ok(Some(object)) => object,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        let mut unexpected = Vec::new();
+        for entry in object_entries::<I>(object) {
+            match entry {
+                Ok((key, value)) if self.is_additional(key) => match self.schema {
+                    Some(schema) => {
+                        validator.collect_node_errors(schema, value, &path.push(key), errors)
+                    }
+                    None => unexpected.push(key.to_owned()),
+                },
+                Ok(_) => {}
+                Err(error) => errors.push(invalid_instance(&error, path, location)),
+            }
+        }
+        if !unexpected.is_empty() {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::AdditionalProperties { unexpected },
+                path,
+                location,
+            ));
+        }
+    }
+}
+
+pub(crate) fn compile_additional_properties<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let mut compile = || {
+        let mut properties = Vec::new();
+        if let Some(object) = parent.get("properties").and_then(Json::as_object) {
+            for entry in object_entries::<J>(object) {
+                properties.push(entry?.0.to_owned());
+            }
+        }
+        let mut patterns = Vec::new();
+        if let Some(object) = parent.get("patternProperties").and_then(Json::as_object) {
+            for entry in object_entries::<J>(object) {
+                patterns.push(validation::compile_regex(
+                    ctx,
+                    "patternProperties",
+                    entry?.0,
+                )?);
+            }
+        }
+        let schema = if value.as_boolean() == Some(false) {
+            None
+        } else {
+            Some(ctx.subschema("additionalProperties", value)?)
+        };
+        Ok(KeywordValue::AdditionalProperties(AdditionalProperties {
+            properties,
+            patterns,
+            schema,
+        }))
+    };
+    Some(compile())
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PropertyNames {
+    schema: NodeId,
+}
+
+impl PropertyNames {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.push(self.schema);
+    }
+    pub(crate) fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        let Some(object) = instance.try_as_object()? else {
+            return Ok(true);
+        };
+        Ok(object_entries::<I>(object).all(|entry| match entry {
+            Ok((key, _)) => validator.is_valid_at(self.schema, &Value::String(key.to_owned())),
+            Err(_) => false,
+        }))
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let object = match instance.try_as_object() {
+            Ok(Some(object)) => object,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        for entry in object_entries::<I>(object) {
+            match entry {
+                Ok((key, _)) => validator.collect_errors_at(
+                    self.schema,
+                    &Value::String(key.to_owned()),
+                    location,
+                    path,
+                    errors,
+                ),
+                Err(error) => errors.push(invalid_instance(&error, path, location)),
+            }
+        }
+    }
+}
+
+pub(crate) fn compile_property_names<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(
+        ctx.subschema("propertyNames", value)
+            .map(|schema| KeywordValue::PropertyNames(PropertyNames { schema })),
+    )
+}
+
+/// Schemas applied to array items by their position.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct PrefixItems {
+    schemas: Vec<NodeId>,
+}
+
+impl PrefixItems {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.extend_from_slice(&self.schemas);
+    }
+    pub(crate) fn schema_at(&self, idx: usize) -> Option<NodeId> {
+        self.schemas.get(idx).copied()
+    }
+    pub(crate) fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        let Some(array) = instance.try_as_array()? else {
+            return Ok(true);
+        };
+        Ok(array
+            .iter()
+            .zip(&self.schemas)
+            .all(|(item, schema)| item.is_ok_and(|item| validator.is_valid_node(*schema, item))))
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let array = match instance.try_as_array() {
+            Ok(Some(array)) => array,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        for (idx, (item, schema)) in array.iter().zip(&self.schemas).enumerate() {
+            match item {
+                Ok(item) => validator.collect_node_errors(*schema, item, &path.push(idx), errors),
+                Err(error) => errors.push(invalid_instance(&error, path, location)),
+            }
+        }
+    }
+}
+
+/// A schema applied to all array items starting at a given position.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Items {
+    skip: usize,
+    /// `None` if the items are forbidden via `false`.
+    schema: Option<NodeId>,
+}
+
+impl Items {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.extend(self.schema);
+    }
+    pub(crate) fn schema_at(&self, idx: usize) -> Option<NodeId> {
+        self.schema.filter(|_| idx >= self.skip)
+    }
+    /// Report items that are forbidden via `false` given only the array length.
+    pub(crate) fn collect_length_errors(
+        &self,
+        len: usize,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if self.schema.is_none() && len > self.skip {
+            errors.push(ValidationError::new(
+                ValidationErrorKind::AdditionalItems { limit: self.skip },
+                path,
+                location,
+            ));
+        }
+    }
+    pub(crate) fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        let Some(array) = instance.try_as_array()? else {
+            return Ok(true);
+        };
+        Ok(match self.schema {
+            Some(schema) => array
+                .iter()
+                .skip(self.skip)
+                .all(|item| item.is_ok_and(|item| validator.is_valid_node(schema, item))),
+            None => array.len() <= self.skip,
+        })
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let array = match instance.try_as_array() {
+            Ok(Some(array)) => array,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        let Some(schema) = self.schema else {
+            self.collect_length_errors(array.len(), location, path, errors);
+            return;
+        };
+        for (idx, item) in array.iter().enumerate().skip(self.skip) {
+            match item {
+                Ok(item) => validator.collect_node_errors(schema, item, &path.push(idx), errors),
+                Err(error) => errors.push(invalid_instance(&error, path, location)),
+            }
+        }
+    }
+}
+
+fn compile_items_from<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    keyword: &str,
+    skip: usize,
+    value: &'a J,
+) -> CompileResult {
+    if value.as_boolean() == Some(true) {
+        return None;
+    }
+    let schema = if value.as_boolean() == Some(false) {
+        None
+    } else {
+        match ctx.subschema(keyword, value) {
+            Ok(schema) => Some(schema),
+            Err(error) => return Some(Err(error)),
+        }
+    };
+    Some(Ok(KeywordValue::Items(Items { skip, schema })))
+}
+
+/// `items` before Draft 2020-12, which is either a schema for all items or an array of schemas.
+pub(crate) fn compile_items_legacy<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    if let Some(array) = value.as_array() {
+        let mut compile = || {
+            let mut schemas = Vec::with_capacity(array.len());
+            for (idx, item) in array.try_iter()?.enumerate() {
+                schemas.push(ctx.subschema_in("items", idx, item?)?);
+            }
+            Ok(KeywordValue::PrefixItems(PrefixItems { schemas }))
+        };
+        Some(compile())
+    } else {
+        compile_items_from(ctx, "items", 0, value)
+    }
+}
+
+/// `additionalItems`, which only applies when `items` is an array.
+pub(crate) fn compile_additional_items<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let skip = parent.get("items").and_then(Json::as_array)?.len();
+    compile_items_from(ctx, "additionalItems", skip, value)
+}
+
+pub(crate) fn compile_prefix_items<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(
+        compile_array_of_schemas(ctx, "prefixItems", value)
+            .map(|schemas| KeywordValue::PrefixItems(PrefixItems { schemas })),
+    )
+}
+
+/// `items` in Draft 2020-12, which applies to all items after `prefixItems`.
+pub(crate) fn compile_items<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let skip = parent
+        .get("prefixItems")
+        .and_then(Json::as_array)
+        .map_or(0, JsonArray::len);
+    compile_items_from(ctx, "items", skip, value)
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Contains {
+    schema: NodeId,
+    min: Option<ContainsLimit>,
+    max: Option<ContainsLimit>,
+}
+
+/// `minContains` or `maxContains`, which report errors at their own location.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+struct ContainsLimit {
+    value: u64,
+    #[cfg_attr(feature = "snapshot", serde(with = "crate::snapshot::pointer"))]
+    location: JsonPointer,
+}
+
+impl Contains {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes.push(self.schema);
+    }
+    fn count<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        array: &I::Array,
+    ) -> u64 {
+        array
+            .iter()
+            .filter(|item| {
+                item.as_ref()
+                    .is_ok_and(|item| validator.is_valid_node(self.schema, *item))
+            })
+            .count() as u64
+    }
+    pub(crate) fn schema(&self) -> NodeId {
+        self.schema
+    }
+    fn error_kind(&self, count: u64) -> Option<(ValidationErrorKind, Option<&JsonPointer>)> {
+        match (&self.min, &self.max) {
+            (Some(min), _) if count < min.value => Some((
+                ValidationErrorKind::MinContains { limit: min.value },
+                Some(&min.location),
+            )),
+            (None, _) if count == 0 => Some((ValidationErrorKind::Contains, None)),
+            (_, Some(max)) if count > max.value => Some((
+                ValidationErrorKind::MaxContains { limit: max.value },
+                Some(&max.location),
+            )),
+            _ => None,
+        }
+    }
+    pub(crate) fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        let Some(array) = instance.try_as_array()? else {
+            return Ok(true);
+        };
+        Ok(self
+            .error_kind(self.count::<J, I>(validator, array))
+            .is_none())
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let array = match instance.try_as_array() {
+            Ok(Some(array)) => array,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        self.collect_count_errors(self.count::<J, I>(validator, array), location, path, errors);
+    }
+    /// Report errors given the number of items that are valid against the `contains` schema.
+    pub(crate) fn collect_count_errors(
+        &self,
+        count: u64,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some((kind, keyword_location)) = self.error_kind(count) {
+            errors.push(ValidationError::new(
+                kind,
+                path,
+                keyword_location.unwrap_or(location),
+            ));
+        }
+    }
+}
+
+fn compile_contains_with<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    value: &'a J,
+    with_limits: bool,
+) -> CompileResult {
+    let limit = |keyword: &str| -> Result<Option<ContainsLimit>, crate::BuildError> {
+        if !with_limits {
+            return Ok(None);
+        }
+        match parent.get(keyword) {
+            Some(value) => match value.as_number().and_then(JsonNumber::as_float) {
+                Some(number) if number >= 0.0 && number.fract() == 0.0 => Ok(Some(ContainsLimit {
+                    value: number as u64,
+                    location: ctx.location().join(keyword),
+                })),
+                _ => Err(ctx.invalid(keyword, "Expected a non-negative integer")),
+            },
+            None => Ok(None),
+        }
+    };
+    let limits = limit("minContains").and_then(|min| Ok((min, limit("maxContains")?)));
+    let (min, max) = match limits {
+        Ok(limits) => limits,
+        Err(error) => return Some(Err(error)),
+    };
+    Some(
+        ctx.subschema("contains", value)
+            .map(|schema| KeywordValue::Contains(Contains { schema, min, max })),
+    )
+}
+
+pub(crate) fn compile_contains<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    compile_contains_with(ctx, parent, value, false)
+}
+
+/// `contains` as of Draft 2019-09, which respects `minContains` and `maxContains`.
+pub(crate) fn compile_contains_with_limits<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    compile_contains_with(ctx, parent, value, true)
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct DependentSchemas {
+    schemas: Vec<(String, NodeId)>,
+}
+
+impl DependentSchemas {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut References) {
+        refs.nodes
+            .extend(self.schemas.iter().map(|(_, schema)| *schema));
+    }
+    pub(crate) fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        let Some(object) = instance.try_as_object()? else {
+            return Ok(true);
+        };
+        Ok(self.schemas.iter().all(|(property, schema)| {
+            !object.contains_key(property) || validator.is_valid_node(*schema, instance)
+        }))
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let object = match instance.try_as_object() {
+            Ok(Some(object)) => object,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        for (property, schema) in &self.schemas {
+            if object.contains_key(property) {
+                validator.collect_node_errors(*schema, instance, path, errors);
+            }
+        }
+    }
+}
+
+pub(crate) fn compile_dependent_schemas<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(
+        compile_map_of_schemas(ctx, "dependentSchemas", value)
+            .map(|schemas| KeywordValue::DependentSchemas(DependentSchemas { schemas })),
+    )
+}
+
+/// The legacy `dependencies` keyword, where each value is either a schema or a list of properties.
+pub(crate) fn compile_dependencies<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let Some(object) = value.as_object() else {
+        return Some(Err(ctx.invalid("dependencies", "Expected an object")));
+    };
+    let mut compile = || {
+        let mut schemas = Vec::new();
+        for entry in object_entries::<J>(object) {
+            let (property, value) = entry?;
+            if !value.is_array() {
+                schemas.push((
+                    property.to_owned(),
+                    ctx.subschema_in("dependencies", property, value)?,
+                ));
+            }
+        }
+        let required =
+            validation::compile_dependent_required_from(ctx, "dependencies", value, true)
+                .transpose()?;
+        Ok((schemas, required))
+    };
+    match compile() {
+        Ok((schemas, None)) => Some(Ok(KeywordValue::DependentSchemas(DependentSchemas {
+            schemas,
+        }))),
+        Ok((schemas, Some(required))) if schemas.is_empty() => Some(Ok(required)),
+        Ok((schemas, Some(required))) => Some(Ok(KeywordValue::AllOf(AllOf::inline(
+            ctx,
+            vec![
+                KeywordValue::DependentSchemas(DependentSchemas { schemas }),
+                required,
+            ],
+        )))),
+        Err(error) => Some(Err(error)),
+    }
+}
+
+impl AllOf {
+    /// Combine several keywords compiled from the same location into a single one.
+    fn inline<J: Json>(ctx: &mut Context<'_, '_, J>, keywords: Vec<KeywordValue>) -> AllOf {
+        let schemas = keywords
+            .into_iter()
+            .map(|keyword| ctx.push_keyword_node("dependencies", keyword))
+            .collect();
+        AllOf { schemas }
+    }
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::Draft;
+
+    #[test_case(json!({"allOf": [{"type": "integer"}, {"minimum": 2}]}), json!(3), true)]
+    #[test_case(json!({"allOf": [{"type": "integer"}, {"minimum": 2}]}), json!(1), false)]
+    #[test_case(json!({"anyOf": [{"type": "integer"}, {"type": "string"}]}), json!("a"), true)]
+    #[test_case(json!({"anyOf": [{"type": "integer"}, {"type": "string"}]}), json!(null), false)]
+    #[test_case(json!({"oneOf": [{"type": "integer"}, {"minimum": 2}]}), json!(1), true)]
+    #[test_case(json!({"oneOf": [{"type": "integer"}, {"minimum": 2}]}), json!(3), false)]
+    #[test_case(json!({"not": {"type": "integer"}}), json!(3), false)]
+    #[test_case(json!({"if": {"type": "integer"}, "then": {"minimum": 2}}), json!(1), false)]
+    #[test_case(json!({"if": {"type": "integer"}, "else": {"minLength": 2}}), json!("a"), false)]
+    #[test_case(json!({"properties": {"a": {"type": "integer"}}}), json!({"a": "b"}), false)]
+    #[test_case(json!({"patternProperties": {"^x-": {"type": "integer"}}}), json!({"x-a": 1, "b": "c"}), true)]
+    #[test_case(json!({"patternProperties": {"^x-": {"type": "integer"}}}), json!({"x-a": "c"}), false)]
+    #[test_case(json!({"properties": {"a": {}}, "additionalProperties": false}), json!({"a": 1}), true)]
+    #[test_case(json!({"properties": {"a": {}}, "additionalProperties": false}), json!({"b": 1}), false)]
+    #[test_case(json!({"additionalProperties": {"type": "string"}}), json!({"b": 1}), false)]
+    #[test_case(json!({"propertyNames": {"maxLength": 2}}), json!({"abc": 1}), false)]
+    #[test_case(json!({"propertyNames": {"maxLength": 2}}), json!({"ab": 1}), true)]
+    #[test_case(json!({"prefixItems": [{"type": "integer"}], "items": false}), json!([1]), true)]
+    #[test_case(json!({"prefixItems": [{"type": "integer"}], "items": false}), json!([1, 2]), false)]
+    #[test_case(json!({"items": {"type": "integer"}}), json!([1, "a"]), false)]
+    #[test_case(json!({"contains": {"type": "integer"}}), json!(["a"]), false)]
+    #[test_case(json!({"contains": {"type": "integer"}, "minContains": 2}), json!([1, "a"]), false)]
+    #[test_case(json!({"contains": {"type": "integer"}, "maxContains": 1}), json!([1, 2]), false)]
+    #[test_case(json!({"contains": {"type": "integer"}, "minContains": 0}), json!([]), true)]
+    #[test_case(json!({"dependentSchemas": {"a": {"required": ["b"]}}}), json!({"a": 1}), false)]
+    #[test_case(json!(false), json!(1), false)]
+    #[test_case(json!(true), json!(1), true)]
+    fn test_is_valid(schema: Value, instance: Value, expected: bool) {
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.iter_errors(&instance).next().is_none(), expected);
+    }
+
+    #[test_case(json!({"items": [{"type": "integer"}], "additionalItems": false}), json!([1, 2]), false)]
+    #[test_case(json!({"items": [{"type": "integer"}], "additionalItems": false}), json!([1]), true)]
+    #[test_case(json!({"dependencies": {"a": ["b"]}}), json!({"a": 1}), false)]
+    #[test_case(json!({"dependencies": {"a": {"required": ["b"]}, "c": ["d"]}}), json!({"c": 1, "d": 2}), true)]
+    #[test_case(json!({"dependencies": {"a": {"required": ["b"]}, "c": ["d"]}}), json!({"a": 1, "d": 2}), false)]
+    fn test_is_valid_draft7(schema: Value, instance: Value, expected: bool) {
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .draft(Draft::Draft07)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.iter_errors(&instance).next().is_none(), expected);
+    }
+
+    #[test]
+    fn test_error_paths() {
+        let schema = json!({
+            "properties": {
+                "a": {"items": {"type": "integer"}},
+                "b": {"additionalProperties": false}
+            }
+        });
+        let instance = json!({"a": [1, "x"], "b": {"c": 1}});
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let errors: Vec<_> = validator
+            .iter_errors(&instance)
+            .map(|error| {
+                (
+                    error.instance_path().to_string(),
+                    error.schema_path().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                ("/a/1".to_string(), "/properties/a/items/type".to_string()),
+                (
+                    "/b".to_string(),
+                    "/properties/b/additionalProperties".to_string()
+                ),
+            ]
+        );
+    }
+}
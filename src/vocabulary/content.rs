@@ -0,0 +1,377 @@
+//! Keywords from the "Content" vocabulary.
+//!
+//! These keywords are annotations by default and are only asserted if enabled via
+//! `ValidatorBuilder::validate_content`. Unknown encodings and media types are ignored.
+use std::borrow::Cow;
+
+use jpointer::{JsonPointer, JsonPointerNode};
+use jsonlike::prelude::*;
+
+use crate::{
+    compiler::{CompileResult, Context},
+    content::media_type_essence,
+    error::ValidationErrorKind,
+    graph::NodeId,
+    vocabulary::{invalid_instance, KeywordValue},
+    ValidationError, Validator,
+};
+
+/// A `contentEncoding` or `contentMediaType` value that refers to a registered extension.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+struct Handler {
+    /// The value as written in the schema.
+    name: String,
+    /// Index within the content encodings or media types of `Extensions`.
+    index: usize,
+    #[cfg_attr(feature = "snapshot", serde(with = "crate::snapshot::pointer"))]
+    location: JsonPointer,
+}
+
+/// `contentEncoding`, `contentMediaType` and `contentSchema` compiled together, so the content is
+/// decoded and parsed only once.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Content {
+    encoding: Option<Handler>,
+    media_type: Option<Handler>,
+    schema: Option<NodeId>,
+}
+
+impl Content {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut super::References) {
+        refs.content_encodings
+            .extend(self.encoding.as_ref().map(|handler| handler.index));
+        refs.content_media_types
+            .extend(self.media_type.as_ref().map(|handler| handler.index));
+        refs.nodes.extend(self.schema);
+    }
+    fn decode<'s, J: Json>(
+        &self,
+        validator: &Validator<J>,
+        value: &'s str,
+    ) -> Option<Cow<'s, [u8]>> {
+        match &self.encoding {
+            Some(encoding) => validator.extensions().content_encodings[encoding.index]
+                .value
+                .decode(value)
+                .map(Cow::Owned),
+            None => Some(Cow::Borrowed(value.as_bytes())),
+        }
+    }
+    pub(crate) fn is_valid<J: Json + 'static, I: Json>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> bool {
+        let Ok(value) = instance.try_as_string() else {
+            return false;
+        };
+        let Some(value) = value else {
+            return true;
+        };
+        let Some(content) = self.decode(validator, value.as_ref()) else {
+            return false;
+        };
+        let Some(handler) = &self.media_type else {
+            return true;
+        };
+        let media_type = &validator.extensions().content_media_types[handler.index].value;
+        match self
+            .schema
+            .and_then(|schema| Some((schema, media_type.parse(&content)?)))
+        {
+            Some((schema, parsed)) => validator.is_valid_node(schema, &parsed),
+            None => media_type.is_valid(&content),
+        }
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let value = match instance.try_as_string() {
+            Ok(Some(value)) => value,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        let Some(content) = self.decode(validator, value.as_ref()) else {
+            if let Some(encoding) = &self.encoding {
+                errors.push(ValidationError::new(
+                    ValidationErrorKind::ContentEncoding {
+                        encoding: encoding.name.clone(),
+                    },
+                    path,
+                    &encoding.location,
+                ));
+            }
+            return;
+        };
+        let Some(handler) = &self.media_type else {
+            return;
+        };
+        let media_type = &validator.extensions().content_media_types[handler.index].value;
+        match self
+            .schema
+            .and_then(|schema| Some((schema, media_type.parse(&content)?)))
+        {
+            Some((schema, parsed)) => validator.collect_node_errors(schema, &parsed, path, errors),
+            None if media_type.is_valid(&content) => {}
+            None => errors.push(ValidationError::new(
+                ValidationErrorKind::ContentMediaType {
+                    media_type: handler.name.clone(),
+                },
+                path,
+                &handler.location,
+            )),
+        }
+    }
+}
+
+fn compile_content<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    with_schema: bool,
+) -> CompileResult {
+    if !ctx.validate_content() {
+        return None;
+    }
+    let name = |keyword: &str| match parent.get(keyword) {
+        Some(value) => match value.as_string() {
+            Some(name) => Ok(Some(name.as_ref().to_owned())),
+            None => Err(ctx.invalid(keyword, "Expected a string")),
+        },
+        None => Ok(None),
+    };
+    let names =
+        name("contentEncoding").and_then(|encoding| Ok((encoding, name("contentMediaType")?)));
+    let (encoding, media_type) = match names {
+        Ok(names) => names,
+        Err(error) => return Some(Err(error)),
+    };
+    let encoding = match encoding {
+        Some(name) => {
+            // An unknown encoding makes the content opaque, so nothing can be asserted
+            let index = ctx.content_encoding(&name.to_ascii_lowercase())?;
+            Some(Handler {
+                name,
+                index,
+                location: ctx.location().join("contentEncoding"),
+            })
+        }
+        None => None,
+    };
+    let media_type = media_type.and_then(|name| {
+        let index = ctx.content_media_type(&media_type_essence(&name))?;
+        Some(Handler {
+            name,
+            index,
+            location: ctx.location().join("contentMediaType"),
+        })
+    });
+    if encoding.is_none() && media_type.is_none() {
+        return None;
+    }
+    let schema = match parent.get("contentSchema") {
+        Some(value) if with_schema && media_type.is_some() => {
+            match ctx.subschema("contentSchema", value) {
+                Ok(schema) => Some(schema),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        _ => None,
+    };
+    Some(Ok(KeywordValue::Content(Content {
+        encoding,
+        media_type,
+        schema,
+    })))
+}
+
+fn is_compiled_by_encoding<J: Json>(parent: &J::Object) -> bool {
+    parent.get("contentEncoding").is_some()
+}
+
+/// `contentEncoding` and `contentMediaType` before Draft 2019-09, which has no `contentSchema`.
+pub(crate) fn compile_content_encoding_draft7<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    _: &'a J,
+) -> CompileResult {
+    compile_content(ctx, parent, false)
+}
+
+pub(crate) fn compile_content_media_type_draft7<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    _: &'a J,
+) -> CompileResult {
+    if is_compiled_by_encoding::<J>(parent) {
+        return None;
+    }
+    compile_content(ctx, parent, false)
+}
+
+pub(crate) fn compile_content_encoding<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    _: &'a J,
+) -> CompileResult {
+    compile_content(ctx, parent, true)
+}
+
+pub(crate) fn compile_content_media_type<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    _: &'a J,
+) -> CompileResult {
+    if is_compiled_by_encoding::<J>(parent) {
+        return None;
+    }
+    compile_content(ctx, parent, true)
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::{content::ContentMediaType, Draft};
+
+    fn validator(schema: &Value) -> crate::Validator<Value> {
+        crate::blocking::ValidatorBuilder::default()
+            .validate_content(true)
+            .build(schema)
+            .expect("Invalid schema")
+    }
+
+    #[test_case(json!({"contentEncoding": "base64"}), json!("eyJmb28iOiAiYmFyIn0="), true)]
+    #[test_case(json!({"contentEncoding": "base64"}), json!("eyJmb28iOi%iYmFyIn0="), false)]
+    #[test_case(json!({"contentEncoding": "base64url"}), json!("P_8-"), true)]
+    #[test_case(json!({"contentEncoding": "quoted-printable"}), json!("a=ZZb"), false)]
+    #[test_case(json!({"contentEncoding": "unknown"}), json!("%"), true)]
+    #[test_case(json!({"contentMediaType": "application/json"}), json!("{\"a\": 1}"), true)]
+    #[test_case(json!({"contentMediaType": "application/json; charset=utf-8"}), json!("{"), false)]
+    #[test_case(json!({"contentMediaType": "text/html"}), json!("{"), true)]
+    #[test_case(json!({"contentEncoding": "base64", "contentMediaType": "application/json"}), json!("e30="), true)]
+    #[test_case(json!({"contentEncoding": "base64", "contentMediaType": "application/json"}), json!("ew=="), false)]
+    #[test_case(json!({"contentMediaType": "application/json", "contentSchema": {"required": ["a"]}}), json!("{}"), false)]
+    #[test_case(json!({"contentMediaType": "application/json", "contentSchema": {"required": ["a"]}}), json!("{\"a\": 1}"), true)]
+    #[test_case(json!({"contentMediaType": "application/json"}), json!(42), true)]
+    fn test_is_valid(schema: Value, instance: Value, expected: bool) {
+        let validator = validator(&schema);
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.iter_errors(&instance).next().is_none(), expected);
+    }
+
+    #[test]
+    fn test_annotation_only_by_default() {
+        let schema = json!({"contentEncoding": "base64", "contentMediaType": "application/json"});
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        assert!(validator.is_valid(&json!("%")));
+    }
+
+    #[test]
+    fn test_content_schema_not_in_draft7() {
+        let schema = json!({"contentMediaType": "application/json", "contentSchema": false});
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .draft(Draft::Draft07)
+            .validate_content(true)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("{}")));
+        assert!(!validator.is_valid(&json!("{")));
+    }
+
+    #[test]
+    fn test_error_paths() {
+        let schema = json!({
+            "properties": {
+                "payload": {
+                    "contentEncoding": "base64",
+                    "contentMediaType": "application/json",
+                    "contentSchema": {"properties": {"a": {"type": "string"}}}
+                }
+            }
+        });
+        let validator = validator(&schema);
+        // {"a": 1}
+        let instance = json!({"payload": "eyJhIjogMX0="});
+        let errors: Vec<_> = validator
+            .iter_errors(&instance)
+            .map(|error| {
+                (
+                    error.instance_path().to_string(),
+                    error.schema_path().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            vec![(
+                "/payload/a".to_string(),
+                "/properties/payload/contentSchema/properties/a/type".to_string()
+            )]
+        );
+        let error = validator
+            .validate(&json!({"payload": "%"}))
+            .expect_err("Should fail");
+        assert_eq!(
+            error.schema_path().to_string(),
+            "/properties/payload/contentEncoding"
+        );
+    }
+
+    struct Csv;
+
+    impl ContentMediaType<Value> for Csv {
+        fn is_valid(&self, content: &[u8]) -> bool {
+            content.contains(&b',')
+        }
+    }
+
+    #[test]
+    fn test_custom_media_type() {
+        let schema = json!({"contentMediaType": "text/csv"});
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .validate_content(true)
+            .content_media_type("text/csv", Csv)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!("a,b")));
+        assert!(!validator.is_valid(&json!("a")));
+    }
+
+    /// Counts parse attempts, so content is known to be parsed only once.
+    #[derive(Default)]
+    struct Counting(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl ContentMediaType<Value> for Counting {
+        fn is_valid(&self, content: &[u8]) -> bool {
+            self.parse(content).is_some()
+        }
+        fn parse(&self, content: &[u8]) -> Option<Value> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            serde_json::from_slice(content).ok()
+        }
+    }
+
+    #[test_case(json!({"contentMediaType": "application/x", "contentSchema": {"type": "integer"}}), "1", true)]
+    #[test_case(json!({"contentMediaType": "application/x", "contentSchema": {"type": "integer"}}), "\"a\"", false)]
+    #[test_case(json!({"contentMediaType": "application/x"}), "1", true)]
+    fn test_parsed_once(schema: Value, content: &str, expected: bool) {
+        let media_type = Counting::default();
+        let parsed = std::sync::Arc::clone(&media_type.0);
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .validate_content(true)
+            .content_media_type("application/x", media_type)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(&json!(content)), expected);
+        assert_eq!(parsed.load(std::sync::atomic::Ordering::Relaxed), 1);
+    }
+}
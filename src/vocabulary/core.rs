@@ -0,0 +1,122 @@
+//! Keywords from the "Core" vocabulary.
+use jpointer::JsonPointerNode;
+use jsonlike::prelude::*;
+
+use crate::{
+    compiler::{CompileResult, Context},
+    graph::NodeId,
+    vocabulary::KeywordValue,
+    ValidationError, Validator,
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Ref {
+    schema: NodeId,
+}
+
+impl Ref {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut super::References) {
+        refs.nodes.push(self.schema);
+    }
+    pub(crate) fn schema(&self) -> NodeId {
+        self.schema
+    }
+    pub(crate) fn is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> bool {
+        validator.is_valid_node(self.schema, instance)
+    }
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        validator.collect_node_errors(self.schema, instance, path, errors);
+    }
+}
+
+pub(crate) fn compile_ref<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let Some(reference) = value.as_string() else {
+        return Some(Err(ctx.invalid("$ref", "Expected a string")));
+    };
+    Some(
+        ctx.reference(reference.as_ref())
+            .map(|schema| KeywordValue::Ref(Ref { schema })),
+    )
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    use crate::Draft;
+
+    #[test_case(json!({"$ref": "#/$defs/a", "$defs": {"a": {"type": "integer"}}}), json!(1), true)]
+    #[test_case(json!({"$ref": "#/$defs/a", "$defs": {"a": {"type": "integer"}}}), json!("a"), false)]
+    #[test_case(json!({"$ref": "#x", "$defs": {"a": {"$anchor": "x", "type": "integer"}}}), json!("a"), false)]
+    #[test_case(json!({"$ref": "a.json", "$defs": {"a": {"$id": "a.json", "type": "integer"}}}), json!("a"), false)]
+    #[test_case(json!({"$ref": "#/$defs/a", "$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"type": "integer"}}}), json!("a"), false)]
+    #[test_case(json!({"$ref": "#/$defs/a", "minimum": 5, "$defs": {"a": {}}}), json!(1), false; "siblings apply")]
+    #[test_case(json!({"properties": {"a": {"$ref": "#"}}, "type": "object"}), json!({"a": {"a": {}}}), true; "recursive")]
+    #[test_case(json!({"properties": {"a": {"$ref": "#"}}, "type": "object"}), json!({"a": {"a": 1}}), false; "recursive invalid")]
+    fn test_is_valid(schema: Value, instance: Value, expected: bool) {
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.iter_errors(&instance).next().is_none(), expected);
+    }
+
+    #[test_case(Draft::Draft04)]
+    #[test_case(Draft::Draft06)]
+    #[test_case(Draft::Draft07)]
+    fn test_legacy_siblings_ignored(draft: Draft) {
+        let schema = json!({"$ref": "#/definitions/a", "minimum": 5, "definitions": {"a": {}}});
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .draft(draft)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert!(validator.is_valid(&json!(1)));
+    }
+
+    #[test]
+    fn test_error_location() {
+        let schema = json!({
+            "properties": {"a": {"$ref": "#/$defs/positive"}},
+            "$defs": {"positive": {"minimum": 0}}
+        });
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let instance = json!({"a": -1});
+        let error = validator.validate(&instance).expect_err("Should fail");
+        assert_eq!(error.instance_path().to_string(), "/a");
+        assert_eq!(error.schema_path().to_string(), "/$defs/positive/minimum");
+    }
+
+    #[test_case(json!({"$ref": "#/$defs/missing"}); "missing pointer")]
+    #[test_case(json!({"$ref": "#missing"}); "missing anchor")]
+    fn test_unresolvable(schema: Value) {
+        let error = crate::blocking::validator_for(&schema).expect_err("Should fail");
+        assert!(
+            error.to_string().contains("Unresolvable reference"),
+            "{error}"
+        );
+    }
+
+    #[test_case(Draft::Draft201909, json!({"$recursiveAnchor": true, "items": {"$recursiveRef": "#"}, "unevaluatedItems": false}))]
+    #[test_case(Draft::Draft202012, json!({"$dynamicAnchor": "meta", "items": {"$dynamicRef": "#meta"}, "unevaluatedProperties": false}))]
+    fn test_dynamic_keywords_build(draft: Draft, schema: Value) {
+        crate::blocking::ValidatorBuilder::default()
+            .draft(draft)
+            .build(&schema)
+            .expect("Valid schema");
+    }
+}
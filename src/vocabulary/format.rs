@@ -0,0 +1,93 @@
+//! The `format` keyword.
+//!
+//! There are no built-in formats yet, so only formats registered via `ValidatorBuilder::format`
+//! are asserted and all other formats are treated as annotations.
+use jsonlike::prelude::*;
+
+use crate::{
+    compiler::{CompileResult, Context},
+    error::ValidationErrorKind,
+    vocabulary::KeywordValue,
+    Validator,
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Format {
+    /// Index within `Extensions::formats`.
+    index: usize,
+}
+
+impl Format {
+    #[cfg(feature = "snapshot")]
+    pub(crate) fn references(&self, refs: &mut super::References) {
+        refs.formats.push(self.index);
+    }
+    pub(crate) fn try_is_valid<J: Json, I: Json>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        Ok(instance.try_as_string()?.is_none_or(|value| {
+            validator.extensions().formats[self.index]
+                .value
+                .is_valid(value.as_ref())
+        }))
+    }
+    pub(crate) fn error_kind<J: Json>(&self, validator: &Validator<J>) -> ValidationErrorKind {
+        ValidationErrorKind::Format {
+            format: validator.extensions().formats[self.index].name.clone(),
+        }
+    }
+}
+
+pub(crate) fn compile_format<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let Some(name) = value.as_string() else {
+        return Some(Err(ctx.invalid("format", "Expected a string")));
+    };
+    let name = name.as_ref();
+    let factory = ctx.custom_format(name)?;
+    let schema = ctx.schema();
+    Some(
+        factory
+            .init(schema)
+            .and_then(|format| ctx.add_format(name, schema, format))
+            .map(|index| KeywordValue::Format(Format { index })),
+    )
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::json;
+
+    use crate::{BoxedFormat, BuildResult, Draft};
+
+    struct Even;
+
+    impl crate::Format for Even {
+        fn is_valid(&self, value: &str) -> bool {
+            value.len().is_multiple_of(2)
+        }
+    }
+
+    #[test]
+    fn test_custom_format() {
+        let schema = json!({"format": "even", "properties": {"a": {"format": "unknown"}}});
+        for draft in [Draft::Draft04, Draft::Draft201909, Draft::Draft202012] {
+            let validator = crate::blocking::ValidatorBuilder::default()
+                .draft(draft)
+                .format("even", |_: &_| -> BuildResult<BoxedFormat> {
+                    Ok(Box::new(Even))
+                })
+                .build(&schema)
+                .expect("Invalid schema");
+            assert!(validator.is_valid(&json!("ab")));
+            assert!(!validator.is_valid(&json!("abc")));
+            assert!(validator.is_valid(&json!({"a": "abc"})));
+        }
+    }
+}
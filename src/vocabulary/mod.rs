@@ -1,19 +1,315 @@
-use std::sync::Arc;
+pub(crate) mod applicator;
+pub(crate) mod content;
+pub(crate) mod core;
+pub(crate) mod format;
+pub(crate) mod validation;
 
-use jsonlike::Json;
+use std::any::Any;
 
-use crate::{BoxedKeyword, BuildResult};
+use jpointer::{JsonPointer, JsonPointerNode};
+use jsonlike::prelude::*;
 
+#[cfg(feature = "snapshot")]
+use crate::graph::NodeId;
+use crate::{
+    compiler::{CompileResult, Context},
+    error::ValidationErrorKind,
+    BoxedKeyword, BuildResult, ValidationError, Validator,
+};
+
+/// A compiled (sub)schema.
 #[derive(Debug, Clone)]
-pub(crate) enum KeywordValue<J: Json> {
-    Type(Type),
-    Custom(Arc<dyn Keyword<J>>),
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum SchemaNode {
+    Boolean {
+        valid: bool,
+        #[cfg_attr(feature = "snapshot", serde(with = "crate::snapshot::pointer"))]
+        location: JsonPointer,
+    },
+    Keywords(Vec<CompiledKeyword>),
+}
+
+/// A keyword together with its location within the schema.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct CompiledKeyword {
+    #[cfg_attr(feature = "snapshot", serde(with = "crate::snapshot::pointer"))]
+    pub(crate) location: JsonPointer,
+    pub(crate) value: KeywordValue,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) enum KeywordValue {
+    Type(validation::Type),
+    Const(validation::Const),
+    Enum(validation::Enum),
+    MultipleOf(validation::MultipleOf),
+    Maximum(validation::Maximum),
+    ExclusiveMaximum(validation::ExclusiveMaximum),
+    Minimum(validation::Minimum),
+    ExclusiveMinimum(validation::ExclusiveMinimum),
+    MaxLength(validation::MaxLength),
+    MinLength(validation::MinLength),
+    Pattern(validation::Pattern),
+    MaxItems(validation::MaxItems),
+    MinItems(validation::MinItems),
+    UniqueItems,
+    MaxProperties(validation::MaxProperties),
+    MinProperties(validation::MinProperties),
+    Required(validation::Required),
+    DependentRequired(validation::DependentRequired),
+    Ref(core::Ref),
+    AllOf(applicator::AllOf),
+    AnyOf(applicator::AnyOf),
+    OneOf(applicator::OneOf),
+    Not(applicator::Not),
+    IfThenElse(applicator::IfThenElse),
+    Properties(applicator::Properties),
+    PatternProperties(applicator::PatternProperties),
+    AdditionalProperties(applicator::AdditionalProperties),
+    PropertyNames(applicator::PropertyNames),
+    PrefixItems(applicator::PrefixItems),
+    Items(applicator::Items),
+    Contains(applicator::Contains),
+    DependentSchemas(applicator::DependentSchemas),
+    Format(format::Format),
+    Content(content::Content),
+    Custom(Custom),
+}
+
+impl KeywordValue {
+    pub(crate) fn is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> bool {
+        self.try_is_valid(validator, instance).unwrap_or(false)
+    }
+
+    /// Fails if the instance can not be read by its backend. Applicators only fail for the
+    /// instance itself, as errors of other values are reported by their subschemas.
+    fn try_is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> Result<bool, JsonError> {
+        match self {
+            KeywordValue::Type(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::Const(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::Enum(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::MultipleOf(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::Maximum(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::ExclusiveMaximum(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::Minimum(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::ExclusiveMinimum(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::MaxLength(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::MinLength(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::Pattern(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::MaxItems(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::MinItems(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::UniqueItems => validation::unique_items_try_is_valid(instance),
+            KeywordValue::MaxProperties(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::MinProperties(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::Required(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::DependentRequired(keyword) => keyword.try_is_valid(instance),
+            KeywordValue::Ref(keyword) => Ok(keyword.is_valid(validator, instance)),
+            KeywordValue::AllOf(keyword) => Ok(keyword.is_valid(validator, instance)),
+            KeywordValue::AnyOf(keyword) => Ok(keyword.is_valid(validator, instance)),
+            KeywordValue::OneOf(keyword) => Ok(keyword.is_valid(validator, instance)),
+            KeywordValue::Not(keyword) => Ok(keyword.is_valid(validator, instance)),
+            KeywordValue::IfThenElse(keyword) => Ok(keyword.is_valid(validator, instance)),
+            KeywordValue::Properties(keyword) => keyword.try_is_valid(validator, instance),
+            KeywordValue::PatternProperties(keyword) => keyword.try_is_valid(validator, instance),
+            KeywordValue::AdditionalProperties(keyword) => {
+                keyword.try_is_valid(validator, instance)
+            }
+            KeywordValue::PropertyNames(keyword) => keyword.try_is_valid(validator, instance),
+            KeywordValue::PrefixItems(keyword) => keyword.try_is_valid(validator, instance),
+            KeywordValue::Items(keyword) => keyword.try_is_valid(validator, instance),
+            KeywordValue::Contains(keyword) => keyword.try_is_valid(validator, instance),
+            KeywordValue::DependentSchemas(keyword) => keyword.try_is_valid(validator, instance),
+            KeywordValue::Format(keyword) => keyword.try_is_valid(validator, instance),
+            KeywordValue::Content(keyword) => Ok(keyword.is_valid(validator, instance)),
+            KeywordValue::Custom(keyword) => Ok(keyword.is_valid(validator, instance)),
+        }
+    }
+
+    pub(crate) fn collect_errors<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let kind = match self {
+            KeywordValue::Ref(keyword) => {
+                return keyword.collect_errors(validator, instance, path, errors)
+            }
+            KeywordValue::AllOf(keyword) => {
+                return keyword.collect_errors(validator, instance, path, errors)
+            }
+            KeywordValue::IfThenElse(keyword) => {
+                return keyword.collect_errors(validator, instance, path, errors)
+            }
+            KeywordValue::Properties(keyword) => {
+                return keyword.collect_errors(validator, instance, location, path, errors)
+            }
+            KeywordValue::PatternProperties(keyword) => {
+                return keyword.collect_errors(validator, instance, location, path, errors)
+            }
+            KeywordValue::AdditionalProperties(keyword) => {
+                return keyword.collect_errors(validator, instance, location, path, errors)
+            }
+            KeywordValue::PropertyNames(keyword) => {
+                return keyword.collect_errors(validator, instance, location, path, errors)
+            }
+            KeywordValue::PrefixItems(keyword) => {
+                return keyword.collect_errors(validator, instance, location, path, errors)
+            }
+            KeywordValue::Items(keyword) => {
+                return keyword.collect_errors(validator, instance, location, path, errors)
+            }
+            KeywordValue::Contains(keyword) => {
+                return keyword.collect_errors(validator, instance, location, path, errors)
+            }
+            KeywordValue::DependentSchemas(keyword) => {
+                return keyword.collect_errors(validator, instance, location, path, errors)
+            }
+            KeywordValue::Required(keyword) => {
+                return keyword.collect_errors(instance, location, path, errors)
+            }
+            KeywordValue::DependentRequired(keyword) => {
+                return keyword.collect_errors(instance, location, path, errors)
+            }
+            KeywordValue::Content(keyword) => {
+                return keyword.collect_errors(validator, instance, location, path, errors)
+            }
+            _ => match self.try_is_valid(validator, instance) {
+                Ok(true) => return,
+                Ok(false) => self.error_kind(validator, instance),
+                Err(error) => invalid_instance_kind(&error),
+            },
+        };
+        errors.push(ValidationError::new(kind, path, location));
+    }
+
+    /// The error of a keyword that reports a single error at most.
+    fn error_kind<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> ValidationErrorKind {
+        match self {
+            KeywordValue::Type(keyword) => keyword.error_kind(),
+            KeywordValue::Const(keyword) => keyword.error_kind(),
+            KeywordValue::Enum(keyword) => keyword.error_kind(),
+            KeywordValue::MultipleOf(keyword) => keyword.error_kind(),
+            KeywordValue::Maximum(keyword) => keyword.error_kind(),
+            KeywordValue::ExclusiveMaximum(keyword) => keyword.error_kind(),
+            KeywordValue::Minimum(keyword) => keyword.error_kind(),
+            KeywordValue::ExclusiveMinimum(keyword) => keyword.error_kind(),
+            KeywordValue::MaxLength(keyword) => keyword.error_kind(),
+            KeywordValue::MinLength(keyword) => keyword.error_kind(),
+            KeywordValue::Pattern(keyword) => keyword.error_kind(),
+            KeywordValue::MaxItems(keyword) => keyword.error_kind(),
+            KeywordValue::MinItems(keyword) => keyword.error_kind(),
+            KeywordValue::UniqueItems => ValidationErrorKind::UniqueItems,
+            KeywordValue::MaxProperties(keyword) => keyword.error_kind(),
+            KeywordValue::MinProperties(keyword) => keyword.error_kind(),
+            KeywordValue::AnyOf(_) => ValidationErrorKind::AnyOf,
+            KeywordValue::OneOf(keyword) => keyword.error_kind(validator, instance),
+            KeywordValue::Not(_) => ValidationErrorKind::Not,
+            KeywordValue::Format(keyword) => keyword.error_kind(validator),
+            KeywordValue::Custom(keyword) => ValidationErrorKind::Custom {
+                keyword: validator.extensions().keywords[keyword.index].name.clone(),
+            },
+            _ => unreachable!("Other keywords collect their own errors"),
+        }
+    }
+}
+
+/// Graph nodes and extension indices that compiled keywords refer to.
+#[cfg(feature = "snapshot")]
+#[derive(Debug, Default)]
+pub(crate) struct References {
+    pub(crate) nodes: Vec<NodeId>,
+    pub(crate) keywords: Vec<usize>,
+    pub(crate) formats: Vec<usize>,
+    pub(crate) content_encodings: Vec<usize>,
+    pub(crate) content_media_types: Vec<usize>,
 }
 
+#[cfg(feature = "snapshot")]
+impl KeywordValue {
+    pub(crate) fn references(&self, refs: &mut References) {
+        match self {
+            KeywordValue::Ref(keyword) => keyword.references(refs),
+            KeywordValue::AllOf(keyword) => keyword.references(refs),
+            KeywordValue::AnyOf(keyword) => keyword.references(refs),
+            KeywordValue::OneOf(keyword) => keyword.references(refs),
+            KeywordValue::Not(keyword) => keyword.references(refs),
+            KeywordValue::IfThenElse(keyword) => keyword.references(refs),
+            KeywordValue::Properties(keyword) => keyword.references(refs),
+            KeywordValue::PatternProperties(keyword) => keyword.references(refs),
+            KeywordValue::AdditionalProperties(keyword) => keyword.references(refs),
+            KeywordValue::PropertyNames(keyword) => keyword.references(refs),
+            KeywordValue::PrefixItems(keyword) => keyword.references(refs),
+            KeywordValue::Items(keyword) => keyword.references(refs),
+            KeywordValue::Contains(keyword) => keyword.references(refs),
+            KeywordValue::DependentSchemas(keyword) => keyword.references(refs),
+            KeywordValue::Format(keyword) => keyword.references(refs),
+            KeywordValue::Content(keyword) => keyword.references(refs),
+            KeywordValue::Custom(keyword) => refs.keywords.push(keyword.index),
+            KeywordValue::Type(_)
+            | KeywordValue::Const(_)
+            | KeywordValue::Enum(_)
+            | KeywordValue::MultipleOf(_)
+            | KeywordValue::Maximum(_)
+            | KeywordValue::ExclusiveMaximum(_)
+            | KeywordValue::Minimum(_)
+            | KeywordValue::ExclusiveMinimum(_)
+            | KeywordValue::MaxLength(_)
+            | KeywordValue::MinLength(_)
+            | KeywordValue::Pattern(_)
+            | KeywordValue::MaxItems(_)
+            | KeywordValue::MinItems(_)
+            | KeywordValue::UniqueItems
+            | KeywordValue::MaxProperties(_)
+            | KeywordValue::MinProperties(_)
+            | KeywordValue::Required(_)
+            | KeywordValue::DependentRequired(_) => {}
+        }
+    }
+}
+
+/// A keyword defined by the user via `ValidatorBuilder::keyword`.
 #[derive(Debug, Clone)]
-pub struct Type {}
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Custom {
+    /// Index within `Extensions::keywords`.
+    index: usize,
+}
 
-pub trait Keyword<J: Json>: Send + Sync + core::fmt::Debug {
+impl Custom {
+    /// Custom keywords are defined for the schema backend. `Validator` converts instances of
+    /// other backends (and property names) into it before validation, so no other type gets here.
+    fn is_valid<J: Json + 'static, I: Json + 'static>(
+        &self,
+        validator: &Validator<J>,
+        instance: &I,
+    ) -> bool {
+        let instance = (instance as &dyn Any)
+            .downcast_ref::<J>()
+            .expect("Instances are converted into the schema backend");
+        validator.extensions().keywords[self.index]
+            .value
+            .is_valid(instance)
+    }
+}
+
+pub trait Keyword<J: Json>: Send + Sync + ::core::fmt::Debug {
     fn is_valid(&self, instance: &J) -> bool;
 }
 
@@ -21,20 +317,57 @@ mod sealed {
     pub trait Sealed<J> {}
 }
 
-pub trait KeywordFactory<'a, J: Json>: Send + Sync + sealed::Sealed<J> + 'a {
-    fn init(&self, schema: &'a J) -> BuildResult<BoxedKeyword<J>>;
+pub trait KeywordFactory<J: Json>: Send + Sync + sealed::Sealed<J> {
+    fn init(&self, schema: &J) -> BuildResult<BoxedKeyword<J>>;
 }
 
-impl<'a, F, J: Json + 'a> sealed::Sealed<J> for F where
-    F: Fn(&'a J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a
+impl<F, J: Json> sealed::Sealed<J> for F where
+    F: Fn(&J) -> BuildResult<BoxedKeyword<J>> + Send + Sync
 {
 }
 
-impl<'a, F, J: Json + 'a> KeywordFactory<'a, J> for F
+impl<F, J: Json> KeywordFactory<J> for F
 where
-    F: Fn(&'a J) -> BuildResult<BoxedKeyword<J>> + Send + Sync + 'a,
+    F: Fn(&J) -> BuildResult<BoxedKeyword<J>> + Send + Sync,
 {
-    fn init(&self, schema: &'a J) -> BuildResult<BoxedKeyword<J>> {
+    fn init(&self, schema: &J) -> BuildResult<BoxedKeyword<J>> {
         self(schema)
     }
 }
+
+pub(crate) fn compile_custom<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    name: &str,
+    value: &'a J,
+) -> CompileResult {
+    let factory = ctx.custom_keyword(name)?;
+    Some(
+        factory
+            .init(value)
+            .and_then(|keyword| ctx.add_keyword(name, value, keyword))
+            .map(|index| KeywordValue::Custom(Custom { index })),
+    )
+}
+
+/// Iterate over object entries, converting backend errors into validation errors.
+pub(crate) fn object_entries<'i, J: Json + 'i>(
+    object: &'i J::Object,
+) -> impl Iterator<Item = Result<(&'i str, &'i J), JsonError>> {
+    object
+        .iter()
+        .map(|(key, value)| key.map(|key| (key.as_ref(), value)))
+}
+
+fn invalid_instance_kind(error: &JsonError) -> ValidationErrorKind {
+    ValidationErrorKind::InvalidInstance {
+        reason: error.to_string(),
+    }
+}
+
+pub(crate) fn invalid_instance(
+    error: &JsonError,
+    path: &JsonPointerNode<'_>,
+    location: &JsonPointer,
+) -> ValidationError {
+    ValidationError::new(invalid_instance_kind(error), path, location)
+}
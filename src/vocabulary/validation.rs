@@ -0,0 +1,686 @@
+//! Keywords from the "Validation" vocabulary.
+use core::cmp::Ordering;
+
+use fancy_regex::Regex;
+use jpointer::{JsonPointer, JsonPointerNode};
+use jsonlike::prelude::*;
+
+use crate::{
+    compiler::{CompileResult, Context},
+    error::{PrimitiveType, ValidationErrorKind},
+    number::{self, Decimal},
+    value::{self, Value},
+    vocabulary::{invalid_instance, object_entries, KeywordValue},
+    ValidationError,
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Type {
+    types: Vec<PrimitiveType>,
+}
+
+impl Type {
+    pub(crate) fn try_is_valid<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+        for ty in &self.types {
+            if is_of_type(*ty, instance)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+    pub(crate) fn error_kind(&self) -> ValidationErrorKind {
+        ValidationErrorKind::Type {
+            expected: self.types.clone(),
+        }
+    }
+}
+
+pub(crate) fn is_of_type<J: Json>(ty: PrimitiveType, instance: &J) -> Result<bool, JsonError> {
+    match ty {
+        PrimitiveType::Array => instance.try_is_array(),
+        PrimitiveType::Boolean => instance.try_is_boolean(),
+        PrimitiveType::Integer => Ok(instance.try_as_number()?.is_some_and(number::is_integer)),
+        PrimitiveType::Null => instance.try_is_null(),
+        PrimitiveType::Number => instance.try_is_number(),
+        PrimitiveType::Object => instance.try_is_object(),
+        PrimitiveType::String => instance.try_is_string(),
+    }
+}
+
+pub(crate) fn compile_type<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let parse = |value: &J| {
+        value
+            .as_string()
+            .and_then(|name| PrimitiveType::from_str(name.as_ref()))
+            .ok_or_else(|| ctx.invalid("type", "Unknown type"))
+    };
+    let types = if let Some(array) = value.as_array() {
+        let mut types = Vec::new();
+        for item in array.iter() {
+            match item.map_err(Into::into).and_then(parse) {
+                Ok(ty) => types.push(ty),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        types
+    } else {
+        match parse(value) {
+            Ok(ty) => vec![ty],
+            Err(error) => return Some(Err(error)),
+        }
+    };
+    Some(Ok(KeywordValue::Type(Type { types })))
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Const {
+    value: Value,
+}
+
+impl Const {
+    /// Whether the expected value is neither an array nor an object.
+    pub(crate) fn is_scalar(&self) -> bool {
+        !matches!(self.value, Value::Array(_) | Value::Object(_))
+    }
+    pub(crate) fn try_is_valid<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+        self.value.try_equals(instance)
+    }
+    pub(crate) fn error_kind(&self) -> ValidationErrorKind {
+        ValidationErrorKind::Const {
+            expected: self.value.to_string(),
+        }
+    }
+}
+
+pub(crate) fn compile_const<'a, J: Json>(
+    _: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    Some(Value::from_json(value).map(|value| KeywordValue::Const(Const { value })))
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Enum {
+    options: Value,
+}
+
+impl Enum {
+    /// Whether no option is an array or an object.
+    pub(crate) fn is_scalar(&self) -> bool {
+        let Value::Array(options) = &self.options else {
+            unreachable!("`enum` is always compiled from an array")
+        };
+        options
+            .iter()
+            .flatten()
+            .all(|option| !matches!(option, Value::Array(_) | Value::Object(_)))
+    }
+    pub(crate) fn try_is_valid<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+        let Value::Array(options) = &self.options else {
+            unreachable!("`enum` is always compiled from an array")
+        };
+        for option in options.iter().flatten() {
+            if option.try_equals(instance)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+    pub(crate) fn error_kind(&self) -> ValidationErrorKind {
+        ValidationErrorKind::Enum {
+            options: self.options.to_string(),
+        }
+    }
+}
+
+pub(crate) fn compile_enum<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    if !value.is_array() {
+        return Some(Err(ctx.invalid("enum", "Expected an array")));
+    }
+    Some(Value::from_json(value).map(|options| KeywordValue::Enum(Enum { options })))
+}
+
+fn number<J: Json>(
+    ctx: &Context<'_, '_, J>,
+    keyword: &str,
+    value: &J,
+) -> Result<value::Number, crate::BuildError> {
+    value
+        .as_number()
+        .map(value::Number::from_json)
+        .ok_or_else(|| ctx.invalid(keyword, "Expected a number"))
+}
+
+fn non_negative_integer<J: Json>(
+    ctx: &Context<'_, '_, J>,
+    keyword: &str,
+    value: &J,
+) -> Result<u64, crate::BuildError> {
+    match value.as_number().and_then(JsonNumber::as_float) {
+        Some(number) if number >= 0.0 && number.fract() == 0.0 => Ok(number as u64),
+        _ => Err(ctx.invalid(keyword, "Expected a non-negative integer")),
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct MultipleOf {
+    multiple_of: f64,
+    divisor: Decimal,
+}
+
+impl MultipleOf {
+    pub(crate) fn try_is_valid<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+        Ok(instance
+            .try_as_number()?
+            .is_none_or(|value| number::is_multiple_of(value, &self.divisor, self.multiple_of)))
+    }
+    pub(crate) fn error_kind(&self) -> ValidationErrorKind {
+        ValidationErrorKind::MultipleOf {
+            multiple_of: self.multiple_of,
+        }
+    }
+}
+
+pub(crate) fn compile_multiple_of<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let divisor = value.as_number().and_then(Decimal::from_number);
+    Some(match (number(ctx, "multipleOf", value), divisor) {
+        (Ok(multiple_of), Some(divisor)) if divisor.is_positive() => {
+            Ok(KeywordValue::MultipleOf(MultipleOf {
+                multiple_of: multiple_of.as_f64(),
+                divisor,
+            }))
+        }
+        (Ok(_), _) => Err(ctx.invalid("multipleOf", "Expected a number greater than zero")),
+        (Err(error), _) => Err(error),
+    })
+}
+
+macro_rules! numeric_limit {
+    ($name:ident, $compile:ident, $keyword:literal, $op:tt) => {
+        #[derive(Debug, Clone)]
+        #[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+        pub(crate) struct $name {
+            limit: value::Number,
+        }
+
+        impl $name {
+            pub(crate) fn try_is_valid<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+                Ok(instance.try_as_number()?.is_none_or(|value| {
+                    number::compare(value, &self.limit)
+                        .is_some_and(|ordering| ordering $op Ordering::Equal)
+                }))
+            }
+            pub(crate) fn error_kind(&self) -> ValidationErrorKind {
+                ValidationErrorKind::$name {
+                    limit: self.limit.as_f64(),
+                }
+            }
+        }
+
+        pub(crate) fn $compile<'a, J: Json>(
+            ctx: &mut Context<'a, '_, J>,
+            _: &'a J::Object,
+            value: &'a J,
+        ) -> CompileResult {
+            Some(number(ctx, $keyword, value).map(|limit| KeywordValue::$name($name { limit })))
+        }
+    };
+}
+
+numeric_limit!(Maximum, compile_maximum, "maximum", <=);
+numeric_limit!(ExclusiveMaximum, compile_exclusive_maximum, "exclusiveMaximum", <);
+numeric_limit!(Minimum, compile_minimum, "minimum", >=);
+numeric_limit!(ExclusiveMinimum, compile_exclusive_minimum, "exclusiveMinimum", >);
+
+/// Draft 4 `maximum`, which is made exclusive by a boolean `exclusiveMaximum` sibling.
+pub(crate) fn compile_maximum_draft4<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let exclusive = parent
+        .get("exclusiveMaximum")
+        .and_then(Json::as_boolean)
+        .unwrap_or(false);
+    Some(number(ctx, "maximum", value).map(|limit| {
+        if exclusive {
+            KeywordValue::ExclusiveMaximum(ExclusiveMaximum { limit })
+        } else {
+            KeywordValue::Maximum(Maximum { limit })
+        }
+    }))
+}
+
+/// Draft 4 `minimum`, which is made exclusive by a boolean `exclusiveMinimum` sibling.
+pub(crate) fn compile_minimum_draft4<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    parent: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let exclusive = parent
+        .get("exclusiveMinimum")
+        .and_then(Json::as_boolean)
+        .unwrap_or(false);
+    Some(number(ctx, "minimum", value).map(|limit| {
+        if exclusive {
+            KeywordValue::ExclusiveMinimum(ExclusiveMinimum { limit })
+        } else {
+            KeywordValue::Minimum(Minimum { limit })
+        }
+    }))
+}
+
+macro_rules! size_limit {
+    ($name:ident, $compile:ident, $keyword:literal, $size:expr, $op:tt) => {
+        #[derive(Debug, Clone)]
+        #[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+        pub(crate) struct $name {
+            limit: u64,
+        }
+
+        impl $name {
+            pub(crate) fn try_is_valid<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+                let size: fn(&J) -> Result<Option<usize>, JsonError> = $size;
+                Ok(size(instance)?.is_none_or(|size| self.allows(size)))
+            }
+            pub(crate) fn allows(&self, size: usize) -> bool {
+                size as u64 $op self.limit
+            }
+            pub(crate) fn error_kind(&self) -> ValidationErrorKind {
+                ValidationErrorKind::$name { limit: self.limit }
+            }
+        }
+
+        pub(crate) fn $compile<'a, J: Json>(
+            ctx: &mut Context<'a, '_, J>,
+            _: &'a J::Object,
+            value: &'a J,
+        ) -> CompileResult {
+            Some(
+                non_negative_integer(ctx, $keyword, value)
+                    .map(|limit| KeywordValue::$name($name { limit })),
+            )
+        }
+    };
+}
+
+size_limit!(MaxLength, compile_max_length, "maxLength", |instance| {
+    Ok(instance.try_as_string()?.map(|s| s.as_ref().chars().count()))
+}, <=);
+size_limit!(MinLength, compile_min_length, "minLength", |instance| {
+    Ok(instance.try_as_string()?.map(|s| s.as_ref().chars().count()))
+}, >=);
+size_limit!(MaxItems, compile_max_items, "maxItems", |instance| {
+    Ok(instance.try_as_array()?.map(JsonArray::len))
+}, <=);
+size_limit!(MinItems, compile_min_items, "minItems", |instance| {
+    Ok(instance.try_as_array()?.map(JsonArray::len))
+}, >=);
+size_limit!(MaxProperties, compile_max_properties, "maxProperties", |instance| {
+    Ok(instance.try_as_object()?.map(JsonObject::len))
+}, <=);
+size_limit!(MinProperties, compile_min_properties, "minProperties", |instance| {
+    Ok(instance.try_as_object()?.map(JsonObject::len))
+}, >=);
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Pattern {
+    pattern: String,
+    #[cfg_attr(feature = "snapshot", serde(with = "crate::snapshot::regex"))]
+    regex: Regex,
+}
+
+impl Pattern {
+    pub(crate) fn try_is_valid<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+        Ok(instance
+            .try_as_string()?
+            .is_none_or(|value| self.regex.is_match(value.as_ref()).unwrap_or(false)))
+    }
+    pub(crate) fn error_kind(&self) -> ValidationErrorKind {
+        ValidationErrorKind::Pattern {
+            pattern: self.pattern.clone(),
+        }
+    }
+}
+
+pub(crate) fn compile_regex<J: Json>(
+    ctx: &Context<'_, '_, J>,
+    keyword: &str,
+    pattern: &str,
+) -> Result<Regex, crate::BuildError> {
+    Regex::new(pattern).map_err(|error| ctx.invalid(keyword, error.to_string()))
+}
+
+pub(crate) fn compile_pattern<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    let Some(pattern) = value.as_string() else {
+        return Some(Err(ctx.invalid("pattern", "Expected a string")));
+    };
+    let pattern = pattern.as_ref();
+    Some(compile_regex(ctx, "pattern", pattern).map(|regex| {
+        KeywordValue::Pattern(Pattern {
+            pattern: pattern.to_owned(),
+            regex,
+        })
+    }))
+}
+
+pub(crate) fn unique_items_try_is_valid<J: Json>(instance: &J) -> Result<bool, JsonError> {
+    let Some(array) = instance.try_as_array()? else {
+        return Ok(true);
+    };
+    let mut seen: Vec<&J> = Vec::new();
+    for item in array.try_iter()? {
+        let item = item?;
+        for previous in &seen {
+            if value::try_equal(*previous, item)? {
+                return Ok(false);
+            }
+        }
+        seen.push(item);
+    }
+    Ok(true)
+}
+
+pub(crate) fn compile_unique_items<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    match value.as_boolean() {
+        Some(true) => Some(Ok(KeywordValue::UniqueItems)),
+        Some(false) => None,
+        None => Some(Err(ctx.invalid("uniqueItems", "Expected a boolean"))),
+    }
+}
+
+fn string_array<J: Json>(
+    ctx: &Context<'_, '_, J>,
+    keyword: &str,
+    value: &J,
+) -> Result<Vec<String>, crate::BuildError> {
+    let Some(array) = value.as_array() else {
+        return Err(ctx.invalid(keyword, "Expected an array of strings"));
+    };
+    let mut strings = Vec::with_capacity(array.len());
+    for item in array.try_iter()? {
+        match item?.as_string() {
+            Some(string) => strings.push(string.as_ref().to_owned()),
+            None => return Err(ctx.invalid(keyword, "Expected an array of strings")),
+        }
+    }
+    Ok(strings)
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Required {
+    properties: Vec<String>,
+}
+
+impl Required {
+    pub(crate) fn try_is_valid<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+        Ok(instance.try_as_object()?.is_none_or(|object| {
+            self.properties
+                .iter()
+                .all(|property| object.contains_key(property))
+        }))
+    }
+    pub(crate) fn collect_errors<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let object = match instance.try_as_object() {
+            Ok(object) => object,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        if let Some(object) = object {
+            for property in &self.properties {
+                if !object.contains_key(property) {
+                    errors.push(ValidationError::new(
+                        ValidationErrorKind::Required {
+                            property: property.clone(),
+                        },
+                        path,
+                        location,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn compile_required<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    match string_array(ctx, "required", value) {
+        Ok(properties) if properties.is_empty() => None,
+        Ok(properties) => Some(Ok(KeywordValue::Required(Required { properties }))),
+        Err(error) => Some(Err(error)),
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "snapshot", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct DependentRequired {
+    dependencies: Vec<(String, Required)>,
+}
+
+impl DependentRequired {
+    pub(crate) fn try_is_valid<J: Json>(&self, instance: &J) -> Result<bool, JsonError> {
+        let Some(object) = instance.try_as_object()? else {
+            return Ok(true);
+        };
+        Ok(self.dependencies.iter().all(|(property, required)| {
+            !object.contains_key(property)
+                || required
+                    .properties
+                    .iter()
+                    .all(|property| object.contains_key(property))
+        }))
+    }
+    pub(crate) fn collect_errors<J: Json>(
+        &self,
+        instance: &J,
+        location: &JsonPointer,
+        path: &JsonPointerNode<'_>,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let object = match instance.try_as_object() {
+            Ok(Some(object)) => object,
+            Ok(None) => return,
+            Err(error) => return errors.push(invalid_instance(&error, path, location)),
+        };
+        for (property, required) in &self.dependencies {
+            if object.contains_key(property) {
+                required.collect_errors(instance, &location.join(property.as_str()), path, errors);
+            }
+        }
+    }
+}
+
+/// Compile `dependentRequired` or the array form of the legacy `dependencies` keyword.
+pub(crate) fn compile_dependent_required_from<'a, J: Json>(
+    ctx: &Context<'a, '_, J>,
+    keyword: &str,
+    value: &'a J,
+    only_arrays: bool,
+) -> CompileResult {
+    let Some(object) = value.as_object() else {
+        return Some(Err(ctx.invalid(keyword, "Expected an object")));
+    };
+    let mut dependencies = Vec::new();
+    for entry in object_entries::<J>(object) {
+        let (property, value) = match entry {
+            Ok(entry) => entry,
+            Err(error) => return Some(Err(error.into())),
+        };
+        if only_arrays && !value.is_array() {
+            continue;
+        }
+        match string_array(ctx, keyword, value) {
+            Ok(properties) => dependencies.push((property.to_owned(), Required { properties })),
+            Err(error) => return Some(Err(error)),
+        }
+    }
+    if dependencies.is_empty() {
+        return None;
+    }
+    Some(Ok(KeywordValue::DependentRequired(DependentRequired {
+        dependencies,
+    })))
+}
+
+pub(crate) fn compile_dependent_required<'a, J: Json>(
+    ctx: &mut Context<'a, '_, J>,
+    _: &'a J::Object,
+    value: &'a J,
+) -> CompileResult {
+    compile_dependent_required_from(ctx, "dependentRequired", value, false)
+}
+
+#[cfg(all(test, feature = "serde_json"))]
+mod tests {
+    use serde_json::{json, Value};
+    use test_case::test_case;
+
+    #[test_case(json!({"type": "integer"}), json!(1), true)]
+    #[test_case(json!({"type": "integer"}), json!(1.0), true)]
+    #[test_case(json!({"type": "integer"}), json!(1.5), false)]
+    #[test_case(json!({"type": ["string", "null"]}), json!(null), true)]
+    #[test_case(json!({"type": ["string", "null"]}), json!(1), false)]
+    #[test_case(json!({"const": {"a": [1]}}), json!({"a": [1.0]}), true)]
+    #[test_case(json!({"const": {"a": [1]}}), json!({"a": [2]}), false)]
+    #[test_case(json!({"enum": [1, "a"]}), json!("a"), true)]
+    #[test_case(json!({"enum": [1, "a"]}), json!("b"), false)]
+    #[test_case(json!({"multipleOf": 2}), json!(4), true)]
+    #[test_case(json!({"multipleOf": 2}), json!(5), false)]
+    #[test_case(json!({"multipleOf": 0.5}), json!(1.5), true)]
+    #[test_case(json!({"maximum": 3}), json!(3), true)]
+    #[test_case(json!({"exclusiveMaximum": 3}), json!(3), false)]
+    #[test_case(json!({"minimum": 3}), json!(2), false)]
+    #[test_case(json!({"exclusiveMinimum": 3}), json!(3.5), true)]
+    #[test_case(json!({"maxLength": 2}), json!("ab"), true)]
+    #[test_case(json!({"maxLength": 2}), json!("abc"), false)]
+    #[test_case(json!({"minLength": 2}), json!("ä"), false)]
+    #[test_case(json!({"pattern": "^a+$"}), json!("aaa"), true)]
+    #[test_case(json!({"pattern": "^a+$"}), json!("ab"), false)]
+    #[test_case(json!({"maxItems": 1}), json!([1, 2]), false)]
+    #[test_case(json!({"minItems": 1}), json!([]), false)]
+    #[test_case(json!({"uniqueItems": true}), json!([1, 1.0]), false)]
+    #[test_case(json!({"uniqueItems": true}), json!([1, "1"]), true)]
+    #[test_case(json!({"maxProperties": 1}), json!({"a": 1, "b": 2}), false)]
+    #[test_case(json!({"minProperties": 1}), json!({}), false)]
+    #[test_case(json!({"required": ["a"]}), json!({"a": 1}), true)]
+    #[test_case(json!({"required": ["a"]}), json!({"b": 1}), false)]
+    #[test_case(json!({"dependentRequired": {"a": ["b"]}}), json!({"a": 1}), false)]
+    #[test_case(json!({"dependentRequired": {"a": ["b"]}}), json!({"c": 1}), true)]
+    fn test_is_valid(schema: Value, instance: Value, expected: bool) {
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+        assert_eq!(validator.iter_errors(&instance).next().is_none(), expected);
+    }
+
+    #[test_case(json!({"multipleOf": 0.1}), json!(0.3), true)]
+    #[test_case(json!({"multipleOf": 0.01}), json!(19.99), true)]
+    #[test_case(json!({"multipleOf": 0.01}), json!(19.999), false)]
+    #[test_case(json!({"multipleOf": 2}), json!(18446744073709551615_u64), false)]
+    #[test_case(json!({"multipleOf": 5}), json!(18446744073709551615_u64), true)]
+    #[test_case(json!({"maximum": 9007199254740992_u64}), json!(9007199254740993_u64), false)]
+    #[test_case(json!({"minimum": 9007199254740993_u64}), json!(9007199254740992_u64), false)]
+    #[test_case(json!({"exclusiveMaximum": 9007199254740993_u64}), json!(9007199254740992_u64), true)]
+    #[test_case(json!({"const": 9007199254740993_u64}), json!(9007199254740992_u64), false)]
+    #[test_case(json!({"const": 9007199254740993_u64}), json!(9007199254740993_u64), true)]
+    #[test_case(json!({"enum": [-9007199254740993_i64]}), json!(-9007199254740992_i64), false)]
+    #[test_case(json!({"uniqueItems": true}), json!([9007199254740993_u64, 9007199254740992_u64]), true)]
+    #[test_case(json!({"type": "integer"}), json!(18446744073709551615_u64), true)]
+    fn test_exact_numbers(schema: Value, instance: Value, expected: bool) {
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test_case(r#"{"type": "integer"}"#, "1.00000000000000000001", false)]
+    #[test_case(r#"{"maximum": 1}"#, "1.00000000000000000001", false)]
+    #[test_case(r#"{"const": 0.1}"#, "0.10000000000000000001", false)]
+    #[test_case(
+        r#"{"multipleOf": 0.00000000000000000001}"#,
+        "12345678901234567890.12345678901234567891",
+        true
+    )]
+    #[test_case(r#"{"maximum": 1e400}"#, "5", true)]
+    #[test_case(r#"{"minimum": -1e400}"#, "5", true)]
+    #[test_case(r#"{"maximum": 1e400}"#, "1e401", false)]
+    #[test_case(r#"{"minimum": 1e400}"#, "1e401", true)]
+    #[test_case(r#"{"minimum": 1e400}"#, "5", false)]
+    #[test_case(r#"{"exclusiveMaximum": -1e400}"#, "-1e401", true)]
+    fn test_arbitrary_precision(schema: &str, instance: &str, expected: bool) {
+        let schema: Value = serde_json::from_str(schema).expect("Invalid JSON");
+        let instance: Value = serde_json::from_str(instance).expect("Invalid JSON");
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[test_case(json!({"maximum": 3, "exclusiveMaximum": true}), json!(3), false)]
+    #[test_case(json!({"minimum": 3, "exclusiveMinimum": true}), json!(3), false)]
+    #[test_case(json!({"minimum": 3, "exclusiveMinimum": false}), json!(3), true)]
+    fn test_draft4_exclusive_limits(schema: Value, instance: Value, expected: bool) {
+        let validator = crate::blocking::ValidatorBuilder::default()
+            .draft(crate::Draft::Draft04)
+            .build(&schema)
+            .expect("Invalid schema");
+        assert_eq!(validator.is_valid(&instance), expected);
+    }
+
+    #[test_case(json!({"type": "unknown"}))]
+    #[test_case(json!({"minLength": -1}))]
+    #[test_case(json!({"multipleOf": 0}))]
+    #[test_case(json!({"pattern": "("}))]
+    #[test_case(json!({"required": [1]}))]
+    fn test_invalid_schema(schema: Value) {
+        assert!(crate::blocking::validator_for(&schema).is_err());
+    }
+
+    #[test]
+    fn test_required_errors() {
+        let schema = json!({"required": ["a", "b"]});
+        let validator = crate::blocking::validator_for(&schema).expect("Invalid schema");
+        let errors: Vec<_> = validator
+            .iter_errors(&json!({}))
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "\"a\" is a required property".to_string(),
+                "\"b\" is a required property".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Loading schemas and instances from YAML files.
+//!
+//! `serde_yaml::Value` implements `Json`, so YAML documents are validated without converting
+//! them to JSON first. Relative `$ref`s to other YAML files resolve via `FileSystemResolver`:
+//!
+//! ```rust,no_run
+//! # fn test() -> Result<(), Box<dyn std::error::Error>> {
+//! use jsonschema::{blocking::ValidatorBuilder, resolver::FileSystemResolver};
+//!
+//! let schema = jsonschema::yaml::load("schemas/deployment.yaml")?;
+//! let validator = ValidatorBuilder::default()
+//!     .resolver(FileSystemResolver::new("schemas"))
+//!     .build(&schema)?;
+//! for manifest in jsonschema::yaml::load_all("manifests.yaml")? {
+//!     validator.validate(&manifest)?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::path::Path;
+
+use jsonlike::{Json, JsonError};
+use serde::Deserialize;
+use serde_yaml::{Deserializer, Value};
+
+use crate::files;
+
+/// Read a single YAML document from `path`.
+pub fn load(path: impl AsRef<Path>) -> Result<Value, JsonError> {
+    files::load(path.as_ref(), Value::from_str)
+}
+
+/// Read every document of a multi-document YAML stream from `path`, e.g. manifests separated
+/// by `---`.
+pub fn load_all(path: impl AsRef<Path>) -> Result<Vec<Value>, JsonError> {
+    files::load(path.as_ref(), from_str_all)
+}
+
+/// Parse every document of a multi-document YAML stream.
+///
+/// Documents are expanded the same way as by `Json::from_str`, i.e. with merge keys applied.
+pub fn from_str_all(s: &str) -> Result<Vec<Value>, JsonError> {
+    Deserializer::from_str(s)
+        .map(|document| {
+            let mut value = Value::deserialize(document)?;
+            value.apply_merge()?;
+            Ok(value)
+        })
+        .collect::<Result<_, serde_yaml::Error>>()
+        .map_err(|err| JsonError::new(Box::new(err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use serde_yaml::Value;
+    use test_case::test_case;
+
+    use super::{from_str_all, load, load_all};
+    use crate::{
+        blocking::{validator_for, ValidatorBuilder},
+        resolver::FileSystemResolver,
+        Json, ValidationErrorKind,
+    };
+
+    #[test_case("a: 1", 1)]
+    #[test_case("---\na: 1\n---\nb: 2\n", 2)]
+    #[test_case("a: &x 1\n---\nb: {<<: {c: 1}}\n---\n- 1\n", 3)]
+    fn test_from_str_all(input: &str, expected: usize) {
+        let documents = from_str_all(input).expect("Valid YAML");
+        assert_eq!(documents.len(), expected);
+    }
+
+    #[test]
+    fn test_from_str_all_merge_keys() {
+        let documents = from_str_all("a: 1\n---\n<<: {b: 2}\nc: 3\n").expect("Valid YAML");
+        assert!(documents[1].equal(&Value::from_str("{b: 2, c: 3}").expect("Valid YAML")));
+    }
+
+    #[test_case("replicas: !custom x", "type: integer")]
+    #[test_case("replicas: !custom 3", "minimum: 1")]
+    #[test_case("replicas: !custom [1]", "maxItems: 1")]
+    #[test_case("replicas: .nan", "minimum: 1")]
+    #[test_case("replicas: -.inf", "type: number")]
+    fn test_instance_without_json_equivalent(manifest: &str, replicas: &str) {
+        let schema = Value::from_str(&format!("properties: {{replicas: {{{replicas}}}}}"))
+            .expect("Valid YAML");
+        let validator = validator_for(&schema).expect("Valid schema");
+        let manifest = Value::from_str(manifest).expect("Valid YAML");
+        assert!(!validator.is_valid(&manifest));
+        let errors: Vec<_> = validator.iter_errors(&manifest).collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].instance_path().to_string(), "/replicas");
+        assert!(matches!(
+            errors[0].kind(),
+            ValidationErrorKind::InvalidInstance { .. }
+        ));
+    }
+
+    #[test_case("properties: {replicas: !custom x}")]
+    #[test_case("properties: {replicas: {minimum: .nan}}")]
+    #[test_case("!custom {type: object}")]
+    fn test_schema_without_json_equivalent(schema: &str) {
+        let schema = Value::from_str(schema).expect("Valid YAML");
+        assert!(validator_for(&schema).is_err());
+        assert!(crate::lint::lint(&schema).is_err());
+    }
+
+    #[test]
+    fn test_validate_files() {
+        let directory =
+            std::env::temp_dir().join(format!("jsonschema-yaml-{}", std::process::id()));
+        fs::create_dir_all(&directory).expect("Can create directories");
+        fs::write(
+            directory.join("schema.yaml"),
+            "properties:\n  replicas:\n    $ref: defs.yaml#/count\nrequired: [kind]\n",
+        )
+        .expect("Can write files");
+        fs::write(
+            directory.join("defs.yaml"),
+            "count:\n  type: integer\n  minimum: 1\n",
+        )
+        .expect("Can write files");
+        fs::write(
+            directory.join("manifests.yaml"),
+            "kind: Deployment\nreplicas: 3\n---\nkind: Deployment\nreplicas: 0\n---\nreplicas: 1\n",
+        )
+        .expect("Can write files");
+
+        let schema = load(directory.join("schema.yaml")).expect("Valid YAML");
+        let validator = ValidatorBuilder::default()
+            .resolver(FileSystemResolver::new(&directory))
+            .build(&schema)
+            .expect("Valid schema");
+        let valid: Vec<_> = load_all(directory.join("manifests.yaml"))
+            .expect("Valid YAML")
+            .iter()
+            .map(|manifest| validator.is_valid(manifest))
+            .collect();
+        assert_eq!(valid, [true, false, false]);
+        fs::remove_dir_all(&directory).expect("Can remove directories");
+    }
+}